@@ -0,0 +1,5 @@
+DROP TABLE votes;
+DROP TABLE layers;
+DROP TABLE memes;
+DROP TABLE templates;
+DROP TABLE users;
@@ -0,0 +1,52 @@
+CREATE TABLE users (
+	id UUID PRIMARY KEY,
+	username TEXT NOT NULL UNIQUE,
+	password_hash TEXT NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE templates (
+	id UUID PRIMARY KEY,
+	name TEXT NOT NULL,
+	image_url TEXT NOT NULL,
+	width INTEGER NOT NULL,
+	height INTEGER NOT NULL,
+	created_by UUID REFERENCES users (id) ON DELETE SET NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE memes (
+	id UUID PRIMARY KEY,
+	user_id UUID REFERENCES users (id) ON DELETE SET NULL,
+	template_id UUID REFERENCES templates (id) ON DELETE SET NULL,
+	main_img_url TEXT NOT NULL,
+	width INTEGER NOT NULL,
+	height INTEGER NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX memes_created_at_idx ON memes (created_at DESC);
+
+-- One row per text box, `position` keeps the drawing order of the document.
+CREATE TABLE layers (
+	meme_id UUID NOT NULL REFERENCES memes (id) ON DELETE CASCADE,
+	position INTEGER NOT NULL,
+	text TEXT NOT NULL,
+	x DOUBLE PRECISION NOT NULL,
+	y DOUBLE PRECISION NOT NULL,
+	rotation DOUBLE PRECISION NOT NULL,
+	scale_x DOUBLE PRECISION NOT NULL,
+	scale_y DOUBLE PRECISION NOT NULL,
+	font_size INTEGER NOT NULL,
+	font_family TEXT NOT NULL,
+	font_effect TEXT NOT NULL,
+	PRIMARY KEY (meme_id, position)
+);
+
+CREATE TABLE votes (
+	user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	meme_id UUID NOT NULL REFERENCES memes (id) ON DELETE CASCADE,
+	value SMALLINT NOT NULL CHECK (value IN (-1, 1)),
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	PRIMARY KEY (user_id, meme_id)
+);
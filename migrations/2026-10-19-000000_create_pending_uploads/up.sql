@@ -0,0 +1,12 @@
+-- Uploads handed out as presigned urls but not confirmed yet. Rows past `expires_at` are swept together with their objects.
+CREATE TABLE pending_uploads (
+	id UUID PRIMARY KEY,
+	user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	name TEXT NOT NULL,
+	object_key TEXT NOT NULL UNIQUE,
+	content_type TEXT NOT NULL,
+	expires_at TIMESTAMPTZ NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX pending_uploads_expires_at_idx ON pending_uploads (expires_at);
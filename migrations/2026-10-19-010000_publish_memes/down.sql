@@ -0,0 +1,6 @@
+DROP INDEX memes_published_at_idx;
+
+ALTER TABLE memes
+	DROP COLUMN published_at,
+	DROP COLUMN image_url,
+	DROP COLUMN title;
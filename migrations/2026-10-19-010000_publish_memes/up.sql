@@ -0,0 +1,7 @@
+-- A published meme is a post: it has a title, a rendered image and shows up in the feed.
+ALTER TABLE memes
+	ADD COLUMN title TEXT,
+	ADD COLUMN image_url TEXT,
+	ADD COLUMN published_at TIMESTAMPTZ;
+
+CREATE INDEX memes_published_at_idx ON memes (published_at DESC, id DESC) WHERE published_at IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE meme_views;
+DROP TABLE meme_stats;
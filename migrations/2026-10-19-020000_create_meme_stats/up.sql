@@ -0,0 +1,30 @@
+-- Counters kept next to the meme so feeds can sort without aggregating votes per request.
+CREATE TABLE meme_stats (
+	meme_id UUID PRIMARY KEY REFERENCES memes (id) ON DELETE CASCADE,
+	upvotes INTEGER NOT NULL DEFAULT 0,
+	downvotes INTEGER NOT NULL DEFAULT 0,
+	views BIGINT NOT NULL DEFAULT 0,
+	hot DOUBLE PRECISION NOT NULL DEFAULT 0,
+	updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX meme_stats_hot_idx ON meme_stats (hot DESC, meme_id DESC);
+
+-- One row per viewer per meme per day, a view only counts when its row is new.
+CREATE TABLE meme_views (
+	meme_id UUID NOT NULL REFERENCES memes (id) ON DELETE CASCADE,
+	viewer TEXT NOT NULL,
+	viewed_on DATE NOT NULL,
+	PRIMARY KEY (meme_id, viewer, viewed_on)
+);
+
+INSERT INTO meme_stats (meme_id, upvotes, downvotes, hot)
+SELECT
+	m.id,
+	COUNT(v.value) FILTER (WHERE v.value > 0),
+	COUNT(v.value) FILTER (WHERE v.value < 0),
+	SIGN(COALESCE(SUM(v.value), 0)) * LOG(GREATEST(ABS(COALESCE(SUM(v.value), 0)), 1)) + EXTRACT(EPOCH FROM m.published_at) / 45000
+FROM memes m
+LEFT JOIN votes v ON v.meme_id = m.id
+WHERE m.published_at IS NOT NULL
+GROUP BY m.id;
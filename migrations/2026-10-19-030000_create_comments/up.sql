@@ -0,0 +1,27 @@
+-- Deleted comments keep their row, with the body cleared, so replies below them stay in place.
+CREATE TABLE comments (
+	id UUID PRIMARY KEY,
+	meme_id UUID NOT NULL REFERENCES memes (id) ON DELETE CASCADE,
+	parent_id UUID REFERENCES comments (id) ON DELETE CASCADE,
+	user_id UUID REFERENCES users (id) ON DELETE SET NULL,
+	body TEXT NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	edited_at TIMESTAMPTZ,
+	deleted_at TIMESTAMPTZ
+);
+
+CREATE INDEX comments_meme_id_idx ON comments (meme_id, created_at);
+CREATE INDEX comments_user_id_idx ON comments (user_id, created_at DESC);
+
+CREATE TABLE notifications (
+	id UUID PRIMARY KEY,
+	user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	actor_id UUID REFERENCES users (id) ON DELETE SET NULL,
+	kind TEXT NOT NULL,
+	meme_id UUID NOT NULL REFERENCES memes (id) ON DELETE CASCADE,
+	comment_id UUID REFERENCES comments (id) ON DELETE CASCADE,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	read_at TIMESTAMPTZ
+);
+
+CREATE INDEX notifications_user_id_idx ON notifications (user_id, created_at DESC);
@@ -0,0 +1,3 @@
+DROP INDEX memes_parent_id_idx;
+
+ALTER TABLE memes DROP COLUMN parent_id;
@@ -0,0 +1,4 @@
+-- A remix points at the post it was forked from. Lineage survives the parent being deleted only as far as the nearest remaining ancestor.
+ALTER TABLE memes ADD COLUMN parent_id UUID REFERENCES memes (id) ON DELETE SET NULL;
+
+CREATE INDEX memes_parent_id_idx ON memes (parent_id) WHERE parent_id IS NOT NULL;
@@ -0,0 +1,13 @@
+DROP INDEX templates_tags_idx;
+DROP INDEX templates_search_idx;
+DROP INDEX memes_tags_idx;
+DROP INDEX memes_search_idx;
+
+DROP FUNCTION template_search_document;
+DROP FUNCTION meme_search_document;
+
+ALTER TABLE templates DROP COLUMN tags;
+
+ALTER TABLE memes
+	DROP COLUMN tags,
+	DROP COLUMN caption;
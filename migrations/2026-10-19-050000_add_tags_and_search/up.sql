@@ -0,0 +1,28 @@
+-- Caption is the text of every layer, kept on the meme so search does not have to aggregate layers.
+ALTER TABLE memes
+	ADD COLUMN caption TEXT NOT NULL DEFAULT '',
+	ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE templates ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+UPDATE memes m
+SET caption = l.caption
+FROM (SELECT meme_id, string_agg(text, ' / ' ORDER BY position) AS caption FROM layers GROUP BY meme_id) l
+WHERE l.meme_id = m.id;
+
+-- Search documents. They are immutable so the expression indexes below can be built on them, and queries must call the same functions to use those indexes.
+CREATE FUNCTION meme_search_document(title TEXT, caption TEXT, tags TEXT[]) RETURNS TSVECTOR
+LANGUAGE SQL IMMUTABLE PARALLEL SAFE AS $$
+	SELECT setweight(to_tsvector('english'::REGCONFIG, coalesce(title, '') || ' ' || array_to_string(tags, ' ')), 'A')
+		|| setweight(to_tsvector('english'::REGCONFIG, caption), 'B')
+$$;
+
+CREATE FUNCTION template_search_document(name TEXT, tags TEXT[]) RETURNS TSVECTOR
+LANGUAGE SQL IMMUTABLE PARALLEL SAFE AS $$
+	SELECT to_tsvector('english'::REGCONFIG, name || ' ' || array_to_string(tags, ' '))
+$$;
+
+CREATE INDEX memes_search_idx ON memes USING GIN (meme_search_document(title, caption, tags)) WHERE published_at IS NOT NULL;
+CREATE INDEX memes_tags_idx ON memes USING GIN (tags) WHERE published_at IS NOT NULL;
+CREATE INDEX templates_search_idx ON templates USING GIN (template_search_document(name, tags));
+CREATE INDEX templates_tags_idx ON templates USING GIN (tags);
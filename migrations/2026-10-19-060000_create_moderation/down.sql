@@ -0,0 +1,9 @@
+DROP TABLE blocked_images;
+DROP TABLE audit_log;
+DROP TABLE reports;
+
+ALTER TABLE memes DROP COLUMN hidden_at;
+
+ALTER TABLE users
+	DROP COLUMN banned_at,
+	DROP COLUMN role;
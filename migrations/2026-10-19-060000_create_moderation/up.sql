@@ -0,0 +1,40 @@
+ALTER TABLE users
+	ADD COLUMN role TEXT NOT NULL DEFAULT 'user' CHECK (role IN ('user', 'moderator')),
+	ADD COLUMN banned_at TIMESTAMPTZ;
+
+-- Hidden posts stay around for review but drop out of feeds, search, lineage and post pages.
+ALTER TABLE memes ADD COLUMN hidden_at TIMESTAMPTZ;
+
+-- `reporter_id` is null for reports filed by the automatic filters on publish.
+CREATE TABLE reports (
+	id UUID PRIMARY KEY,
+	meme_id UUID NOT NULL REFERENCES memes (id) ON DELETE CASCADE,
+	reporter_id UUID REFERENCES users (id) ON DELETE CASCADE,
+	reason TEXT NOT NULL,
+	details TEXT NOT NULL DEFAULT '',
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	resolved_at TIMESTAMPTZ,
+	UNIQUE (meme_id, reporter_id)
+);
+
+CREATE INDEX reports_open_idx ON reports (meme_id) WHERE resolved_at IS NULL;
+
+-- Append only record of moderator actions. Targets are plain ids so entries outlive the posts and accounts they name.
+CREATE TABLE audit_log (
+	id UUID PRIMARY KEY,
+	actor_id UUID REFERENCES users (id) ON DELETE SET NULL,
+	action TEXT NOT NULL,
+	meme_id UUID,
+	user_id UUID,
+	note TEXT NOT NULL DEFAULT '',
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX audit_log_created_at_idx ON audit_log (created_at DESC);
+
+-- Sha256 of renders moderators deleted, so the exact same image cannot simply be published again.
+CREATE TABLE blocked_images (
+	sha256 TEXT PRIMARY KEY,
+	blocked_by UUID REFERENCES users (id) ON DELETE SET NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,3 @@
+ALTER TABLE memes DROP COLUMN dhash;
+
+ALTER TABLE templates DROP COLUMN dhash;
@@ -0,0 +1,5 @@
+-- 64 bit dHash of the image, see `src/server/image_hash.rs`. Similar images have hashes a small Hamming distance apart, which is
+-- computed as `bit_count((a # b)::BIT(64))`. Rows from before this migration have no hash and are skipped by similarity lookups.
+ALTER TABLE templates ADD COLUMN dhash BIGINT;
+
+ALTER TABLE memes ADD COLUMN dhash BIGINT;
@@ -0,0 +1,2 @@
+DROP TABLE game_bets;
+DROP TABLE meme_records;
@@ -0,0 +1,37 @@
+-- Memes scraped or imported from other sites, with their engagement when last observed. They only feed the analytics game and are
+-- never shown in the feed. A record is settled once it was observed at least a day after it was posted, see `src/server/game.rs`.
+CREATE TABLE meme_records (
+	id UUID PRIMARY KEY,
+	source TEXT NOT NULL,
+	external_id TEXT NOT NULL,
+	title TEXT NOT NULL,
+	image_url TEXT NOT NULL,
+	posted_at TIMESTAMPTZ NOT NULL,
+	score BIGINT NOT NULL,
+	comments BIGINT NOT NULL,
+	engagement DOUBLE PRECISION NOT NULL,
+	observed_at TIMESTAMPTZ NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	UNIQUE (source, external_id)
+);
+
+CREATE INDEX meme_records_source_engagement_idx ON meme_records (source, engagement);
+
+-- `percentile` and `payout` are filled in when the bet settles, `payout` being 0 for a lost bet.
+CREATE TABLE game_bets (
+	id UUID PRIMARY KEY,
+	user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	record_id UUID NOT NULL REFERENCES meme_records (id) ON DELETE CASCADE,
+	window_low SMALLINT NOT NULL,
+	window_high SMALLINT NOT NULL,
+	stake BIGINT NOT NULL CHECK (stake > 0),
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	settled_at TIMESTAMPTZ,
+	percentile DOUBLE PRECISION,
+	payout BIGINT,
+	CHECK (0 <= window_low AND window_low < window_high AND window_high <= 100),
+	UNIQUE (user_id, record_id)
+);
+
+CREATE INDEX game_bets_user_idx ON game_bets (user_id, created_at DESC);
+CREATE INDEX game_bets_open_idx ON game_bets (record_id) WHERE settled_at IS NULL;
@@ -0,0 +1 @@
+ALTER TABLE meme_records DROP COLUMN dhash;
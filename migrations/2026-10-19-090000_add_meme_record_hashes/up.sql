@@ -0,0 +1,2 @@
+-- Perceptual hash of the record's image, so an import skips memes already gathered from another source or under another id.
+ALTER TABLE meme_records ADD COLUMN dhash BIGINT;
@@ -0,0 +1,5 @@
+DROP TABLE tournament_scores;
+DROP TABLE tournament_entries;
+DROP TABLE tournament_judges;
+DROP TABLE tournaments;
+ALTER TABLE memes DROP COLUMN watermark_url;
@@ -0,0 +1,51 @@
+-- Brand watermark drawn over the meme, see `MemeDocument::watermark_url`. Tournament entries must carry their tournament's.
+ALTER TABLE memes ADD COLUMN watermark_url TEXT;
+
+-- Sponsored meme contests. `template_id` is the template every entry must be made on, if any. `prizes` holds the points paid to
+-- first place, second place and so on. Results are written to the entries when a moderator finalizes the tournament.
+CREATE TABLE tournaments (
+	id UUID PRIMARY KEY,
+	sponsor TEXT NOT NULL,
+	title TEXT NOT NULL,
+	brief TEXT NOT NULL,
+	template_id UUID REFERENCES templates (id) ON DELETE SET NULL,
+	watermark_url TEXT NOT NULL,
+	starts_at TIMESTAMPTZ NOT NULL,
+	ends_at TIMESTAMPTZ NOT NULL,
+	judging TEXT NOT NULL CHECK (judging IN ('votes', 'panel', 'combined')),
+	prizes BIGINT[] NOT NULL,
+	created_by UUID REFERENCES users (id) ON DELETE SET NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	finalized_at TIMESTAMPTZ,
+	CHECK (starts_at < ends_at)
+);
+
+CREATE INDEX tournaments_ends_at_idx ON tournaments (ends_at DESC);
+
+-- The sponsor's panel. Judges score entries but cannot enter themselves.
+CREATE TABLE tournament_judges (
+	tournament_id UUID NOT NULL REFERENCES tournaments (id) ON DELETE CASCADE,
+	user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	PRIMARY KEY (tournament_id, user_id)
+);
+
+-- One published meme per user and tournament. `place` and `prize` are set on finalization, `prize` being 0 outside the prizes.
+CREATE TABLE tournament_entries (
+	meme_id UUID PRIMARY KEY REFERENCES memes (id) ON DELETE CASCADE,
+	tournament_id UUID NOT NULL REFERENCES tournaments (id) ON DELETE CASCADE,
+	user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	place INT,
+	prize BIGINT,
+	UNIQUE (tournament_id, user_id)
+);
+
+CREATE INDEX tournament_entries_user_idx ON tournament_entries (user_id);
+
+CREATE TABLE tournament_scores (
+	meme_id UUID NOT NULL REFERENCES tournament_entries (meme_id) ON DELETE CASCADE,
+	judge_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	score SMALLINT NOT NULL CHECK (score BETWEEN 1 AND 10),
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	PRIMARY KEY (meme_id, judge_id)
+);
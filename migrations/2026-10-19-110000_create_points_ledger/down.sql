@@ -0,0 +1,4 @@
+DROP TABLE ledger_entries;
+DROP FUNCTION ledger_check_balanced();
+DROP TABLE ledger_transactions;
+DROP TABLE ledger_accounts;
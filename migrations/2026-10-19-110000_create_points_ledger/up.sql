@@ -0,0 +1,131 @@
+-- Double-entry bookkeeping for points, see `src/server/ledger.rs`. Points only ever move between accounts: the entries of every
+-- transaction add up to zero. New points come out of the mint, whose balance is minus everything ever issued, and the house banks
+-- the analytics game, so those two may go negative while no other account can.
+CREATE TABLE ledger_accounts (
+	id UUID PRIMARY KEY,
+	kind TEXT NOT NULL CHECK (kind IN ('mint', 'house', 'rake', 'wallet', 'seat', 'pool')),
+	-- The owner of a wallet or seat.
+	user_id UUID REFERENCES users (id),
+	-- The poker table of a seat, the tournament of a prize pool.
+	scope UUID,
+	balance BIGINT NOT NULL DEFAULT 0,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	CHECK (balance >= 0 OR kind IN ('mint', 'house')),
+	UNIQUE NULLS NOT DISTINCT (kind, user_id, scope)
+);
+
+-- `idempotency_key` names the operation a transaction records, like `payout:<bet id>`, so retrying it finds the transaction
+-- instead of moving the points twice.
+CREATE TABLE ledger_transactions (
+	id UUID PRIMARY KEY,
+	idempotency_key TEXT NOT NULL UNIQUE,
+	kind TEXT NOT NULL,
+	memo TEXT NOT NULL,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Entries are never updated or deleted. `balance_after` is the account's balance right after the entry, for account histories.
+CREATE TABLE ledger_entries (
+	id BIGSERIAL PRIMARY KEY,
+	transaction_id UUID NOT NULL REFERENCES ledger_transactions (id),
+	account_id UUID NOT NULL REFERENCES ledger_accounts (id),
+	amount BIGINT NOT NULL CHECK (amount <> 0),
+	balance_after BIGINT NOT NULL,
+	UNIQUE (transaction_id, account_id)
+);
+
+CREATE INDEX ledger_entries_account_idx ON ledger_entries (account_id, id DESC);
+
+-- Checked at commit, once every entry of the transaction is in.
+CREATE FUNCTION ledger_check_balanced() RETURNS TRIGGER
+LANGUAGE plpgsql AS $$
+BEGIN
+	IF (SELECT SUM(amount) FROM ledger_entries WHERE transaction_id = NEW.transaction_id) <> 0 THEN
+		RAISE EXCEPTION 'ledger transaction % does not balance', NEW.transaction_id;
+	END IF;
+	RETURN NULL;
+END
+$$;
+
+CREATE CONSTRAINT TRIGGER ledger_entries_balanced AFTER INSERT ON ledger_entries
+DEFERRABLE INITIALLY DEFERRED FOR EACH ROW EXECUTE FUNCTION ledger_check_balanced();
+
+-- Replay what balances were derived from so far: 1000 welcome points per user, game stakes and payouts, tournament prizes.
+INSERT INTO ledger_accounts (id, kind) VALUES (gen_random_uuid(), 'mint'), (gen_random_uuid(), 'house'), (gen_random_uuid(), 'rake');
+INSERT INTO ledger_accounts (id, kind, user_id) SELECT gen_random_uuid(), 'wallet', id FROM users;
+INSERT INTO ledger_accounts (id, kind, scope) SELECT gen_random_uuid(), 'pool', id FROM tournaments;
+
+CREATE TEMPORARY TABLE ledger_backfill AS
+SELECT gen_random_uuid() AS id, key, kind, memo, created_at, debit, credit, amount
+FROM (
+	SELECT 'welcome:' || u.id AS key, 'welcome' AS kind, 'Welcome points' AS memo, u.created_at, mint.id AS debit, w.id AS credit, 1000::BIGINT AS amount
+	FROM users u
+	JOIN ledger_accounts w ON w.kind = 'wallet' AND w.user_id = u.id
+	JOIN ledger_accounts mint ON mint.kind = 'mint'
+	UNION ALL
+	SELECT 'bet:' || b.user_id || ':' || b.record_id, 'bet', 'Bet on ' || r.title, b.created_at, w.id, house.id, b.stake
+	FROM game_bets b
+	JOIN meme_records r ON r.id = b.record_id
+	JOIN ledger_accounts w ON w.kind = 'wallet' AND w.user_id = b.user_id
+	JOIN ledger_accounts house ON house.kind = 'house'
+	UNION ALL
+	SELECT 'payout:' || b.id, 'payout', 'Won the bet on ' || r.title, b.settled_at, house.id, w.id, b.payout
+	FROM game_bets b
+	JOIN meme_records r ON r.id = b.record_id
+	JOIN ledger_accounts w ON w.kind = 'wallet' AND w.user_id = b.user_id
+	JOIN ledger_accounts house ON house.kind = 'house'
+	WHERE b.payout > 0
+	UNION ALL
+	SELECT 'tournament:' || t.id || ':fund', 'sponsor', 'Prize pool of ' || t.title, t.created_at, mint.id, p.id, s.total
+	FROM tournaments t
+	JOIN (SELECT id, SUM(prize)::BIGINT AS total FROM tournaments, unnest(prizes) prize GROUP BY id) s ON s.id = t.id
+	JOIN ledger_accounts p ON p.kind = 'pool' AND p.scope = t.id
+	JOIN ledger_accounts mint ON mint.kind = 'mint'
+	WHERE s.total > 0
+	UNION ALL
+	SELECT 'prize:' || e.meme_id, 'prize', 'Place ' || e.place || ' in ' || t.title, t.finalized_at, p.id, w.id, e.prize
+	FROM tournament_entries e
+	JOIN tournaments t ON t.id = e.tournament_id
+	JOIN ledger_accounts p ON p.kind = 'pool' AND p.scope = t.id
+	JOIN ledger_accounts w ON w.kind = 'wallet' AND w.user_id = e.user_id
+	WHERE e.prize > 0 AND t.finalized_at IS NOT NULL
+) backfill;
+
+INSERT INTO ledger_transactions (id, idempotency_key, kind, memo, created_at) SELECT id, key, kind, memo, created_at FROM ledger_backfill;
+
+INSERT INTO ledger_entries (transaction_id, account_id, amount, balance_after)
+SELECT id, account_id, amount, 0
+FROM (
+	SELECT id, key, created_at, 0 AS leg, debit AS account_id, -amount AS amount FROM ledger_backfill
+	UNION ALL
+	SELECT id, key, created_at, 1, credit, amount FROM ledger_backfill
+) legs
+ORDER BY created_at, key, leg;
+
+-- Prizes nobody won go back to the mint.
+INSERT INTO ledger_transactions (id, idempotency_key, kind, memo, created_at)
+SELECT gen_random_uuid(), 'tournament:' || t.id || ':close', 'sponsor', 'Unclaimed prizes of ' || t.title, t.finalized_at
+FROM tournaments t
+JOIN ledger_accounts p ON p.kind = 'pool' AND p.scope = t.id
+WHERE t.finalized_at IS NOT NULL AND (SELECT COALESCE(SUM(amount), 0) FROM ledger_entries WHERE account_id = p.id) > 0;
+
+INSERT INTO ledger_entries (transaction_id, account_id, amount, balance_after)
+SELECT tx.id, legs.account_id, legs.amount, 0
+FROM ledger_transactions tx
+JOIN tournaments t ON tx.idempotency_key = 'tournament:' || t.id || ':close'
+JOIN ledger_accounts p ON p.kind = 'pool' AND p.scope = t.id
+JOIN ledger_accounts mint ON mint.kind = 'mint'
+CROSS JOIN LATERAL (SELECT COALESCE(SUM(amount), 0)::BIGINT AS unclaimed FROM ledger_entries WHERE account_id = p.id) u
+CROSS JOIN LATERAL (VALUES (p.id, -u.unclaimed, 0), (mint.id, u.unclaimed, 1)) legs (account_id, amount, leg)
+ORDER BY tx.created_at, tx.id, legs.leg;
+
+UPDATE ledger_entries e
+SET balance_after = running.balance
+FROM (SELECT id, SUM(amount) OVER (PARTITION BY account_id ORDER BY id) AS balance FROM ledger_entries) running
+WHERE running.id = e.id;
+
+UPDATE ledger_accounts a SET balance = totals.balance
+FROM (SELECT account_id, SUM(amount)::BIGINT AS balance FROM ledger_entries GROUP BY account_id) totals
+WHERE totals.account_id = a.id;
+
+DROP TABLE ledger_backfill;
@@ -0,0 +1,9 @@
+-- Blobs kept only while they are used, like images copied by the image proxy. Rows unused for long enough are swept together with
+-- their blobs, unless a saved meme, template or tournament points at the blob, in which case it is kept and the row dropped.
+CREATE TABLE cached_blobs (
+	key TEXT PRIMARY KEY,
+	kind TEXT NOT NULL,
+	last_used_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX cached_blobs_kind_last_used_at_idx ON cached_blobs (kind, last_used_at);
@@ -0,0 +1,75 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::{generate_api_key, require_active_user, require_user},
+		db::{models::ApiKey, repo, with_conn},
+	},
+	chrono::Utc,
+	diesel::Connection,
+};
+
+const MAX_API_KEYS: i64 = 10;
+const MAX_API_KEY_NAME_LEN: usize = 64;
+
+/// A key for the HTTP API, without the key itself.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ApiKeyInfo {
+	pub id: Uuid,
+	pub name: String,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+	/// Milliseconds since the unix epoch, `None` for keys never used.
+	pub last_used_at: Option<i64>,
+}
+
+#[cfg(feature = "server")]
+impl From<ApiKey> for ApiKeyInfo {
+	fn from(key: ApiKey) -> Self {
+		Self { id: key.id, name: key.name, created_at: key.created_at.timestamp_millis(), last_used_at: key.last_used_at.map(|at| at.timestamp_millis()) }
+	}
+}
+
+/// A freshly created key. `key` is not stored and cannot be shown again.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CreatedApiKey {
+	pub info: ApiKeyInfo,
+	pub key: String,
+}
+
+#[server]
+pub async fn list_api_keys() -> Result<Vec<ApiKeyInfo>, ServerFnError> {
+	let user = require_user().await?;
+	let keys = with_conn(move |conn| Ok(repo::api_keys::list_active(conn, user.id)?)).await?;
+	Ok(keys.into_iter().map(Into::into).collect())
+}
+
+#[server]
+pub async fn create_api_key(name: String) -> Result<CreatedApiKey, ServerFnError> {
+	let user = require_active_user().await?;
+	let name = name.trim().to_owned();
+	(!name.is_empty() && name.chars().count() <= MAX_API_KEY_NAME_LEN).or_bad_request("key name must be 1 to 64 characters")?;
+	let (key, key_hash) = generate_api_key();
+	let api_key = ApiKey { id: Uuid::new_v4(), user_id: user.id, name, key_hash, created_at: Utc::now(), last_used_at: None, revoked_at: None };
+	let created = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			if repo::api_keys::count_active(conn, user.id)? >= MAX_API_KEYS {
+				return Ok(HttpError::bad_request(format!("at most {MAX_API_KEYS} keys, revoke one first")));
+			}
+			repo::api_keys::create(conn, &api_key)?;
+			Ok(Ok(api_key))
+		})
+	})
+	.await??;
+	Ok(CreatedApiKey { info: created.into(), key })
+}
+
+#[server]
+pub async fn revoke_api_key(id: Uuid) -> Result<(), ServerFnError> {
+	let user = require_user().await?;
+	with_conn(move |conn| Ok(repo::api_keys::revoke(conn, user.id, id)?)).await?.or_not_found("key not found")?;
+	Ok(())
+}
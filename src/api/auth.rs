@@ -0,0 +1,79 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::{self, hash_password, verify_password},
+		db::{repo, with_conn},
+		ledger,
+	},
+	diesel::result::{DatabaseErrorKind, Error as DieselError},
+	dioxus::fullstack::StatusCode,
+};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct UserInfo {
+	pub id: Uuid,
+	pub username: String,
+	/// Shows the moderation tools. Server functions check the role in the database, not this flag.
+	pub moderator: bool,
+}
+
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+pub fn validate_username(username: &str) -> Result<(), &'static str> {
+	if !(3..=32).contains(&username.len()) {
+		return Err("username must be 3 to 32 characters");
+	}
+	if !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+		return Err("username may only contain letters, digits, '_' and '-'");
+	}
+	Ok(())
+}
+
+#[server]
+pub async fn register(username: String, password: String) -> Result<UserInfo, ServerFnError> {
+	validate_username(&username).or_else(HttpError::bad_request)?;
+	(password.len() >= MIN_PASSWORD_LEN).or_bad_request("password is too short")?;
+	let password_hash = hash_password(&password)?;
+	let user = with_conn(move |conn| {
+		if repo::users::find_by_username(conn, &username)?.is_some() {
+			return Ok(None);
+		}
+		// Another registration can take the name between the lookup and the insert.
+		let user = match repo::users::create(conn, &username, &password_hash) {
+			Err(DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => return Ok(None),
+			user => user?,
+		};
+		ledger::open_wallet(conn, user.id)?;
+		Ok(Some(user))
+	})
+	.await?
+	.or_http_error(StatusCode::CONFLICT, "username is taken")?;
+	let user = UserInfo { id: user.id, username: user.username, moderator: false };
+	auth::start_session(&user)?;
+	Ok(user)
+}
+
+#[server]
+pub async fn login(username: String, password: String) -> Result<UserInfo, ServerFnError> {
+	let user = with_conn(move |conn| Ok(repo::users::find_by_username(conn, &username)?)).await?;
+	let user = user.filter(|user| verify_password(&password, &user.password_hash)).or_unauthorized("wrong username or password")?;
+	user.banned_at.is_none().or_forbidden("this account is banned")?;
+	let user = UserInfo { id: user.id, username: user.username.clone(), moderator: user.is_moderator() };
+	auth::start_session(&user)?;
+	Ok(user)
+}
+
+#[server]
+pub async fn logout() -> Result<(), ServerFnError> {
+	auth::end_session()?;
+	Ok(())
+}
+
+#[server]
+pub async fn get_current_user() -> Result<Option<UserInfo>, ServerFnError> {
+	Ok(auth::current_user().await)
+}
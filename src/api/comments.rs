@@ -0,0 +1,172 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::{
+		api::notifications::NotificationKind,
+		server::{
+			auth::{current_user, require_active_user},
+			db::{
+				models::{Comment, Notification},
+				repo, with_conn,
+			},
+		},
+	},
+	chrono::{TimeDelta, Utc},
+	diesel::{Connection, PgConnection},
+	dioxus::fullstack::StatusCode,
+};
+
+pub const MAX_COMMENT_LEN: usize = 2000;
+/// Per account limits on new comments: at most this many in a minute and in an hour.
+const COMMENTS_PER_MINUTE: i64 = 5;
+const COMMENTS_PER_HOUR: i64 = 60;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CommentInfo {
+	pub id: Uuid,
+	pub meme_id: Uuid,
+	pub parent_id: Option<Uuid>,
+	pub author_id: Option<Uuid>,
+	pub author: Option<String>,
+	/// Markdown-lite source, empty once deleted.
+	pub body: String,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+	pub edited: bool,
+	pub deleted: bool,
+}
+
+#[cfg(feature = "server")]
+impl CommentInfo {
+	fn new(comment: Comment, author: Option<String>) -> Self {
+		let deleted = comment.deleted_at.is_some();
+		Self {
+			id: comment.id,
+			meme_id: comment.meme_id,
+			parent_id: comment.parent_id,
+			author_id: comment.user_id.filter(|_| !deleted),
+			author: author.filter(|_| !deleted),
+			body: comment.body,
+			created_at: comment.created_at.timestamp_millis(),
+			edited: comment.edited_at.is_some(),
+			deleted,
+		}
+	}
+}
+
+#[cfg(feature = "server")]
+fn validate_body(body: &str) -> Result<String, HttpError> {
+	let body = body.trim();
+	(!body.is_empty() && body.chars().count() <= MAX_COMMENT_LEN).or_bad_request("comment must be 1 to 2000 characters")?;
+	Ok(body.to_owned())
+}
+
+#[cfg(feature = "server")]
+fn check_rate_limit(conn: &mut PgConnection, user_id: Uuid) -> anyhow::Result<Result<(), HttpError>> {
+	let now = Utc::now();
+	let last_minute = repo::comments::count_by_user_since(conn, user_id, now - TimeDelta::minutes(1))?;
+	let last_hour = repo::comments::count_by_user_since(conn, user_id, now - TimeDelta::hours(1))?;
+	Ok((last_minute < COMMENTS_PER_MINUTE && last_hour < COMMENTS_PER_HOUR).or_http_error(StatusCode::TOO_MANY_REQUESTS, "slow down, too many comments"))
+}
+
+/// Whoever should hear about a new comment: the parent comment's author for replies, the post's author otherwise. Never the commenter.
+#[cfg(feature = "server")]
+fn notify(conn: &mut PgConnection, comment: &Comment, parent: Option<&Comment>, post_author: Option<Uuid>) -> anyhow::Result<()> {
+	let (recipient, kind) = match parent {
+		Some(parent) => (parent.user_id, NotificationKind::CommentReply),
+		None => (post_author, NotificationKind::PostComment),
+	};
+	let Some(recipient) = recipient.filter(|recipient| Some(*recipient) != comment.user_id) else {
+		return Ok(());
+	};
+	repo::notifications::create(
+		conn,
+		&Notification {
+			id: Uuid::new_v4(),
+			user_id: recipient,
+			actor_id: comment.user_id,
+			kind: kind.to_string(),
+			meme_id: comment.meme_id,
+			comment_id: Some(comment.id),
+			created_at: comment.created_at,
+			read_at: None,
+		},
+	)?;
+	Ok(())
+}
+
+/// The caller's own comment, as long as it is not deleted.
+#[cfg(feature = "server")]
+async fn own_comment(id: Uuid, user_id: Uuid) -> Result<Comment, ServerFnError> {
+	let comment = with_conn(move |conn| Ok(repo::comments::find(conn, id)?)).await?;
+	let comment = comment.filter(|comment| comment.deleted_at.is_none()).or_not_found("comment not found")?;
+	(comment.user_id == Some(user_id)).or_forbidden("not your comment")?;
+	Ok(comment)
+}
+
+/// Flat list in posting order, the client nests replies under their `parent_id`. Posts hidden by moderation show no comments to
+/// anyone but moderators.
+#[server]
+pub async fn list_comments(meme_id: Uuid) -> Result<Vec<CommentInfo>, ServerFnError> {
+	let moderator = current_user().await.is_some_and(|user| user.moderator);
+	let comments = with_conn(move |conn| {
+		if !repo::memes::find(conn, meme_id)?.is_some_and(|meme| meme.is_visible_post(moderator)) {
+			return Ok(HttpError::not_found("post not found"));
+		}
+		Ok(Ok(repo::comments::list_for_meme(conn, meme_id)?))
+	})
+	.await??;
+	Ok(comments.into_iter().map(|(comment, author)| CommentInfo::new(comment, author)).collect())
+}
+
+#[server]
+pub async fn create_comment(meme_id: Uuid, parent_id: Option<Uuid>, body: String) -> Result<CommentInfo, ServerFnError> {
+	let user = require_active_user().await?;
+	let body = validate_body(&body)?;
+	let username = user.username.clone();
+	let comment = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			// Concurrent comments by the user wait here, so each counts the ones committed before it.
+			repo::users::lock(conn, user.id)?;
+			if let Err(e) = check_rate_limit(conn, user.id)? {
+				return Ok(Err(e));
+			}
+			let Some(meme) = repo::memes::find(conn, meme_id)?.filter(|meme| meme.is_visible_post(user.moderator)) else {
+				return Ok(HttpError::not_found("post not found"));
+			};
+			let parent = match parent_id {
+				Some(parent_id) => match repo::comments::find(conn, parent_id)? {
+					Some(parent) if parent.meme_id == meme_id && parent.deleted_at.is_none() => Some(parent),
+					_ => return Ok(HttpError::bad_request("cannot reply to that comment")),
+				},
+				None => None,
+			};
+			let comment = repo::comments::create(conn, meme_id, parent_id, user.id, &body)?;
+			notify(conn, &comment, parent.as_ref(), meme.user_id)?;
+			Ok(Ok(comment))
+		})
+	})
+	.await??;
+	Ok(CommentInfo::new(comment, Some(username)))
+}
+
+#[server]
+pub async fn edit_comment(id: Uuid, body: String) -> Result<CommentInfo, ServerFnError> {
+	let user = require_active_user().await?;
+	let body = validate_body(&body)?;
+	own_comment(id, user.id).await?;
+	let comment = with_conn(move |conn| Ok(repo::comments::edit(conn, id, &body)?)).await?;
+	Ok(CommentInfo::new(comment, Some(user.username)))
+}
+
+/// Replies stay visible under a deleted comment, only its body and author go away.
+#[server]
+pub async fn delete_comment(id: Uuid) -> Result<CommentInfo, ServerFnError> {
+	let user = require_active_user().await?;
+	own_comment(id, user.id).await?;
+	let comment = with_conn(move |conn| Ok(repo::comments::soft_delete(conn, id)?)).await?;
+	Ok(CommentInfo::new(comment, None))
+}
@@ -0,0 +1,96 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::{current_user, require_active_user, viewer_key},
+		db::{models::MemeStats, repo, with_conn},
+	},
+	chrono::Utc,
+	diesel::Connection,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Engagement {
+	pub upvotes: i64,
+	pub downvotes: i64,
+	pub views: i64,
+	/// The caller's vote: 1, -1, or 0 when they have not voted or are logged out.
+	pub my_vote: i16,
+}
+
+impl Engagement {
+	pub fn score(&self) -> i64 {
+		self.upvotes - self.downvotes
+	}
+}
+
+#[cfg(feature = "server")]
+impl Engagement {
+	fn new(stats: Option<MemeStats>, my_vote: i16) -> Self {
+		stats.map_or(Self { my_vote, ..Self::default() }, |stats| Self {
+			upvotes: stats.upvotes.into(),
+			downvotes: stats.downvotes.into(),
+			views: stats.views,
+			my_vote,
+		})
+	}
+}
+
+/// Fails with 404 unless the meme is a post the caller can see.
+#[cfg(feature = "server")]
+async fn ensure_published(meme_id: Uuid, moderator: bool) -> Result<(), ServerFnError> {
+	let meme = with_conn(move |conn| Ok(repo::memes::find(conn, meme_id)?)).await?;
+	meme.filter(|meme| meme.is_visible_post(moderator)).or_not_found("post not found")?;
+	Ok(())
+}
+
+/// Sets the caller's vote on a published meme: 1 up, -1 down, 0 takes the vote back. Each account holds one vote per meme. Votes on
+/// the entries of an ended tournament are closed, so its standings stay as they were at the end.
+#[server]
+pub async fn vote(meme_id: Uuid, value: i16) -> Result<Engagement, ServerFnError> {
+	let user = require_active_user().await?;
+	(-1..=1).contains(&value).or_bad_request("vote must be -1, 0 or 1")?;
+	ensure_published(meme_id, user.moderator).await?;
+	let stats = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			if repo::tournaments::voting_closed(conn, meme_id, Utc::now())? {
+				return Ok(HttpError::bad_request("voting on this tournament entry has closed"));
+			}
+			if value == 0 {
+				repo::votes::retract(conn, user.id, meme_id)?;
+			} else {
+				repo::votes::cast(conn, user.id, meme_id, value)?;
+			}
+			Ok(Ok(repo::stats::refresh_votes(conn, meme_id)?))
+		})
+	})
+	.await??;
+	Ok(Engagement::new(Some(stats), value))
+}
+
+#[server]
+pub async fn get_engagement(meme_id: Uuid) -> Result<Engagement, ServerFnError> {
+	let user = current_user().await;
+	let (stats, my_vote) = with_conn(move |conn| {
+		let stats = repo::stats::find(conn, meme_id)?;
+		let my_vote = match user {
+			Some(user) => repo::votes::find_for_user(conn, user.id, &[meme_id])?.first().map_or(0, |(_, value)| *value),
+			None => 0,
+		};
+		Ok((stats, my_vote))
+	})
+	.await?;
+	Ok(Engagement::new(stats, my_vote))
+}
+
+/// Counts a view of a published meme, at most once per viewer per day. Returns whether this call counted.
+#[server]
+pub async fn record_view(meme_id: Uuid) -> Result<bool, ServerFnError> {
+	ensure_published(meme_id, current_user().await.is_some_and(|user| user.moderator)).await?;
+	let viewer = viewer_key().await?;
+	let today = Utc::now().date_naive();
+	Ok(with_conn(move |conn| Ok(repo::stats::record_view(conn, meme_id, &viewer, today)?)).await?)
+}
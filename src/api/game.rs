@@ -0,0 +1,139 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game::percentile::PercentileWindow;
+
+#[cfg(feature = "server")]
+use {
+	crate::api::points::TransactionKind,
+	crate::game::percentile::validate_stake,
+	crate::server::{
+		auth::require_active_user,
+		db::{
+			models::{GameBet, MemeRecord},
+			repo, with_conn,
+		},
+		game::{MIN_REFERENCE_SIZE, settle_bet},
+		ledger::{self, Account, Posted, Posting},
+	},
+	diesel::Connection,
+};
+
+const MAX_LISTED_BETS: i64 = 50;
+
+/// A meme to bet on. Its engagement stays hidden until the bet settles.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct GameRound {
+	pub record_id: Uuid,
+	pub source: String,
+	pub title: String,
+	pub image_url: String,
+	/// Milliseconds since the unix epoch.
+	pub posted_at: i64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BetResult {
+	pub percentile: f64,
+	/// 0 for a lost bet.
+	pub payout: i64,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BetInfo {
+	pub id: Uuid,
+	pub round: GameRound,
+	pub window: PercentileWindow,
+	pub stake: i64,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+	/// `None` while the meme's engagement is not final.
+	pub result: Option<BetResult>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GameOverview {
+	pub balance: i64,
+	pub bets: Vec<BetInfo>,
+}
+
+#[cfg(feature = "server")]
+impl From<MemeRecord> for GameRound {
+	fn from(record: MemeRecord) -> Self {
+		Self { record_id: record.id, source: record.source, title: record.title, image_url: record.image_url, posted_at: record.posted_at.timestamp_millis() }
+	}
+}
+
+#[cfg(feature = "server")]
+impl BetInfo {
+	fn new(bet: GameBet, record: MemeRecord) -> Self {
+		Self {
+			id: bet.id,
+			round: record.into(),
+			window: PercentileWindow { low: bet.window_low as u8, high: bet.window_high as u8 },
+			stake: bet.stake,
+			created_at: bet.created_at.timestamp_millis(),
+			result: bet.percentile.zip(bet.payout).map(|(percentile, payout)| BetResult { percentile, payout }),
+		}
+	}
+}
+
+/// The next meme to bet on, `None` once the caller has bet on everything there is.
+#[server]
+pub async fn next_round() -> Result<Option<GameRound>, ServerFnError> {
+	let user = require_active_user().await?;
+	let record = with_conn(move |conn| Ok(repo::meme_records::random_unplayed(conn, user.id, MIN_REFERENCE_SIZE)?)).await?;
+	Ok(record.map(Into::into))
+}
+
+/// Stakes points on the meme's percentile landing in `window`. Memes whose engagement is already final settle right away.
+#[server]
+pub async fn place_bet(record_id: Uuid, window: PercentileWindow, stake: i64) -> Result<BetInfo, ServerFnError> {
+	let user = require_active_user().await?;
+	let window = PercentileWindow::new(window.low, window.high).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	validate_stake(stake).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let (bet, record) = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			let Some(record) = repo::meme_records::find(conn, record_id)? else {
+				return Ok(HttpError::not_found("meme not found"));
+			};
+			if repo::game_bets::exists(conn, user.id, record_id)? {
+				return Ok(HttpError::bad_request("you already bet on this meme"));
+			}
+			// One bet per player and meme, so the pair names the stake.
+			let stake_posting = Posting::transfer(
+				format!("bet:{}:{record_id}", user.id),
+				TransactionKind::Bet,
+				format!("Bet on {}", record.title),
+				Account::Wallet(user.id),
+				Account::House,
+				stake,
+			);
+			match ledger::post(conn, &stake_posting)? {
+				Posted::Applied => {},
+				Posted::Duplicate => return Ok(HttpError::bad_request("you already bet on this meme")),
+				Posted::Insufficient(_) => return Ok(HttpError::bad_request("not enough points")),
+			}
+			let bet = repo::game_bets::create(conn, user.id, record_id, window, stake)?;
+			let bet = settle_bet(conn, &bet, &record)?.unwrap_or(bet);
+			Ok(Ok((bet, record)))
+		})
+	})
+	.await??;
+	Ok(BetInfo::new(bet, record))
+}
+
+/// The caller's points and most recent bets.
+#[server]
+pub async fn game_overview() -> Result<GameOverview, ServerFnError> {
+	let user = require_active_user().await?;
+	let (balance, bets) = with_conn(move |conn| {
+		ledger::open_wallet(conn, user.id)?;
+		let balance = ledger::balance(conn, Account::Wallet(user.id))?;
+		let bets = repo::game_bets::list_for_user(conn, user.id, MAX_LISTED_BETS)?;
+		Ok((balance, bets))
+	})
+	.await?;
+	Ok(GameOverview { balance, bets: bets.into_iter().map(|(bet, record)| BetInfo::new(bet, record)).collect() })
+}
@@ -0,0 +1,58 @@
+use crate::stores::meme_canvas::MemeDocument;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::{current_user, require_active_user},
+		meme_store::{Deleter, Deletion, with_store},
+	},
+	dioxus::fullstack::StatusCode,
+};
+
+const MAX_LIST_LIMIT: u32 = 100;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct MemeSummary {
+	pub id: Uuid,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+	pub main_img_url: String,
+	/// Text of every text box, joined for display.
+	pub caption: String,
+}
+
+/// Saves the meme for sharing, owned by the caller when signed in. Memes saved without an account can only be deleted by moderators.
+#[server]
+pub async fn save_meme(document: MemeDocument) -> Result<Uuid, ServerFnError> {
+	document.validate().or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let owner = current_user().await.map(|user| user.id);
+	Ok(with_store(move |store| store.save(&document, owner)).await?)
+}
+
+#[server]
+pub async fn get_meme(id: Uuid) -> Result<MemeDocument, ServerFnError> {
+	Ok(with_store(move |store| store.get(id)).await?.or_not_found("meme not found")?)
+}
+
+#[server]
+pub async fn list_recent_memes(limit: u32) -> Result<Vec<MemeSummary>, ServerFnError> {
+	Ok(with_store(move |store| store.list_recent(limit.min(MAX_LIST_LIMIT) as usize)).await?)
+}
+
+/// Deletes a saved, unpublished meme of the caller's, or any of them for moderators. Published posts go through moderation, so the
+/// removal is audited.
+#[server]
+pub async fn delete_meme(id: Uuid) -> Result<(), ServerFnError> {
+	let user = require_active_user().await?;
+	let deleter = Deleter { user_id: user.id, moderator: user.moderator };
+	let (status, message) = match with_store(move |store| store.delete(id, deleter)).await? {
+		Deletion::Deleted => return Ok(()),
+		Deletion::NotFound => (StatusCode::NOT_FOUND, "meme not found"),
+		Deletion::Forbidden => (StatusCode::FORBIDDEN, "only its author or a moderator can delete this meme"),
+		Deletion::Published => (StatusCode::BAD_REQUEST, "published posts are removed through moderation"),
+	};
+	Err(HttpError::new(status, message).into())
+}
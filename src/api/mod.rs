@@ -0,0 +1,16 @@
+pub mod api_keys;
+pub mod auth;
+pub mod comments;
+pub mod engagement;
+pub mod game;
+pub mod memes;
+pub mod moderation;
+pub mod notifications;
+pub mod points;
+pub mod posts;
+pub mod remixes;
+pub mod roadmap;
+pub mod search;
+pub mod tables;
+pub mod templates;
+pub mod tournaments;
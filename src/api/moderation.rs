@@ -0,0 +1,211 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::{require_active_user, require_moderator},
+		db::{
+			models::AuditEntry,
+			repo::{self, reports::QueueRow},
+			with_conn,
+		},
+		filters::render_hash,
+	},
+	diesel::Connection,
+	std::str::FromStr,
+};
+
+pub const MAX_REPORT_DETAILS_LEN: usize = 500;
+pub const MAX_MOD_NOTE_LEN: usize = 500;
+const QUEUE_SIZE: i64 = 50;
+const AUDIT_LOG_SIZE: i64 = 100;
+
+/// Stored as its snake case name in `reports.reason`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum ReportReason {
+	Spam,
+	Harassment,
+	Hate,
+	Sexual,
+	Violence,
+	Copyright,
+	Other,
+	/// Filed by an automatic filter on publish, never by a user.
+	Filter,
+}
+
+impl ReportReason {
+	/// The reasons offered in the report form.
+	pub fn selectable() -> impl Iterator<Item = Self> {
+		Self::iter().filter(|reason| *reason != Self::Filter)
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Spam => "Spam",
+			Self::Harassment => "Harassment or bullying",
+			Self::Hate => "Hate speech",
+			Self::Sexual => "Sexual content",
+			Self::Violence => "Violence or gore",
+			Self::Copyright => "Copyright infringement",
+			Self::Other => "Something else",
+			Self::Filter => "Automatic filter",
+		}
+	}
+}
+
+/// Stored as its snake case name in `audit_log.action`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum ModAction {
+	/// Close the reports and leave the post up.
+	Dismiss,
+	Hide,
+	Unhide,
+	/// Delete the post and block its image from being published again.
+	Delete,
+	/// Ban the author and hide all of their posts.
+	Ban,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct QueueItem {
+	pub meme_id: Uuid,
+	pub title: String,
+	pub image_url: String,
+	pub author_id: Option<Uuid>,
+	pub author: Option<String>,
+	pub hidden: bool,
+	pub reports: i64,
+	pub reasons: Vec<ReportReason>,
+	pub details: Vec<String>,
+	/// Milliseconds since the unix epoch.
+	pub first_reported_at: i64,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+	pub id: Uuid,
+	pub moderator: Option<String>,
+	/// A [`ModAction`] name.
+	pub action: String,
+	pub meme_id: Option<Uuid>,
+	pub user_id: Option<Uuid>,
+	pub note: String,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+}
+
+#[cfg(feature = "server")]
+impl From<QueueRow> for QueueItem {
+	fn from(row: QueueRow) -> Self {
+		Self {
+			meme_id: row.meme_id,
+			title: row.title,
+			image_url: row.image_url,
+			author_id: row.author_id,
+			author: row.author,
+			hidden: row.hidden,
+			reports: row.reports,
+			reasons: row.reasons.iter().filter_map(|reason| ReportReason::from_str(reason).ok()).collect(),
+			details: row.details,
+			first_reported_at: row.first_reported_at.timestamp_millis(),
+		}
+	}
+}
+
+#[cfg(feature = "server")]
+impl From<(AuditEntry, Option<String>)> for AuditLogEntry {
+	fn from((entry, moderator): (AuditEntry, Option<String>)) -> Self {
+		Self {
+			id: entry.id,
+			moderator,
+			action: entry.action,
+			meme_id: entry.meme_id,
+			user_id: entry.user_id,
+			note: entry.note,
+			created_at: entry.created_at.timestamp_millis(),
+		}
+	}
+}
+
+#[server]
+pub async fn report_post(meme_id: Uuid, reason: ReportReason, details: String) -> Result<(), ServerFnError> {
+	let user = require_active_user().await?;
+	(reason != ReportReason::Filter).or_bad_request("pick a reason")?;
+	let details = details.trim().to_owned();
+	(details.chars().count() <= MAX_REPORT_DETAILS_LEN).or_bad_request("details must be at most 500 characters")?;
+	with_conn(move |conn| {
+		if repo::posts::find(conn, meme_id)?.is_none() {
+			return Ok(false);
+		}
+		repo::reports::upsert(conn, meme_id, user.id, &reason.to_string(), &details)?;
+		Ok(true)
+	})
+	.await?
+	.or_not_found("post not found")?;
+	Ok(())
+}
+
+#[server]
+pub async fn moderation_queue() -> Result<Vec<QueueItem>, ServerFnError> {
+	require_moderator().await?;
+	let rows = with_conn(|conn| Ok(repo::reports::queue(conn, QUEUE_SIZE)?)).await?;
+	Ok(rows.into_iter().map(Into::into).collect())
+}
+
+#[server]
+pub async fn moderation_log() -> Result<Vec<AuditLogEntry>, ServerFnError> {
+	require_moderator().await?;
+	let entries = with_conn(|conn| Ok(repo::audit_log::list_recent(conn, AUDIT_LOG_SIZE)?)).await?;
+	Ok(entries.into_iter().map(Into::into).collect())
+}
+
+/// Applies `action` to a post, closes its open reports and records the action in the audit log.
+#[server]
+pub async fn moderate(meme_id: Uuid, action: ModAction, note: String) -> Result<(), ServerFnError> {
+	let moderator = require_moderator().await?;
+	let note = note.trim().to_owned();
+	(note.chars().count() <= MAX_MOD_NOTE_LEN).or_bad_request("note must be at most 500 characters")?;
+	let meme = with_conn(move |conn| Ok(repo::memes::find(conn, meme_id)?)).await?.or_not_found("post not found")?;
+	if action == ModAction::Ban {
+		let author_id = meme.user_id.or_bad_request("this post has no author to ban")?;
+		let author = with_conn(move |conn| Ok(repo::users::find(conn, author_id)?)).await?.or_not_found("author not found")?;
+		(!author.is_moderator()).or_forbidden("moderators cannot be banned")?;
+	}
+	with_conn(move |conn| {
+		conn.transaction(|conn| {
+			match action {
+				ModAction::Dismiss => {},
+				ModAction::Hide => {
+					repo::memes::set_hidden(conn, meme.id, true)?;
+				},
+				ModAction::Unhide => {
+					repo::memes::set_hidden(conn, meme.id, false)?;
+				},
+				ModAction::Delete => {
+					if let Some(hash) = meme.image_url.as_deref().and_then(render_hash) {
+						repo::blocked_images::block(conn, hash, Some(moderator.id))?;
+					}
+					repo::memes::delete(conn, meme.id)?;
+				},
+				ModAction::Ban => {
+					if let Some(author_id) = meme.user_id {
+						repo::users::ban(conn, author_id)?;
+						repo::memes::hide_all_by_user(conn, author_id)?;
+						repo::reports::resolve_for_author(conn, author_id)?;
+					}
+				},
+			}
+			repo::reports::resolve_for_memes(conn, &[meme.id])?;
+			repo::audit_log::record(conn, Some(moderator.id), &action.to_string(), Some(meme.id), meme.user_id, &note)?;
+			Ok(())
+		})
+	})
+	.await?;
+	Ok(())
+}
@@ -0,0 +1,70 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use crate::server::{
+	auth::require_user,
+	db::{repo, with_conn},
+};
+
+const MAX_NOTIFICATIONS: i64 = 50;
+
+/// Stored as its snake case name in `notifications.kind`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum NotificationKind {
+	/// Someone replied to one of your comments.
+	CommentReply,
+	/// Someone commented on one of your posts.
+	PostComment,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct NotificationInfo {
+	pub id: Uuid,
+	pub kind: NotificationKind,
+	pub actor: Option<String>,
+	pub meme_id: Uuid,
+	pub meme_title: Option<String>,
+	pub comment_id: Option<Uuid>,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+	pub read: bool,
+}
+
+#[server]
+pub async fn list_notifications() -> Result<Vec<NotificationInfo>, ServerFnError> {
+	let user = require_user().await?;
+	let rows = with_conn(move |conn| Ok(repo::notifications::list_for_user(conn, user.id, MAX_NOTIFICATIONS)?)).await?;
+	Ok(
+		rows
+			.into_iter()
+			.filter_map(|(notification, actor, meme_title)| {
+				Some(NotificationInfo {
+					id: notification.id,
+					kind: notification.kind.parse().ok()?,
+					actor,
+					meme_id: notification.meme_id,
+					meme_title,
+					comment_id: notification.comment_id,
+					created_at: notification.created_at.timestamp_millis(),
+					read: notification.read_at.is_some(),
+				})
+			})
+			.collect(),
+	)
+}
+
+#[server]
+pub async fn unread_notification_count() -> Result<i64, ServerFnError> {
+	let user = require_user().await?;
+	Ok(with_conn(move |conn| Ok(repo::notifications::count_unread(conn, user.id)?)).await?)
+}
+
+#[server]
+pub async fn mark_notifications_read() -> Result<(), ServerFnError> {
+	let user = require_user().await?;
+	with_conn(move |conn| Ok(repo::notifications::mark_all_read(conn, user.id)?)).await?;
+	Ok(())
+}
@@ -0,0 +1,108 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::require_user,
+		db::{
+			models::{LedgerEntry, LedgerTransaction},
+			repo, with_conn,
+		},
+		ledger::{self, Account},
+	},
+	std::str::FromStr,
+};
+
+const HISTORY_PAGE_SIZE: i64 = 50;
+
+/// What a ledger transaction records. Stored as its snake_case name in `ledger_transactions.kind`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum TransactionKind {
+	/// Points every player starts with.
+	Welcome,
+	/// A stake on the analytics game.
+	Bet,
+	/// A won bet paid out by the house.
+	Payout,
+	/// A tournament's prize pool issued, or its unclaimed prizes returned.
+	Sponsor,
+	Prize,
+	BuyIn,
+	CashOut,
+	/// Chips won and lost over a poker hand, and the rake taken from its pots.
+	Hand,
+	/// Chips paid back from a table that closed with the server.
+	Refund,
+}
+
+impl TransactionKind {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Welcome => "Welcome",
+			Self::Bet => "Bet",
+			Self::Payout => "Bet won",
+			Self::Sponsor => "Sponsorship",
+			Self::Prize => "Prize",
+			Self::BuyIn => "Buy-in",
+			Self::CashOut => "Cash-out",
+			Self::Hand => "Poker hand",
+			Self::Refund => "Refund",
+		}
+	}
+}
+
+/// One movement on the caller's points.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PointsLine {
+	pub id: i64,
+	/// `None` for kinds this build does not know.
+	pub kind: Option<TransactionKind>,
+	pub memo: String,
+	/// Positive when points came in.
+	pub amount: i64,
+	/// The balance right after.
+	pub balance: i64,
+	/// Milliseconds since the unix epoch.
+	pub created_at: i64,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PointsHistory {
+	pub balance: i64,
+	/// Newest first.
+	pub lines: Vec<PointsLine>,
+	/// Pass back to load older lines, `None` on the last page.
+	pub next_cursor: Option<i64>,
+}
+
+#[cfg(feature = "server")]
+impl PointsLine {
+	fn new(entry: LedgerEntry, transaction: LedgerTransaction) -> Self {
+		Self {
+			id: entry.id,
+			kind: TransactionKind::from_str(&transaction.kind).ok(),
+			memo: transaction.memo,
+			amount: entry.amount,
+			balance: entry.balance_after,
+			created_at: transaction.created_at.timestamp_millis(),
+		}
+	}
+}
+
+/// The caller's balance and the movements that led to it, newest first.
+#[server]
+pub async fn points_history(cursor: Option<i64>) -> Result<PointsHistory, ServerFnError> {
+	let user = require_user().await?;
+	let (balance, entries) = with_conn(move |conn| {
+		ledger::open_wallet(conn, user.id)?;
+		let wallet = ledger::account(conn, Account::Wallet(user.id))?;
+		let entries = repo::ledger::history(conn, wallet.id, cursor, HISTORY_PAGE_SIZE)?;
+		Ok((wallet.balance, entries))
+	})
+	.await?;
+	let next_cursor = entries.last().filter(|_| entries.len() as i64 == HISTORY_PAGE_SIZE).map(|(entry, _)| entry.id);
+	let lines = entries.into_iter().map(|(entry, transaction)| PointsLine::new(entry, transaction)).collect();
+	Ok(PointsHistory { balance, lines, next_cursor })
+}
@@ -0,0 +1,208 @@
+use crate::stores::meme_canvas::MemeDocument;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::api::{moderation::ReportReason, tournaments::check_entry},
+	crate::server::{
+		auth::{current_user, require_active_user},
+		blob::{BLOB_STORE, BlobKind, decode_data_url, store_image},
+		db::{
+			repo::{self, posts::PostRow},
+			with_conn,
+		},
+		filters::{Submission, Verdict, screen},
+		image_hash::dhash_blocking,
+		render::{RenderFormat, render_document},
+	},
+	crate::tags::normalize_tags,
+	diesel::{Connection, PgConnection},
+	dioxus::fullstack::StatusCode,
+	std::str::FromStr,
+};
+
+pub const MAX_TITLE_LEN: usize = 120;
+const FEED_PAGE_SIZE: i64 = 20;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum FeedSort {
+	/// Score decayed by age, so fresh posts with some votes outrank old ones with many.
+	#[default]
+	Hot,
+	New,
+	Top,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PostSummary {
+	pub id: Uuid,
+	pub title: String,
+	pub image_url: String,
+	pub author: Option<String>,
+	/// Milliseconds since the unix epoch.
+	pub published_at: i64,
+	pub score: i64,
+	pub views: i64,
+	/// The caller's vote: 1, -1, or 0 when they have not voted or are logged out.
+	pub my_vote: i16,
+	pub tags: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PostDetail {
+	pub summary: PostSummary,
+	pub document: MemeDocument,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct FeedPage {
+	pub posts: Vec<PostSummary>,
+	/// Pass back to [`get_feed`] for the next page, `None` once the feed is exhausted.
+	pub next_cursor: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl From<PostRow> for PostSummary {
+	fn from(row: PostRow) -> Self {
+		Self {
+			id: row.id,
+			title: row.title,
+			image_url: row.image_url,
+			author: row.author,
+			published_at: row.published_at.timestamp_millis(),
+			score: row.score,
+			views: row.views,
+			my_vote: 0,
+			tags: row.tags,
+		}
+	}
+}
+
+/// Cursors are `<sort key>_<id>` of the last post on the page: the hot score, microseconds of `published_at` or the score.
+#[cfg(feature = "server")]
+fn parse_cursor<K: FromStr>(cursor: Option<&str>) -> Result<Option<(K, Uuid)>, HttpError> {
+	let Some(cursor) = cursor else {
+		return Ok(None);
+	};
+	let parsed = cursor.split_once('_').and_then(|(key, id)| Some((key.parse().ok()?, id.parse().ok()?)));
+	Ok(Some(parsed.or_bad_request("invalid cursor")?))
+}
+
+/// Converts rows to summaries carrying the caller's own votes.
+#[cfg(feature = "server")]
+pub(crate) fn summaries(conn: &mut PgConnection, rows: Vec<PostRow>, user_id: Option<Uuid>) -> anyhow::Result<Vec<PostSummary>> {
+	let mut posts: Vec<PostSummary> = rows.into_iter().map(Into::into).collect();
+	if let Some(user_id) = user_id {
+		let votes = repo::votes::find_for_user(conn, user_id, &posts.iter().map(|post| post.id).collect::<Vec<_>>())?;
+		for post in &mut posts {
+			post.my_vote = votes.iter().find(|(meme_id, _)| *meme_id == post.id).map_or(0, |(_, value)| *value);
+		}
+	}
+	Ok(posts)
+}
+
+/// Renders are screened by the content filters, then uploaded before the post is created so a failed upload never leaves a post
+/// without an image. `parent_id` is the post being remixed and `tournament_id` the tournament the post is entered into, if any.
+/// Tournament entries are rendered on the server from `document`, and `render` is ignored for them.
+#[server]
+pub async fn publish_meme(
+	document: MemeDocument,
+	title: String,
+	render: String,
+	tags: Vec<String>,
+	parent_id: Option<Uuid>,
+	tournament_id: Option<Uuid>,
+) -> Result<Uuid, ServerFnError> {
+	let user = require_active_user().await?;
+	document.validate().or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let title = title.trim().to_owned();
+	(!title.is_empty() && title.chars().count() <= MAX_TITLE_LEN).or_bad_request("title must be 1 to 120 characters")?;
+	let tags = normalize_tags(tags).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	if let Some(parent_id) = parent_id {
+		with_conn(move |conn| Ok(repo::posts::find(conn, parent_id)?)).await?.or_bad_request("remixed post not found")?;
+	}
+	if let Some(tournament_id) = tournament_id {
+		let document = document.clone();
+		with_conn(move |conn| check_entry(conn, tournament_id, user.id, &document)).await??;
+	}
+	let (bytes, content_type) = if tournament_id.is_some() {
+		// Entries are judged on their image, so it is drawn from the checked document rather than taken from the client.
+		let rendered = render_document(document.clone(), RenderFormat::Png).await.map_err(|(status, message)| HttpError::new(status, message))?;
+		(rendered.bytes, rendered.mime.content_type().to_owned())
+	} else {
+		decode_data_url(&render).or_else(|e| HttpError::bad_request(format!("{e:#}")))?
+	};
+	let dhash = dhash_blocking(bytes.clone()).await.or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let caption = document.caption();
+	let verdict = screen(&Submission { title: &title, caption: &caption, tags: &tags, image: &bytes, dhash }).await?;
+	if let Verdict::Reject(reason) = verdict {
+		return Err(HttpError::new(StatusCode::UNPROCESSABLE_ENTITY, reason).into());
+	}
+	let key = store_image(BlobKind::Render, bytes, Some(&content_type)).await.or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let image_url = BLOB_STORE.public_url(&key);
+	let meme = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			let meme = repo::memes::insert_document(conn, Some(user.id), &document)?;
+			let meme = repo::memes::publish(conn, meme.id, &title, &image_url, &tags, parent_id)?;
+			repo::memes::set_dhash(conn, meme.id, dhash)?;
+			repo::stats::refresh_votes(conn, meme.id)?;
+			if let Some(tournament_id) = tournament_id {
+				repo::tournaments::add_entry(conn, tournament_id, meme.id, user.id)?;
+			}
+			if let Verdict::Flag(details) = &verdict {
+				repo::reports::create_automatic(conn, meme.id, &ReportReason::Filter.to_string(), details)?;
+			}
+			Ok(meme)
+		})
+	})
+	.await?;
+	Ok(meme.id)
+}
+
+#[server]
+pub async fn get_feed(sort: FeedSort, cursor: Option<String>) -> Result<FeedPage, ServerFnError> {
+	let user_id = current_user().await.map(|user| user.id);
+	let cursor = cursor.as_deref();
+	let rows = match sort {
+		FeedSort::Hot => {
+			let after = parse_cursor::<f64>(cursor)?;
+			with_conn(move |conn| Ok(repo::posts::list_hot(conn, after, FEED_PAGE_SIZE)?)).await?
+		},
+		FeedSort::New => {
+			let after = match parse_cursor::<i64>(cursor)? {
+				Some((micros, id)) => Some((chrono::DateTime::from_timestamp_micros(micros).or_bad_request("invalid cursor")?, id)),
+				None => None,
+			};
+			with_conn(move |conn| Ok(repo::posts::list_new(conn, after, FEED_PAGE_SIZE)?)).await?
+		},
+		FeedSort::Top => {
+			let after = parse_cursor::<i64>(cursor)?;
+			with_conn(move |conn| Ok(repo::posts::list_top(conn, after, FEED_PAGE_SIZE)?)).await?
+		},
+	};
+	let next_cursor = rows.last().filter(|_| rows.len() as i64 == FEED_PAGE_SIZE).map(|last| match sort {
+		FeedSort::Hot => format!("{}_{}", last.hot, last.id),
+		FeedSort::New => format!("{}_{}", last.published_at.timestamp_micros(), last.id),
+		FeedSort::Top => format!("{}_{}", last.score, last.id),
+	});
+	let posts = with_conn(move |conn| summaries(conn, rows, user_id)).await?;
+	Ok(FeedPage { posts, next_cursor })
+}
+
+#[server]
+pub async fn get_post(id: Uuid) -> Result<PostDetail, ServerFnError> {
+	let user_id = current_user().await.map(|user| user.id);
+	let post = with_conn(move |conn| {
+		let Some(row) = repo::posts::find(conn, id)? else {
+			return Ok(None);
+		};
+		let document = repo::memes::find_document(conn, id)?.unwrap_or_default();
+		let summary = summaries(conn, vec![row], user_id)?.remove(0);
+		Ok(Some(PostDetail { summary, document }))
+	})
+	.await?;
+	Ok(post.or_not_found("post not found")?)
+}
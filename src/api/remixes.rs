@@ -0,0 +1,46 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use crate::server::db::{repo, repo::remixes::RemixRow, with_conn};
+
+/// How far the lineage is followed in either direction, which also bounds the recursive queries.
+const MAX_LINEAGE_DEPTH: i32 = 10;
+const MAX_REMIXES: i64 = 100;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RemixNode {
+	pub id: Uuid,
+	pub parent_id: Option<Uuid>,
+	pub title: String,
+	pub image_url: String,
+	pub author: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Lineage {
+	/// The chain this post was remixed from, the original first.
+	pub ancestors: Vec<RemixNode>,
+	/// Remixes of this post and of those remixes, each listed after its parent.
+	pub remixes: Vec<RemixNode>,
+}
+
+#[cfg(feature = "server")]
+impl From<RemixRow> for RemixNode {
+	fn from(row: RemixRow) -> Self {
+		Self { id: row.id, parent_id: row.parent_id, title: row.title, image_url: row.image_url, author: row.author }
+	}
+}
+
+#[server]
+pub async fn get_lineage(id: Uuid) -> Result<Lineage, ServerFnError> {
+	Ok(
+		with_conn(move |conn| {
+			let ancestors = repo::remixes::ancestors(conn, id, MAX_LINEAGE_DEPTH)?;
+			let remixes = repo::remixes::descendants(conn, id, MAX_LINEAGE_DEPTH, MAX_REMIXES)?;
+			Ok(Lineage { ancestors: ancestors.into_iter().map(Into::into).collect(), remixes: remixes.into_iter().map(Into::into).collect() })
+		})
+		.await?,
+	)
+}
@@ -0,0 +1,152 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "server")]
+use {
+	anyhow::Context,
+	parking_lot::Mutex,
+	std::{sync::LazyLock, time::SystemTime},
+};
+
+/// The roadmap shipped with the build, used unless `ROADMAP_FILE` points at another.
+#[cfg(feature = "server")]
+const EMBEDDED_ROADMAP: &str = include_str!("../../assets/roadmap.json");
+
+#[cfg(feature = "server")]
+static EMBEDDED_STAGES: LazyLock<Vec<RoadmapStage>> = LazyLock::new(|| parse_roadmap(EMBEDDED_ROADMAP).expect("the embedded roadmap is checked by the tests"));
+
+/// The enabled stages of `ROADMAP_FILE`, with the modification time of the file they were read from.
+#[cfg(feature = "server")]
+static FILE_STAGES: Mutex<Option<(SystemTime, Vec<RoadmapStage>)>> = Mutex::new(None);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display, strum::EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+	Planned,
+	#[strum(serialize = "In progress")]
+	InProgress,
+	Done,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RoadmapItem {
+	pub title: String,
+	pub description: String,
+	pub status: ItemStatus,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RoadmapStage {
+	pub title: String,
+	pub description: String,
+	/// Disabled stages stay in the file but are not shown.
+	#[serde(default = "enabled")]
+	pub enabled: bool,
+	pub items: Vec<RoadmapItem>,
+}
+
+fn enabled() -> bool {
+	true
+}
+
+impl RoadmapStage {
+	/// The stage's [`progress`].
+	pub fn progress(&self) -> u32 {
+		progress(&self.items)
+	}
+}
+
+/// Share of the items that is finished, from 0 to 100. Items in progress count for half.
+pub fn progress<'a>(items: impl IntoIterator<Item = &'a RoadmapItem>) -> u32 {
+	let (halves, count) = items.into_iter().fold((0_u32, 0_u32), |(halves, count), item| {
+		let done = match item.status {
+			ItemStatus::Planned => 0,
+			ItemStatus::InProgress => 1,
+			ItemStatus::Done => 2,
+		};
+		(halves + done, count + 1)
+	});
+	(halves * 50).checked_div(count).unwrap_or(0)
+}
+
+/// The enabled stages in `contents`.
+#[cfg(feature = "server")]
+fn parse_roadmap(contents: &str) -> serde_json::Result<Vec<RoadmapStage>> {
+	let stages = serde_json::from_str::<Vec<RoadmapStage>>(contents)?;
+	Ok(stages.into_iter().filter(|stage| stage.enabled).collect())
+}
+
+/// `ROADMAP_FILE` is read again only once it was modified, so edits show up without a restart.
+#[cfg(feature = "server")]
+async fn read_roadmap() -> anyhow::Result<Vec<RoadmapStage>> {
+	let Ok(path) = std::env::var("ROADMAP_FILE") else {
+		return Ok(EMBEDDED_STAGES.clone());
+	};
+	let modified = tokio::fs::metadata(&path).await.and_then(|metadata| metadata.modified()).with_context(|| format!("cannot read ROADMAP_FILE at {path}"))?;
+	if let Some((read_at, stages)) = &*FILE_STAGES.lock()
+		&& *read_at == modified
+	{
+		return Ok(stages.clone());
+	}
+	let contents = tokio::fs::read_to_string(&path).await.with_context(|| format!("cannot read ROADMAP_FILE at {path}"))?;
+	let stages = parse_roadmap(&contents).with_context(|| format!("cannot parse {path}"))?;
+	*FILE_STAGES.lock() = Some((modified, stages.clone()));
+	Ok(stages)
+}
+
+/// The enabled roadmap stages.
+#[server]
+pub async fn roadmap() -> Result<Vec<RoadmapStage>, ServerFnError> {
+	Ok(read_roadmap().await?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(status: ItemStatus) -> RoadmapItem {
+		RoadmapItem { title: String::new(), description: String::new(), status }
+	}
+
+	#[test]
+	fn progress_counts_items_in_progress_for_half() {
+		use ItemStatus::{Done, InProgress, Planned};
+		let cases: &[(&[ItemStatus], u32)] = &[
+			(&[], 0),
+			(&[Planned], 0),
+			(&[InProgress], 50),
+			(&[Done], 100),
+			(&[Done, Planned], 50),
+			(&[Done, InProgress, Planned], 50),
+			(&[Done, Done, InProgress], 83),
+			(&[Done, Planned, Planned], 33),
+		];
+		for &(statuses, percent) in cases {
+			let items = statuses.iter().map(|&status| item(status)).collect::<Vec<_>>();
+			assert_eq!(progress(&items), percent, "{statuses:?}");
+		}
+	}
+
+	#[cfg(feature = "server")]
+	#[test]
+	fn embedded_roadmap_parses() {
+		let stages = parse_roadmap(EMBEDDED_ROADMAP).unwrap();
+		assert!(!stages.is_empty());
+		assert!(stages.iter().all(|stage| !stage.title.is_empty() && !stage.items.is_empty()), "{stages:#?}");
+	}
+
+	#[cfg(feature = "server")]
+	#[test]
+	fn disabled_stages_are_dropped() {
+		let stages = parse_roadmap(
+			r#"[
+				{ "title": "Shown", "description": "", "items": [{ "title": "A", "description": "", "status": "in_progress" }] },
+				{ "title": "Hidden", "description": "", "enabled": false, "items": [] }
+			]"#,
+		)
+		.unwrap();
+		assert_eq!(stages.iter().map(|stage| stage.title.as_str()).collect::<Vec<_>>(), ["Shown"]);
+		assert_eq!(stages[0].items[0].status, ItemStatus::InProgress);
+		assert!(parse_roadmap(r#"[{ "title": "Bad", "description": "", "items": [{ "title": "A", "description": "", "status": "finished" }] }]"#).is_err());
+	}
+}
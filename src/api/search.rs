@@ -0,0 +1,42 @@
+use crate::api::{posts::PostSummary, templates::TemplateInfo};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "server")]
+use crate::{
+	api::posts::summaries,
+	server::{
+		auth::current_user,
+		db::{repo, with_conn},
+	},
+	tags::normalize_tags,
+};
+
+pub const MAX_QUERY_LEN: usize = 200;
+const MAX_RESULTS: i64 = 40;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+	pub posts: Vec<PostSummary>,
+	pub templates: Vec<TemplateInfo>,
+}
+
+/// Full text search over post titles, captions and tags and over template names and tags. Every tag in `tags` must be present on a result.
+#[server]
+pub async fn search(query: String, tags: Vec<String>) -> Result<SearchResults, ServerFnError> {
+	let query = query.trim().to_owned();
+	(query.chars().count() <= MAX_QUERY_LEN).or_bad_request("search query is too long")?;
+	let tags = normalize_tags(tags).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	if query.is_empty() && tags.is_empty() {
+		return Ok(SearchResults::default());
+	}
+	let user_id = current_user().await.map(|user| user.id);
+	Ok(
+		with_conn(move |conn| {
+			let rows = repo::posts::search(conn, &query, &tags, MAX_RESULTS)?;
+			let templates = repo::templates::search(conn, &query, &tags, MAX_RESULTS)?;
+			Ok(SearchResults { posts: summaries(conn, rows, user_id)?, templates: templates.into_iter().map(Into::into).collect() })
+		})
+		.await?,
+	)
+}
@@ -0,0 +1,165 @@
+use dioxus::{
+	fullstack::{WebSocketOptions, Websocket},
+	prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::game::poker::{
+	cards::Card,
+	meme::MemeBet,
+	table::{Action, Blinds, LegalActions, Street},
+};
+
+#[cfg(feature = "server")]
+use {
+	crate::game::poker::{Holdem, meme::MemePoker},
+	crate::server::{
+		auth::require_active_user,
+		db::with_conn,
+		tables::{self, meme_deck},
+	},
+};
+
+/// Most a big blind can be, so a buy-in of [`BUY_IN_BIG_BLINDS`] stays readable.
+pub const MAX_BIG_BLIND: u64 = 1000;
+/// Chips a player sits down with, in big blinds. A chip is a point, taken from the player's wallet and paid back when they leave.
+pub const BUY_IN_BIG_BLINDS: u64 = 100;
+/// Share of a seat's winnings over a hand, in percent, taken for the rake. Hands that end before the flop are not raked.
+pub const RAKE_PERCENT: u64 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display, strum::EnumIter)]
+pub enum TableKind {
+	#[strum(serialize = "Hold'em")]
+	Holdem,
+	/// Dealt from settled meme bets, see [`crate::game::poker::meme`].
+	#[strum(serialize = "Meme poker")]
+	Meme,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct TableSummary {
+	pub id: Uuid,
+	pub kind: TableKind,
+	pub blinds: Blinds,
+	pub players: usize,
+	pub seats: usize,
+	pub hands_played: u64,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CardView {
+	Playing(Card),
+	Meme(MemeBet),
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SeatView {
+	pub username: String,
+	pub stack: u64,
+	/// Put in on the current street.
+	pub bet: u64,
+	pub in_hand: bool,
+	pub folded: bool,
+	pub all_in: bool,
+	/// Timed out or chose to skip hands. Sitting back in takes a [`ClientMessage::Join`].
+	pub sitting_out: bool,
+	pub connected: bool,
+	/// The viewer's own cards, and everyone's once the hand is shown down.
+	pub hole: Option<Vec<CardView>>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct HandView {
+	pub number: u64,
+	/// Table seat of the button.
+	pub button: usize,
+	pub street: Street,
+	pub board: Vec<CardView>,
+	pub pot: u64,
+	pub current_bet: u64,
+	/// Table seat whose turn it is.
+	pub to_act: Option<usize>,
+	/// Milliseconds since the unix epoch at which the seat to act is checked or folded for.
+	pub deadline: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Payout {
+	pub seat: usize,
+	pub username: String,
+	/// After the rake.
+	pub amount: u64,
+	/// What the hand was worth at showdown, `None` when everyone else folded.
+	pub hand: Option<String>,
+}
+
+/// Everything one viewer may see of a table. Every change sends a new one, so a client never patches state itself.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TableView {
+	pub id: Uuid,
+	pub kind: TableKind,
+	pub blinds: Blinds,
+	/// Grows with every change, so a client can drop views that arrive out of order after reconnecting.
+	pub version: u64,
+	pub seats: Vec<Option<SeatView>>,
+	/// The hand being played, or the last one until the next is dealt.
+	pub hand: Option<HandView>,
+	pub payouts: Vec<Payout>,
+	/// The viewer's seat.
+	pub you: Option<usize>,
+	/// What the viewer may do, empty unless it is their turn.
+	pub legal: LegalActions,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+	/// Take a free seat with a fresh buy-in paid from the player's points, or sit back in after sitting out.
+	Join,
+	/// Give up the seat, folding what is left of the current hand.
+	Leave,
+	Act(Action),
+	/// Ask for the current view again.
+	Resync,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+	Table(TableView),
+	/// A message the table refused. The connection stays open.
+	Error(String),
+}
+
+#[server]
+pub async fn list_tables() -> Result<Vec<TableSummary>, ServerFnError> {
+	Ok(tables::list())
+}
+
+/// Opens a table with the caller's choice of game and blinds, or sends them to an empty one that already plays it. Tables close
+/// once nobody has been seated at them for a while.
+#[server]
+pub async fn open_table(kind: TableKind, small_blind: u64, big_blind: u64) -> Result<Uuid, ServerFnError> {
+	let user = require_active_user().await?;
+	let blinds = Blinds::new(small_blind, big_blind).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	(blinds.big <= MAX_BIG_BLIND).or_bad_request(format!("the big blind can be at most {MAX_BIG_BLIND}"))?;
+	if let Some(id) = tables::find_empty(kind, blinds) {
+		return Ok(id);
+	}
+	let opened = match kind {
+		TableKind::Holdem => tables::open(kind, blinds, user.id, Holdem),
+		TableKind::Meme => {
+			let deck = with_conn(meme_deck).await?;
+			tables::open(kind, blinds, user.id, MemePoker::new(deck))
+		},
+	};
+	opened.or_else(|e| HttpError::bad_request(format!("{e:#}")))
+}
+
+/// The live connection to a table. A fresh [`TableView`] arrives on connect and after every change, so reconnecting is all a
+/// client needs to resync.
+#[get("/api/tables/{id}/socket")]
+pub async fn table_socket(id: Uuid, options: WebSocketOptions) -> Result<Websocket<ClientMessage, ServerMessage>, ServerFnError> {
+	let user = require_active_user().await?;
+	let table = tables::find(id).or_not_found("table not found")?;
+	Ok(options.on_upgrade(move |socket| tables::serve(table, user, socket)))
+}
@@ -0,0 +1,188 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "server")]
+use {
+	crate::server::{
+		auth::require_active_user,
+		blob::{BLOB_STORE, BlobKind, ImageMime, MAX_IMAGE_BYTES, decode_data_url, image_dimensions, store_image, validate_image},
+		db::{
+			models::Template,
+			repo::{
+				self,
+				templates::{NewTemplate, SimilarTemplateRow},
+			},
+			with_conn,
+		},
+		image_hash::{CAPTION_FREE_MASK, DUPLICATE_TEMPLATE_DISTANCE, TEMPLATE_MATCH_DISTANCE, dhash_blocking},
+		image_proxy, uploads,
+	},
+	crate::tags::normalize_tags,
+	chrono::Utc,
+	diesel::Connection,
+	dioxus::fullstack::StatusCode,
+};
+
+const MAX_TEMPLATE_NAME_LEN: usize = 80;
+const MAX_SIMILAR_TEMPLATES: i64 = 4;
+const MAX_RECOGNIZED_TEMPLATES: i64 = 6;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct TemplateInfo {
+	pub id: Uuid,
+	pub name: String,
+	pub image_url: String,
+	pub width: u32,
+	pub height: u32,
+	pub tags: Vec<String>,
+}
+
+#[cfg(feature = "server")]
+impl From<Template> for TemplateInfo {
+	fn from(template: Template) -> Self {
+		Self {
+			id: template.id,
+			name: template.name,
+			image_url: template.image_url,
+			width: template.width as u32,
+			height: template.height as u32,
+			tags: template.tags,
+		}
+	}
+}
+
+/// An existing template that looks like an upload, `distance` being how many of the 64 hash bits differ.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SimilarTemplate {
+	pub template: TemplateInfo,
+	pub distance: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum UploadOutcome {
+	Created(TemplateInfo),
+	/// The upload looks like templates we already have. It stays pending, so it can still be confirmed with `allow_duplicate`.
+	Duplicates(Vec<SimilarTemplate>),
+}
+
+#[cfg(feature = "server")]
+impl From<SimilarTemplateRow> for SimilarTemplate {
+	fn from(row: SimilarTemplateRow) -> Self {
+		Self { template: row.template.into(), distance: row.distance as u32 }
+	}
+}
+
+/// Where the browser sends the image bytes, with the `Content-Type` header it must send them with.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PresignedUpload {
+	pub id: Uuid,
+	pub upload_url: String,
+	pub content_type: String,
+}
+
+/// Starts a direct upload. `PUT` the image to `upload_url`, then call [`confirm_template_upload`] with the id.
+#[server]
+pub async fn create_template_upload(name: String, content_type: String) -> Result<PresignedUpload, ServerFnError> {
+	let user = require_active_user().await?;
+	let name = name.trim().to_owned();
+	(!name.is_empty() && name.chars().count() <= MAX_TEMPLATE_NAME_LEN).or_bad_request("template name must be 1 to 80 characters")?;
+	let mime = ImageMime::from_content_type(&content_type).or_bad_request("unsupported image format, use png, jpeg, gif or webp")?;
+	let (upload, upload_url) = uploads::create(user.id, name, mime).await?;
+	Ok(PresignedUpload { id: upload.id, upload_url, content_type: upload.content_type })
+}
+
+/// Checks what actually landed in storage and, if it is a valid image, moves it to its content addressed key and registers the template.
+/// Unless `allow_duplicate` is set, an upload that looks like an existing template comes back as [`UploadOutcome::Duplicates`] instead.
+#[server]
+pub async fn confirm_template_upload(id: Uuid, tags: Vec<String>, allow_duplicate: bool) -> Result<UploadOutcome, ServerFnError> {
+	let user = require_active_user().await?;
+	let tags = normalize_tags(tags).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let upload = with_conn(move |conn| Ok(repo::pending_uploads::find(conn, id)?)).await?.or_not_found("upload not found")?;
+	(upload.user_id == user.id).or_forbidden("not your upload")?;
+	(upload.expires_at > Utc::now()).or_bad_request("upload expired, start again")?;
+
+	let meta = BLOB_STORE.head(&upload.object_key).await?.or_bad_request("nothing was uploaded")?;
+	if meta.size > MAX_IMAGE_BYTES as u64 {
+		uploads::discard(&upload).await?;
+		return Err(HttpError::new(StatusCode::PAYLOAD_TOO_LARGE, "image is too large").into());
+	}
+	let bytes = BLOB_STORE.get(&upload.object_key).await?.or_bad_request("nothing was uploaded")?;
+	let dimensions = validate_image(&bytes, Some(&upload.content_type)).and_then(|_| image_dimensions(&bytes));
+	let (width, height) = match dimensions {
+		Ok(dimensions) => dimensions,
+		Err(e) => {
+			uploads::discard(&upload).await?;
+			return Err(HttpError::new(StatusCode::BAD_REQUEST, format!("{e:#}")).into());
+		},
+	};
+
+	let dhash = dhash_blocking(bytes.clone()).await?;
+	if !allow_duplicate {
+		let similar = with_conn(move |conn| Ok(repo::templates::find_similar(conn, dhash, DUPLICATE_TEMPLATE_DISTANCE, MAX_SIMILAR_TEMPLATES)?)).await?;
+		if !similar.is_empty() {
+			let similar = similar.into_iter().map(Into::into).collect();
+			return Ok(UploadOutcome::Duplicates(similar));
+		}
+	}
+
+	let key = store_image(BlobKind::Template, bytes, Some(&upload.content_type)).await?;
+	let image_url = BLOB_STORE.public_url(&key);
+	let (upload_id, name) = (upload.id, upload.name.clone());
+	let template = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			// A concurrent confirm of the same upload loses here instead of creating the template twice.
+			anyhow::ensure!(repo::pending_uploads::delete(conn, upload_id)?, "upload already confirmed");
+			let template = NewTemplate {
+				name: &name,
+				image_url: &image_url,
+				width: width as i32,
+				height: height as i32,
+				tags: &tags,
+				dhash: Some(dhash),
+				created_by: Some(user.id),
+			};
+			Ok(repo::templates::create(conn, &template)?)
+		})
+	})
+	.await?;
+	BLOB_STORE.delete(&upload.object_key).await?;
+	Ok(UploadOutcome::Created(template.into()))
+}
+
+/// Throws away an upload that will not be confirmed, e.g. because the user picked an existing template instead.
+#[server]
+pub async fn discard_template_upload(id: Uuid) -> Result<(), ServerFnError> {
+	let user = require_active_user().await?;
+	let upload = with_conn(move |conn| Ok(repo::pending_uploads::find(conn, id)?)).await?.or_not_found("upload not found")?;
+	(upload.user_id == user.id).or_forbidden("not your upload")?;
+	uploads::discard(&upload).await?;
+	Ok(())
+}
+
+#[server]
+pub async fn list_templates() -> Result<Vec<TemplateInfo>, ServerFnError> {
+	let templates = with_conn(|conn| Ok(repo::templates::list(conn, 200)?)).await?;
+	Ok(templates.into_iter().map(Into::into).collect())
+}
+
+/// Copies an image from another site into our storage and returns the url to load it from, so drawing it does not taint the canvas.
+#[server]
+pub async fn proxy_image(url: String) -> Result<String, ServerFnError> {
+	let user = require_active_user().await?;
+	image_proxy::within_rate_limit(user.id).or_http_error(StatusCode::TOO_MANY_REQUESTS, "slow down, too many images copied")?;
+	let proxied = image_proxy::proxy(url.trim()).await.or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	Ok(proxied)
+}
+
+/// Finds the templates a finished meme was most likely made from. `image` is a `data:` url of the meme.
+#[server]
+pub async fn recognize_template(image: String) -> Result<Vec<SimilarTemplate>, ServerFnError> {
+	require_active_user().await?;
+	let (bytes, content_type) = decode_data_url(&image).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	validate_image(&bytes, Some(&content_type)).or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let dhash = dhash_blocking(bytes).await.or_else(|e| HttpError::bad_request(format!("{e:#}")))?;
+	let matches =
+		with_conn(move |conn| Ok(repo::templates::find_similar_masked(conn, dhash, CAPTION_FREE_MASK, TEMPLATE_MATCH_DISTANCE, MAX_RECOGNIZED_TEMPLATES)?)).await?;
+	Ok(matches.into_iter().map(Into::into).collect())
+}
@@ -0,0 +1,346 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::templates::TemplateInfo;
+
+#[cfg(feature = "server")]
+use {
+	crate::api::posts::MAX_TITLE_LEN,
+	crate::server::{
+		auth::{current_user, require_active_user, require_moderator},
+		db::{
+			models::Tournament,
+			repo::{self, tournaments::EntryRow},
+			with_conn,
+		},
+		tournaments::{finalize, fund, standings},
+	},
+	crate::stores::meme_canvas::MemeDocument,
+	chrono::{DateTime, Utc},
+	diesel::{Connection, PgConnection},
+	std::str::FromStr,
+};
+
+pub const MAX_SPONSOR_LEN: usize = 80;
+pub const MAX_BRIEF_LEN: usize = 2000;
+pub const MAX_PRIZES: usize = 10;
+pub const MAX_PRIZE: i64 = 1_000_000;
+pub const MAX_JUDGES: usize = 10;
+pub const MIN_SCORE: i16 = 1;
+pub const MAX_SCORE: i16 = 10;
+const LISTED_TOURNAMENTS: i64 = 50;
+
+/// How entries are ranked. Stored as its lowercase name in `tournaments.judging`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize, strum::Display, strum::EnumString, strum::EnumIter)]
+#[strum(serialize_all = "lowercase")]
+pub enum JudgingMode {
+	/// Upvotes minus downvotes from everyone.
+	#[default]
+	Votes,
+	/// Average score from the sponsor's judges.
+	Panel,
+	/// Both, weighted equally by position.
+	Combined,
+}
+
+impl JudgingMode {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Votes => "Community votes",
+			Self::Panel => "Sponsor panel",
+			Self::Combined => "Votes and sponsor panel",
+		}
+	}
+
+	pub fn uses_panel(self) -> bool {
+		self != Self::Votes
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, strum::Display)]
+pub enum TournamentPhase {
+	Upcoming,
+	Open,
+	/// Closed for entries, waiting for the results.
+	Judging,
+	Finalized,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct TournamentSummary {
+	pub id: Uuid,
+	pub sponsor: String,
+	pub title: String,
+	/// Milliseconds since the unix epoch.
+	pub starts_at: i64,
+	/// Milliseconds since the unix epoch.
+	pub ends_at: i64,
+	pub judging: JudgingMode,
+	/// Points paid to first place, second place and so on.
+	pub prizes: Vec<i64>,
+	pub entries: i64,
+	/// As of when the summary was loaded.
+	pub phase: TournamentPhase,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+	pub meme_id: Uuid,
+	pub title: String,
+	pub image_url: String,
+	pub author: Option<String>,
+	pub votes: i64,
+	/// Average judge score, `None` until a judge scored the entry.
+	pub panel: Option<f64>,
+	/// The caller's score when they are a judge.
+	pub my_score: Option<i16>,
+	/// Current standing, final once the tournament is finalized.
+	pub place: u32,
+	/// Points won, set once the tournament is finalized.
+	pub prize: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TournamentDetail {
+	pub summary: TournamentSummary,
+	pub brief: String,
+	/// The template entries must be made on.
+	pub template: Option<TemplateInfo>,
+	/// The brand watermark every entry carries.
+	pub watermark_url: String,
+	pub judges: Vec<String>,
+	pub leaderboard: Vec<LeaderboardEntry>,
+	pub you_judge: bool,
+	pub you_entered: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct NewTournament {
+	pub sponsor: String,
+	pub title: String,
+	pub brief: String,
+	pub template_id: Option<Uuid>,
+	pub watermark_url: String,
+	/// Milliseconds since the unix epoch.
+	pub starts_at: i64,
+	/// Milliseconds since the unix epoch.
+	pub ends_at: i64,
+	pub judging: JudgingMode,
+	pub prizes: Vec<i64>,
+	/// Usernames of the sponsor's panel.
+	pub judges: Vec<String>,
+}
+
+#[cfg(feature = "server")]
+impl TournamentSummary {
+	fn new(tournament: &Tournament, entries: i64) -> Self {
+		Self {
+			id: tournament.id,
+			sponsor: tournament.sponsor.clone(),
+			title: tournament.title.clone(),
+			starts_at: tournament.starts_at.timestamp_millis(),
+			ends_at: tournament.ends_at.timestamp_millis(),
+			judging: judging_mode(tournament),
+			prizes: tournament.prizes.clone(),
+			entries,
+			phase: tournament.phase(Utc::now()),
+		}
+	}
+}
+
+#[cfg(feature = "server")]
+fn judging_mode(tournament: &Tournament) -> JudgingMode {
+	JudgingMode::from_str(&tournament.judging).unwrap_or_default()
+}
+
+#[cfg(feature = "server")]
+impl LeaderboardEntry {
+	fn new(row: EntryRow, place: u32) -> Self {
+		Self {
+			meme_id: row.meme_id,
+			title: row.title,
+			image_url: row.image_url,
+			author: row.author,
+			votes: row.votes,
+			panel: row.panel,
+			my_score: row.my_score,
+			place: row.place.map_or(place, |place| place as u32),
+			prize: row.prize,
+		}
+	}
+}
+
+/// Checks that `document` may be entered into the tournament by the user. Entries must be made while the tournament is open, on
+/// its template when it has one, and carry its watermark.
+#[cfg(feature = "server")]
+pub(crate) fn check_entry(conn: &mut PgConnection, tournament_id: Uuid, user_id: Uuid, document: &MemeDocument) -> anyhow::Result<Result<(), HttpError>> {
+	let Some(tournament) = repo::tournaments::find(conn, tournament_id)? else {
+		return Ok(HttpError::not_found("tournament not found"));
+	};
+	if tournament.phase(Utc::now()) != TournamentPhase::Open {
+		return Ok(HttpError::bad_request("this tournament is not open for entries"));
+	}
+	if repo::tournaments::is_judge(conn, tournament_id, user_id)? {
+		return Ok(HttpError::forbidden("judges cannot enter their own tournament"));
+	}
+	if repo::tournaments::has_entered(conn, tournament_id, user_id)? {
+		return Ok(HttpError::bad_request("you already entered this tournament"));
+	}
+	if let Some(template_id) = tournament.template_id {
+		let template = repo::templates::find(conn, template_id)?;
+		if template.is_none_or(|template| template.image_url != document.main_img_url) {
+			return Ok(HttpError::bad_request("entries must use the tournament's template"));
+		}
+	}
+	if document.watermark_url.as_deref() != Some(tournament.watermark_url.as_str()) {
+		return Ok(HttpError::bad_request("entries must carry the sponsor's watermark"));
+	}
+	Ok(Ok(()))
+}
+
+#[server]
+pub async fn list_tournaments() -> Result<Vec<TournamentSummary>, ServerFnError> {
+	let tournaments = with_conn(|conn| Ok(repo::tournaments::list(conn, LISTED_TOURNAMENTS)?)).await?;
+	Ok(tournaments.iter().map(|(tournament, entries)| TournamentSummary::new(tournament, *entries)).collect())
+}
+
+/// The tournament with its current standings. Judges also get their own scores.
+#[server]
+pub async fn get_tournament(id: Uuid) -> Result<TournamentDetail, ServerFnError> {
+	let user_id = current_user().await.map(|user| user.id);
+	let detail = with_conn(move |conn| {
+		let Some(tournament) = repo::tournaments::find(conn, id)? else {
+			return Ok(None);
+		};
+		let judges = repo::tournaments::judges(conn, id)?;
+		let you_judge = user_id.is_some_and(|user_id| judges.iter().any(|(judge_id, _)| *judge_id == user_id));
+		let you_entered = match user_id {
+			Some(user_id) => repo::tournaments::has_entered(conn, id, user_id)?,
+			None => false,
+		};
+		let rows = repo::tournaments::entries(conn, id, user_id.filter(|_| you_judge))?;
+		// Finalized entries come back in their final order.
+		let order = if tournament.finalized_at.is_some() { (0..rows.len()).collect() } else { standings(judging_mode(&tournament), &rows) };
+		let mut rows = rows.into_iter().map(Some).collect::<Vec<_>>();
+		let leaderboard =
+			order.into_iter().enumerate().filter_map(|(place, index)| Some(LeaderboardEntry::new(rows[index].take()?, place as u32 + 1))).collect::<Vec<_>>();
+		let template = tournament.template_id.map(|template_id| repo::templates::find(conn, template_id)).transpose()?.flatten().map(Into::into);
+		Ok(Some(TournamentDetail {
+			summary: TournamentSummary::new(&tournament, leaderboard.len() as i64),
+			brief: tournament.brief,
+			template,
+			watermark_url: tournament.watermark_url,
+			judges: judges.into_iter().map(|(_, username)| username).collect(),
+			leaderboard,
+			you_judge,
+			you_entered,
+		}))
+	})
+	.await?;
+	Ok(detail.or_not_found("tournament not found")?)
+}
+
+/// Opens a sponsored tournament. Moderators set these up on behalf of sponsors.
+#[server]
+pub async fn create_tournament(new: NewTournament) -> Result<Uuid, ServerFnError> {
+	let moderator = require_moderator().await?;
+	let sponsor = new.sponsor.trim().to_owned();
+	let title = new.title.trim().to_owned();
+	let brief = new.brief.trim().to_owned();
+	let watermark_url = new.watermark_url.trim().to_owned();
+	(!sponsor.is_empty() && sponsor.chars().count() <= MAX_SPONSOR_LEN).or_bad_request("sponsor must be 1 to 80 characters")?;
+	(!title.is_empty() && title.chars().count() <= MAX_TITLE_LEN).or_bad_request("title must be 1 to 120 characters")?;
+	(!brief.is_empty() && brief.chars().count() <= MAX_BRIEF_LEN).or_bad_request("brief must be 1 to 2000 characters")?;
+	((watermark_url.starts_with("https://") || watermark_url.starts_with("http://")) && watermark_url.len() <= 2048)
+		.or_bad_request("watermark must be an http(s) url")?;
+	let starts_at = DateTime::from_timestamp_millis(new.starts_at).or_bad_request("invalid start")?;
+	let ends_at = DateTime::from_timestamp_millis(new.ends_at).or_bad_request("invalid end")?;
+	(starts_at < ends_at && Utc::now() < ends_at).or_bad_request("the tournament must end after it starts and in the future")?;
+	(new.prizes.len() <= MAX_PRIZES && new.prizes.iter().all(|prize| (1..=MAX_PRIZE).contains(prize)))
+		.or_bad_request(format!("at most {MAX_PRIZES} prizes of 1 to {MAX_PRIZE} points"))?;
+	(new.judges.len() <= MAX_JUDGES).or_bad_request(format!("at most {MAX_JUDGES} judges"))?;
+	(!new.judging.uses_panel() || !new.judges.is_empty()).or_bad_request("panel judging needs at least one judge")?;
+	let id = with_conn(move |conn| {
+		conn.transaction(|conn| {
+			if let Some(template_id) = new.template_id
+				&& repo::templates::find(conn, template_id)?.is_none()
+			{
+				return Ok(HttpError::bad_request("template not found"));
+			}
+			let mut judge_ids = Vec::new();
+			for username in &new.judges {
+				match repo::users::find_by_username(conn, username.trim())? {
+					Some(judge) => judge_ids.push(judge.id),
+					None => return Ok(HttpError::bad_request(format!("no user named {username}"))),
+				}
+			}
+			let tournament = Tournament {
+				id: Uuid::new_v4(),
+				sponsor,
+				title,
+				brief,
+				template_id: new.template_id,
+				watermark_url,
+				starts_at,
+				ends_at,
+				judging: new.judging.to_string(),
+				prizes: new.prizes,
+				created_by: Some(moderator.id),
+				created_at: Utc::now(),
+				finalized_at: None,
+			};
+			repo::tournaments::create(conn, &tournament, &judge_ids)?;
+			fund(conn, &tournament)?;
+			Ok(Ok(tournament.id))
+		})
+	})
+	.await??;
+	Ok(id)
+}
+
+/// Records a judge's score for an entry, replacing an earlier one. Scores can change until the tournament is finalized.
+#[server]
+pub async fn score_entry(meme_id: Uuid, score: i16) -> Result<(), ServerFnError> {
+	let user = require_active_user().await?;
+	(MIN_SCORE..=MAX_SCORE).contains(&score).or_bad_request(format!("scores go from {MIN_SCORE} to {MAX_SCORE}"))?;
+	with_conn(move |conn| {
+		let Some(entry) = repo::tournaments::find_entry(conn, meme_id)? else {
+			return Ok(HttpError::not_found("entry not found"));
+		};
+		if !repo::tournaments::is_judge(conn, entry.tournament_id, user.id)? {
+			return Ok(HttpError::forbidden("only the tournament's judges can score entries"));
+		}
+		if repo::tournaments::find(conn, entry.tournament_id)?.is_none_or(|tournament| tournament.finalized_at.is_some()) {
+			return Ok(HttpError::bad_request("this tournament is finalized"));
+		}
+		repo::tournaments::score(conn, meme_id, user.id, score)?;
+		Ok(Ok(()))
+	})
+	.await??;
+	Ok(())
+}
+
+/// Fixes the standings of an ended tournament and pays out its prizes.
+#[server]
+pub async fn finalize_tournament(id: Uuid) -> Result<(), ServerFnError> {
+	require_moderator().await?;
+	with_conn(move |conn| {
+		conn.transaction(|conn| {
+			let Some(tournament) = repo::tournaments::lock(conn, id)? else {
+				return Ok(HttpError::not_found("tournament not found"));
+			};
+			if tournament.finalized_at.is_some() {
+				return Ok(HttpError::bad_request("this tournament is already finalized"));
+			}
+			let now = Utc::now();
+			if now < tournament.ends_at {
+				return Ok(HttpError::bad_request("this tournament has not ended yet"));
+			}
+			finalize(conn, &tournament, judging_mode(&tournament), now)?;
+			Ok(Ok(()))
+		})
+	})
+	.await??;
+	Ok(())
+}
@@ -0,0 +1,21 @@
+//! Hashes the templates and posts in the database at `DATABASE_URL` that were stored before image hashes were, so duplicate, repost
+//! and template recognition searches find them. Rows that already have a hash are left alone, so it is safe to run again.
+//!
+//! ```text
+//! backfill-hashes
+//! ```
+
+use memetopia::server::{db, image_hash};
+
+fn main() -> anyhow::Result<()> {
+	dioxus::logger::initialize_default();
+	tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(run())
+}
+
+async fn run() -> anyhow::Result<()> {
+	db::init()?;
+	db::pool()?;
+	let report = image_hash::backfill().await?;
+	println!("{report}");
+	Ok(())
+}
@@ -0,0 +1,66 @@
+//! Imports memes and their engagement metrics for the analytics game into the database at `DATABASE_URL`.
+//!
+//! ```text
+//! ingest [--fixtures DIR] [FILE.json | FILE.csv | URL]...
+//! ```
+//!
+//! `--fixtures` serves `DIR` on a local port and imports every `.json` and `.csv` file in it over HTTP, so the whole pipeline, image
+//! downloads included, runs offline. It needs the `fixtures` feature.
+
+use anyhow::Context;
+#[cfg(feature = "fixtures")]
+use memetopia::server::ingest::FixtureServer;
+use memetopia::server::{
+	db,
+	ingest::{self, FileSource, HttpSource, MemeSource},
+};
+
+const USAGE: &str = "usage: ingest [--fixtures DIR] [FILE.json | FILE.csv | URL]...";
+
+fn main() -> anyhow::Result<()> {
+	dioxus::logger::initialize_default();
+	tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(run(std::env::args().skip(1).collect()))
+}
+
+async fn run(args: Vec<String>) -> anyhow::Result<()> {
+	db::init()?;
+	db::pool()?;
+	let client = ingest::http_client()?;
+	// Kept alive until every source has been imported.
+	#[cfg(feature = "fixtures")]
+	let mut fixtures = Vec::new();
+	let mut sources: Vec<Box<dyn MemeSource>> = Vec::new();
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-h" | "--help" => {
+				println!("{USAGE}");
+				return Ok(());
+			},
+			"--fixtures" => {
+				let dir = args.next().context(USAGE)?;
+				#[cfg(not(feature = "fixtures"))]
+				anyhow::bail!("cannot serve {dir}, ingest was built without the fixtures feature");
+				#[cfg(feature = "fixtures")]
+				{
+					let server = FixtureServer::start(&dir).await?;
+					let mut files = std::fs::read_dir(&dir)?.map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned())).collect::<std::io::Result<Vec<_>>>()?;
+					files.retain(|name| name.ends_with(".json") || name.ends_with(".csv"));
+					files.sort();
+					for name in files {
+						sources.push(Box::new(HttpSource::new(client.clone(), &server.url(&name))?));
+					}
+					fixtures.push(server);
+				}
+			},
+			url if url.starts_with("http://") || url.starts_with("https://") => sources.push(Box::new(HttpSource::new(client.clone(), url)?)),
+			path => sources.push(Box::new(FileSource::new(path))),
+		}
+	}
+	anyhow::ensure!(!sources.is_empty(), "nothing to import\n{USAGE}");
+	for source in &sources {
+		let report = ingest::import(source.as_ref(), &client).await?;
+		println!("{}: {report}", source.name());
+	}
+	Ok(())
+}
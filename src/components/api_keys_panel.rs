@@ -0,0 +1,121 @@
+use crate::{
+	api::api_keys::{ApiKeyInfo, CreatedApiKey, create_api_key, list_api_keys, revoke_api_key},
+	components::local_time::LocalTime,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+
+/// Keys for `POST /api/render`. A new key is shown once, only its hash is kept.
+#[component]
+pub fn ApiKeysPanel() -> Element {
+	let toast = use_toast();
+	let mut keys = use_signal(Vec::<ApiKeyInfo>::new);
+	let mut error = use_signal(|| None::<String>);
+	let mut name = use_signal(String::new);
+	let mut created = use_signal(|| None::<String>);
+
+	use_future(move || async move {
+		match list_api_keys().await {
+			Ok(list) => keys.set(list),
+			Err(e) => error.set(Some(e.to_string())),
+		}
+	});
+
+	let create = move |_| async move {
+		match create_api_key(name()).await {
+			Ok(CreatedApiKey { info, key }) => {
+				keys.write().insert(0, info);
+				created.set(Some(key));
+				name.set(String::new());
+			},
+			Err(e) => toast.error("Could not create key".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	let copy = move |_| {
+		if let Some(key) = created() {
+			document::eval(&format!("navigator.clipboard.writeText({})", serde_json::to_string(&key).unwrap_or_default()));
+			toast.success("Key copied".to_owned(), ToastOptions::new());
+		}
+	};
+
+	rsx! {
+		section { class: "space-y-3",
+			h2 { class: "text-lg font-semibold", "API keys" }
+			p { class: "text-sm text-slate-400",
+				"Render memes from scripts and bots with "
+				code { "POST /api/render" }
+				" and an "
+				code { "Authorization: Bearer" }
+				" header."
+			}
+			div { class: "flex gap-2",
+				input {
+					r#type: "text",
+					placeholder: "Key name",
+					maxlength: 64,
+					value: "{name}",
+					oninput: move |evt| name.set(evt.value()),
+					class: "flex-1 min-w-0 px-2 py-1 text-sm border rounded focus:outline-none bg-transparent",
+				}
+				button {
+					onclick: create,
+					disabled: name.read().trim().is_empty(),
+					class: "px-3 py-1 bg-blue-500 text-white rounded-md hover:bg-blue-600 transition-colors duration-200 text-sm font-medium",
+					"Create"
+				}
+			}
+			if let Some(key) = created() {
+				div { class: "space-y-1 border-2 border-blue-500 rounded-lg p-2",
+					p { class: "text-sm", "Copy this key now, it will not be shown again." }
+					div { class: "flex gap-2",
+						input {
+							r#type: "text",
+							readonly: true,
+							value: "{key}",
+							class: "flex-1 min-w-0 px-2 py-1 text-sm font-mono border rounded focus:outline-none bg-transparent",
+						}
+						button {
+							onclick: copy,
+							class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+							"Copy"
+						}
+					}
+				}
+			}
+			if let Some(error) = error() {
+				p { class: "text-sm text-red-400", "{error}" }
+			}
+			if keys.read().is_empty() {
+				p { class: "text-sm text-slate-400", "No keys yet." }
+			}
+			for ApiKeyInfo { id, name, created_at, last_used_at } in keys() {
+				div { key: "{id}", class: "flex items-center gap-3 border rounded-lg p-2 text-sm",
+					div { class: "flex-1 min-w-0",
+						p { class: "font-medium truncate", "{name}" }
+						p { class: "text-xs text-slate-400",
+							"Created "
+							LocalTime { millis: created_at }
+							if let Some(last_used_at) = last_used_at {
+								", last used "
+								LocalTime { millis: last_used_at }
+							} else {
+								", never used"
+							}
+						}
+					}
+					button {
+						onclick: move |_| async move {
+							match revoke_api_key(id).await {
+								Ok(()) => keys.write().retain(|key| key.id != id),
+								Err(e) => toast.error("Could not revoke key".to_owned(), ToastOptions::new().description(e.to_string())),
+							}
+						},
+						class: "px-2 py-1 bg-red-500 rounded text-xs hover:bg-red-600 transition-colors duration-200",
+						"Revoke"
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,205 @@
+use crate::{
+	api::comments::{CommentInfo, MAX_COMMENT_LEN, create_comment, delete_comment, edit_comment, list_comments},
+	components::markdown_lite::MarkdownLite,
+	router::Route,
+	stores::session::use_session,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use uuid::Uuid;
+
+/// Replies deeper than this stop indenting further, so long threads stay readable on small screens.
+const MAX_INDENT_DEPTH: usize = 5;
+
+fn replace_comment(mut comments: Signal<Vec<CommentInfo>>, updated: CommentInfo) {
+	if let Some(comment) = comments.write().iter_mut().find(|comment| comment.id == updated.id) {
+		*comment = updated;
+	}
+}
+
+/// Comment thread under a post. Comments arrive as a flat list and are nested here by `parent_id`.
+#[component]
+pub fn CommentSection(meme_id: Uuid) -> Element {
+	let session = use_session();
+	let mut comments = use_signal(Vec::<CommentInfo>::new);
+	let mut error = use_signal(|| None::<String>);
+
+	use_future(move || async move {
+		match list_comments(meme_id).await {
+			Ok(list) => comments.set(list),
+			Err(e) => error.set(Some(e.to_string())),
+		}
+	});
+
+	let roots = comments.read().iter().filter(|comment| comment.parent_id.is_none()).cloned().collect::<Vec<_>>();
+
+	rsx! {
+		section { class: "space-y-4",
+			h2 { class: "text-xl font-semibold", "Comments ({comments.read().len()})" }
+			if session.is_logged_in() {
+				CommentForm { meme_id, parent_id: None, comments }
+			} else {
+				Link {
+					to: Route::Login { redirect: Route::MemePost { id: meme_id.to_string() }.to_string() },
+					class: "block text-sm text-blue-400 hover:text-blue-300",
+					"Log in to comment"
+				}
+			}
+			if let Some(error) = error() {
+				p { class: "text-sm text-red-400", "{error}" }
+			}
+			for comment in roots {
+				CommentNode { key: "{comment.id}", comment, comments, depth: 0 }
+			}
+		}
+	}
+}
+
+#[component]
+fn CommentForm(meme_id: Uuid, parent_id: Option<Uuid>, comments: Signal<Vec<CommentInfo>>, on_done: Option<EventHandler>) -> Element {
+	let toast = use_toast();
+	let mut body = use_signal(String::new);
+	let mut sending = use_signal(|| false);
+
+	let submit = move |e: FormEvent| async move {
+		e.prevent_default();
+		sending.set(true);
+		match create_comment(meme_id, parent_id, body()).await {
+			Ok(comment) => {
+				comments.write().push(comment);
+				body.set(String::new());
+				if let Some(on_done) = on_done {
+					on_done.call(());
+				}
+			},
+			Err(e) => toast.error("Could not post comment".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		sending.set(false);
+	};
+
+	rsx! {
+		form { class: "space-y-2", onsubmit: submit,
+			textarea {
+				value: "{body}",
+				maxlength: MAX_COMMENT_LEN,
+				rows: 3,
+				placeholder: if parent_id.is_some() { "Write a reply" } else { "Add a comment" },
+				oninput: move |evt| body.set(evt.value()),
+				class: "w-full px-3 py-2 text-sm border rounded-lg focus:outline-none bg-transparent",
+			}
+			div { class: "flex items-center justify-between",
+				span { class: "text-xs text-slate-500", "**bold** *italic* `code` [link](https://...)" }
+				button {
+					r#type: "submit",
+					disabled: sending() || body.read().trim().is_empty(),
+					class: "px-3 py-1 bg-blue-500 text-white rounded-md hover:bg-blue-600 disabled:opacity-50 transition-colors duration-200 text-sm font-medium",
+					if parent_id.is_some() {
+						"Reply"
+					} else {
+						"Comment"
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn CommentNode(comment: CommentInfo, comments: Signal<Vec<CommentInfo>>, depth: usize) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let mut replying = use_signal(|| false);
+	let mut editing = use_signal(|| None::<String>);
+
+	let id = comment.id;
+	let is_own = !comment.deleted && session.user.read().as_ref().is_some_and(|user| Some(user.id) == comment.author_id);
+	let replies = comments.read().iter().filter(|reply| reply.parent_id == Some(id)).cloned().collect::<Vec<_>>();
+
+	let save = move |_| async move {
+		let Some(body) = editing() else {
+			return;
+		};
+		match edit_comment(id, body).await {
+			Ok(updated) => {
+				replace_comment(comments, updated);
+				editing.set(None);
+			},
+			Err(e) => toast.error("Could not edit comment".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+	let delete = move |_| async move {
+		match delete_comment(id).await {
+			Ok(updated) => replace_comment(comments, updated),
+			Err(e) => toast.error("Could not delete comment".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	rsx! {
+		div { class: if depth > 0 && depth <= MAX_INDENT_DEPTH { "ml-4 pl-4 border-l border-white/20 space-y-3" } else { "space-y-3" },
+			div { class: "space-y-1",
+				div { class: "flex gap-2 text-xs text-slate-400",
+					span { class: "font-semibold", {comment.author.clone().unwrap_or_else(|| "[deleted]".to_owned())} }
+					if comment.edited && !comment.deleted {
+						span { "edited" }
+					}
+				}
+				if let Some(draft) = editing() {
+					textarea {
+						value: "{draft}",
+						maxlength: MAX_COMMENT_LEN,
+						rows: 3,
+						oninput: move |evt| editing.set(Some(evt.value())),
+						class: "w-full px-3 py-2 text-sm border rounded-lg focus:outline-none bg-transparent",
+					}
+					div { class: "flex gap-2",
+						button {
+							onclick: save,
+							class: "px-2 py-1 bg-blue-500 rounded text-xs hover:bg-blue-600 transition-colors duration-200",
+							"Save"
+						}
+						button {
+							onclick: move |_| editing.set(None),
+							class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+							"Cancel"
+						}
+					}
+				} else if comment.deleted {
+					p { class: "text-sm italic text-slate-500", "This comment was deleted." }
+				} else {
+					p { class: "text-sm break-words",
+						MarkdownLite { text: comment.body.clone() }
+					}
+				}
+				if !comment.deleted && editing().is_none() {
+					div { class: "flex gap-3 text-xs text-slate-400",
+						if session.is_logged_in() {
+							button { onclick: move |_| replying.toggle(), class: "hover:text-white", "Reply" }
+						}
+						if is_own {
+							button {
+								onclick: {
+									let body = comment.body.clone();
+									move |_| editing.set(Some(body.clone()))
+								},
+								class: "hover:text-white",
+								"Edit"
+							}
+							button { onclick: delete, class: "hover:text-red-400", "Delete" }
+						}
+					}
+				}
+				if replying() {
+					CommentForm {
+						meme_id: comment.meme_id,
+						parent_id: Some(id),
+						comments,
+						on_done: move |_| replying.set(false),
+					}
+				}
+			}
+			for reply in replies {
+				CommentNode { key: "{reply.id}", comment: reply, comments, depth: depth + 1 }
+			}
+		}
+	}
+}
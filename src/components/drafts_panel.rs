@@ -0,0 +1,68 @@
+use crate::stores::{
+	drafts::{Draft, Drafts},
+	meme_canvas::{MemeCanvas, MemeCanvasStoreImplExt, MemeDocument},
+};
+use dioxus::prelude::*;
+
+#[component]
+pub fn DraftsPanel(mut meme_canvas: Store<MemeCanvas>, mut drafts: Drafts) -> Element {
+	let current = (drafts.current)();
+
+	rsx! {
+		div { class: "space-y-3",
+			div { class: "flex items-center justify-between",
+				h2 { class: "text-lg font-semibold", "Drafts" }
+				button {
+					onclick: move |_| {
+						drafts.create(MemeDocument::default());
+						meme_canvas.load_document(MemeDocument::default());
+					},
+					class: "px-3 py-1 bg-blue-500 text-white rounded-md hover:bg-blue-600 transition-colors duration-200 text-sm font-medium",
+					"New"
+				}
+			}
+			if drafts.drafts.read().is_empty() {
+				p { class: "text-sm text-slate-400", "Edits are saved automatically." }
+			}
+			for Draft { id, name, thumbnail, .. } in drafts.sorted() {
+				div {
+					key: "{id}",
+					class: if current == Some(id) { "flex items-center gap-3 border-2 border-blue-500 rounded-lg p-2" } else { "flex items-center gap-3 border rounded-lg p-2" },
+					if let Some(thumbnail) = thumbnail {
+						img {
+							src: thumbnail,
+							class: "w-12 h-12 object-cover rounded cursor-pointer",
+							onclick: move |_| {
+								if let Some(document) = drafts.open(id) {
+									meme_canvas.load_document(document);
+								}
+							},
+						}
+					} else {
+						div { class: "w-12 h-12 rounded bg-slate-700" }
+					}
+					input {
+						r#type: "text",
+						value: "{name}",
+						oninput: move |evt| drafts.rename(id, evt.value()),
+						class: "flex-1 min-w-0 px-2 py-1 text-sm border rounded focus:outline-none bg-transparent",
+					}
+					button {
+						onclick: move |_| {
+							if let Some(document) = drafts.open(id) {
+								meme_canvas.load_document(document);
+							}
+						},
+						class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+						"Open"
+					}
+					button {
+						onclick: move |_| drafts.delete(id),
+						class: "px-2 py-1 bg-red-500 rounded text-xs hover:bg-red-600 transition-colors duration-200",
+						"Delete"
+					}
+				}
+			}
+		}
+	}
+}
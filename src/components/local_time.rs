@@ -0,0 +1,15 @@
+use dioxus::prelude::*;
+use web_sys::wasm_bindgen::JsValue;
+
+/// Formats a timestamp in the viewer's locale once mounted, since the server does not know the viewer's time zone.
+#[component]
+pub fn LocalTime(millis: i64) -> Element {
+	let mut text = use_signal(String::new);
+	use_effect(move || {
+		text.set(js_sys::Date::new(&(millis as f64).into()).to_locale_string("default", &JsValue::UNDEFINED).into());
+	});
+
+	rsx! {
+		time { "{text}" }
+	}
+}
@@ -0,0 +1,167 @@
+//! A small, safe subset of markdown for user text: `**bold**`, `*italic*`, `` `code` ``, `[text](https://…)`, bare http(s) links and line
+//! breaks. Everything else renders as plain text, and nothing is ever emitted as raw html.
+
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Inline {
+	Text(String),
+	Bold(String),
+	Italic(String),
+	Code(String),
+	Link { text: String, href: String },
+	LineBreak,
+}
+
+pub fn parse(source: &str) -> Vec<Inline> {
+	let mut inlines = Vec::new();
+	let mut text = String::new();
+	let mut rest = source;
+	while let Some(c) = rest.chars().next() {
+		if let Some((inline, len)) = token(rest) {
+			if !text.is_empty() {
+				inlines.push(Inline::Text(std::mem::take(&mut text)));
+			}
+			inlines.push(inline);
+			rest = &rest[len..];
+		} else {
+			text.push(c);
+			rest = &rest[c.len_utf8()..];
+		}
+	}
+	if !text.is_empty() {
+		inlines.push(Inline::Text(text));
+	}
+	inlines
+}
+
+/// The inline starting at the beginning of `s`, with how many bytes it spans.
+fn token(s: &str) -> Option<(Inline, usize)> {
+	if s.starts_with('\n') {
+		return Some((Inline::LineBreak, 1));
+	}
+	if let Some(inner) = delimited(s, "**") {
+		return Some((Inline::Bold(inner.to_owned()), inner.len() + 4));
+	}
+	if let Some(inner) = delimited(s, "*") {
+		return Some((Inline::Italic(inner.to_owned()), inner.len() + 2));
+	}
+	if let Some(inner) = delimited(s, "`") {
+		return Some((Inline::Code(inner.to_owned()), inner.len() + 2));
+	}
+	if let Some(rest) = s.strip_prefix('[') {
+		let (text, rest) = rest.split_once("](")?;
+		let (href, _) = rest.split_once(')')?;
+		if is_http_url(href) && !text.is_empty() && !text.contains(['\n', '[']) {
+			return Some((Inline::Link { text: text.to_owned(), href: href.to_owned() }, text.len() + href.len() + 4));
+		}
+		return None;
+	}
+	if s.starts_with("https://") || s.starts_with("http://") {
+		let url = s.split(char::is_whitespace).next()?.trim_end_matches(['.', ',', ')', '!', '?', ';', ':', '\'', '"']);
+		return is_http_url(url).then(|| (Inline::Link { text: url.to_owned(), href: url.to_owned() }, url.len()));
+	}
+	None
+}
+
+/// Text between a pair of `marker`s on one line, not padded with spaces, so `2 * 3 * 4` stays plain.
+fn delimited<'a>(s: &'a str, marker: &str) -> Option<&'a str> {
+	let after = s.strip_prefix(marker)?;
+	let inner = &after[..after.find(marker)?];
+	(!inner.is_empty() && !inner.starts_with(char::is_whitespace) && !inner.ends_with(char::is_whitespace) && !inner.contains('\n')).then_some(inner)
+}
+
+fn is_http_url(url: &str) -> bool {
+	url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")).is_some_and(|rest| !rest.is_empty() && !rest.contains(char::is_whitespace))
+}
+
+fn render(inline: Inline) -> Element {
+	match inline {
+		Inline::Text(text) => rsx! { "{text}" },
+		Inline::Bold(text) => rsx! {
+			strong { "{text}" }
+		},
+		Inline::Italic(text) => rsx! {
+			em { "{text}" }
+		},
+		Inline::Code(text) => rsx! {
+			code { class: "px-1 rounded bg-white/10 text-sm", "{text}" }
+		},
+		Inline::Link { text, href } => rsx! {
+			a {
+				href,
+				target: "_blank",
+				rel: "nofollow noopener noreferrer",
+				class: "text-cyan-400 hover:underline",
+				"{text}"
+			}
+		},
+		Inline::LineBreak => rsx! {
+			br {}
+		},
+	}
+}
+
+#[component]
+pub fn MarkdownLite(text: String) -> Element {
+	rsx! {
+		for inline in parse(&text) {
+			{render(inline)}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn text(s: &str) -> Inline {
+		Inline::Text(s.to_owned())
+	}
+
+	#[test]
+	fn html_stays_text() {
+		for source in ["<script>alert(1)</script>", "<img src=x onerror=alert(1)>", "&lt;b&gt; & <b>bold</b>", "<a href=\"javascript:alert(1)\">x</a>"] {
+			assert_eq!(parse(source), [text(source)], "{source}");
+		}
+		assert_eq!(parse("**<script>**"), [Inline::Bold("<script>".to_owned())]);
+	}
+
+	#[test]
+	fn only_http_links_are_links() {
+		assert_eq!(parse("[meme](https://example.com/a?b=c)"), [Inline::Link { text: "meme".to_owned(), href: "https://example.com/a?b=c".to_owned() }]);
+		for source in [
+			"[click](javascript:alert(1))",
+			"[click](JAVASCRIPT:alert(1))",
+			"[click](data:text/html;base64,PHNjcmlwdD4=)",
+			"[click](//evil.example)",
+			"[click](vbscript:msgbox)",
+			"[click](https://)",
+			"javascript:alert(1)",
+		] {
+			assert!(!parse(source).iter().any(|inline| matches!(inline, Inline::Link { .. })), "{source}");
+		}
+		assert_eq!(
+			parse("see https://example.com/x."),
+			[text("see "), Inline::Link { text: "https://example.com/x".to_owned(), href: "https://example.com/x".to_owned() }, text(".")]
+		);
+	}
+
+	#[test]
+	fn parses_emphasis_code_and_breaks() {
+		assert_eq!(
+			parse("**bold** *it* `a*b`\nnext"),
+			[
+				Inline::Bold("bold".to_owned()),
+				text(" "),
+				Inline::Italic("it".to_owned()),
+				text(" "),
+				Inline::Code("a*b".to_owned()),
+				Inline::LineBreak,
+				text("next"),
+			]
+		);
+		assert_eq!(parse("2 * 3 * 4"), [text("2 * 3 * 4")]);
+		assert_eq!(parse("**unclosed"), [text("**unclosed")]);
+	}
+}
@@ -0,0 +1,18 @@
+pub mod api_keys_panel;
+pub mod comments;
+pub mod drafts_panel;
+pub mod local_time;
+pub mod markdown_lite;
+pub mod notification_list;
+pub mod post_card;
+pub mod publish_panel;
+pub mod remix_tree;
+pub mod report_button;
+pub mod require_auth;
+pub mod search_bar;
+pub mod share_panel;
+pub mod tag_list;
+pub mod template_picker;
+pub mod template_recognizer;
+pub mod user_menu;
+pub mod vote_buttons;
@@ -0,0 +1,63 @@
+use crate::{
+	api::notifications::{NotificationInfo, NotificationKind, list_notifications, mark_notifications_read},
+	router::Route,
+};
+use dioxus::prelude::*;
+
+#[component]
+pub fn NotificationList() -> Element {
+	let mut notifications = use_signal(Vec::<NotificationInfo>::new);
+	let mut error = use_signal(|| None::<String>);
+
+	use_future(move || async move {
+		match list_notifications().await {
+			Ok(list) => notifications.set(list),
+			Err(e) => error.set(Some(e.to_string())),
+		}
+	});
+
+	let mark_read = move |_| async move {
+		if mark_notifications_read().await.is_ok() {
+			for notification in notifications.write().iter_mut() {
+				notification.read = true;
+			}
+		}
+	};
+	let unread = notifications.read().iter().filter(|notification| !notification.read).count();
+
+	rsx! {
+		section { class: "space-y-3",
+			div { class: "flex items-center justify-between",
+				h2 { class: "text-lg font-semibold", "Notifications ({unread} unread)" }
+				if unread > 0 {
+					button {
+						onclick: mark_read,
+						class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+						"Mark all read"
+					}
+				}
+			}
+			if let Some(error) = error() {
+				p { class: "text-sm text-red-400", "{error}" }
+			}
+			if notifications.read().is_empty() {
+				p { class: "text-sm text-slate-400", "Nothing yet." }
+			}
+			for NotificationInfo { id, kind, actor, meme_id, meme_title, read, .. } in notifications() {
+				Link {
+					key: "{id}",
+					to: Route::MemePost { id: meme_id.to_string() },
+					class: if read { "block border rounded-lg p-2 text-sm text-slate-400" } else { "block border-2 border-blue-500 rounded-lg p-2 text-sm" },
+					{
+						let actor = actor.unwrap_or_else(|| "Someone".to_owned());
+						let title = meme_title.unwrap_or_else(|| "your post".to_owned());
+						match kind {
+							NotificationKind::CommentReply => format!("{actor} replied to your comment on {title}"),
+							NotificationKind::PostComment => format!("{actor} commented on {title}"),
+						}
+					}
+				}
+			}
+		}
+	}
+}
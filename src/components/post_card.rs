@@ -0,0 +1,36 @@
+use crate::{
+	api::posts::PostSummary,
+	components::{tag_list::TagList, vote_buttons::VoteButtons},
+	router::Route,
+};
+use dioxus::prelude::*;
+
+#[component]
+pub fn PostCard(post: PostSummary) -> Element {
+	let PostSummary { id, title, image_url, author, score, views, my_vote, tags, .. } = post;
+
+	rsx! {
+		article { class: "border border-white/20 rounded-xl overflow-hidden",
+			Link { to: Route::MemePost { id: id.to_string() },
+				img { src: "{image_url}", alt: "{title}", loading: "lazy", class: "w-full" }
+			}
+			div { class: "flex items-center justify-between gap-4 p-3",
+				div { class: "min-w-0",
+					Link {
+						to: Route::MemePost { id: id.to_string() },
+						class: "block font-semibold truncate hover:text-cyan-400",
+						"{title}"
+					}
+					if let Some(author) = author {
+						p { class: "text-xs text-slate-400", "by {author}" }
+					}
+					TagList { tags }
+				}
+				div { class: "flex items-center gap-4 shrink-0",
+					span { class: "text-xs text-slate-400", "{views} views" }
+					VoteButtons { meme_id: id, score, my_vote }
+				}
+			}
+		}
+	}
+}
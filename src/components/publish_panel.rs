@@ -0,0 +1,94 @@
+use crate::{
+	api::posts::{MAX_TITLE_LEN, publish_meme},
+	router::Route,
+	stores::{
+		meme_canvas::{MemeCanvas, MemeCanvasStoreImplExt},
+		session::use_session,
+	},
+	tags::parse_tags,
+	utils::meme_canvas_png,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use uuid::Uuid;
+
+/// `parent_id` is the post being remixed, recorded as the new post's parent. `tournament_id` enters the post into a tournament.
+#[component]
+pub fn PublishPanel(meme_canvas: Store<MemeCanvas>, parent_id: Option<Uuid>, tournament_id: Option<Uuid>) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let navigator = use_navigator();
+	let mut title = use_signal(String::new);
+	let mut tags = use_signal(String::new);
+	let mut publishing = use_signal(|| false);
+
+	if !session.is_logged_in() {
+		return rsx! {
+			Link { to: Route::Login { redirect: String::new() }, class: "block text-sm text-blue-400 hover:text-blue-300", "Log in to publish" }
+		};
+	}
+
+	let publish = move |_| async move {
+		let tags = match parse_tags(&tags.read()) {
+			Ok(tags) => tags,
+			Err(e) => {
+				toast.error("Invalid tags".to_owned(), ToastOptions::new().description(e.to_string()));
+				return;
+			},
+		};
+		let render = match meme_canvas_png() {
+			Ok(render) => render,
+			Err(e) => {
+				toast.error("Could not render meme".to_owned(), ToastOptions::new().description(e.to_string()));
+				return;
+			},
+		};
+		publishing.set(true);
+		match publish_meme(meme_canvas.document(), title(), render, tags, parent_id, tournament_id).await {
+			Ok(id) => {
+				navigator.push(Route::MemePost { id: id.to_string() });
+			},
+			Err(e) => toast.error("Could not publish".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		publishing.set(false);
+	};
+
+	rsx! {
+		div { class: "space-y-2",
+			if let Some(parent_id) = parent_id {
+				Link {
+					to: Route::MemePost { id: parent_id.to_string() },
+					class: "block text-sm text-blue-400 hover:text-blue-300",
+					"Remixing a post"
+				}
+			}
+			input {
+				r#type: "text",
+				value: "{title}",
+				maxlength: MAX_TITLE_LEN,
+				oninput: move |evt| title.set(evt.value()),
+				placeholder: "Title",
+				class: "w-full px-4 py-3 text-base border-2 rounded-lg focus:outline-none transition-all duration-200",
+			}
+			input {
+				r#type: "text",
+				value: "{tags}",
+				oninput: move |evt| tags.set(evt.value()),
+				placeholder: "Tags, e.g. cats monday",
+				class: "w-full px-4 py-2 text-sm border-2 rounded-lg focus:outline-none transition-all duration-200",
+			}
+			button {
+				onclick: publish,
+				disabled: publishing() || title.read().trim().is_empty(),
+				class: "w-full cursor-pointer font-semibold py-3 px-4 rounded-lg bg-blue-500 hover:bg-blue-600 disabled:opacity-50 transition-colors duration-200 shadow-md hover:shadow-lg",
+				if publishing() {
+					"Publishing..."
+				} else if tournament_id.is_some() {
+					"Publish and enter"
+				} else {
+					"Publish"
+				}
+			}
+		}
+	}
+}
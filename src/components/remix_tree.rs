@@ -0,0 +1,79 @@
+use crate::{
+	api::remixes::{Lineage, RemixNode, get_lineage},
+	router::Route,
+};
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+/// Where a post came from and what has been made of it since.
+#[component]
+pub fn RemixTree(meme_id: Uuid) -> Element {
+	let mut lineage = use_signal(Lineage::default);
+
+	use_future(move || async move {
+		if let Ok(loaded) = get_lineage(meme_id).await {
+			lineage.set(loaded);
+		}
+	});
+
+	let Lineage { ancestors, remixes } = lineage();
+	if ancestors.is_empty() && remixes.is_empty() {
+		return rsx! {};
+	}
+	let children = remixes.iter().filter(|remix| remix.parent_id == Some(meme_id)).cloned().collect::<Vec<_>>();
+
+	rsx! {
+		section { class: "space-y-3",
+			if !ancestors.is_empty() {
+				div { class: "flex flex-wrap items-center gap-2 text-sm text-slate-400",
+					span { "Remix of" }
+					for (index , ancestor) in ancestors.into_iter().enumerate() {
+						if index > 0 {
+							span { "›" }
+						}
+						Link {
+							key: "{ancestor.id}",
+							to: Route::MemePost { id: ancestor.id.to_string() },
+							class: "text-blue-400 hover:text-blue-300",
+							"{ancestor.title}"
+						}
+					}
+				}
+			}
+			if !children.is_empty() {
+				h2 { class: "text-xl font-semibold", "Remixes ({remixes.len()})" }
+				for remix in children {
+					RemixBranch { key: "{remix.id}", node: remix, remixes: remixes.clone() }
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn RemixBranch(node: RemixNode, remixes: Vec<RemixNode>) -> Element {
+	let children = remixes.iter().filter(|remix| remix.parent_id == Some(node.id)).cloned().collect::<Vec<_>>();
+
+	rsx! {
+		div { class: "space-y-2",
+			Link {
+				to: Route::MemePost { id: node.id.to_string() },
+				class: "flex items-center gap-3 border rounded-lg p-2 hover:border-blue-500",
+				img { src: "{node.image_url}", alt: "{node.title}", class: "w-12 h-12 object-cover rounded" }
+				div { class: "min-w-0",
+					p { class: "text-sm font-semibold truncate", "{node.title}" }
+					if let Some(author) = &node.author {
+						p { class: "text-xs text-slate-400", "by {author}" }
+					}
+				}
+			}
+			if !children.is_empty() {
+				div { class: "ml-4 pl-4 border-l border-white/20 space-y-2",
+					for child in children {
+						RemixBranch { key: "{child.id}", node: child, remixes: remixes.clone() }
+					}
+				}
+			}
+		}
+	}
+}
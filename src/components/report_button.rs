@@ -0,0 +1,66 @@
+use crate::{
+	api::moderation::{MAX_REPORT_DETAILS_LEN, ReportReason, report_post},
+	stores::session::use_session,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use uuid::Uuid;
+
+#[component]
+pub fn ReportButton(meme_id: Uuid) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let mut open = use_signal(|| false);
+	let mut reason = use_signal(|| ReportReason::Spam);
+	let mut details = use_signal(String::new);
+
+	if !session.is_logged_in() {
+		return rsx! {};
+	}
+
+	let submit = move |e: FormEvent| async move {
+		e.prevent_default();
+		match report_post(meme_id, reason(), details()).await {
+			Ok(()) => {
+				toast.success("Thanks, a moderator will take a look".to_owned(), ToastOptions::new());
+				open.set(false);
+				details.set(String::new());
+			},
+			Err(e) => toast.error("Could not send report".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	rsx! {
+		div { class: "space-y-2",
+			button { onclick: move |_| open.toggle(), class: "text-xs text-slate-400 hover:text-red-400", "Report" }
+			if open() {
+				form { class: "space-y-2 border rounded-lg p-3", onsubmit: submit,
+					select {
+						onchange: move |evt| {
+							if let Ok(parsed) = evt.value().parse() {
+								reason.set(parsed);
+							}
+						},
+						class: "w-full px-2 py-1 text-sm border rounded bg-black",
+						for option in ReportReason::selectable() {
+							option { key: "{option}", value: "{option}", selected: option == reason(), "{option.label()}" }
+						}
+					}
+					textarea {
+						value: "{details}",
+						maxlength: MAX_REPORT_DETAILS_LEN,
+						rows: 2,
+						placeholder: "Anything a moderator should know (optional)",
+						oninput: move |evt| details.set(evt.value()),
+						class: "w-full px-3 py-2 text-sm border rounded-lg focus:outline-none bg-transparent",
+					}
+					button {
+						r#type: "submit",
+						class: "px-3 py-1 bg-red-500 text-white rounded-md hover:bg-red-600 transition-colors duration-200 text-sm font-medium",
+						"Send report"
+					}
+				}
+			}
+		}
+	}
+}
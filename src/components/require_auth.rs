@@ -0,0 +1,26 @@
+use crate::{router::Route, stores::session::use_session};
+use dioxus::prelude::*;
+
+/// Layout for routes that need an account. Sends anonymous visitors to the login page and brings them back afterwards.
+#[component]
+pub fn RequireAuth() -> Element {
+	let session = use_session();
+	let route = use_route::<Route>();
+	let navigator = use_navigator();
+
+	use_effect(move || {
+		if (session.loaded)() && !session.is_logged_in() {
+			navigator.replace(Route::Login { redirect: route.to_string() });
+		}
+	});
+
+	if session.is_logged_in() {
+		rsx! {
+			Outlet::<Route> {}
+		}
+	} else {
+		rsx! {
+			p { class: "text-center text-slate-400 p-10", "Checking your session..." }
+		}
+	}
+}
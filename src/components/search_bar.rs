@@ -0,0 +1,33 @@
+use crate::{api::search::MAX_QUERY_LEN, router::Route};
+use dioxus::prelude::*;
+use dioxus_free_icons::{Icon, icons::bs_icons::BsSearch};
+
+#[component]
+pub fn SearchBar() -> Element {
+	let navigator = use_navigator();
+	let mut query = use_signal(String::new);
+
+	let submit = move |e: FormEvent| {
+		e.prevent_default();
+		let q = query.read().trim().to_owned();
+		if !q.is_empty() {
+			navigator.push(Route::Search { q });
+		}
+	};
+
+	rsx! {
+		form { class: "flex items-center gap-2 mr-auto", onsubmit: submit,
+			input {
+				r#type: "search",
+				value: "{query}",
+				maxlength: MAX_QUERY_LEN,
+				placeholder: "Search memes, #tags",
+				oninput: move |evt| query.set(evt.value()),
+				class: "w-48 px-3 py-1 text-sm border border-white/20 rounded-lg focus:outline-none bg-transparent",
+			}
+			button { r#type: "submit", class: "text-slate-400 hover:text-white",
+				Icon { icon: BsSearch, width: 16, height: 16 }
+			}
+		}
+	}
+}
@@ -0,0 +1,64 @@
+use crate::{
+	api::memes::save_meme,
+	router::Route,
+	share::encode_for_link,
+	stores::meme_canvas::{MemeCanvas, MemeCanvasStoreImplExt, MemeDocument},
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+
+async fn share_route(document: MemeDocument) -> anyhow::Result<Route> {
+	if let Some(m) = encode_for_link(&document)? {
+		return Ok(Route::Generator { m, id: String::new(), remix: String::new(), tournament: String::new() });
+	}
+	let id = save_meme(document).await.map_err(|e| anyhow::anyhow!("{e}"))?;
+	Ok(Route::Generator { m: String::new(), id: id.to_string(), remix: String::new(), tournament: String::new() })
+}
+
+#[component]
+pub fn SharePanel(meme_canvas: Store<MemeCanvas>) -> Element {
+	let toast = use_toast();
+	let mut link = use_signal(|| None::<String>);
+
+	let share = move |_| async move {
+		match share_route(meme_canvas.document()).await {
+			Ok(route) => {
+				let origin = gloo::utils::window().location().origin().unwrap_or_default();
+				link.set(Some(format!("{origin}{route}")));
+			},
+			Err(e) => toast.error("Could not create link".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	let copy = move |_| {
+		if let Some(link) = link() {
+			document::eval(&format!("navigator.clipboard.writeText({})", serde_json::to_string(&link).unwrap_or_default()));
+			toast.success("Link copied".to_owned(), ToastOptions::new());
+		}
+	};
+
+	rsx! {
+		div { class: "space-y-2",
+			button {
+				onclick: share,
+				class: "w-full cursor-pointer font-semibold py-3 px-4 rounded-lg transition-colors duration-200 shadow-md hover:shadow-lg",
+				"Share link"
+			}
+			if let Some(link) = link() {
+				div { class: "flex gap-2",
+					input {
+						r#type: "text",
+						readonly: true,
+						value: "{link}",
+						class: "flex-1 min-w-0 px-2 py-1 text-sm border rounded focus:outline-none bg-transparent",
+					}
+					button {
+						onclick: copy,
+						class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+						"Copy"
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,19 @@
+use crate::router::Route;
+use dioxus::prelude::*;
+
+/// Tags as links to a search for that tag.
+#[component]
+pub fn TagList(tags: Vec<String>) -> Element {
+	rsx! {
+		div { class: "flex flex-wrap gap-2",
+			for tag in tags {
+				Link {
+					key: "{tag}",
+					to: Route::Search { q: format!("#{tag}") },
+					class: "px-2 py-0.5 rounded-full bg-white/10 text-xs text-cyan-400 hover:bg-white/20",
+					"#{tag}"
+				}
+			}
+		}
+	}
+}
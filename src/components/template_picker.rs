@@ -0,0 +1,162 @@
+use crate::{
+	api::templates::{SimilarTemplate, UploadOutcome, confirm_template_upload, create_template_upload, discard_template_upload, list_templates},
+	stores::{
+		meme_canvas::{MemeCanvas, MemeCanvasStoreExt},
+		session::use_session,
+	},
+	tags::parse_tags,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use uuid::Uuid;
+
+/// Presigns, sends the bytes straight to storage, then asks the server to verify them and create the template.
+/// Returns the upload id too, since a duplicate upload stays pending until the user decides what to do with it.
+async fn upload_file(file: dioxus::html::FileData, tags: &str) -> anyhow::Result<(Uuid, UploadOutcome)> {
+	let tags = parse_tags(tags)?;
+	let content_type = file.content_type().unwrap_or_default();
+	let file_name = file.name();
+	let name = file_name.rsplit_once('.').map_or(file_name.as_str(), |(stem, _)| stem).to_owned();
+	let upload = create_template_upload(name, content_type).await.map_err(|e| anyhow::anyhow!("{e}"))?;
+	let bytes = file.read_bytes().await?;
+	let response =
+		gloo::net::http::Request::put(&upload.upload_url).header("Content-Type", &upload.content_type).body(js_sys::Uint8Array::from(&bytes[..]))?.send().await?;
+	anyhow::ensure!(response.ok(), "upload rejected with status {}", response.status());
+	let outcome = confirm_template_upload(upload.id, tags, false).await.map_err(|e| anyhow::anyhow!("{e}"))?;
+	Ok((upload.id, outcome))
+}
+
+/// A pending upload that looks like templates we already have.
+#[derive(Clone, PartialEq, Debug)]
+struct DuplicateUpload {
+	id: Uuid,
+	similar: Vec<SimilarTemplate>,
+}
+
+/// Grid of uploaded templates; clicking one swaps the canvas background. Logged in users can upload their own.
+#[component]
+pub fn TemplatePicker(meme_canvas: Store<MemeCanvas>) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let mut templates = use_resource(list_templates);
+	let mut uploading = use_signal(|| false);
+	let mut tags = use_signal(String::new);
+	let mut duplicate = use_signal(|| None::<DuplicateUpload>);
+
+	let upload = move |evt: FormEvent| async move {
+		let Some(file) = evt.files().into_iter().next() else {
+			return;
+		};
+		uploading.set(true);
+		match upload_file(file, &tags()).await {
+			Ok((_, UploadOutcome::Created(template))) => {
+				meme_canvas.main_img_url().set(template.image_url);
+				tags.set(String::new());
+				templates.restart();
+			},
+			Ok((id, UploadOutcome::Duplicates(similar))) => duplicate.set(Some(DuplicateUpload { id, similar })),
+			Err(e) => toast.error("Upload failed".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		uploading.set(false);
+	};
+
+	let upload_anyway = move |id: Uuid| async move {
+		uploading.set(true);
+		let result = async { confirm_template_upload(id, parse_tags(&tags())?, true).await.map_err(|e| anyhow::anyhow!("{e}")) }.await;
+		match result {
+			Ok(UploadOutcome::Created(template)) => {
+				meme_canvas.main_img_url().set(template.image_url);
+				tags.set(String::new());
+				duplicate.set(None);
+				templates.restart();
+			},
+			Ok(UploadOutcome::Duplicates(_)) => {},
+			Err(e) => toast.error("Upload failed".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		uploading.set(false);
+	};
+
+	// Using an existing template or backing out both leave the upload unused, so it is thrown away right away instead of waiting for the sweeper.
+	let discard = move |id: Uuid, image_url: Option<String>| async move {
+		if let Some(image_url) = image_url {
+			meme_canvas.main_img_url().set(image_url);
+		}
+		duplicate.set(None);
+		tags.set(String::new());
+		if let Err(e) = discard_template_upload(id).await {
+			toast.error("Cannot discard upload".to_owned(), ToastOptions::new().description(e.to_string()));
+		}
+	};
+
+	rsx! {
+		div { class: "space-y-2",
+			h2 { class: "text-lg font-semibold", "Templates" }
+			if let Some(Ok(templates)) = templates() {
+				div { class: "grid grid-cols-4 gap-2 max-h-48 overflow-y-auto",
+					for template in templates {
+						img {
+							key: "{template.id}",
+							src: "{template.image_url}",
+							title: "{template.name}",
+							onclick: move |_| meme_canvas.main_img_url().set(template.image_url.clone()),
+							class: "w-full aspect-square object-cover rounded cursor-pointer hover:ring-2 hover:ring-blue-500",
+						}
+					}
+				}
+			}
+			if let Some(DuplicateUpload { id, similar }) = duplicate() {
+				div { class: "p-2 space-y-2 border border-yellow-500 rounded",
+					p { class: "text-sm font-medium", "This template already exists" }
+					p { class: "text-xs text-gray-400", "Pick one of these, or upload yours anyway." }
+					div { class: "grid grid-cols-4 gap-2",
+						for SimilarTemplate { template, .. } in similar {
+							img {
+								key: "{template.id}",
+								src: "{template.image_url}",
+								title: "{template.name}",
+								onclick: move |_| discard(id, Some(template.image_url.clone())),
+								class: "w-full aspect-square object-cover rounded cursor-pointer hover:ring-2 hover:ring-blue-500",
+							}
+						}
+					}
+					div { class: "flex gap-2",
+						button {
+							class: "px-2 py-1 text-xs border rounded hover:bg-gray-700 disabled:opacity-50",
+							disabled: uploading(),
+							onclick: move |_| upload_anyway(id),
+							"Upload anyway"
+						}
+						button {
+							class: "px-2 py-1 text-xs text-gray-400 hover:text-gray-200",
+							onclick: move |_| discard(id, None),
+							"Cancel"
+						}
+					}
+				}
+			}
+			if session.is_logged_in() && duplicate.read().is_none() {
+				input {
+					r#type: "text",
+					value: "{tags}",
+					oninput: move |evt| tags.set(evt.value()),
+					placeholder: "Tags for your upload",
+					class: "w-full px-2 py-1 text-sm border rounded focus:outline-none bg-transparent",
+				}
+				label { class: "block text-sm cursor-pointer text-blue-400 hover:text-blue-300",
+					if uploading() {
+						"Uploading..."
+					} else {
+						"Upload template"
+					}
+					input {
+						r#type: "file",
+						accept: "image/png,image/jpeg,image/gif,image/webp",
+						class: "hidden",
+						disabled: uploading(),
+						onchange: upload,
+					}
+				}
+			}
+		}
+	}
+}
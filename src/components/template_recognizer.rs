@@ -0,0 +1,84 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use dioxus::prelude::*;
+
+use crate::{
+	api::templates::{SimilarTemplate, recognize_template},
+	router::Route,
+	share::encode_for_link,
+	stores::meme_canvas::MemeDocument,
+};
+
+async fn recognize_file(file: dioxus::html::FileData) -> anyhow::Result<Vec<SimilarTemplate>> {
+	let content_type = file.content_type().unwrap_or_default();
+	let bytes = file.read_bytes().await?;
+	let image = format!("data:{content_type};base64,{}", STANDARD.encode(&bytes));
+	recognize_template(image).await.map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+/// Takes a finished meme and lists the templates it was likely made from, each opening a blank copy in the generator.
+#[component]
+pub fn TemplateRecognizer() -> Element {
+	let mut matches = use_signal(|| None::<Result<Vec<SimilarTemplate>, String>>);
+	let mut working = use_signal(|| false);
+
+	let recognize = move |evt: FormEvent| async move {
+		let Some(file) = evt.files().into_iter().next() else {
+			return;
+		};
+		working.set(true);
+		matches.set(Some(recognize_file(file).await.map_err(|e| e.to_string())));
+		working.set(false);
+	};
+
+	rsx! {
+		div { class: "space-y-2",
+			label { class: "block text-sm cursor-pointer text-blue-400 hover:text-blue-300",
+				if working() {
+					"Looking for the template..."
+				} else {
+					"Find the template of a meme"
+				}
+				input {
+					r#type: "file",
+					accept: "image/png,image/jpeg,image/gif,image/webp",
+					class: "hidden",
+					disabled: working(),
+					onchange: recognize,
+				}
+			}
+			{
+				match matches() {
+					None => rsx! {},
+					Some(Err(e)) => rsx! {
+						p { class: "text-sm text-red-400", "{e}" }
+					},
+					Some(Ok(matches)) if matches.is_empty() => rsx! {
+						p { class: "text-sm text-slate-400", "No known template matches this meme." }
+					},
+					Some(Ok(matches)) => rsx! {
+						div { class: "grid grid-cols-3 gap-4",
+							for SimilarTemplate { template, .. } in matches {
+								Link {
+									key: "{template.id}",
+									to: Route::Generator {
+										m: encode_for_link(&MemeDocument::from_template(&template)).ok().flatten().unwrap_or_default(),
+										id: String::new(),
+										remix: String::new(),
+										tournament: String::new(),
+									},
+									class: "space-y-1",
+									img {
+										src: "{template.image_url}",
+										alt: "{template.name}",
+										class: "w-full aspect-square object-cover rounded hover:ring-2 hover:ring-blue-500",
+									}
+									p { class: "text-sm truncate", "{template.name}" }
+								}
+							}
+						}
+					},
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,62 @@
+use crate::{api::auth::logout, router::Route, stores::session::use_session};
+use dioxus::prelude::*;
+use dioxus_free_icons::{Icon, icons::bs_icons::BsPersonCircle};
+
+#[component]
+pub fn UserMenu() -> Element {
+	let mut session = use_session();
+	let mut open = use_signal(|| false);
+	let navigator = use_navigator();
+
+	let Some(user) = session.user.read().clone() else {
+		return rsx! {
+			Link { to: Route::Login { redirect: String::new() }, class: "text-sm font-medium hover:text-cyan-400", "Log in" }
+		};
+	};
+
+	rsx! {
+		div { class: "relative",
+			button {
+				onclick: move |_| open.toggle(),
+				class: "flex items-center gap-2 text-sm font-medium hover:text-cyan-400",
+				Icon { icon: BsPersonCircle, width: 20, height: 20 }
+				"{user.username}"
+			}
+			if open() {
+				div { class: "absolute right-0 mt-2 w-40 rounded-lg border border-white/20 bg-black/90 py-1 shadow-lg",
+					Link {
+						to: Route::Profile {},
+						onclick: move |_| open.set(false),
+						class: "block px-4 py-2 text-sm hover:bg-white/10",
+						"Profile"
+					}
+					Link {
+						to: Route::Points {},
+						onclick: move |_| open.set(false),
+						class: "block px-4 py-2 text-sm hover:bg-white/10",
+						"Points"
+					}
+					if user.moderator {
+						Link {
+							to: Route::Moderation {},
+							onclick: move |_| open.set(false),
+							class: "block px-4 py-2 text-sm hover:bg-white/10",
+							"Moderation"
+						}
+					}
+					button {
+						onclick: move |_| async move {
+							if logout().await.is_ok() {
+								session.user.set(None);
+								open.set(false);
+								navigator.push(Route::Home {});
+							}
+						},
+						class: "block w-full text-left px-4 py-2 text-sm hover:bg-white/10",
+						"Log out"
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,53 @@
+use crate::{api::engagement::vote, router::Route, stores::session::use_session};
+use dioxus::prelude::*;
+use dioxus_free_icons::{
+	Icon,
+	icons::bs_icons::{BsArrowDownCircle, BsArrowDownCircleFill, BsArrowUpCircle, BsArrowUpCircleFill},
+};
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use uuid::Uuid;
+
+/// Up and down vote buttons around the score. Clicking the active direction again takes the vote back.
+#[component]
+pub fn VoteButtons(meme_id: Uuid, score: i64, my_vote: i16) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let navigator = use_navigator();
+	let mut score = use_signal(|| score);
+	let mut my_vote = use_signal(|| my_vote);
+
+	let cast = move |value: i16| async move {
+		if !session.is_logged_in() {
+			navigator.push(Route::Login { redirect: Route::MemePost { id: meme_id.to_string() }.to_string() });
+			return;
+		}
+		let value = if my_vote() == value { 0 } else { value };
+		match vote(meme_id, value).await {
+			Ok(engagement) => {
+				score.set(engagement.score());
+				my_vote.set(engagement.my_vote);
+			},
+			Err(e) => toast.error("Could not vote".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	rsx! {
+		div { class: "flex items-center gap-2",
+			button { onclick: move |_| cast(1), class: "hover:text-orange-400", title: "Upvote",
+				if my_vote() > 0 {
+					Icon { icon: BsArrowUpCircleFill, width: 20, height: 20, class: "text-orange-400" }
+				} else {
+					Icon { icon: BsArrowUpCircle, width: 20, height: 20 }
+				}
+			}
+			span { class: "text-sm font-semibold tabular-nums", "{score}" }
+			button { onclick: move |_| cast(-1), class: "hover:text-blue-400", title: "Downvote",
+				if my_vote() < 0 {
+					Icon { icon: BsArrowDownCircleFill, width: 20, height: 20, class: "text-blue-400" }
+				} else {
+					Icon { icon: BsArrowDownCircle, width: 20, height: 20 }
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,4 @@
+//! Game rules shared by the server, which settles bets, and the client, which previews them. Everything here is pure.
+
+pub mod percentile;
+pub mod poker;
@@ -0,0 +1,162 @@
+//! The meme analytics game: players bet on which engagement percentile window a scraped meme ends up in.
+//!
+//! A meme's percentile is its rank among the settled memes of the same source, placed uniformly at random within the span its ties
+//! share. Many memes tie at no engagement at all, and giving them all one mid-rank would pile them onto a single percentile that a
+//! narrow window could bet on. Drawn this way percentiles are spread evenly over `0..100` however the values tie, so a window `width`
+//! points wide hits with probability `width / 100` and the fair multiplier is `100 / width`. The house keeps [`HOUSE_EDGE`] of that.
+
+use serde::{Deserialize, Serialize};
+
+/// Windows start and end on multiples of this.
+pub const WINDOW_STEP: u8 = 5;
+pub const HOUSE_EDGE: f64 = 0.05;
+pub const MIN_STAKE: i64 = 1;
+pub const MAX_STAKE: i64 = 1000;
+/// A comment takes more effort than a vote, so it counts for more.
+const COMMENT_WEIGHT: f64 = 3.0;
+
+/// Percentiles from `low` up to `high`, with the top window also holding 100 itself.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PercentileWindow {
+	pub low: u8,
+	pub high: u8,
+}
+
+impl PercentileWindow {
+	pub fn new(low: u8, high: u8) -> anyhow::Result<Self> {
+		anyhow::ensure!(low < high && high <= 100, "window must be within 0 to 100 and not empty");
+		anyhow::ensure!(low.is_multiple_of(WINDOW_STEP) && high.is_multiple_of(WINDOW_STEP), "window bounds must be multiples of {WINDOW_STEP}");
+		Ok(Self { low, high })
+	}
+
+	pub fn width(self) -> u8 {
+		self.high - self.low
+	}
+
+	pub fn contains(self, percentile: f64) -> bool {
+		percentile >= f64::from(self.low) && (percentile < f64::from(self.high) || (self.high == 100 && percentile <= 100.0))
+	}
+
+	/// What a winning bet returns per point staked, the stake included.
+	pub fn multiplier(self) -> f64 {
+		(1.0 - HOUSE_EDGE) * 100.0 / f64::from(self.width())
+	}
+
+	/// Points paid out for a winning bet of `stake`, rounded down.
+	pub fn payout(self, stake: i64) -> i64 {
+		(stake as f64 * self.multiplier()).floor() as i64
+	}
+}
+
+pub fn validate_stake(stake: i64) -> anyhow::Result<()> {
+	anyhow::ensure!((MIN_STAKE..=MAX_STAKE).contains(&stake), "stake must be between {MIN_STAKE} and {MAX_STAKE} points");
+	Ok(())
+}
+
+/// One number for how well a meme did. Log scaled so a few viral posts do not squash everything else together.
+pub fn engagement(score: i64, comments: i64) -> f64 {
+	(score.max(0) as f64 + COMMENT_WEIGHT * comments.max(0) as f64).ln_1p()
+}
+
+/// Percentile of a value given how many of the `total` reference values are below and equal to it, `None` without a reference.
+/// `tie_break`, uniform in `0.0..1.0`, places the value within the span of the values equal to it.
+pub fn percentile_rank(below: u64, equal: u64, total: u64, tie_break: f64) -> Option<f64> {
+	(total > 0).then(|| ((below as f64 + tie_break * equal as f64) / total as f64 * 100.0).clamp(0.0, 100.0))
+}
+
+/// What a settled bet pays out: [`PercentileWindow::payout`] when the window holds `percentile`, nothing otherwise.
+pub fn settle(window: PercentileWindow, stake: i64, percentile: f64) -> i64 {
+	if window.contains(percentile) { window.payout(stake) } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::game::poker::rng::SeededRng;
+
+	fn windows() -> impl Iterator<Item = PercentileWindow> {
+		(0..=100).step_by(WINDOW_STEP.into()).flat_map(|low| (low + WINDOW_STEP..=100).step_by(WINDOW_STEP.into()).map(move |high| PercentileWindow { low, high }))
+	}
+
+	#[test]
+	fn percentile_rank_is_bounded_and_monotonic_in_below() {
+		assert_eq!(percentile_rank(0, 0, 0, 0.5), None);
+		for total in 1..=40 {
+			for equal in 0..=total {
+				for tie_break in [0.0, 0.25, 0.5, 0.999] {
+					let mut previous = -1.0;
+					for below in 0..=total - equal {
+						let rank = percentile_rank(below, equal, total, tie_break).expect("reference is not empty");
+						// Only a value missing from the reference can rank above all of it.
+						assert!((0.0..100.0).contains(&rank) || (equal == 0 && rank == 100.0), "{below} {equal} {total} {tie_break} gave {rank}");
+						assert!(rank > previous, "{below} {equal} {total} {tie_break} gave {rank} after {previous}");
+						previous = rank;
+					}
+				}
+			}
+		}
+	}
+
+	/// Ranks memes drawn from a reference where most never got any engagement, and checks every window hits about as often as its
+	/// width says, which is what the multipliers are priced on.
+	#[test]
+	fn windows_hit_at_their_width_despite_heavy_ties() {
+		let mut reference = vec![engagement(0, 0); 700];
+		reference.extend((1..=200).map(|score| engagement(score % 20, 0)));
+		reference.extend((1..=100).map(|score| engagement(score * 10, score)));
+		let total = reference.len() as u64;
+		let mut rng = SeededRng::new(41);
+		let percentiles = (0..200_000)
+			.map(|_| {
+				let value = reference[rng.below(total) as usize];
+				let below = reference.iter().filter(|other| **other < value).count() as u64;
+				let equal = reference.iter().filter(|other| **other == value).count() as u64;
+				percentile_rank(below, equal, total, rng.unit()).expect("reference is not empty")
+			})
+			.collect::<Vec<_>>();
+		for window in windows() {
+			let hit_rate = percentiles.iter().filter(|percentile| window.contains(**percentile)).count() as f64 / percentiles.len() as f64;
+			let expected = f64::from(window.width()) / 100.0;
+			assert!((hit_rate - expected).abs() < 0.01, "{window:?} hit {hit_rate}, expected {expected}");
+		}
+	}
+
+	#[test]
+	fn windows_must_be_ordered_non_empty_and_on_steps() {
+		for low in 0..=u8::MAX {
+			for high in 0..=u8::MAX {
+				let valid = low < high && high <= 100 && low.is_multiple_of(WINDOW_STEP) && high.is_multiple_of(WINDOW_STEP);
+				assert_eq!(PercentileWindow::new(low, high).is_ok(), valid, "{low}..{high}");
+			}
+		}
+		assert_eq!(windows().count(), 210);
+	}
+
+	#[test]
+	fn settle_pays_nothing_outside_and_at_most_the_odds_inside() {
+		for window in windows() {
+			for stake in [MIN_STAKE, 7, 100, 333, MAX_STAKE] {
+				for tenth in 0..=1000 {
+					let percentile = f64::from(tenth) / 10.0;
+					let paid = settle(window, stake, percentile);
+					if window.contains(percentile) {
+						assert!(paid >= 0 && paid as f64 <= stake as f64 * window.multiplier(), "{window:?} {stake} {percentile} paid {paid}");
+						assert_eq!(paid, window.payout(stake));
+					} else {
+						assert_eq!(paid, 0, "{window:?} {stake} {percentile}");
+						assert!(percentile < f64::from(window.low) || percentile >= f64::from(window.high));
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn every_percentile_falls_in_exactly_one_window_of_a_partition() {
+		for tenth in 0..=1000 {
+			let percentile = f64::from(tenth) / 10.0;
+			let partition = (0..100).step_by(WINDOW_STEP.into()).map(|low| PercentileWindow { low, high: low + WINDOW_STEP });
+			assert_eq!(partition.filter(|window| window.contains(percentile)).count(), 1, "{percentile}");
+		}
+	}
+}
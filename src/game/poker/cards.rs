@@ -0,0 +1,97 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize, strum::EnumIter)]
+pub enum Rank {
+	Two = 2,
+	Three,
+	Four,
+	Five,
+	Six,
+	Seven,
+	Eight,
+	Nine,
+	Ten,
+	Jack,
+	Queen,
+	King,
+	Ace,
+}
+
+impl Rank {
+	pub fn value(self) -> u8 {
+		self as u8
+	}
+
+	pub fn symbol(self) -> char {
+		match self {
+			Self::Ten => 'T',
+			Self::Jack => 'J',
+			Self::Queen => 'Q',
+			Self::King => 'K',
+			Self::Ace => 'A',
+			rank => char::from(b'0' + rank.value()),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize, strum::EnumIter)]
+pub enum Suit {
+	Clubs,
+	Diamonds,
+	Hearts,
+	Spades,
+}
+
+impl Suit {
+	pub fn symbol(self) -> char {
+		match self {
+			Self::Clubs => 'c',
+			Self::Diamonds => 'd',
+			Self::Hearts => 'h',
+			Self::Spades => 's',
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct Card {
+	pub rank: Rank,
+	pub suit: Suit,
+}
+
+impl Card {
+	pub fn new(rank: Rank, suit: Suit) -> Self {
+		Self { rank, suit }
+	}
+}
+
+/// `As`, `Td`, `2c`.
+impl fmt::Display for Card {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}{}", self.rank.symbol(), self.suit.symbol())
+	}
+}
+
+/// Parses what [`Card`]'s `Display` writes.
+impl FromStr for Card {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> anyhow::Result<Self> {
+		use strum::IntoEnumIterator;
+		let mut chars = s.chars();
+		let (Some(rank), Some(suit), None) = (chars.next(), chars.next(), chars.next()) else {
+			anyhow::bail!("cards are written as rank and suit, like As or Td");
+		};
+		let rank = Rank::iter().find(|candidate| candidate.symbol() == rank.to_ascii_uppercase()).ok_or_else(|| anyhow::anyhow!("unknown rank {rank}"))?;
+		let suit = Suit::iter().find(|candidate| candidate.symbol() == suit.to_ascii_lowercase()).ok_or_else(|| anyhow::anyhow!("unknown suit {suit}"))?;
+		Ok(Self::new(rank, suit))
+	}
+}
+
+/// The 52 cards in a fixed order, to be shuffled.
+pub fn standard_deck() -> Vec<Card> {
+	use strum::IntoEnumIterator;
+	Suit::iter().flat_map(|suit| Rank::iter().map(move |rank| Card::new(rank, suit))).collect()
+}
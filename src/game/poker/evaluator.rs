@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+
+use super::cards::Card;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize, strum::Display)]
+#[strum(serialize_all = "title_case")]
+pub enum HandCategory {
+	HighCard,
+	OnePair,
+	TwoPair,
+	ThreeOfAKind,
+	Straight,
+	Flush,
+	FullHouse,
+	FourOfAKind,
+	StraightFlush,
+}
+
+/// Orders five card hands: by category, then by the ranks that break ties within it, most significant first and zero padded.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct HandValue {
+	pub category: HandCategory,
+	pub tiebreak: [u8; 5],
+}
+
+/// Top rank of the straight the sorted, distinct `ranks` form, counting A-2-3-4-5 as five high.
+fn straight_high(ranks: &[u8]) -> Option<u8> {
+	match ranks {
+		[14, 5, 4, 3, 2] => Some(5),
+		[high, .., low] if ranks.len() == 5 && high - low == 4 => Some(*high),
+		_ => None,
+	}
+}
+
+pub fn evaluate_five(cards: &[Card; 5]) -> HandValue {
+	let mut ranks = cards.map(|card| card.rank.value());
+	ranks.sort_unstable_by(|a, b| b.cmp(a));
+	let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+	let mut distinct = ranks.to_vec();
+	distinct.dedup();
+	let straight = straight_high(&distinct);
+
+	// (count, rank), biggest groups first and higher ranks first within a size.
+	let mut groups = distinct.iter().map(|&rank| (ranks.iter().filter(|&&other| other == rank).count(), rank)).collect::<Vec<_>>();
+	groups.sort_unstable_by(|a, b| b.cmp(a));
+	let mut by_group = [0; 5];
+	for (slot, (_, rank)) in by_group.iter_mut().zip(&groups) {
+		*slot = *rank;
+	}
+
+	let (category, tiebreak) = match (straight, flush, groups[0].0, groups.get(1).map(|group| group.0)) {
+		(Some(high), true, ..) => (HandCategory::StraightFlush, [high, 0, 0, 0, 0]),
+		(_, _, 4, _) => (HandCategory::FourOfAKind, by_group),
+		(_, _, 3, Some(2)) => (HandCategory::FullHouse, by_group),
+		(_, true, ..) => (HandCategory::Flush, ranks),
+		(Some(high), ..) => (HandCategory::Straight, [high, 0, 0, 0, 0]),
+		(_, _, 3, _) => (HandCategory::ThreeOfAKind, by_group),
+		(_, _, 2, Some(2)) => (HandCategory::TwoPair, by_group),
+		(_, _, 2, _) => (HandCategory::OnePair, by_group),
+		_ => (HandCategory::HighCard, ranks),
+	};
+	HandValue { category, tiebreak }
+}
+
+/// The best five card hand among five to seven cards, and the cards making it.
+pub fn best_hand(cards: &[Card]) -> (HandValue, [Card; 5]) {
+	assert!((5..=7).contains(&cards.len()), "a hand is made from 5 to 7 cards");
+	let mut best: Option<(HandValue, [Card; 5])> = None;
+	for mask in 0_u32..(1 << cards.len()) {
+		if mask.count_ones() != 5 {
+			continue;
+		}
+		let mut picked = [cards[0]; 5];
+		for (slot, card) in picked.iter_mut().zip(cards.iter().enumerate().filter(|(index, _)| mask & (1 << index) != 0).map(|(_, card)| card)) {
+			*slot = *card;
+		}
+		let value = evaluate_five(&picked);
+		if best.as_ref().is_none_or(|(best_value, _)| value > *best_value) {
+			best = Some((value, picked));
+		}
+	}
+	best.expect("at least one five card combination")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{HashMap, HashSet};
+
+	use super::*;
+	use crate::game::poker::cards::standard_deck;
+
+	fn cards(hand: &str) -> Vec<Card> {
+		hand.split_whitespace().map(|card| card.parse().expect("valid card")).collect()
+	}
+
+	fn value(hand: &str) -> HandValue {
+		best_hand(&cards(hand)).0
+	}
+
+	#[test]
+	fn every_five_card_hand_lands_in_its_category() {
+		let deck = standard_deck();
+		let mut counts = HashMap::<HandCategory, u64>::new();
+		let mut distinct = HashSet::new();
+		for a in 0..52 {
+			for b in a + 1..52 {
+				for c in b + 1..52 {
+					for d in c + 1..52 {
+						for e in d + 1..52 {
+							let value = evaluate_five(&[deck[a], deck[b], deck[c], deck[d], deck[e]]);
+							*counts.entry(value.category).or_default() += 1;
+							distinct.insert(value);
+						}
+					}
+				}
+			}
+		}
+		assert_eq!(counts[&HandCategory::StraightFlush], 40);
+		assert_eq!(counts[&HandCategory::FourOfAKind], 624);
+		assert_eq!(counts[&HandCategory::FullHouse], 3744);
+		assert_eq!(counts[&HandCategory::Flush], 5108);
+		assert_eq!(counts[&HandCategory::Straight], 10_200);
+		assert_eq!(counts[&HandCategory::ThreeOfAKind], 54_912);
+		assert_eq!(counts[&HandCategory::TwoPair], 123_552);
+		assert_eq!(counts[&HandCategory::OnePair], 1_098_240);
+		assert_eq!(counts[&HandCategory::HighCard], 1_302_540);
+		// The number of distinct five card hand values in poker.
+		assert_eq!(distinct.len(), 7462);
+	}
+
+	#[test]
+	fn categories_order() {
+		let ladder = [
+			"As Kd 9c 7h 3s",
+			"2s 2d 3c 4h 6s",
+			"2s 2d 3c 3h 4s",
+			"2s 2d 2c 3h 4s",
+			"As 2d 3c 4h 5s",
+			"2s 3s 4s 5s 7s",
+			"2s 2d 2c 3h 3s",
+			"2s 2d 2c 2h 3s",
+			"As 2s 3s 4s 5s",
+		];
+		for pair in ladder.windows(2) {
+			assert!(value(pair[0]) < value(pair[1]), "{} should lose to {}", pair[0], pair[1]);
+			assert!(value(pair[0]).category < value(pair[1]).category);
+		}
+		assert_eq!(HandCategory::ThreeOfAKind.to_string(), "Three Of A Kind");
+	}
+
+	#[test]
+	fn kickers_break_ties_within_a_category() {
+		assert!(value("Ah Ad Kc Kd 2s") > value("Ah Ad Qc Qd Ks"));
+		assert!(value("Ah Ad Kc Kd 3s") > value("As Ac Kh Ks 2s"));
+		assert!(value("9h 9d Ac 5d 2s") > value("9s 9c Kh Qd Js"));
+		assert!(value("Ah Qd 9c 7h 4s") > value("Ad Qc 9h 7s 3d"));
+		assert!(value("Kh Kd Kc 2s 2d") > value("Qh Qd Qc As Ad"));
+		assert_eq!(value("Kh Kd Kc 2s 2d 2c 7h").tiebreak[..2], [13, 2]);
+		// Suits never matter, and the board can play for both.
+		assert_eq!(value("Ah Ad Kc Kd 3s 2c 2d"), value("As Ac Kh Ks 3d"));
+		assert_eq!(value("Ah Kh Qd Jd 9c 2s 3s"), value("As Ks Qc Jc 9d 4h 5h"));
+	}
+
+	#[test]
+	fn the_wheel_is_the_lowest_straight() {
+		let wheel = value("As 2d 3c 4h 5s");
+		assert_eq!(wheel.category, HandCategory::Straight);
+		assert_eq!(wheel.tiebreak, [5, 0, 0, 0, 0]);
+		assert!(wheel < value("2d 3c 4h 5s 6s"));
+		assert!(wheel > value("As Ad Ac Kh Qs"));
+		assert_eq!(value("Ts Js Qs Ks As 2c 2d").category, HandCategory::StraightFlush);
+		assert!(value("Ah 2h 3h 4h 5h") < value("2h 3h 4h 5h 6h"));
+		// Ace high does not wrap around.
+		assert_eq!(value("Qs Kd Ac 2h 3s").category, HandCategory::HighCard);
+	}
+
+	#[test]
+	fn best_hand_picks_five_of_seven() {
+		assert_eq!(value("2c 3c 4c 5c 7d 8d 9d").category, HandCategory::HighCard);
+		assert_eq!(value("2c 3c 4c 5c 6d 8c 9d").category, HandCategory::Flush);
+		let (made, used) = best_hand(&cards("Kh Kd Kc 2s 2d 2c 7h"));
+		assert_eq!(made.category, HandCategory::FullHouse);
+		assert_eq!(used.iter().filter(|card| card.rank.value() == 13).count(), 3);
+	}
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Variant;
+use crate::game::percentile::{PercentileWindow, settle};
+
+/// What every meme bet card is worth going in, in points. Its showdown value is what this stake pays back.
+pub const MEME_BET_STAKE: i64 = 100;
+
+/// A card of meme poker: a bet that a scraped meme lands in a percentile window of its source.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MemeBet {
+	pub record_id: Uuid,
+	pub title: String,
+	pub image_url: String,
+	pub window: PercentileWindow,
+	pub percentile: f64,
+}
+
+impl MemeBet {
+	pub fn returns(&self) -> i64 {
+		settle(self.window, MEME_BET_STAKE, self.percentile)
+	}
+}
+
+/// The summed return of a player's best five bets. Equal totals go to whoever holds the bigger single return, then the next.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct MemeHandValue {
+	pub total: i64,
+	/// Returns of the counted bets, highest first.
+	pub returns: Vec<i64>,
+}
+
+pub fn best_meme_hand(bets: &[MemeBet]) -> MemeHandValue {
+	let mut returns = bets.iter().map(MemeBet::returns).collect::<Vec<_>>();
+	returns.sort_unstable_by(|a, b| b.cmp(a));
+	returns.truncate(5);
+	MemeHandValue { total: returns.iter().sum(), returns }
+}
+
+/// Hold'em dealt from a deck of settled meme bets instead of playing cards.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MemePoker {
+	deck: Vec<MemeBet>,
+}
+
+impl MemePoker {
+	pub fn new(deck: Vec<MemeBet>) -> Self {
+		Self { deck }
+	}
+}
+
+impl Variant for MemePoker {
+	type Card = MemeBet;
+	type Value = MemeHandValue;
+
+	fn deck(&self) -> Vec<MemeBet> {
+		self.deck.clone()
+	}
+
+	fn value(&self, cards: &[MemeBet]) -> MemeHandValue {
+		best_meme_hand(cards)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn bet(window: PercentileWindow, percentile: f64) -> MemeBet {
+		MemeBet { record_id: Uuid::nil(), title: String::new(), image_url: String::new(), window, percentile }
+	}
+
+	fn hand(returns: &[i64]) -> MemeHandValue {
+		MemeHandValue { total: returns.iter().sum(), returns: returns.to_vec() }
+	}
+
+	#[test]
+	fn bets_return_their_settlement() {
+		let window = PercentileWindow::new(0, 50).expect("valid window");
+		assert_eq!(bet(window, 10.0).returns(), window.payout(MEME_BET_STAKE));
+		assert_eq!(bet(window, 75.0).returns(), 0);
+	}
+
+	#[test]
+	fn the_best_five_bets_count() {
+		let wide = PercentileWindow::new(0, 50).expect("valid window");
+		let narrow = PercentileWindow::new(0, 10).expect("valid window");
+		let bets = [bet(wide, 1.0), bet(narrow, 1.0), bet(wide, 90.0), bet(wide, 2.0), bet(wide, 3.0), bet(wide, 4.0), bet(narrow, 50.0)];
+		let value = best_meme_hand(&bets);
+		assert_eq!(
+			value.returns,
+			vec![narrow.payout(MEME_BET_STAKE), wide.payout(MEME_BET_STAKE), wide.payout(MEME_BET_STAKE), wide.payout(MEME_BET_STAKE), wide.payout(MEME_BET_STAKE)]
+		);
+		assert_eq!(value.total, value.returns.iter().sum::<i64>());
+		assert_eq!(best_meme_hand(&bets[2..3]), hand(&[0]));
+	}
+
+	#[test]
+	fn totals_rank_first_then_the_biggest_returns() {
+		assert!(hand(&[190, 190, 0, 0, 0]) > hand(&[190, 95, 0, 0, 0]));
+		assert!(hand(&[380, 0, 0, 0, 0]) > hand(&[190, 190, 0, 0, 0]));
+		assert!(hand(&[190, 190, 0, 0, 0]) > hand(&[190, 95, 95, 0, 0]));
+		assert_eq!(hand(&[190, 95, 0, 0, 0]).cmp(&hand(&[190, 95, 0, 0, 0])), std::cmp::Ordering::Equal);
+	}
+}
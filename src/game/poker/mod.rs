@@ -0,0 +1,43 @@
+//! Meme poker: no-limit Texas hold'em whose betting engine plays either with regular cards or with a deck of meme bets.
+//!
+//! Every shuffle comes from a [`rng::SeededRng`], so a hand is fully reproduced by its variant, stacks, button and seed.
+
+pub mod cards;
+pub mod evaluator;
+pub mod meme;
+pub mod pots;
+pub mod rng;
+pub mod table;
+
+use std::fmt::Debug;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// What a hand is dealt from and how the hands at showdown compare.
+pub trait Variant {
+	type Card: Clone + Debug + Serialize + DeserializeOwned;
+	type Value: Ord + Clone + Debug + Serialize + DeserializeOwned;
+
+	/// The full deck, before shuffling.
+	fn deck(&self) -> Vec<Self::Card>;
+
+	/// Value of the best hand a player makes from their hole cards and the board.
+	fn value(&self, cards: &[Self::Card]) -> Self::Value;
+}
+
+/// Regular hold'em with a 52 card deck.
+#[derive(Clone, Copy, Debug, Default, Serialize, serde::Deserialize)]
+pub struct Holdem;
+
+impl Variant for Holdem {
+	type Card = cards::Card;
+	type Value = evaluator::HandValue;
+
+	fn deck(&self) -> Vec<cards::Card> {
+		cards::standard_deck()
+	}
+
+	fn value(&self, cards: &[cards::Card]) -> evaluator::HandValue {
+		evaluator::best_hand(cards).0
+	}
+}
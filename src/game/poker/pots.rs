@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Pot {
+	pub amount: u64,
+	/// Seats that can win this pot, ascending.
+	pub eligible: Vec<usize>,
+}
+
+/// Splits what every seat put in over the hand into the main pot and side pots.
+///
+/// Each live contribution level closes a pot that only players who matched it can win. Folded chips stay in the pots they
+/// reached but make nobody eligible, and anything put in above the deepest live level goes to the last pot.
+pub fn build_pots(contributions: &[u64], folded: &[bool]) -> Vec<Pot> {
+	let live = |seat: &usize| !folded[*seat];
+	let mut levels = (0..contributions.len()).filter(live).map(|seat| contributions[seat]).collect::<Vec<_>>();
+	levels.sort_unstable();
+	levels.dedup();
+
+	let mut pots: Vec<Pot> = Vec::new();
+	let mut previous = 0;
+	for (index, &level) in levels.iter().enumerate() {
+		let cap = if index + 1 == levels.len() { u64::MAX } else { level };
+		let amount = contributions.iter().map(|&put_in| put_in.min(cap) - put_in.min(previous)).sum();
+		let eligible = (0..contributions.len()).filter(live).filter(|&seat| contributions[seat] >= level).collect::<Vec<_>>();
+		previous = level;
+		match pots.last_mut() {
+			Some(last) if last.eligible == eligible => last.amount += amount,
+			_ if amount == 0 => {},
+			_ => pots.push(Pot { amount, eligible }),
+		}
+	}
+	pots
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PotAward {
+	pub amount: u64,
+	/// `(seat, chips)` for every winner of the pot.
+	pub shares: Vec<(usize, u64)>,
+}
+
+/// Splits every pot between its best eligible hands. Chips that don't divide evenly go one each to the winners closest to
+/// the left of the button. A pot whose eligible seats have no value, as when everyone else folded, goes to all of them.
+pub fn award<V: Ord>(pots: &[Pot], values: &[Option<V>], button: usize) -> Vec<PotAward> {
+	let seats = values.len();
+	pots
+		.iter()
+		.map(|pot| {
+			let best = pot.eligible.iter().filter_map(|&seat| values[seat].as_ref()).max();
+			let mut winners = pot.eligible.iter().copied().filter(|&seat| values[seat].as_ref() == best).collect::<Vec<_>>();
+			winners.sort_unstable_by_key(|&seat| (seat + seats - button - 1) % seats);
+			let count = winners.len() as u64;
+			let odd = (pot.amount % count) as usize;
+			let shares = winners.iter().enumerate().map(|(index, &seat)| (seat, pot.amount / count + u64::from(index < odd))).collect();
+			PotAward { amount: pot.amount, shares }
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn several_all_ins_make_a_side_pot_per_level() {
+		let pots = build_pots(&[50, 100, 250, 250], &[false; 4]);
+		assert_eq!(
+			pots,
+			vec![Pot { amount: 200, eligible: vec![0, 1, 2, 3] }, Pot { amount: 150, eligible: vec![1, 2, 3] }, Pot { amount: 300, eligible: vec![2, 3] },]
+		);
+		let pots = build_pots(&[100, 300, 300, 50], &[false, false, false, true]);
+		assert_eq!(pots, vec![Pot { amount: 350, eligible: vec![0, 1, 2] }, Pot { amount: 400, eligible: vec![1, 2] }]);
+	}
+
+	#[test]
+	fn uncalled_chips_and_folded_chips() {
+		// Whatever nobody matched forms a pot only its owner can win.
+		let pots = build_pots(&[1000, 300], &[false, false]);
+		assert_eq!(pots, vec![Pot { amount: 600, eligible: vec![0, 1] }, Pot { amount: 700, eligible: vec![0] }]);
+		// Folded chips above every live level stay in the pot.
+		let pots = build_pots(&[500, 200, 200], &[true, false, false]);
+		assert_eq!(pots, vec![Pot { amount: 900, eligible: vec![1, 2] }]);
+	}
+
+	#[test]
+	fn every_pot_goes_to_its_best_eligible_hand() {
+		let pots = build_pots(&[50, 100, 250, 250], &[false; 4]);
+		let awards = award(&pots, &[Some(9), Some(5), Some(1), Some(3)], 0);
+		assert_eq!(awards.iter().map(|award| award.shares.clone()).collect::<Vec<_>>(), vec![vec![(0, 200)], vec![(1, 150)], vec![(3, 300)]]);
+		assert_eq!(awards.iter().map(|award| award.amount).sum::<u64>(), 650);
+	}
+
+	#[test]
+	fn odd_chips_go_left_of_the_button() {
+		let awards = award(&[Pot { amount: 101, eligible: vec![0, 1, 2] }], &[Some(1), Some(5), Some(5)], 1);
+		assert_eq!(awards[0].shares, vec![(2, 51), (1, 50)]);
+		let awards = award(&[Pot { amount: 11, eligible: vec![0, 1, 2, 3] }], &[Some(4), Some(4), Some(4), Some(4)], 2);
+		assert_eq!(awards[0].shares, vec![(3, 3), (0, 3), (1, 3), (2, 2)]);
+	}
+
+	#[test]
+	fn an_uncontested_pot_goes_to_who_is_left() {
+		let awards = award(&[Pot { amount: 10, eligible: vec![0] }], &[None::<u8>, None], 1);
+		assert_eq!(awards[0].shares, vec![(0, 10)]);
+	}
+}
@@ -0,0 +1,74 @@
+/// `SplitMix64`. Tiny and fully specified, so a seed reproduces the same deck on every platform and every version of the crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeededRng {
+	state: u64,
+}
+
+impl SeededRng {
+	pub fn new(seed: u64) -> Self {
+		Self { state: seed }
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	/// Uniform in `0..bound`, rejecting the biased tail instead of taking a plain modulo.
+	pub fn below(&mut self, bound: u64) -> u64 {
+		assert!(bound > 0, "bound must be positive");
+		let zone = u64::MAX - u64::MAX % bound;
+		loop {
+			let value = self.next_u64();
+			if value < zone {
+				return value % bound;
+			}
+		}
+	}
+
+	/// Uniform in `0.0..1.0`, from the top 53 bits.
+	pub fn unit(&mut self) -> f64 {
+		(self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+	}
+
+	/// Fisher-Yates.
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			items.swap(i, self.below(i as u64 + 1) as usize);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::game::poker::cards::standard_deck;
+
+	fn shuffled(seed: u64) -> Vec<crate::game::poker::cards::Card> {
+		let mut deck = standard_deck();
+		SeededRng::new(seed).shuffle(&mut deck);
+		deck
+	}
+
+	#[test]
+	fn a_seed_always_deals_the_same_deck() {
+		assert_eq!(shuffled(7), shuffled(7));
+		assert_ne!(shuffled(7), shuffled(8));
+		let mut sorted = shuffled(7);
+		sorted.sort_unstable();
+		let mut deck = standard_deck();
+		deck.sort_unstable();
+		assert_eq!(sorted, deck);
+	}
+
+	#[test]
+	fn matches_the_reference_split_mix() {
+		assert_eq!(SeededRng::new(0).next_u64(), 0xe220_a839_7b1d_cdaf);
+		let mut rng = SeededRng::new(42);
+		assert!((0..1000).all(|_| rng.below(6) < 6));
+		assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.unit())));
+	}
+}
@@ -0,0 +1,546 @@
+use anyhow::{bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use super::{
+	Variant,
+	pots::{PotAward, award, build_pots},
+	rng::SeededRng,
+};
+
+pub const MIN_SEATS: usize = 2;
+pub const MAX_SEATS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Blinds {
+	pub small: u64,
+	pub big: u64,
+}
+
+impl Blinds {
+	pub fn new(small: u64, big: u64) -> anyhow::Result<Self> {
+		ensure!(small > 0 && small <= big, "the small blind must be positive and at most the big blind");
+		Ok(Self { small, big })
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize, strum::Display)]
+pub enum Street {
+	Preflop,
+	Flop,
+	Turn,
+	River,
+	/// The hand is over and its pots are paid out, whether or not cards were shown.
+	Complete,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Action {
+	Fold,
+	Check,
+	Call,
+	/// Bet or raise so the seat has this much in front of it on the current street.
+	RaiseTo(u64),
+	AllIn,
+}
+
+/// What the seat to act may do right now.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct LegalActions {
+	pub check: bool,
+	/// Chips a call puts in, capped by the stack.
+	pub call: Option<u64>,
+	/// Smallest and largest street totals a raise may go to. The largest is all-in, which is allowed below the smallest.
+	pub raise: Option<(u64, u64)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Seat<C> {
+	pub stack: u64,
+	pub hole: Vec<C>,
+	/// Put in on the current street.
+	pub street_bet: u64,
+	/// Put in over the whole hand.
+	pub total_bet: u64,
+	pub folded: bool,
+	pub all_in: bool,
+	/// The bet this seat last acted facing on this street. Only a full raise above it lets the seat raise again.
+	acted_at: Option<u64>,
+}
+
+impl<C> Seat<C> {
+	fn live(&self) -> bool {
+		!self.folded
+	}
+
+	fn can_act(&self) -> bool {
+		!self.folded && !self.all_in
+	}
+
+	fn put_in(&mut self, chips: u64) {
+		let chips = chips.min(self.stack);
+		self.stack -= chips;
+		self.street_bet += chips;
+		self.total_bet += chips;
+		self.all_in = self.stack == 0;
+	}
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HandResult<V> {
+	/// Values of the hands that reached a showdown, by seat. Empty when everyone else folded.
+	pub shown: Vec<(usize, V)>,
+	pub pots: Vec<PotAward>,
+}
+
+/// One hand of no-limit hold'em, from the blinds to the payout.
+///
+/// The [`Variant`] decides what is dealt and how hands compare; the betting is the same for all of them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Hand<V: Variant> {
+	variant: V,
+	blinds: Blinds,
+	button: usize,
+	seats: Vec<Seat<V::Card>>,
+	/// Undealt cards, drawn from the end.
+	deck: Vec<V::Card>,
+	board: Vec<V::Card>,
+	street: Street,
+	to_act: Option<usize>,
+	/// What everyone has to match on this street.
+	current_bet: u64,
+	/// Size of the last full bet or raise, the least the next raise must add.
+	min_raise: u64,
+	/// The bet the last full raise made. Seats that acted facing it can't raise again after a short all-in.
+	full_raise_bet: u64,
+	result: Option<HandResult<V::Value>>,
+}
+
+impl<V: Variant> Hand<V> {
+	/// Shuffles with `seed`, posts the blinds and deals the hole cards. Heads-up the button posts the small blind.
+	pub fn new(variant: V, blinds: Blinds, stacks: &[u64], button: usize, seed: u64) -> anyhow::Result<Self> {
+		ensure!((MIN_SEATS..=MAX_SEATS).contains(&stacks.len()), "a hand needs {MIN_SEATS} to {MAX_SEATS} players");
+		ensure!(stacks.iter().all(|&stack| stack > 0), "every player needs chips");
+		ensure!(button < stacks.len(), "the button must be on a seat");
+		let mut deck = variant.deck();
+		ensure!(deck.len() >= 2 * stacks.len() + 8, "the deck is too small for {} players", stacks.len());
+		SeededRng::new(seed).shuffle(&mut deck);
+
+		let seats =
+			stacks.iter().map(|&stack| Seat { stack, hole: Vec::new(), street_bet: 0, total_bet: 0, folded: false, all_in: false, acted_at: None }).collect();
+		let mut hand = Self {
+			variant,
+			blinds,
+			button,
+			seats,
+			deck,
+			board: Vec::new(),
+			street: Street::Preflop,
+			to_act: None,
+			current_bet: blinds.big,
+			min_raise: blinds.big,
+			full_raise_bet: blinds.big,
+			result: None,
+		};
+		let small_blind = if stacks.len() == 2 { button } else { hand.next_seat(button) };
+		let big_blind = hand.next_seat(small_blind);
+		hand.seats[small_blind].put_in(blinds.small);
+		hand.seats[big_blind].put_in(blinds.big);
+		for _ in 0..2 {
+			let mut seat = button;
+			for _ in 0..hand.seats.len() {
+				seat = hand.next_seat(seat);
+				let card = hand.draw();
+				hand.seats[seat].hole.push(card);
+			}
+		}
+		hand.to_act = hand.next_to_act(big_blind);
+		if hand.to_act.is_none() {
+			hand.finish_street();
+		}
+		Ok(hand)
+	}
+
+	pub fn variant(&self) -> &V {
+		&self.variant
+	}
+
+	pub fn blinds(&self) -> Blinds {
+		self.blinds
+	}
+
+	pub fn button(&self) -> usize {
+		self.button
+	}
+
+	pub fn seats(&self) -> &[Seat<V::Card>] {
+		&self.seats
+	}
+
+	pub fn board(&self) -> &[V::Card] {
+		&self.board
+	}
+
+	pub fn street(&self) -> Street {
+		self.street
+	}
+
+	pub fn to_act(&self) -> Option<usize> {
+		self.to_act
+	}
+
+	pub fn current_bet(&self) -> u64 {
+		self.current_bet
+	}
+
+	pub fn pot(&self) -> u64 {
+		self.seats.iter().map(|seat| seat.total_bet).sum()
+	}
+
+	pub fn result(&self) -> Option<&HandResult<V::Value>> {
+		self.result.as_ref()
+	}
+
+	pub fn legal_actions(&self) -> LegalActions {
+		let Some(index) = self.to_act else { return LegalActions::default() };
+		let seat = &self.seats[index];
+		let to_call = self.current_bet.saturating_sub(seat.street_bet);
+		let most = seat.street_bet + seat.stack;
+		let raise = (self.may_raise(index) && most > self.current_bet).then(|| ((self.current_bet + self.min_raise).min(most), most));
+		LegalActions { check: to_call == 0, call: (to_call > 0).then(|| to_call.min(seat.stack)), raise }
+	}
+
+	/// Plays `action` for `seat`, moving the hand on to the next seat, street or payout.
+	pub fn apply(&mut self, seat: usize, action: Action) -> anyhow::Result<()> {
+		ensure!(self.to_act == Some(seat), "it is not seat {seat}'s turn");
+		let legal = self.legal_actions();
+		let facing = self.current_bet;
+		match action {
+			Action::Fold => self.seats[seat].folded = true,
+			Action::Check => ensure!(legal.check, "there is a bet to call"),
+			Action::Call => {
+				let Some(chips) = legal.call else { bail!("there is nothing to call") };
+				self.seats[seat].put_in(chips);
+			},
+			Action::RaiseTo(total) => {
+				let Some((least, most)) = legal.raise else { bail!("raising is closed") };
+				ensure!(total <= most, "seat {seat} only has {most} to bet");
+				ensure!(total >= least, "a raise must go to at least {least}");
+				self.raise_to(seat, total);
+			},
+			Action::AllIn => {
+				let most = self.seats[seat].street_bet + self.seats[seat].stack;
+				if most > facing {
+					ensure!(legal.raise.is_some(), "raising is closed, call instead");
+					self.raise_to(seat, most);
+				} else {
+					self.seats[seat].put_in(most);
+				}
+			},
+		}
+		self.seats[seat].acted_at = Some(facing.max(self.seats[seat].street_bet));
+
+		if self.seats.iter().filter(|other| other.live()).count() == 1 {
+			self.showdown();
+			return Ok(());
+		}
+		self.to_act = self.next_to_act(seat);
+		if self.to_act.is_none() {
+			self.finish_street();
+		}
+		Ok(())
+	}
+
+	fn raise_to(&mut self, seat: usize, total: u64) {
+		let increase = total - self.current_bet;
+		let seat = &mut self.seats[seat];
+		seat.put_in(total - seat.street_bet);
+		// An all-in short of a full raise makes others call more but does not reopen the betting.
+		if increase >= self.min_raise {
+			self.min_raise = increase;
+			self.full_raise_bet = total;
+		}
+		self.current_bet = total;
+	}
+
+	fn may_raise(&self, index: usize) -> bool {
+		let opponents_can_call = self.seats.iter().enumerate().any(|(other, seat)| other != index && seat.can_act());
+		let reopened = self.seats[index].acted_at.is_none_or(|acted_at| acted_at < self.full_raise_bet);
+		opponents_can_call && reopened
+	}
+
+	fn next_seat(&self, seat: usize) -> usize {
+		(seat + 1) % self.seats.len()
+	}
+
+	/// The first seat after `after` that still owes an action on this street.
+	fn next_to_act(&self, after: usize) -> Option<usize> {
+		let mut seat = after;
+		for _ in 0..self.seats.len() {
+			seat = self.next_seat(seat);
+			let candidate = &self.seats[seat];
+			if candidate.can_act() && (candidate.acted_at.is_none() || candidate.street_bet < self.current_bet) {
+				return Some(seat);
+			}
+		}
+		None
+	}
+
+	fn draw(&mut self) -> V::Card {
+		self.deck.pop().expect("the deck holds enough cards for the hand")
+	}
+
+	fn deal_board(&mut self, cards: usize) {
+		let _burn = self.draw();
+		for _ in 0..cards {
+			let card = self.draw();
+			self.board.push(card);
+		}
+	}
+
+	/// Deals the next street, or all of them when at most one player can still bet, and pays out after the river.
+	fn finish_street(&mut self) {
+		loop {
+			let cards = match self.street {
+				Street::Preflop => 3,
+				Street::Flop | Street::Turn => 1,
+				Street::River | Street::Complete => {
+					self.showdown();
+					return;
+				},
+			};
+			self.deal_board(cards);
+			self.street = match self.street {
+				Street::Preflop => Street::Flop,
+				Street::Flop => Street::Turn,
+				_ => Street::River,
+			};
+			for seat in &mut self.seats {
+				seat.street_bet = 0;
+				seat.acted_at = None;
+			}
+			self.current_bet = 0;
+			self.min_raise = self.blinds.big;
+			self.full_raise_bet = 0;
+			if self.seats.iter().filter(|seat| seat.can_act()).count() > 1 {
+				self.to_act = self.next_to_act(self.button);
+				return;
+			}
+		}
+	}
+
+	fn showdown(&mut self) {
+		self.street = Street::Complete;
+		self.to_act = None;
+		let contested = self.seats.iter().filter(|seat| seat.live()).count() > 1;
+		let values = self
+			.seats
+			.iter()
+			.map(|seat| {
+				(contested && seat.live()).then(|| {
+					let cards = seat.hole.iter().chain(&self.board).cloned().collect::<Vec<_>>();
+					self.variant.value(&cards)
+				})
+			})
+			.collect::<Vec<_>>();
+		let contributions = self.seats.iter().map(|seat| seat.total_bet).collect::<Vec<_>>();
+		let folded = self.seats.iter().map(|seat| seat.folded).collect::<Vec<_>>();
+		let pots = award(&build_pots(&contributions, &folded), &values, self.button);
+		for (seat, chips) in pots.iter().flat_map(|pot| &pot.shares) {
+			self.seats[*seat].stack += chips;
+		}
+		let shown = values.into_iter().enumerate().filter_map(|(seat, value)| Some((seat, value?))).collect();
+		self.result = Some(HandResult { shown, pots });
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::game::{
+		percentile::PercentileWindow,
+		poker::{
+			Holdem,
+			meme::{MemeBet, MemePoker},
+		},
+	};
+
+	fn blinds() -> Blinds {
+		Blinds::new(5, 10).expect("valid blinds")
+	}
+
+	/// Chips at the table, counting what is in front of every seat until the pots are paid.
+	fn chips<V: Variant>(hand: &Hand<V>) -> u64 {
+		hand.seats().iter().map(|seat| seat.stack + if hand.street() == Street::Complete { 0 } else { seat.total_bet }).sum()
+	}
+
+	#[test]
+	fn blinds_are_posted_left_of_the_button() {
+		assert!(Blinds::new(0, 10).is_err());
+		assert!(Blinds::new(20, 10).is_err());
+		let hand = Hand::new(Holdem, blinds(), &[1000, 1000, 1000, 1000], 2, 1).expect("valid hand");
+		let bets = hand.seats().iter().map(|seat| seat.street_bet).collect::<Vec<_>>();
+		assert_eq!(bets, [10, 0, 0, 5]);
+		assert_eq!(hand.to_act(), Some(1));
+		assert_eq!(hand.pot(), 15);
+		assert!(hand.seats().iter().all(|seat| seat.hole.len() == 2));
+		assert!(Hand::new(Holdem, blinds(), &[1000], 0, 1).is_err());
+		assert!(Hand::new(Holdem, blinds(), &[1000, 0], 0, 1).is_err());
+		assert!(Hand::new(Holdem, blinds(), &[1000, 1000], 2, 1).is_err());
+	}
+
+	#[test]
+	fn heads_up_the_button_posts_small_and_acts_first_preflop_only() {
+		let mut hand = Hand::new(Holdem, blinds(), &[1000, 1000], 0, 1).expect("valid hand");
+		assert_eq!((hand.seats()[0].street_bet, hand.seats()[1].street_bet), (5, 10));
+		assert_eq!(hand.to_act(), Some(0));
+		assert!(hand.apply(1, Action::Check).is_err());
+		assert!(hand.apply(0, Action::Check).is_err());
+		hand.apply(0, Action::Call).expect("call");
+		assert_eq!(hand.to_act(), Some(1));
+		assert_eq!(hand.legal_actions(), LegalActions { check: true, call: None, raise: Some((20, 1000)) });
+		hand.apply(1, Action::Check).expect("check");
+		assert_eq!(hand.street(), Street::Flop);
+		assert_eq!(hand.board().len(), 3);
+		assert_eq!(hand.to_act(), Some(1));
+		hand.apply(1, Action::Check).expect("check");
+		hand.apply(0, Action::Check).expect("check");
+		assert_eq!(hand.street(), Street::Turn);
+		assert_eq!(hand.to_act(), Some(1));
+	}
+
+	#[test]
+	fn raises_must_add_at_least_the_last_full_raise() {
+		let mut hand = Hand::new(Holdem, blinds(), &[1000, 1000, 1000], 0, 3).expect("valid hand");
+		assert!(hand.apply(0, Action::RaiseTo(15)).is_err());
+		hand.apply(0, Action::RaiseTo(30)).expect("raise");
+		hand.apply(1, Action::Call).expect("call");
+		assert_eq!(hand.legal_actions().raise, Some((50, 1000)));
+		hand.apply(2, Action::RaiseTo(80)).expect("re-raise");
+		assert_eq!(hand.legal_actions().raise, Some((130, 1000)));
+		assert!(hand.apply(0, Action::RaiseTo(120)).is_err());
+		assert!(hand.apply(0, Action::RaiseTo(1001)).is_err());
+		hand.apply(0, Action::Call).expect("call");
+		hand.apply(1, Action::Call).expect("call");
+		assert_eq!(hand.street(), Street::Flop);
+		assert_eq!(hand.to_act(), Some(1));
+		// A new street starts the minimum back at the big blind.
+		assert_eq!(hand.legal_actions().raise, Some((10, 920)));
+	}
+
+	#[test]
+	fn a_short_all_in_does_not_reopen_the_betting() {
+		let mut hand = Hand::new(Holdem, blinds(), &[1000, 1000, 1000, 150], 0, 2).expect("valid hand");
+		assert_eq!(hand.to_act(), Some(3));
+		hand.apply(3, Action::Call).expect("limp");
+		hand.apply(0, Action::RaiseTo(100)).expect("raise");
+		hand.apply(1, Action::Fold).expect("fold");
+		hand.apply(2, Action::Call).expect("call");
+		hand.apply(3, Action::AllIn).expect("short all-in");
+		assert_eq!(hand.current_bet(), 150);
+		assert_eq!(hand.to_act(), Some(0));
+		assert_eq!(hand.legal_actions(), LegalActions { check: false, call: Some(50), raise: None });
+		assert!(hand.apply(0, Action::RaiseTo(300)).is_err());
+		assert!(hand.apply(0, Action::AllIn).is_err());
+		hand.apply(0, Action::Call).expect("call");
+		hand.apply(2, Action::Call).expect("call");
+		assert_eq!(hand.street(), Street::Flop);
+	}
+
+	#[test]
+	fn all_ins_run_the_board_out() {
+		let mut hand = Hand::new(Holdem, blinds(), &[1000, 1000, 1000, 150], 0, 2).expect("valid hand");
+		hand.apply(3, Action::AllIn).expect("all-in");
+		hand.apply(0, Action::AllIn).expect("all-in");
+		hand.apply(1, Action::Fold).expect("fold");
+		hand.apply(2, Action::Call).expect("call");
+		assert_eq!(hand.street(), Street::Complete);
+		assert_eq!(hand.board().len(), 5);
+		assert_eq!(chips(&hand), 3150);
+		let result = hand.result().expect("paid out");
+		assert_eq!(result.shown.iter().map(|(seat, _)| *seat).collect::<Vec<_>>(), [0, 2, 3]);
+		assert_eq!(result.pots.iter().map(|pot| pot.amount).collect::<Vec<_>>(), [455, 1700]);
+
+		// A blind that puts a player all-in deals the hand out at once when nobody else can bet.
+		let hand = Hand::new(Holdem, blinds(), &[3, 7], 0, 4).expect("valid hand");
+		assert_eq!(hand.street(), Street::Complete);
+		assert_eq!(chips(&hand), 10);
+		let mut hand = Hand::new(Holdem, blinds(), &[3, 1000], 0, 4).expect("valid hand");
+		assert!(hand.seats()[0].all_in);
+		assert_eq!(hand.legal_actions(), LegalActions { check: true, call: None, raise: None });
+		hand.apply(1, Action::Check).expect("check");
+		assert_eq!(hand.street(), Street::Complete);
+		assert_eq!(chips(&hand), 1003);
+	}
+
+	#[test]
+	fn showdown_pays_the_best_hand() {
+		let mut hand = Hand::new(Holdem, blinds(), &[1000, 1000, 1000], 1, 5).expect("valid hand");
+		while let Some(seat) = hand.to_act() {
+			let action = if hand.legal_actions().check { Action::Check } else { Action::Call };
+			hand.apply(seat, action).expect("passive play");
+		}
+		let result = hand.result().expect("paid out");
+		assert_eq!(result.shown.len(), 3);
+		let best = result.shown.iter().map(|(_, value)| *value).max().expect("shown hands");
+		for (seat, chips) in &result.pots[0].shares {
+			assert_eq!(result.shown.iter().find(|(shown, _)| shown == seat).map(|(_, value)| *value), Some(best));
+			assert_eq!(hand.seats()[*seat].stack, 990 + chips);
+		}
+		assert_eq!(chips(&hand), 3000);
+	}
+
+	#[test]
+	fn everyone_else_folding_shows_nothing() {
+		let mut hand = Hand::new(Holdem, blinds(), &[1000, 1000], 0, 1).expect("valid hand");
+		hand.apply(0, Action::Call).expect("call");
+		hand.apply(1, Action::Check).expect("check");
+		hand.apply(1, Action::RaiseTo(30)).expect("bet");
+		hand.apply(0, Action::RaiseTo(60)).expect("raise");
+		hand.apply(1, Action::Fold).expect("fold");
+		assert_eq!(hand.street(), Street::Complete);
+		assert_eq!((hand.seats()[0].stack, hand.seats()[1].stack), (1040, 960));
+		assert!(hand.result().expect("paid out").shown.is_empty());
+	}
+
+	#[test]
+	fn random_play_conserves_chips() {
+		for seed in 0..2000 {
+			let mut rng = SeededRng::new(seed ^ 0xabc);
+			let players = 2 + rng.below(9) as usize;
+			let stacks = (0..players).map(|_| 1 + rng.below(500)).collect::<Vec<_>>();
+			let start = stacks.iter().sum::<u64>();
+			let mut hand = Hand::new(Holdem, Blinds::new(2, 5).expect("valid blinds"), &stacks, rng.below(players as u64) as usize, seed).expect("valid hand");
+			while let Some(seat) = hand.to_act() {
+				let legal = hand.legal_actions();
+				let action = match (rng.below(6), legal.raise) {
+					(0, _) => Action::Fold,
+					(1, _) if legal.check => Action::Check,
+					(2, Some((least, most))) => Action::RaiseTo(least + rng.below(most - least + 1)),
+					(3, Some(_)) => Action::AllIn,
+					_ if legal.call.is_some() => Action::Call,
+					_ => Action::Check,
+				};
+				hand.apply(seat, action).unwrap_or_else(|e| panic!("seed {seed}: {action:?} with {legal:?}: {e}"));
+				assert_eq!(chips(&hand), start, "seed {seed}");
+			}
+			assert_eq!(hand.street(), Street::Complete, "seed {seed}");
+			assert_eq!(chips(&hand), start, "seed {seed}");
+		}
+	}
+
+	#[test]
+	fn meme_poker_plays_to_a_showdown() {
+		let window = PercentileWindow::new(0, 50).expect("valid window");
+		let deck = (0..40)
+			.map(|i| MemeBet { record_id: uuid::Uuid::nil(), title: String::new(), image_url: String::new(), window, percentile: f64::from(i) * 2.5 })
+			.collect::<Vec<_>>();
+		assert!(Hand::new(MemePoker::new(deck[..10].to_vec()), blinds(), &[100, 100], 0, 1).is_err());
+		let mut hand = Hand::new(MemePoker::new(deck), Blinds::new(1, 2).expect("valid blinds"), &[100, 100, 100], 1, 9).expect("valid hand");
+		while let Some(seat) = hand.to_act() {
+			let action = if hand.legal_actions().check { Action::Check } else { Action::Call };
+			hand.apply(seat, action).expect("passive play");
+		}
+		assert_eq!(hand.result().expect("paid out").shown.len(), 3);
+		assert_eq!(chips(&hand), 300);
+	}
+}
@@ -1,23 +1,43 @@
 use {
-	crate::router::Route,
+	crate::{
+		components::{search_bar::SearchBar, user_menu::UserMenu},
+		router::Route,
+		stores::session::use_session_provider,
+	},
 	dioxus::prelude::*,
 	dioxus_free_icons::{
 		Icon,
-		icons::bs_icons::{BsHouse, BsSpeedometer2},
+		icons::bs_icons::{BsDice5, BsFire, BsHouse, BsSpeedometer2, BsSuitSpadeFill, BsTrophy},
 	},
 };
 
 #[component]
 pub fn Layout() -> Element {
+	use_session_provider();
+
 	rsx! {
 		div { class: "flex flex-col min-h-screen w-full relative bg-black text-white",
 			header { class: "w-full sticky top-0 z-10  right-0 left-0 flex justify-end gap-4 items-center bg-black/80 backdrop-blur-md px-10 py-2 border-b border-b-white/20",
 				Link { to: Route::Home {}, class: "mr-auto",
 					Icon { icon: BsHouse, width: 24, height: 24 }
 				}
-				Link { to: Route::Generator {}, class: "mr-auto",
+				Link { to: Route::Generator { m: String::new(), id: String::new(), remix: String::new(), tournament: String::new() }, class: "mr-auto",
 					Icon { icon: BsSpeedometer2, width: 24, height: 24 }
 				}
+				Link { to: Route::Feed { sort: String::new() }, class: "mr-auto",
+					Icon { icon: BsFire, width: 24, height: 24 }
+				}
+				Link { to: Route::Tournaments {}, class: "mr-auto",
+					Icon { icon: BsTrophy, width: 24, height: 24 }
+				}
+				Link { to: Route::Game {}, class: "mr-auto",
+					Icon { icon: BsDice5, width: 24, height: 24 }
+				}
+				Link { to: Route::Tables {}, class: "mr-auto",
+					Icon { icon: BsSuitSpadeFill, width: 24, height: 24 }
+				}
+				SearchBar {}
+				UserMenu {}
 			}
 			div { class: "flex-1 pt-4", Outlet::<Route> {} }
 		}
@@ -1,7 +1,14 @@
 #![allow(non_snake_case)]
+pub mod api;
 pub mod application;
+pub mod components;
+pub mod game;
 pub mod layout;
 pub mod pages;
 pub mod router;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod share;
 pub mod stores;
+pub mod tags;
 pub mod utils;
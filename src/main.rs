@@ -2,10 +2,47 @@ use dioxus::prelude::*;
 use dioxus_primitives::toast::ToastProvider;
 use memetopia::application::App;
 
+fn Root() -> Element {
+	rsx! {
+		ToastProvider { App {} }
+	}
+}
+
+#[cfg(not(feature = "server"))]
+fn main() {
+	dioxus::LaunchBuilder::new().launch(Root);
+}
+
+#[cfg(feature = "server")]
 fn main() {
-	dioxus::LaunchBuilder::new().launch(|| {
-		rsx! {
-			ToastProvider { App {} }
-		}
+	use dioxus::fullstack::{
+		extract::DefaultBodyLimit,
+		routing::{get, post},
+	};
+	use memetopia::server::{
+		auth,
+		blob::{self, MAX_IMAGE_BYTES, serve_blob},
+		db, filters, game, ledger,
+		render::{MAX_RENDER_REQUEST_BYTES, render_endpoint},
+		slash_commands::{MAX_SLASH_REQUEST_BYTES, discord_endpoint, slack_endpoint},
+		uploads,
+	};
+
+	dioxus::serve(|| async move {
+		db::init()?;
+		filters::init()?;
+		auth::promote_configured_moderators().await?;
+		ledger::refund_seats().await?;
+		ledger::audit_books().await;
+		uploads::spawn_sweeper();
+		blob::cache::spawn_sweeper();
+		game::spawn_settler();
+		Ok(
+			dioxus::server::router(Root)
+				.route("/blobs/{*key}", get(serve_blob).put(uploads::receive_upload).layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES)))
+				.route("/api/render", post(render_endpoint).layer(DefaultBodyLimit::max(MAX_RENDER_REQUEST_BYTES)))
+				.route("/api/slash/slack", post(slack_endpoint).layer(DefaultBodyLimit::max(MAX_SLASH_REQUEST_BYTES)))
+				.route("/api/slash/discord", post(discord_endpoint).layer(DefaultBodyLimit::max(MAX_SLASH_REQUEST_BYTES))),
+		)
 	});
 }
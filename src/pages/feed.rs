@@ -0,0 +1,94 @@
+use crate::{
+	api::posts::{FeedSort, PostSummary, get_feed},
+	components::post_card::PostCard,
+	router::Route,
+};
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+#[component]
+pub fn Feed(sort: String) -> Element {
+	let sort = sort.parse::<FeedSort>().unwrap_or_default();
+
+	rsx! {
+		div { class: "max-w-2xl mx-auto p-6 space-y-6",
+			nav { class: "flex gap-4",
+				for option in FeedSort::iter() {
+					Link {
+						to: Route::Feed { sort: option.to_string() },
+						class: if option == sort { "font-semibold text-cyan-400 capitalize" } else { "text-slate-400 hover:text-white capitalize" },
+						"{option}"
+					}
+				}
+			}
+			// Keyed so switching sort starts over with a fresh list and cursor.
+			FeedList { key: "{sort}", sort }
+		}
+	}
+}
+
+#[component]
+fn FeedList(sort: FeedSort) -> Element {
+	let mut posts = use_signal(Vec::<PostSummary>::new);
+	let mut cursor = use_signal(|| None::<String>);
+	let mut exhausted = use_signal(|| false);
+	let mut loading = use_signal(|| false);
+	let mut error = use_signal(|| None::<String>);
+
+	let load_more = move || async move {
+		if loading() || exhausted() {
+			return;
+		}
+		loading.set(true);
+		match get_feed(sort, cursor()).await {
+			Ok(page) => {
+				posts.write().extend(page.posts);
+				exhausted.set(page.next_cursor.is_none());
+				cursor.set(page.next_cursor);
+				error.set(None);
+			},
+			Err(e) => error.set(Some(e.to_string())),
+		}
+		loading.set(false);
+	};
+
+	rsx! {
+		div { class: "space-y-6",
+			for post in posts() {
+				PostCard { key: "{post.id}", post }
+			}
+			if let Some(error) = error() {
+				p { class: "text-sm text-red-400", "{error}" }
+			}
+			if exhausted() {
+				p { class: "text-center text-sm text-slate-400",
+					if posts.read().is_empty() {
+						"Nothing published yet."
+					} else {
+						"You reached the end."
+					}
+				}
+			} else {
+				// Loads the next page whenever this scrolls into view, the button covers pages too short to scroll.
+				div {
+					onvisible: move |evt| async move {
+						if evt.is_intersecting().unwrap_or_default() {
+							load_more().await;
+						}
+					},
+					class: "flex justify-center py-4",
+					button {
+						onclick: move |_| load_more(),
+						disabled: loading(),
+						class: "px-4 py-2 bg-slate-600 rounded text-sm hover:bg-slate-500 disabled:opacity-50 transition-colors duration-200",
+						if loading() {
+							"Loading..."
+						} else {
+							"Load more"
+						}
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,184 @@
+use crate::{
+	api::game::{BetInfo, BetResult, GameRound, game_overview, next_round, place_bet},
+	game::percentile::{MAX_STAKE, MIN_STAKE, PercentileWindow, WINDOW_STEP},
+	router::Route,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+
+/// The meme analytics game: guess which engagement percentile a meme scraped from elsewhere landed in.
+#[component]
+pub fn Game() -> Element {
+	let mut overview = use_resource(game_overview);
+	let mut round = use_resource(next_round);
+
+	rsx! {
+		div { class: "max-w-2xl mx-auto p-6 space-y-6",
+			div { class: "flex items-baseline justify-between",
+				h1 { class: "text-3xl font-bold", "Meme Analytics Game" }
+				if let Some(Ok(overview)) = overview() {
+					Link { to: Route::Points {}, class: "text-lg font-semibold text-cyan-400 hover:underline", "{overview.balance} points" }
+				}
+			}
+			p { class: "text-sm text-slate-400",
+				"Bet on how well a meme did compared to others from the same place. Narrow windows pay more."
+			}
+			{
+				match round() {
+					None => rsx! {
+						p { class: "text-sm text-slate-400", "Loading..." }
+					},
+					Some(Err(e)) => rsx! {
+						p { class: "text-sm text-red-400", "{e}" }
+					},
+					Some(Ok(None)) => rsx! {
+						p { class: "text-sm text-slate-400", "No memes left to bet on, check back later." }
+					},
+					Some(Ok(Some(current))) => rsx! {
+						BetForm {
+							key: "{current.record_id}",
+							round: current,
+							on_placed: move |_| {
+								overview.restart();
+								round.restart();
+							},
+						}
+					},
+				}
+			}
+			if let Some(Ok(overview)) = overview() {
+				section { class: "space-y-2",
+					h2 { class: "text-xl font-semibold", "Your bets" }
+					if overview.bets.is_empty() {
+						p { class: "text-sm text-slate-400", "No bets yet." }
+					}
+					for bet in overview.bets {
+						BetRow { key: "{bet.id}", bet }
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn BetForm(round: GameRound, on_placed: EventHandler) -> Element {
+	let toast = use_toast();
+	let mut low = use_signal(|| 50_u8);
+	let mut high = use_signal(|| 100_u8);
+	let mut stake = use_signal(|| 10_i64);
+	let mut placing = use_signal(|| false);
+	let window = PercentileWindow::new(low(), high()).ok();
+	let record_id = round.record_id;
+
+	let place = move |_| async move {
+		let Some(window) = window else {
+			return;
+		};
+		placing.set(true);
+		match place_bet(record_id, window, stake()).await {
+			Ok(bet) => {
+				match bet.result {
+					Some(BetResult { percentile, payout }) if payout > 0 => {
+						toast.success(format!("Won {payout} points"), ToastOptions::new().description(format!("It landed at percentile {percentile:.0}")))
+					},
+					Some(BetResult { percentile, .. }) => {
+						toast.info("Lost".to_owned(), ToastOptions::new().description(format!("It landed at percentile {percentile:.0}")))
+					},
+					None => toast.info("Bet placed".to_owned(), ToastOptions::new().description("It settles once the numbers are final.".to_owned())),
+				}
+				on_placed.call(());
+			},
+			Err(e) => toast.error("Bet failed".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		placing.set(false);
+	};
+
+	rsx! {
+		div { class: "border rounded-xl p-4 space-y-4",
+			img { src: "{round.image_url}", alt: "{round.title}", class: "w-full max-h-96 object-contain rounded" }
+			div {
+				p { class: "font-semibold", "{round.title}" }
+				p { class: "text-xs text-slate-400", "from {round.source}" }
+			}
+			div { class: "flex flex-wrap items-center gap-2 text-sm",
+				"Percentile from"
+				PercentileSelect { value: low(), options: (0..100).step_by(WINDOW_STEP.into()).collect::<Vec<u8>>(), onchange: move |value| low.set(value) }
+				"to"
+				PercentileSelect {
+					value: high(),
+					options: (WINDOW_STEP..=100).step_by(WINDOW_STEP.into()).collect::<Vec<u8>>(),
+					onchange: move |value| high.set(value),
+				}
+				"staking"
+				input {
+					r#type: "number",
+					min: MIN_STAKE,
+					max: MAX_STAKE,
+					value: "{stake}",
+					oninput: move |evt| stake.set(evt.value().parse().unwrap_or(MIN_STAKE)),
+					class: "w-24 px-2 py-1 border rounded bg-transparent",
+				}
+				"points"
+			}
+			if let Some(window) = window {
+				p { class: "text-sm text-slate-400",
+					"Pays {window.multiplier():.2}x, {window.payout(stake())} points if it lands between {window.low} and {window.high}."
+				}
+			} else {
+				p { class: "text-sm text-red-400", "The window must end above where it starts." }
+			}
+			button {
+				class: "px-4 py-2 bg-blue-500 rounded-lg hover:bg-blue-600 disabled:opacity-50",
+				disabled: placing() || window.is_none(),
+				onclick: place,
+				"Place bet"
+			}
+		}
+	}
+}
+
+#[component]
+fn PercentileSelect(value: u8, options: Vec<u8>, onchange: EventHandler<u8>) -> Element {
+	rsx! {
+		select {
+			class: "px-2 py-1 border rounded bg-black",
+			onchange: move |evt| {
+				if let Ok(value) = evt.value().parse() {
+					onchange.call(value);
+				}
+			},
+			for option in options {
+				option { key: "{option}", value: "{option}", selected: option == value, "{option}" }
+			}
+		}
+	}
+}
+
+#[component]
+fn BetRow(bet: BetInfo) -> Element {
+	let BetInfo { round, window, stake, result, .. } = bet;
+
+	rsx! {
+		div { class: "flex items-center gap-3 border rounded-lg p-2 text-sm",
+			img { src: "{round.image_url}", alt: "{round.title}", loading: "lazy", class: "w-12 h-12 object-cover rounded" }
+			div { class: "flex-1 min-w-0",
+				p { class: "truncate", "{round.title}" }
+				p { class: "text-xs text-slate-400", "{window.low} to {window.high}, {stake} points" }
+			}
+			{
+				match result {
+					None => rsx! {
+						span { class: "text-slate-400", "Pending" }
+					},
+					Some(BetResult { percentile, payout }) if payout > 0 => rsx! {
+						span { class: "text-green-400", "+{payout} at {percentile:.0}" }
+					},
+					Some(BetResult { percentile, .. }) => rsx! {
+						span { class: "text-red-400", "Lost at {percentile:.0}" }
+					},
+				}
+			}
+		}
+	}
+}
@@ -1,41 +1,131 @@
-use std::sync::LazyLock;
+use std::time::Duration;
 
-use crate::stores::meme_canvas::use_meme_canvas;
-use crate::stores::text_box::TextBox;
-use crate::utils::MEME_CANVAS_ID;
+use crate::api::{memes::get_meme, posts::get_post, templates::proxy_image, tournaments::{TournamentDetail, get_tournament}};
+use crate::components::{drafts_panel::DraftsPanel, publish_panel::PublishPanel, share_panel::SharePanel, template_picker::TemplatePicker};
+use crate::router::Route;
+use crate::share::decode_document;
+use crate::stores::drafts::use_drafts;
+use crate::stores::session::use_session;
+use crate::stores::meme_canvas::{MemeDocument, use_meme_canvas};
+use crate::utils::{MEME_CANVAS_ID, meme_canvas_thumbnail};
 use crate::{
 	stores::meme_canvas::{MemeCanvasStoreExt, MemeCanvasStoreImplExt},
 	utils::download_canvas_as_image,
 };
 use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use dioxus_sdk::time::use_debounce;
+use uuid::Uuid;
 
-const DEFAULT_WIDTH: u32 = 500;
-const DEFAULT_HEIGHT: u32 = 500;
-const DEFAULT_IMG_URL: &str = "https://i.imgflip.com/4/30b1gx.jpg";
-static DEFAULT_TEXT_BOXES: LazyLock<Vec<TextBox>> = LazyLock::new(|| {
-	vec![
-		TextBox::new("top text".to_owned(), 0.75 * DEFAULT_WIDTH as f64, 0.25 * DEFAULT_HEIGHT as f64, 48, "Arial".to_owned(), "bold".to_owned()),
-		TextBox::new("bottom text".to_owned(), 0.75 * DEFAULT_WIDTH as f64, 0.75 * DEFAULT_HEIGHT as f64, 48, "Arial".to_owned(), "bold".to_owned()),
-	]
-});
+const AUTOSAVE_DEBOUNCE: Duration = Duration::from_millis(800);
+const THUMBNAIL_SIZE: f64 = 96.0;
 
+/// `m` carries a whole document encoded by [`crate::share`], `id` points at one stored on the server and `remix` at a published post to fork.
+/// `tournament` is a tournament being entered: its watermark is added, and its template loaded when it requires one.
 #[component]
-pub fn Generator() -> Element {
-	let mut meme_canvas_store = use_meme_canvas(DEFAULT_WIDTH, DEFAULT_HEIGHT, DEFAULT_IMG_URL.to_owned(), DEFAULT_TEXT_BOXES.clone());
+pub fn Generator(m: String, id: String, remix: String, tournament: String) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let mut drafts = use_drafts();
+	let shared = use_hook(|| (!m.is_empty()).then(|| decode_document(&m).map_err(|e| e.to_string())));
+	let shared_id = use_hook(|| id.parse::<Uuid>().ok());
+	let remix_id = use_hook(|| remix.parse::<Uuid>().ok());
+	let tournament_id = use_hook(|| tournament.parse::<Uuid>().ok());
+	let mut entering = use_signal(|| None::<TournamentDetail>);
+	let from_link = matches!(shared, Some(Ok(_))) || shared_id.is_some() || remix_id.is_some() || tournament_id.is_some();
+	let mut meme_canvas_store = use_meme_canvas(|| match shared.clone() {
+		Some(Ok(document)) => document,
+		_ => drafts.current_document().unwrap_or_default(),
+	});
 	let mut main_img_url = meme_canvas_store.main_img_url();
 
+	use_effect(move || {
+		// A shared meme starts a fresh draft instead of overwriting the one being edited.
+		if from_link {
+			drafts.current.set(None);
+		}
+		if let Some(Err(e)) = shared.clone() {
+			toast.error("Invalid share link".to_owned(), ToastOptions::new().description(e));
+		}
+	});
+	use_future(move || async move {
+		if let Some(id) = shared_id {
+			match get_meme(id).await {
+				Ok(document) => meme_canvas_store.load_document(document),
+				Err(e) => toast.error("Could not load shared meme".to_owned(), ToastOptions::new().description(e.to_string())),
+			}
+		}
+		if let Some(id) = remix_id {
+			match get_post(id).await {
+				Ok(post) => meme_canvas_store.load_document(post.document),
+				Err(e) => toast.error("Could not load meme to remix".to_owned(), ToastOptions::new().description(e.to_string())),
+			}
+		}
+		if let Some(id) = tournament_id {
+			match get_tournament(id).await {
+				Ok(tournament) => {
+					if let Some(template) = &tournament.template {
+						meme_canvas_store.load_document(MemeDocument::from_template(template));
+					}
+					meme_canvas_store.watermark_url().set(Some(tournament.watermark_url.clone()));
+					entering.set(Some(tournament));
+				},
+				Err(e) => toast.error("Could not load tournament".to_owned(), ToastOptions::new().description(e.to_string())),
+			}
+		}
+	});
+	// Images from other sites are swapped for a copy in our storage once the url is entered, since most hosts do not allow
+	// cross origin reads and drawing their images would taint the canvas, breaking downloads and publishing. Copying needs an account,
+	// logged out the image is drawn as is.
+	let proxy_main_image = move |url: String| async move {
+		let same_origin = url.starts_with(env!("SERVER_URL").trim_end_matches('/'));
+		if same_origin || !session.is_logged_in() || !(url.starts_with("http://") || url.starts_with("https://")) {
+			return;
+		}
+		match proxy_image(url.clone()).await {
+			// The url may have been edited again while the copy was made.
+			Ok(proxied) if main_img_url() == url => main_img_url.set(proxied),
+			Ok(_) => {},
+			Err(e) => toast.error("Could not copy the image".to_owned(), ToastOptions::new().description(format!("It may not download or publish: {e}"))),
+		}
+	};
+	let template_locked = entering.read().as_ref().is_some_and(|tournament| tournament.template.is_some());
+
+	let mut autosave = use_debounce(AUTOSAVE_DEBOUNCE, move |document: MemeDocument| drafts.save_current(document, meme_canvas_thumbnail(THUMBNAIL_SIZE)));
+	use_effect(move || autosave.action(meme_canvas_store.document()));
+
 	rsx! {
     div { class: "max-w-6xl mx-auto p-6 min-h-screen",
       div { class: "mb-8",
         h1 { class: "text-3xl font-bold text-center mb-2", "Meme Generator" }
         p { class: "text-center", "Create your own memes with custom text" }
       }
+      if let Some(tournament) = entering() {
+        div { class: "mb-6 border border-cyan-400/40 rounded-xl p-4 text-sm space-y-1",
+          p {
+            "Entering "
+            Link {
+              to: Route::TournamentPage { id: tournament.summary.id.to_string() },
+              class: "font-semibold text-cyan-400 hover:text-cyan-300",
+              "{tournament.summary.title}"
+            }
+            " by {tournament.summary.sponsor}."
+          }
+          p { class: "text-slate-400",
+            if tournament.template.is_some() {
+              "The sponsor's template and watermark stay on your meme."
+            } else {
+              "The sponsor's watermark stays on your meme."
+            }
+          }
+        }
+      }
       div { class: "flex flex-col lg:flex-row gap-8 items-start",
         div { class: "flex justify-center",
           canvas {
             id: MEME_CANVAS_ID,
-            width: 500,
-            height: 500,
+            width: meme_canvas_store.width()(),
+            height: meme_canvas_store.height()(),
             onmousedown: move |e| meme_canvas_store.mouse_down(e),
             onmousemove: move |e| meme_canvas_store.mouse_move(e),
             onmouseup: move |e| meme_canvas_store.mouse_up(e),
@@ -44,14 +134,18 @@ pub fn Generator() -> Element {
         }
         div { class: "w-full lg:w-80 rounded-xl shadow-lg p-6",
           div { class: "space-y-4",
-            div { class: "space-y-2",
-              input {
-                r#type: "url",
-                value: "{main_img_url}",
-                oninput: move |evt| main_img_url.set(evt.value()),
-                placeholder: "Enter image URL...",
-                class: "w-full px-4 py-3 text-base border-2 rounded-lg focus:outline-none transition-all duration-200",
+            if !template_locked {
+              div { class: "space-y-2",
+                input {
+                  r#type: "url",
+                  value: "{main_img_url}",
+                  oninput: move |evt| main_img_url.set(evt.value()),
+                  onchange: move |evt| proxy_main_image(evt.value()),
+                  placeholder: "Enter image URL...",
+                  class: "w-full px-4 py-3 text-base border-2 rounded-lg focus:outline-none transition-all duration-200",
+                }
               }
+              TemplatePicker { meme_canvas: meme_canvas_store }
             }
             hr { class: "border-gray-300" }
             for (index , mut text_box) in meme_canvas_store.text_boxes().iter().enumerate() {
@@ -74,11 +168,26 @@ pub fn Generator() -> Element {
               class: "px-3 py-1 bg-blue-500 text-white rounded-md hover:bg-blue-600 transition-colors duration-200 text-sm font-medium",
               "Add Text"
             }
+            if meme_canvas_store.watermark_url().read().is_some() && entering.read().is_none() {
+              button {
+                onclick: move |_| meme_canvas_store.watermark_url().set(None),
+                class: "px-3 py-1 bg-red-500 rounded-md hover:bg-red-600 transition-colors duration-200 text-sm font-medium",
+                "Remove Watermark"
+              }
+            }
             button {
               onclick: |_| download_canvas_as_image(),
               class: "w-full cursor-pointer font-semibold py-3 px-4 rounded-lg transition-colors duration-200 shadow-md hover:shadow-lg",
               "Download"
             }
+            PublishPanel {
+              meme_canvas: meme_canvas_store,
+              parent_id: remix_id,
+              tournament_id: entering.read().as_ref().map(|tournament| tournament.summary.id),
+            }
+            SharePanel { meme_canvas: meme_canvas_store }
+            hr { class: "border-gray-300" }
+            DraftsPanel { meme_canvas: meme_canvas_store, drafts }
           }
         }
       }
@@ -1,100 +1,13 @@
+use crate::api::roadmap::{ItemStatus, RoadmapItem, RoadmapStage, progress, roadmap};
 use dioxus::prelude::*;
-use std::sync::LazyLock;
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RoadmapStage {
-	pub title: &'static str,
-	pub description: &'static str,
-	pub items: Vec<StageItem>,
-}
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct StageItem {
-	pub title: &'static str,
-	pub description: &'static str,
-}
-
-static ROADMAP_DATA: LazyLock<Vec<RoadmapStage>> = LazyLock::new(|| {
-	vec![
-		RoadmapStage {
-			title: "Meme Generator",
-			description: "The starting point of development. Requires using less common apis.",
-			items: vec![
-				StageItem { title: "Canvas Implementation", description: "Will start with canvas initially as is more well known and lowers inital complexity." },
-				StageItem { title: "WGPU Engine Upgrade", description: "Migrate rendering to WGPU for cross-platform and performance." },
-			],
-		},
-		RoadmapStage {
-			title: "Meme Site Basics",
-			description: "Implement the most common functionality of a meme site.",
-			items: vec![
-				StageItem { title: "Database", description: "PostgreSQL" },
-				StageItem { title: "DB ORM", description: "Diesel" },
-				StageItem { title: "User Authentication", description: "JWT/Cookies" },
-				StageItem { title: "Blob Storage", description: "Try self hosted Minio, move to cloudflare R2 when bandwidth is too high." },
-				StageItem { title: "File Upload API", description: "signed-urls to post, but need to sync with database." },
-			],
-		},
-		// RoadmapStage {
-		// 	title: "Meme Analytics Game",
-		// 	description: "Use data science on scrapped memes to allow users to bet on percentile window the meme falls in.",
-		// 	items: vec![
-		// 		StageItem {
-		// 			title: "Scrape Memes",
-		// 			description: "Gather large collection of memes from the internet along with their engagement metrics.",
-		// 		},
-		// 		StageItem {
-		// 			title: "Data Science",
-		// 			description: "Design the calculations to create the numbers for betting and bet returns.",
-		// 		},
-		// 		StageItem { title: "Implement Game on Site", description: "Add a page for playing the game to the site." },
-		// 	],
-		// },
-		// RoadmapStage {
-		// 	title: "Meme Poker",
-		// 	description: "Use the betting on a meme's percentile to create a meme texas holdem.",
-		// 	items: vec![
-		// 		StageItem {
-		// 			title: "Showdown Value",
-		// 			description: "Defined as the total returns of the best 5 of 7 meme bets in a hand.",
-		// 		},
-		// 		StageItem {
-		// 			title: "Initial System",
-		// 			description: "Start by implement standard poker, then layer on the memes.",
-		// 		},
-		// 	],
-		// },
-		// RoadmapStage {
-		// 	title: "Meme Marketing Tournaments",
-		// 	description: "Allow Companies to create tournaments for purposed marketing memes",
-		// 	items: vec![
-		// 		StageItem {
-		// 			title: "Showdown Value",
-		// 			description: "Defined as the total returns of the best 5 of 7 meme bets in a hand.",
-		// 		},
-		// 		StageItem {
-		// 			title: "Initial System",
-		// 			description: "Start by implement standard poker, then layer on the memes.",
-		// 		},
-		// 	],
-		// },
-		// RoadmapStage {
-		// 	title: "The truest Meme Coin",
-		// 	description: "Design a crypto token based on the memetopia ecosystem",
-		// 	items: vec![
-		// 		StageItem {
-		// 			title: "Creation",
-		// 			description: "buy for meme poker or torunament creation. Cannot withdraw to chain ",
-		// 		},
-		// 		StageItem { title: "Sinks", description: "Rake and tournaments" },
-		// 		StageItem { title: "Distribution", description: "poker winnings, profit share of to tournament winners" },
-		// 	],
-		// },
-	]
-});
+use strum::IntoEnumIterator;
 
 #[component]
 pub fn Home() -> Element {
+	// A server future so the roadmap is in the server rendered html.
+	let stages = use_server_future(roadmap)?;
+	let mut filter = use_signal(|| None::<ItemStatus>);
+
 	rsx! {
 		div { class: "max-w-4xl mx-auto p-6 md:p-8 bg-slate-900 bg-gradient-to-br from-slate-900 via-zinc-900 to-black min-h-screen font-sans text-slate-200",
 			div { class: "text-center mb-12",
@@ -105,32 +18,91 @@ pub fn Home() -> Element {
 					"Forging the future meme site for the memers by the memers."
 				}
 			}
-			div { class: "space-y-10",
-				for (idx , item) in ROADMAP_DATA.iter().enumerate() {
-					div {
-						key: "{idx}",
-						class: "bg-slate-800/50 backdrop-blur-xl rounded-xl border border-white/10 p-6",
-						div { class: "border-b border-white/10 pb-4 mb-4",
-							h3 { class: "text-2xl font-semibold text-slate-100 mb-1",
-								"{item.title}"
+			match stages() {
+				None => rsx! {},
+				Some(Err(e)) => rsx! {
+					p { class: "text-red-400", "{e}" }
+				},
+				Some(Ok(stages)) => rsx! {
+					div { class: "mb-8 space-y-4",
+						ProgressBar { percent: progress(stages.iter().flat_map(|stage| &stage.items)) }
+						nav { class: "flex justify-center gap-4",
+							button {
+								onclick: move |_| filter.set(None),
+								class: if filter().is_none() { "font-semibold text-cyan-400" } else { "text-slate-400 hover:text-white" },
+								"All"
 							}
-							p { class: "text-slate-300 mb-3", "{item.description}" }
-						}
-						div { class: "space-y-3",
-							for (idx , sub_item) in item.items.iter().enumerate() {
-								div {
-									key: "{idx}",
-									class: "flex items-start space-x-3",
-									div {
-										p { class: "font-medium text-slate-200", "{sub_item.title}" }
-										p { class: "text-sm text-slate-400", "{sub_item.description}" }
-									}
+							for status in ItemStatus::iter() {
+								button {
+									onclick: move |_| filter.set(Some(status)),
+									class: if filter() == Some(status) { "font-semibold text-cyan-400" } else { "text-slate-400 hover:text-white" },
+									"{status}"
 								}
 							}
 						}
 					}
+					div { class: "space-y-10",
+						for stage in stages {
+							// Stages with nothing left after the filter are hidden.
+							if filter().is_none_or(|status| stage.items.iter().any(|item| item.status == status)) {
+								StageCard { key: "{stage.title}", stage, filter: filter() }
+							}
+						}
+					}
+				},
+			}
+		}
+	}
+}
+
+#[component]
+fn StageCard(stage: RoadmapStage, filter: Option<ItemStatus>) -> Element {
+	rsx! {
+		div { class: "bg-slate-800/50 backdrop-blur-xl rounded-xl border border-white/10 p-6",
+			div { class: "border-b border-white/10 pb-4 mb-4",
+				h3 { class: "text-2xl font-semibold text-slate-100 mb-1", "{stage.title}" }
+				p { class: "text-slate-300 mb-3", "{stage.description}" }
+				ProgressBar { percent: stage.progress() }
+			}
+			div { class: "space-y-3",
+				for (idx , item) in stage.items.into_iter().enumerate().filter(|(_, item)| filter.is_none_or(|status| item.status == status)) {
+					ItemRow { key: "{idx}", item }
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn ItemRow(item: RoadmapItem) -> Element {
+	let color = match item.status {
+		ItemStatus::Planned => "bg-slate-700 text-slate-300",
+		ItemStatus::InProgress => "bg-amber-500/20 text-amber-300",
+		ItemStatus::Done => "bg-green-500/20 text-green-300",
+	};
+
+	rsx! {
+		div { class: "flex items-start justify-between gap-3",
+			div {
+				p { class: "font-medium text-slate-200", "{item.title}" }
+				p { class: "text-sm text-slate-400", "{item.description}" }
+			}
+			span { class: "shrink-0 px-2 py-0.5 rounded-full text-xs {color}", "{item.status}" }
+		}
+	}
+}
+
+#[component]
+fn ProgressBar(percent: u32) -> Element {
+	rsx! {
+		div { class: "flex items-center gap-3",
+			div { class: "flex-1 h-2 rounded-full bg-slate-700 overflow-hidden",
+				div {
+					class: "h-full bg-gradient-to-r from-cyan-400 to-blue-600",
+					style: "width: {percent}%",
 				}
 			}
+			span { class: "text-sm text-slate-400 w-10 text-right", "{percent}%" }
 		}
 	}
 }
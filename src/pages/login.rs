@@ -0,0 +1,64 @@
+use crate::{api::auth::login, router::Route, stores::session::use_session};
+use dioxus::prelude::*;
+
+#[component]
+pub fn Login(redirect: String) -> Element {
+	let mut session = use_session();
+	let navigator = use_navigator();
+	let mut username = use_signal(String::new);
+	let mut password = use_signal(String::new);
+	let mut error = use_signal(|| None::<String>);
+
+	let submit = move |e: FormEvent| {
+		e.prevent_default();
+		let redirect = redirect.clone();
+		async move {
+			match login(username(), password()).await {
+				Ok(user) => {
+					session.user.set(Some(user));
+					match redirect.parse::<Route>() {
+						Ok(route) if !redirect.is_empty() => navigator.replace(route),
+						_ => navigator.replace(Route::Home {}),
+					};
+				},
+				Err(e) => error.set(Some(e.to_string())),
+			}
+		}
+	};
+
+	rsx! {
+		div { class: "max-w-sm mx-auto p-6 space-y-6",
+			h1 { class: "text-3xl font-bold text-center", "Log in" }
+			form { class: "space-y-4", onsubmit: submit,
+				input {
+					r#type: "text",
+					placeholder: "Username",
+					autocomplete: "username",
+					value: "{username}",
+					oninput: move |evt| username.set(evt.value()),
+					class: "w-full px-4 py-3 text-base border-2 rounded-lg focus:outline-none bg-transparent",
+				}
+				input {
+					r#type: "password",
+					placeholder: "Password",
+					autocomplete: "current-password",
+					value: "{password}",
+					oninput: move |evt| password.set(evt.value()),
+					class: "w-full px-4 py-3 text-base border-2 rounded-lg focus:outline-none bg-transparent",
+				}
+				if let Some(error) = error() {
+					p { class: "text-sm text-red-400", "{error}" }
+				}
+				button {
+					r#type: "submit",
+					class: "w-full cursor-pointer font-semibold py-3 px-4 rounded-lg bg-blue-500 hover:bg-blue-600 transition-colors duration-200",
+					"Log in"
+				}
+			}
+			p { class: "text-center text-sm text-slate-400",
+				"No account yet? "
+				Link { to: Route::Register {}, class: "text-cyan-400 hover:underline", "Register" }
+			}
+		}
+	}
+}
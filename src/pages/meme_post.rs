@@ -0,0 +1,82 @@
+use crate::{
+	api::{
+		engagement::record_view,
+		posts::{PostDetail, PostSummary, get_post},
+	},
+	components::{comments::CommentSection, remix_tree::RemixTree, report_button::ReportButton, tag_list::TagList, vote_buttons::VoteButtons},
+	router::Route,
+};
+use dioxus::prelude::*;
+
+#[component]
+pub fn MemePost(id: String) -> Element {
+	// Keyed so following a link to another post refetches instead of reusing the previous post's resource.
+	rsx! {
+		PostView { key: "{id}", id }
+	}
+}
+
+#[component]
+fn PostView(id: String) -> Element {
+	// A server future so the post, and the Open Graph tags below, are in the server rendered html that link preview crawlers read.
+	let post = use_server_future({
+		let id = id.clone();
+		move || {
+			let id = id.clone();
+			async move { get_post(id.parse().map_err(|_| ServerFnError::new("post not found"))?).await }
+		}
+	})?;
+	// Effects only run in the browser, so server renders and crawlers do not count as views.
+	use_effect(move || {
+		if let Ok(id) = id.parse() {
+			spawn(async move {
+				record_view(id).await.ok();
+			});
+		}
+	});
+
+	let Some(post) = post() else {
+		return rsx! {};
+	};
+	let PostDetail { summary: PostSummary { id, title, image_url, author, score, views, my_vote, tags, .. }, .. } = match post {
+		Ok(post) => post,
+		Err(e) => {
+			return rsx! {
+				p { class: "max-w-2xl mx-auto p-6 text-red-400", "{e}" }
+			};
+		},
+	};
+	let url = format!("{}/m/{id}", env!("SERVER_URL").trim_end_matches('/'));
+
+	rsx! {
+		document::Title { "{title} | Memetopia" }
+		document::Meta { property: "og:type", content: "article" }
+		document::Meta { property: "og:site_name", content: "Memetopia" }
+		document::Meta { property: "og:title", content: "{title}" }
+		document::Meta { property: "og:url", content: "{url}" }
+		document::Meta { property: "og:image", content: "{image_url}" }
+		document::Meta { name: "twitter:card", content: "summary_large_image" }
+		article { class: "max-w-2xl mx-auto p-6 space-y-4",
+			h1 { class: "text-3xl font-bold", "{title}" }
+			div { class: "flex gap-4 text-sm text-slate-400",
+				if let Some(author) = author {
+					span { "by {author}" }
+				}
+				span { "{views} views" }
+			}
+			TagList { tags }
+			img { src: "{image_url}", alt: "{title}", class: "w-full rounded-xl" }
+			div { class: "flex items-center justify-between",
+				VoteButtons { meme_id: id, score, my_vote }
+				Link {
+					to: Route::Generator { m: String::new(), id: String::new(), remix: id.to_string(), tournament: String::new() },
+					class: "px-3 py-1 bg-blue-500 text-white rounded-md hover:bg-blue-600 transition-colors duration-200 text-sm font-medium",
+					"Remix"
+				}
+			}
+			ReportButton { meme_id: id }
+			RemixTree { meme_id: id }
+			CommentSection { meme_id: id }
+		}
+	}
+}
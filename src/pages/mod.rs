@@ -1,2 +1,13 @@
+pub mod feed;
+pub mod game;
 pub mod generator;
 pub mod home;
+pub mod login;
+pub mod meme_post;
+pub mod moderation;
+pub mod points;
+pub mod profile;
+pub mod register;
+pub mod search;
+pub mod tables;
+pub mod tournaments;
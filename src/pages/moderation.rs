@@ -0,0 +1,159 @@
+use crate::{
+	api::moderation::{AuditLogEntry, MAX_MOD_NOTE_LEN, ModAction, QueueItem, moderate, moderation_log, moderation_queue},
+	router::Route,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+
+/// Review queue of reported posts plus the recent audit log. Only moderators get data back, everyone else sees the error.
+#[component]
+pub fn Moderation() -> Element {
+	let mut queue = use_resource(moderation_queue);
+	let mut log = use_resource(moderation_log);
+	let refresh = move |()| {
+		queue.restart();
+		log.restart();
+	};
+
+	rsx! {
+		div { class: "max-w-3xl mx-auto p-6 space-y-6",
+			h1 { class: "text-3xl font-bold", "Moderation" }
+			{
+				match queue() {
+					None => rsx! {
+						p { class: "text-sm text-slate-400", "Loading..." }
+					},
+					Some(Err(e)) => rsx! {
+						p { class: "text-sm text-red-400", "{e}" }
+					},
+					Some(Ok(items)) => rsx! {
+						if items.is_empty() {
+							p { class: "text-sm text-slate-400", "Nothing to review." }
+						}
+						for item in items {
+							QueueCard { key: "{item.meme_id}", item, on_done: refresh }
+						}
+					},
+				}
+			}
+			if let Some(Ok(entries)) = log() {
+				section { class: "space-y-2",
+					h2 { class: "text-xl font-semibold", "Audit log" }
+					for entry in entries {
+						AuditRow { key: "{entry.id}", entry, on_done: refresh }
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn QueueCard(item: QueueItem, on_done: EventHandler) -> Element {
+	let toast = use_toast();
+	let mut note = use_signal(String::new);
+	let meme_id = item.meme_id;
+
+	let act = move |action: ModAction| async move {
+		match moderate(meme_id, action, note()).await {
+			Ok(()) => on_done.call(()),
+			Err(e) => toast.error("Action failed".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	rsx! {
+		article { class: "flex gap-4 border border-white/20 rounded-xl p-4",
+			img { src: "{item.image_url}", alt: "{item.title}", class: "w-32 h-32 object-cover rounded" }
+			div { class: "flex-1 min-w-0 space-y-2",
+				div { class: "flex items-center gap-2",
+					Link {
+						to: Route::MemePost { id: meme_id.to_string() },
+						class: "font-semibold truncate hover:text-cyan-400",
+						"{item.title}"
+					}
+					if item.hidden {
+						span { class: "px-2 py-0.5 rounded-full bg-yellow-500/20 text-xs text-yellow-400", "hidden" }
+					}
+				}
+				p { class: "text-xs text-slate-400",
+					{item.author.clone().unwrap_or_else(|| "[deleted]".to_owned())}
+					" · {item.reports} reports · "
+					{item.reasons.iter().map(|reason| reason.label()).collect::<Vec<_>>().join(", ")}
+				}
+				for (index , details) in item.details.iter().enumerate() {
+					p { key: "{index}", class: "text-sm italic text-slate-300 break-words", "\"{details}\"" }
+				}
+				input {
+					r#type: "text",
+					value: "{note}",
+					maxlength: MAX_MOD_NOTE_LEN,
+					placeholder: "Note for the audit log",
+					oninput: move |evt| note.set(evt.value()),
+					class: "w-full px-2 py-1 text-sm border rounded focus:outline-none bg-transparent",
+				}
+				div { class: "flex flex-wrap gap-2",
+					button {
+						onclick: move |_| act(ModAction::Dismiss),
+						class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+						"Dismiss"
+					}
+					if item.hidden {
+						button {
+							onclick: move |_| act(ModAction::Unhide),
+							class: "px-2 py-1 bg-slate-600 rounded text-xs hover:bg-slate-500 transition-colors duration-200",
+							"Unhide"
+						}
+					} else {
+						button {
+							onclick: move |_| act(ModAction::Hide),
+							class: "px-2 py-1 bg-yellow-600 rounded text-xs hover:bg-yellow-500 transition-colors duration-200",
+							"Hide"
+						}
+					}
+					button {
+						onclick: move |_| act(ModAction::Delete),
+						class: "px-2 py-1 bg-red-500 rounded text-xs hover:bg-red-600 transition-colors duration-200",
+						"Delete"
+					}
+					if item.author_id.is_some() {
+						button {
+							onclick: move |_| act(ModAction::Ban),
+							class: "px-2 py-1 bg-red-700 rounded text-xs hover:bg-red-800 transition-colors duration-200",
+							"Ban author"
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn AuditRow(entry: AuditLogEntry, on_done: EventHandler) -> Element {
+	let toast = use_toast();
+	let unhide = move |meme_id| async move {
+		match moderate(meme_id, ModAction::Unhide, String::new()).await {
+			Ok(()) => on_done.call(()),
+			Err(e) => toast.error("Action failed".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+	let moderator = entry.moderator.clone().unwrap_or_else(|| "[deleted]".to_owned());
+
+	rsx! {
+		div { class: "flex items-center gap-2 text-sm border-b border-white/10 py-1",
+			span { class: "font-semibold", "{moderator}" }
+			span { class: "text-cyan-400", "{entry.action}" }
+			if let Some(meme_id) = entry.meme_id {
+				if entry.action != ModAction::Delete.to_string() {
+					Link { to: Route::MemePost { id: meme_id.to_string() }, class: "text-blue-400 hover:text-blue-300", "post" }
+				}
+				if entry.action == ModAction::Hide.to_string() {
+					button { onclick: move |_| unhide(meme_id), class: "text-xs text-slate-400 hover:text-white", "Unhide" }
+				}
+			}
+			if !entry.note.is_empty() {
+				span { class: "text-slate-400 truncate", "{entry.note}" }
+			}
+		}
+	}
+}
@@ -0,0 +1,101 @@
+use crate::{
+	api::points::{PointsLine, points_history},
+	components::local_time::LocalTime,
+};
+use dioxus::prelude::*;
+
+/// The caller's points and every movement on them. Points pay for bets, poker buy-ins and tournament prizes, and never leave the site.
+#[component]
+pub fn Points() -> Element {
+	let mut lines = use_signal(Vec::<PointsLine>::new);
+	let mut balance = use_signal(|| None::<i64>);
+	let mut cursor = use_signal(|| None::<i64>);
+	let mut exhausted = use_signal(|| false);
+	let mut loading = use_signal(|| false);
+	let mut error = use_signal(|| None::<String>);
+
+	let load_more = move || async move {
+		if loading() || exhausted() {
+			return;
+		}
+		loading.set(true);
+		match points_history(cursor()).await {
+			Ok(page) => {
+				// The first page has the current balance, later ones would move it past lines already shown.
+				if balance().is_none() {
+					balance.set(Some(page.balance));
+				}
+				lines.write().extend(page.lines);
+				exhausted.set(page.next_cursor.is_none());
+				cursor.set(page.next_cursor);
+				error.set(None);
+			},
+			Err(e) => error.set(Some(e.to_string())),
+		}
+		loading.set(false);
+	};
+
+	rsx! {
+		div { class: "max-w-2xl mx-auto p-6 space-y-6",
+			div { class: "flex items-baseline justify-between",
+				h1 { class: "text-3xl font-bold", "Points" }
+				if let Some(balance) = balance() {
+					span { class: "text-lg font-semibold text-cyan-400", "{balance} points" }
+				}
+			}
+			p { class: "text-sm text-slate-400",
+				"Points pay for bets and poker buy-ins and are won in tournaments. They stay on Memetopia and cannot be withdrawn."
+			}
+			div { class: "divide-y divide-white/10 border rounded-lg",
+				for line in lines() {
+					PointsRow { key: "{line.id}", line }
+				}
+			}
+			if let Some(error) = error() {
+				p { class: "text-sm text-red-400", "{error}" }
+			}
+			if !exhausted() {
+				div {
+					onvisible: move |evt| async move {
+						if evt.is_intersecting().unwrap_or_default() {
+							load_more().await;
+						}
+					},
+					class: "flex justify-center py-4",
+					button {
+						onclick: move |_| load_more(),
+						disabled: loading(),
+						class: "px-4 py-2 bg-slate-600 rounded text-sm hover:bg-slate-500 disabled:opacity-50 transition-colors duration-200",
+						if loading() {
+							"Loading..."
+						} else {
+							"Load more"
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn PointsRow(line: PointsLine) -> Element {
+	let label = line.kind.map_or("Other", |kind| kind.label());
+	let (amount, color) = if line.amount > 0 { (format!("+{}", line.amount), "text-green-400") } else { (line.amount.to_string(), "text-red-400") };
+
+	rsx! {
+		div { class: "flex items-center gap-3 p-3 text-sm",
+			div { class: "flex-1 min-w-0",
+				p { class: "truncate", "{line.memo}" }
+				p { class: "text-xs text-slate-400",
+					"{label}, "
+					LocalTime { millis: line.created_at }
+				}
+			}
+			div { class: "text-right",
+				p { class: "font-semibold {color}", "{amount}" }
+				p { class: "text-xs text-slate-400", "{line.balance}" }
+			}
+		}
+	}
+}
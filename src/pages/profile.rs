@@ -0,0 +1,22 @@
+use crate::{
+	components::{api_keys_panel::ApiKeysPanel, notification_list::NotificationList},
+	stores::session::use_session,
+};
+use dioxus::prelude::*;
+
+#[component]
+pub fn Profile() -> Element {
+	let session = use_session();
+	let Some(user) = session.user.read().clone() else {
+		return rsx! {};
+	};
+
+	rsx! {
+		div { class: "max-w-2xl mx-auto p-6 space-y-4",
+			h1 { class: "text-3xl font-bold", "{user.username}" }
+			p { class: "text-sm text-slate-400", "User id {user.id}" }
+			NotificationList {}
+			ApiKeysPanel {}
+		}
+	}
+}
@@ -0,0 +1,93 @@
+use crate::{
+	api::{search::search, templates::TemplateInfo},
+	components::{post_card::PostCard, tag_list::TagList, template_recognizer::TemplateRecognizer},
+	router::Route,
+	share::encode_for_link,
+	stores::{meme_canvas::MemeDocument, session::use_session},
+	tags::parse_query,
+};
+use dioxus::prelude::*;
+
+/// `q` is the raw search box text, `#words` in it filter by tag.
+#[component]
+pub fn Search(q: String) -> Element {
+	// Keyed so a new query from the header starts a fresh search.
+	rsx! {
+		SearchResultsView { key: "{q}", q }
+	}
+}
+
+#[component]
+fn SearchResultsView(q: String) -> Element {
+	let session = use_session();
+	let (text, tags) = parse_query(&q);
+	let results = use_resource({
+		let (text, tags) = (text.clone(), tags.clone());
+		move || search(text.clone(), tags.clone())
+	});
+
+	rsx! {
+		div { class: "max-w-2xl mx-auto p-6 space-y-6",
+			h1 { class: "text-3xl font-bold", "Search" }
+			div { class: "flex flex-wrap items-center gap-2 text-sm text-slate-400",
+				if !text.is_empty() {
+					span { "Matching \"{text}\"" }
+				}
+				TagList { tags }
+			}
+			if session.is_logged_in() {
+				TemplateRecognizer {}
+			}
+			{
+				match results() {
+					None => rsx! {
+						p { class: "text-sm text-slate-400", "Searching..." }
+					},
+					Some(Err(e)) => rsx! {
+						p { class: "text-sm text-red-400", "{e}" }
+					},
+					Some(Ok(results)) => rsx! {
+						if results.posts.is_empty() && results.templates.is_empty() {
+							p { class: "text-sm text-slate-400", "Nothing found." }
+						}
+						if !results.templates.is_empty() {
+							h2 { class: "text-xl font-semibold", "Templates" }
+							div { class: "grid grid-cols-3 gap-4",
+								for template in results.templates {
+									TemplateResult { key: "{template.id}", template }
+								}
+							}
+						}
+						if !results.posts.is_empty() {
+							h2 { class: "text-xl font-semibold", "Posts" }
+							for post in results.posts {
+								PostCard { key: "{post.id}", post }
+							}
+						}
+					},
+				}
+			}
+		}
+	}
+}
+
+/// Opens the generator with the template as the background and blank text boxes.
+#[component]
+fn TemplateResult(template: TemplateInfo) -> Element {
+	let m = encode_for_link(&MemeDocument::from_template(&template)).ok().flatten().unwrap_or_default();
+
+	rsx! {
+		div { class: "space-y-1",
+			Link { to: Route::Generator { m, id: String::new(), remix: String::new(), tournament: String::new() },
+				img {
+					src: "{template.image_url}",
+					alt: "{template.name}",
+					loading: "lazy",
+					class: "w-full aspect-square object-cover rounded hover:ring-2 hover:ring-blue-500",
+				}
+			}
+			p { class: "text-sm truncate", "{template.name}" }
+			TagList { tags: template.tags }
+		}
+	}
+}
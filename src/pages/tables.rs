@@ -0,0 +1,380 @@
+use std::time::Duration;
+
+use crate::{
+	api::tables::{BUY_IN_BIG_BLINDS, CardView, ClientMessage, HandView, MAX_BIG_BLIND, RAKE_PERCENT, SeatView, TableKind, TableView, list_tables, open_table},
+	game::poker::{
+		cards::{Card, Suit},
+		table::{Action, LegalActions, Street},
+	},
+	router::Route,
+	stores::poker_table::{PokerTable, use_poker_table},
+};
+use dioxus::{fullstack::WebsocketState, prelude::*};
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use uuid::Uuid;
+
+/// Open poker tables, and a form to open another.
+#[component]
+pub fn Tables() -> Element {
+	let tables = use_resource(list_tables);
+
+	rsx! {
+		div { class: "max-w-2xl mx-auto p-6 space-y-6",
+			h1 { class: "text-3xl font-bold", "Poker Tables" }
+			OpenTableForm {}
+			{
+				match tables() {
+					None => rsx! {
+						p { class: "text-sm text-slate-400", "Loading..." }
+					},
+					Some(Err(e)) => rsx! {
+						p { class: "text-sm text-red-400", "{e}" }
+					},
+					Some(Ok(tables)) if tables.is_empty() => rsx! {
+						p { class: "text-sm text-slate-400", "No tables are open yet." }
+					},
+					Some(Ok(tables)) => rsx! {
+						div { class: "space-y-2",
+							for table in tables {
+								Link {
+									key: "{table.id}",
+									to: Route::LiveTable { id: table.id.to_string() },
+									class: "flex items-center justify-between border rounded-lg p-3 hover:bg-white/5",
+									span { class: "font-semibold", "{table.kind} {table.blinds.small}/{table.blinds.big}" }
+									span { class: "text-sm text-slate-400", "{table.players}/{table.seats} seated, {table.hands_played} hands" }
+								}
+							}
+						}
+					},
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn OpenTableForm() -> Element {
+	let toast = use_toast();
+	let navigator = use_navigator();
+	let mut kind = use_signal(|| TableKind::Holdem);
+	let mut big_blind = use_signal(|| 10_u64);
+	let mut opening = use_signal(|| false);
+
+	let open = move |_| async move {
+		opening.set(true);
+		let big = big_blind();
+		match open_table(kind(), (big / 2).max(1), big).await {
+			Ok(id) => {
+				navigator.push(Route::LiveTable { id: id.to_string() });
+			},
+			Err(e) => toast.error("Could not open the table".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		opening.set(false);
+	};
+
+	rsx! {
+		div { class: "flex flex-wrap items-center gap-2 border rounded-xl p-4 text-sm",
+			"Open a"
+			select {
+				class: "px-2 py-1 border rounded bg-black",
+				onchange: move |evt| kind.set(if evt.value() == "meme" { TableKind::Meme } else { TableKind::Holdem }),
+				option { value: "holdem", selected: kind() == TableKind::Holdem, "{TableKind::Holdem}" }
+				option { value: "meme", selected: kind() == TableKind::Meme, "{TableKind::Meme}" }
+			}
+			"table with a big blind of"
+			input {
+				r#type: "number",
+				min: 2,
+				max: MAX_BIG_BLIND,
+				value: "{big_blind}",
+				oninput: move |evt| big_blind.set(evt.value().parse().unwrap_or(2)),
+				class: "w-24 px-2 py-1 border rounded bg-transparent",
+			}
+			span { class: "text-slate-400", "({big_blind() * BUY_IN_BIG_BLINDS} point buy-in, {RAKE_PERCENT}% rake after the flop)" }
+			button {
+				class: "ml-auto px-4 py-2 bg-blue-500 rounded-lg hover:bg-blue-600 disabled:opacity-50",
+				disabled: opening() || !(2..=MAX_BIG_BLIND).contains(&big_blind()),
+				onclick: open,
+				"Open table"
+			}
+		}
+	}
+}
+
+#[component]
+pub fn LiveTable(id: String) -> Element {
+	match id.parse::<Uuid>() {
+		// Keyed so moving to another table opens a new connection instead of reusing this one.
+		Ok(id) => rsx! {
+			TableRoom { key: "{id}", id }
+		},
+		Err(_) => rsx! {
+			p { class: "max-w-2xl mx-auto p-6 text-red-400", "table not found" }
+		},
+	}
+}
+
+#[component]
+fn TableRoom(id: Uuid) -> Element {
+	let table = use_poker_table(id);
+	let status = table.status();
+	let error = (table.error)();
+
+	let Some(view) = (table.view)() else {
+		return rsx! {
+			p { class: "text-center text-slate-400 p-10",
+				if status == WebsocketState::Connecting {
+					"Connecting to the table..."
+				} else {
+					"Could not reach the table, retrying..."
+				}
+			}
+		};
+	};
+	let TableView { kind, blinds, version, seats, hand, payouts, you, legal, .. } = view;
+	let sitting_out = you.and_then(|you| seats[you].as_ref()).is_some_and(|seat| seat.sitting_out);
+
+	rsx! {
+		div { class: "max-w-4xl mx-auto p-6 space-y-6",
+			div { class: "flex items-baseline gap-4",
+				h1 { class: "text-3xl font-bold", "{kind} {blinds.small}/{blinds.big}" }
+				if status != WebsocketState::Open {
+					span { class: "text-sm text-yellow-400", "Reconnecting..." }
+				}
+				if you.is_some() {
+					button {
+						class: "ml-auto px-3 py-1 border rounded-lg hover:bg-white/10",
+						onclick: move |_| table.send(ClientMessage::Leave),
+						"Leave"
+					}
+				} else {
+					button {
+						class: "ml-auto px-3 py-1 bg-blue-500 rounded-lg hover:bg-blue-600",
+						onclick: move |_| table.send(ClientMessage::Join),
+						"Sit down"
+					}
+				}
+			}
+			if let Some(hand) = hand.clone() {
+				Board { hand }
+			} else {
+				p { class: "text-center text-slate-400", "Waiting for players..." }
+			}
+			div { class: "grid grid-cols-2 md:grid-cols-3 gap-3",
+				for (seat , player) in seats.into_iter().enumerate() {
+					SeatBox {
+						key: "{seat}",
+						seat,
+						player,
+						you,
+						to_act: hand.as_ref().and_then(|hand| hand.to_act),
+						button: hand.as_ref().map(|hand| hand.button),
+					}
+				}
+			}
+			if sitting_out {
+				div { class: "flex items-center gap-3 text-sm text-slate-400",
+					"You are sitting out."
+					button {
+						class: "px-3 py-1 bg-blue-500 rounded-lg hover:bg-blue-600 text-white",
+						onclick: move |_| table.send(ClientMessage::Join),
+						"Sit back in"
+					}
+				}
+			}
+			if legal != LegalActions::default() {
+				// Keyed so the raise amount starts over at the new minimum every turn.
+				ActionBar { key: "{version}", table, legal, big_blind: blinds.big }
+			}
+			if let Some(error) = error {
+				p { class: "text-sm text-red-400", "{error}" }
+			}
+			if !payouts.is_empty() {
+				section { class: "space-y-1 text-sm",
+					for payout in payouts {
+						p { key: "{payout.seat}",
+							span { class: "font-semibold", "{payout.username}" }
+							" wins {payout.amount}"
+							if let Some(hand) = payout.hand {
+								" with {hand}"
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn Board(hand: HandView) -> Element {
+	let street = match hand.street {
+		Street::Complete => "Hand over".to_owned(),
+		street => street.to_string(),
+	};
+
+	rsx! {
+		div { class: "flex flex-col items-center gap-3",
+			div { class: "flex gap-2 min-h-24",
+				for (index , card) in hand.board.into_iter().enumerate() {
+					CardFace { key: "{index}", card }
+				}
+			}
+			p { class: "text-sm text-slate-400",
+				"Hand #{hand.number}, {street}, pot {hand.pot}"
+				if let Some(deadline) = hand.deadline {
+					", "
+					Countdown { deadline }
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn SeatBox(seat: usize, player: Option<SeatView>, you: Option<usize>, to_act: Option<usize>, button: Option<usize>) -> Element {
+	let Some(player) = player else {
+		return rsx! {
+			div { class: "border border-dashed border-white/20 rounded-xl p-3 text-sm text-slate-500 min-h-24", "Empty seat" }
+		};
+	};
+	let border = if to_act == Some(seat) {
+		"border-cyan-400"
+	} else if you == Some(seat) {
+		"border-white/60"
+	} else {
+		"border-white/20"
+	};
+	let faded = if player.folded || player.sitting_out || !player.in_hand { "opacity-60" } else { "" };
+
+	rsx! {
+		div { class: "border {border} {faded} rounded-xl p-3 space-y-2 text-sm min-h-24",
+			div { class: "flex items-center gap-2",
+				span { class: "font-semibold truncate", "{player.username}" }
+				if button == Some(seat) {
+					span { class: "px-1.5 rounded-full bg-white text-black text-xs font-bold", "D" }
+				}
+				if !player.connected {
+					span { class: "text-xs text-yellow-400", "offline" }
+				}
+			}
+			p { class: "text-slate-400",
+				"{player.stack} chips"
+				if player.bet > 0 {
+					", bet {player.bet}"
+				}
+				if player.all_in {
+					", all in"
+				}
+				if player.folded {
+					", folded"
+				}
+				if player.sitting_out {
+					", sitting out"
+				}
+			}
+			if let Some(hole) = player.hole {
+				div { class: "flex gap-1",
+					for (index , card) in hole.into_iter().enumerate() {
+						CardFace { key: "{index}", card }
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn CardFace(card: CardView) -> Element {
+	match card {
+		CardView::Playing(Card { rank, suit }) => {
+			let (glyph, color) = match suit {
+				Suit::Clubs => ('♣', "text-black"),
+				Suit::Diamonds => ('♦', "text-red-600"),
+				Suit::Hearts => ('♥', "text-red-600"),
+				Suit::Spades => ('♠', "text-black"),
+			};
+			rsx! {
+				span { class: "inline-flex items-center justify-center w-10 h-14 rounded bg-white font-bold text-lg {color}", "{rank.symbol()}{glyph}" }
+			}
+		},
+		CardView::Meme(bet) => rsx! {
+			div { class: "w-20 rounded border border-white/20 overflow-hidden text-xs", title: "{bet.title}",
+				img { src: "{bet.image_url}", alt: "{bet.title}", loading: "lazy", class: "w-20 h-14 object-cover" }
+				p { class: "px-1", "{bet.window.low}-{bet.window.high}: {bet.percentile:.0}" }
+				p { class: "px-1 font-semibold", "{bet.returns()} pts" }
+			}
+		},
+	}
+}
+
+#[component]
+fn Countdown(deadline: i64) -> Element {
+	let mut now = use_signal(js_sys::Date::now);
+	use_future(move || async move {
+		loop {
+			gloo::timers::future::sleep(Duration::from_secs(1)).await;
+			now.set(js_sys::Date::now());
+		}
+	});
+	let seconds = ((deadline as f64 - now()) / 1000.0).max(0.0).ceil();
+
+	rsx! {
+		span { "{seconds}s to act" }
+	}
+}
+
+#[component]
+fn ActionBar(table: PokerTable, legal: LegalActions, big_blind: u64) -> Element {
+	let mut raise_to = use_signal(|| legal.raise.map_or(0, |(least, _)| least));
+
+	rsx! {
+		div { class: "flex flex-wrap items-center gap-2 border rounded-xl p-4",
+			button {
+				class: "px-4 py-2 border rounded-lg hover:bg-white/10",
+				onclick: move |_| table.send(ClientMessage::Act(Action::Fold)),
+				"Fold"
+			}
+			if legal.check {
+				button {
+					class: "px-4 py-2 border rounded-lg hover:bg-white/10",
+					onclick: move |_| table.send(ClientMessage::Act(Action::Check)),
+					"Check"
+				}
+			}
+			if let Some(call) = legal.call {
+				button {
+					class: "px-4 py-2 border rounded-lg hover:bg-white/10",
+					onclick: move |_| table.send(ClientMessage::Act(Action::Call)),
+					"Call {call}"
+				}
+			}
+			if let Some((least, most)) = legal.raise {
+				input {
+					r#type: "number",
+					min: least,
+					max: most,
+					step: big_blind,
+					value: "{raise_to}",
+					oninput: move |evt| raise_to.set(evt.value().parse().unwrap_or(least)),
+					class: "w-28 px-2 py-1 border rounded bg-transparent",
+				}
+				button {
+					class: "px-4 py-2 bg-blue-500 rounded-lg hover:bg-blue-600 disabled:opacity-50",
+					disabled: !(least..=most).contains(&raise_to()),
+					onclick: move |_| table.send(ClientMessage::Act(Action::RaiseTo(raise_to()))),
+					if legal.check {
+						"Bet to {raise_to}"
+					} else {
+						"Raise to {raise_to}"
+					}
+				}
+				button {
+					class: "px-4 py-2 bg-red-500 rounded-lg hover:bg-red-600",
+					onclick: move |_| table.send(ClientMessage::Act(Action::AllIn)),
+					"All in ({most})"
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,449 @@
+use crate::{
+	api::{
+		templates::list_templates,
+		tournaments::{
+			JudgingMode, LeaderboardEntry, MAX_BRIEF_LEN, MAX_SCORE, MAX_SPONSOR_LEN, MIN_SCORE, NewTournament, TournamentPhase, TournamentSummary,
+			create_tournament, finalize_tournament, get_tournament, list_tournaments, score_entry,
+		},
+	},
+	components::{local_time::LocalTime, markdown_lite::MarkdownLite},
+	router::Route,
+	stores::session::use_session,
+};
+use dioxus::prelude::*;
+use dioxus_primitives::toast::{ToastOptions, use_toast};
+use strum::IntoEnumIterator;
+use uuid::Uuid;
+
+/// Sponsored tournaments, newest first, and for moderators a form to set one up.
+#[component]
+pub fn Tournaments() -> Element {
+	let session = use_session();
+	let tournaments = use_resource(list_tournaments);
+	let moderator = session.user.read().as_ref().is_some_and(|user| user.moderator);
+
+	rsx! {
+		div { class: "max-w-3xl mx-auto p-6 space-y-6",
+			h1 { class: "text-3xl font-bold", "Meme Tournaments" }
+			p { class: "text-sm text-slate-400", "Make a meme for a sponsor's brief and win points. Every entry carries the sponsor's watermark." }
+			if moderator {
+				NewTournamentForm {}
+			}
+			{
+				match tournaments() {
+					None => rsx! {
+						p { class: "text-sm text-slate-400", "Loading..." }
+					},
+					Some(Err(e)) => rsx! {
+						p { class: "text-sm text-red-400", "{e}" }
+					},
+					Some(Ok(tournaments)) if tournaments.is_empty() => rsx! {
+						p { class: "text-sm text-slate-400", "No tournaments yet." }
+					},
+					Some(Ok(tournaments)) => rsx! {
+						div { class: "space-y-2",
+							for tournament in tournaments {
+								TournamentCard { key: "{tournament.id}", tournament }
+							}
+						}
+					},
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn TournamentCard(tournament: TournamentSummary) -> Element {
+	let pool = tournament.prizes.iter().sum::<i64>();
+
+	rsx! {
+		Link {
+			to: Route::TournamentPage { id: tournament.id.to_string() },
+			class: "block border rounded-lg p-3 hover:bg-white/5 space-y-1",
+			div { class: "flex items-baseline justify-between gap-2",
+				span { class: "font-semibold", "{tournament.title}" }
+				PhaseBadge { phase: tournament.phase }
+			}
+			p { class: "text-sm text-slate-400",
+				"by {tournament.sponsor}, {tournament.entries} entries, {pool} points in prizes, ends "
+				LocalTime { millis: tournament.ends_at }
+			}
+		}
+	}
+}
+
+#[component]
+fn PhaseBadge(phase: TournamentPhase) -> Element {
+	let color = match phase {
+		TournamentPhase::Upcoming => "text-slate-400",
+		TournamentPhase::Open => "text-green-400",
+		TournamentPhase::Judging => "text-yellow-400",
+		TournamentPhase::Finalized => "text-cyan-400",
+	};
+
+	rsx! {
+		span { class: "text-xs uppercase {color}", "{phase}" }
+	}
+}
+
+#[component]
+fn NewTournamentForm() -> Element {
+	let toast = use_toast();
+	let navigator = use_navigator();
+	let templates = use_resource(list_templates);
+	let mut sponsor = use_signal(String::new);
+	let mut title = use_signal(String::new);
+	let mut brief = use_signal(String::new);
+	let mut template_id = use_signal(|| None::<Uuid>);
+	let mut watermark_url = use_signal(String::new);
+	let mut starts = use_signal(String::new);
+	let mut ends = use_signal(String::new);
+	let mut judging = use_signal(JudgingMode::default);
+	let mut prizes = use_signal(String::new);
+	let mut judges = use_signal(String::new);
+	let mut creating = use_signal(|| false);
+
+	let create = move |_| async move {
+		let Ok(prizes) = prizes.read().split(',').map(str::trim).filter(|prize| !prize.is_empty()).map(str::parse).collect::<Result<Vec<i64>, _>>() else {
+			toast.error("Invalid prizes".to_owned(), ToastOptions::new().description("List the points for each place, e.g. 500, 250, 100".to_owned()));
+			return;
+		};
+		// `datetime-local` values are in the viewer's time zone, which is how `Date.parse` reads them.
+		let (starts_at, ends_at) = (js_sys::Date::parse(&starts.read()), js_sys::Date::parse(&ends.read()));
+		if starts_at.is_nan() || ends_at.is_nan() {
+			toast.error("Invalid dates".to_owned(), ToastOptions::new().description("Pick when the tournament starts and ends".to_owned()));
+			return;
+		}
+		let new = NewTournament {
+			sponsor: sponsor(),
+			title: title(),
+			brief: brief(),
+			template_id: template_id(),
+			watermark_url: watermark_url(),
+			starts_at: starts_at as i64,
+			ends_at: ends_at as i64,
+			judging: judging(),
+			prizes,
+			judges: judges.read().split(',').map(str::trim).filter(|judge| !judge.is_empty()).map(str::to_owned).collect(),
+		};
+		creating.set(true);
+		match create_tournament(new).await {
+			Ok(id) => {
+				navigator.push(Route::TournamentPage { id: id.to_string() });
+			},
+			Err(e) => toast.error("Could not create the tournament".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		creating.set(false);
+	};
+
+	rsx! {
+		details { class: "border rounded-xl p-4 text-sm",
+			summary { class: "cursor-pointer font-semibold", "New tournament" }
+			div { class: "space-y-3 pt-3",
+				input {
+					r#type: "text",
+					value: "{sponsor}",
+					maxlength: MAX_SPONSOR_LEN,
+					oninput: move |evt| sponsor.set(evt.value()),
+					placeholder: "Sponsor",
+					class: "w-full px-3 py-2 border rounded bg-transparent",
+				}
+				input {
+					r#type: "text",
+					value: "{title}",
+					oninput: move |evt| title.set(evt.value()),
+					placeholder: "Title",
+					class: "w-full px-3 py-2 border rounded bg-transparent",
+				}
+				textarea {
+					value: "{brief}",
+					maxlength: MAX_BRIEF_LEN,
+					oninput: move |evt| brief.set(evt.value()),
+					placeholder: "Brief: what the sponsor is looking for",
+					rows: 4,
+					class: "w-full px-3 py-2 border rounded bg-transparent",
+				}
+				input {
+					r#type: "url",
+					value: "{watermark_url}",
+					oninput: move |evt| watermark_url.set(evt.value()),
+					placeholder: "Watermark image URL",
+					class: "w-full px-3 py-2 border rounded bg-transparent",
+				}
+				div { class: "flex flex-wrap items-center gap-2",
+					"Template"
+					select {
+						class: "px-2 py-1 border rounded bg-black",
+						onchange: move |evt| template_id.set(evt.value().parse().ok()),
+						option { value: "", selected: template_id().is_none(), "Any" }
+						if let Some(Ok(templates)) = templates() {
+							for template in templates {
+								option {
+									key: "{template.id}",
+									value: "{template.id}",
+									selected: template_id() == Some(template.id),
+									"{template.name}"
+								}
+							}
+						}
+					}
+					"judged by"
+					select {
+						class: "px-2 py-1 border rounded bg-black",
+						onchange: move |evt| judging.set(evt.value().parse().unwrap_or_default()),
+						for mode in JudgingMode::iter() {
+							option { key: "{mode}", value: "{mode}", selected: judging() == mode, "{mode.label()}" }
+						}
+					}
+				}
+				div { class: "flex flex-wrap items-center gap-2",
+					"From"
+					input {
+						r#type: "datetime-local",
+						value: "{starts}",
+						oninput: move |evt| starts.set(evt.value()),
+						class: "px-2 py-1 border rounded bg-transparent",
+					}
+					"to"
+					input {
+						r#type: "datetime-local",
+						value: "{ends}",
+						oninput: move |evt| ends.set(evt.value()),
+						class: "px-2 py-1 border rounded bg-transparent",
+					}
+				}
+				input {
+					r#type: "text",
+					value: "{prizes}",
+					oninput: move |evt| prizes.set(evt.value()),
+					placeholder: "Prizes by place, e.g. 500, 250, 100",
+					class: "w-full px-3 py-2 border rounded bg-transparent",
+				}
+				if judging().uses_panel() {
+					input {
+						r#type: "text",
+						value: "{judges}",
+						oninput: move |evt| judges.set(evt.value()),
+						placeholder: "Judges' usernames, e.g. alice, bob",
+						class: "w-full px-3 py-2 border rounded bg-transparent",
+					}
+				}
+				button {
+					class: "px-4 py-2 bg-blue-500 rounded-lg hover:bg-blue-600 disabled:opacity-50",
+					disabled: creating(),
+					onclick: create,
+					"Create tournament"
+				}
+			}
+		}
+	}
+}
+
+#[component]
+pub fn TournamentPage(id: String) -> Element {
+	match id.parse::<Uuid>() {
+		Ok(id) => rsx! {
+			TournamentDetails { key: "{id}", id }
+		},
+		Err(_) => rsx! {
+			p { class: "max-w-3xl mx-auto p-6 text-red-400", "tournament not found" }
+		},
+	}
+}
+
+#[component]
+fn TournamentDetails(id: Uuid) -> Element {
+	let toast = use_toast();
+	let session = use_session();
+	let mut tournament = use_resource(move || get_tournament(id));
+	let mut finalizing = use_signal(|| false);
+	let moderator = session.user.read().as_ref().is_some_and(|user| user.moderator);
+
+	let finalize = move |_| async move {
+		finalizing.set(true);
+		match finalize_tournament(id).await {
+			Ok(()) => tournament.restart(),
+			Err(e) => toast.error("Could not finalize".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+		finalizing.set(false);
+	};
+
+	let detail = match tournament() {
+		None => {
+			return rsx! {
+				p { class: "max-w-3xl mx-auto p-6 text-sm text-slate-400", "Loading..." }
+			};
+		},
+		Some(Err(e)) => {
+			return rsx! {
+				p { class: "max-w-3xl mx-auto p-6 text-sm text-red-400", "{e}" }
+			};
+		},
+		Some(Ok(detail)) => detail,
+	};
+	let summary = detail.summary.clone();
+	let phase = summary.phase;
+	let can_enter = phase == TournamentPhase::Open && session.is_logged_in() && !detail.you_judge && !detail.you_entered;
+	let can_score = detail.you_judge && phase != TournamentPhase::Finalized;
+	let judges = detail.judges.join(", ");
+
+	rsx! {
+		div { class: "max-w-3xl mx-auto p-6 space-y-6",
+			div { class: "space-y-1",
+				div { class: "flex items-baseline justify-between gap-2",
+					h1 { class: "text-3xl font-bold", "{summary.title}" }
+					PhaseBadge { phase }
+				}
+				p { class: "text-slate-400",
+					"Sponsored by {summary.sponsor}, "
+					LocalTime { millis: summary.starts_at }
+					" to "
+					LocalTime { millis: summary.ends_at }
+				}
+			}
+			div { class: "text-slate-200 whitespace-pre-wrap",
+				MarkdownLite { text: detail.brief.clone() }
+			}
+			div { class: "grid grid-cols-1 sm:grid-cols-2 gap-4 border rounded-xl p-4 text-sm",
+				div { class: "space-y-2",
+					p {
+						span { class: "text-slate-400", "Judged by " }
+						"{summary.judging.label()}"
+					}
+					if !judges.is_empty() {
+						p {
+							span { class: "text-slate-400", "Panel: " }
+							"{judges}"
+						}
+					}
+					if summary.prizes.is_empty() {
+						p { class: "text-slate-400", "No prizes" }
+					} else {
+						ol { class: "list-decimal list-inside",
+							for (place , prize) in summary.prizes.iter().enumerate() {
+								li { key: "{place}", "{prize} points" }
+							}
+						}
+					}
+				}
+				div { class: "flex gap-3 items-start",
+					if let Some(template) = &detail.template {
+						div { class: "space-y-1",
+							img { src: "{template.image_url}", alt: "{template.name}", class: "w-24 h-24 object-cover rounded" }
+							p { class: "text-xs text-slate-400", "Template: {template.name}" }
+						}
+					}
+					div { class: "space-y-1",
+						img { src: "{detail.watermark_url}", alt: "Sponsor watermark", class: "w-24 h-24 object-contain rounded bg-white/5" }
+						p { class: "text-xs text-slate-400", "Watermark" }
+					}
+				}
+			}
+			div { class: "flex flex-wrap gap-3",
+				if can_enter {
+					Link {
+						to: Route::Generator {
+							m: String::new(),
+							id: String::new(),
+							remix: String::new(),
+							tournament: id.to_string(),
+						},
+						class: "px-4 py-2 bg-blue-500 rounded-lg hover:bg-blue-600",
+						"Make an entry"
+					}
+				}
+				if detail.you_entered {
+					span { class: "text-sm text-slate-400", "You have entered this tournament." }
+				}
+				if moderator && phase == TournamentPhase::Judging {
+					button {
+						class: "px-4 py-2 bg-cyan-600 rounded-lg hover:bg-cyan-700 disabled:opacity-50",
+						disabled: finalizing(),
+						onclick: finalize,
+						"Finalize results"
+					}
+				}
+			}
+			section { class: "space-y-2",
+				h2 { class: "text-xl font-semibold",
+					if phase == TournamentPhase::Finalized {
+						"Results"
+					} else {
+						"Leaderboard"
+					}
+				}
+				if detail.leaderboard.is_empty() {
+					p { class: "text-sm text-slate-400", "No entries yet." }
+				}
+				for entry in detail.leaderboard {
+					LeaderboardRow {
+						key: "{entry.meme_id}",
+						entry,
+						show_panel: summary.judging.uses_panel(),
+						can_score,
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn LeaderboardRow(entry: LeaderboardEntry, show_panel: bool, can_score: bool) -> Element {
+	let LeaderboardEntry { meme_id, title, image_url, author, votes, panel, my_score, place, prize } = entry;
+	let panel = show_panel.then(|| panel.map_or_else(|| "not scored yet".to_owned(), |panel| format!("panel {panel:.1}")));
+
+	rsx! {
+		div { class: "flex items-center gap-3 border rounded-lg p-2 text-sm",
+			span { class: "w-8 text-center text-lg font-bold text-slate-300", "{place}" }
+			Link { to: Route::MemePost { id: meme_id.to_string() },
+				img { src: "{image_url}", alt: "{title}", loading: "lazy", class: "w-12 h-12 object-cover rounded" }
+			}
+			div { class: "flex-1 min-w-0",
+				Link { to: Route::MemePost { id: meme_id.to_string() }, class: "block truncate hover:underline", "{title}" }
+				p { class: "text-xs text-slate-400",
+					if let Some(author) = author {
+						"by {author}, "
+					}
+					"{votes} votes"
+					if let Some(panel) = panel {
+						", {panel}"
+					}
+				}
+			}
+			if can_score {
+				ScoreSelect { meme_id, score: my_score }
+			}
+			if let Some(prize) = prize.filter(|prize| *prize > 0) {
+				span { class: "text-green-400", "+{prize}" }
+			}
+		}
+	}
+}
+
+/// A judge's score for one entry, saved as soon as it is picked.
+#[component]
+fn ScoreSelect(meme_id: Uuid, score: Option<i16>) -> Element {
+	let toast = use_toast();
+	let mut score = use_signal(|| score);
+
+	let save = move |evt: Event<FormData>| async move {
+		let Ok(value) = evt.value().parse::<i16>() else {
+			return;
+		};
+		match score_entry(meme_id, value).await {
+			Ok(()) => score.set(Some(value)),
+			Err(e) => toast.error("Could not save score".to_owned(), ToastOptions::new().description(e.to_string())),
+		}
+	};
+
+	rsx! {
+		select { class: "px-2 py-1 border rounded bg-black", onchange: save,
+			option { value: "", selected: score().is_none(), disabled: true, "Score" }
+			for value in MIN_SCORE..=MAX_SCORE {
+				option { key: "{value}", value: "{value}", selected: score() == Some(value), "{value}" }
+			}
+		}
+	}
+}
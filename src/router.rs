@@ -1,7 +1,22 @@
 use {
 	crate::{
+		components::require_auth::RequireAuth,
 		layout::Layout,
-		pages::{generator::Generator, home::Home},
+		pages::{
+			feed::Feed,
+			game::Game,
+			generator::Generator,
+			home::Home,
+			login::Login,
+			meme_post::MemePost,
+			moderation::Moderation,
+			points::Points,
+			profile::Profile,
+			register::Register,
+			search::Search,
+			tables::{LiveTable, Tables},
+			tournaments::{TournamentPage, Tournaments},
+		},
 	},
 	dioxus::prelude::*,
 };
@@ -12,6 +27,33 @@ pub enum Route {
 	#[layout(Layout)]
     #[route("/")]
     Home {},
-    #[route("/generator")]
-    Generator {},
+    #[route("/generator?:m&:id&:remix&:tournament")]
+    Generator { m: String, id: String, remix: String, tournament: String },
+    #[route("/feed?:sort")]
+    Feed { sort: String },
+    #[route("/m/:id")]
+    MemePost { id: String },
+    #[route("/search?:q")]
+    Search { q: String },
+    #[route("/login?:redirect")]
+    Login { redirect: String },
+    #[route("/register")]
+    Register {},
+    #[route("/tournaments")]
+    Tournaments {},
+    #[route("/tournaments/:id")]
+    TournamentPage { id: String },
+    #[layout(RequireAuth)]
+      #[route("/profile")]
+      Profile {},
+      #[route("/mod")]
+      Moderation {},
+      #[route("/game")]
+      Game {},
+      #[route("/points")]
+      Points {},
+      #[route("/tables")]
+      Tables {},
+      #[route("/tables/:id")]
+      LiveTable { id: String },
 }
@@ -0,0 +1,177 @@
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+use dioxus::{
+	fullstack::{FullstackContext, HeaderMap, HttpError, OrHttpError, http::header},
+	prelude::ServerFnError,
+};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+	api::auth::UserInfo,
+	server::db::{models::User, repo, with_conn},
+};
+
+pub const SESSION_COOKIE: &str = "memetopia_session";
+const SESSION_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// Random id identifying an anonymous browser, so views can be deduplicated without an account.
+pub const VISITOR_COOKIE: &str = "memetopia_visitor";
+const VISITOR_TTL_SECS: i64 = 365 * 24 * 60 * 60;
+/// Starts every API key, so leaked keys are easy to recognize.
+const API_KEY_PREFIX: &str = "mtk_";
+
+/// Signing key from `JWT_SECRET`. Without one every restart logs everybody out, which is fine locally.
+static JWT_SECRET: LazyLock<Vec<u8>> = LazyLock::new(|| std::env::var("JWT_SECRET").map_or_else(|_| Uuid::new_v4().as_bytes().repeat(2), String::into_bytes));
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+	sub: Uuid,
+	name: String,
+	/// Missing from tokens issued before roles existed.
+	#[serde(default)]
+	moderator: bool,
+	exp: i64,
+}
+
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+	let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes()).map_err(|e| anyhow::anyhow!("cannot create salt: {e}"))?;
+	Ok(Argon2::default().hash_password(password.as_bytes(), &salt).map_err(|e| anyhow::anyhow!("cannot hash password: {e}"))?.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+	PasswordHash::new(password_hash).is_ok_and(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+pub fn issue_token(user: &UserInfo) -> anyhow::Result<String> {
+	let claims = Claims { sub: user.id, name: user.username.clone(), moderator: user.moderator, exp: chrono::Utc::now().timestamp() + SESSION_TTL_SECS };
+	jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(&JWT_SECRET)).context("cannot sign session token")
+}
+
+pub fn decode_token(token: &str) -> Option<UserInfo> {
+	let claims = jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(&JWT_SECRET), &Validation::default()).ok()?.claims;
+	Some(UserInfo { id: claims.sub, username: claims.name, moderator: claims.moderator })
+}
+
+fn cookie_attributes() -> &'static str {
+	if env!("ENV").eq_ignore_ascii_case("local") { "HttpOnly; SameSite=Lax; Path=/" } else { "HttpOnly; SameSite=Lax; Path=/; Secure" }
+}
+
+/// Sets the session cookie on the response of the running server function.
+pub fn start_session(user: &UserInfo) -> anyhow::Result<()> {
+	let cookie = format!("{SESSION_COOKIE}={}; Max-Age={SESSION_TTL_SECS}; {}", issue_token(user)?, cookie_attributes());
+	let ctx = FullstackContext::current().context("no request context")?;
+	ctx.add_response_header(header::SET_COOKIE, cookie.parse::<header::HeaderValue>()?);
+	Ok(())
+}
+
+pub fn end_session() -> anyhow::Result<()> {
+	let cookie = format!("{SESSION_COOKIE}=; Max-Age=0; {}", cookie_attributes());
+	let ctx = FullstackContext::current().context("no request context")?;
+	ctx.add_response_header(header::SET_COOKIE, cookie.parse::<header::HeaderValue>()?);
+	Ok(())
+}
+
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+	headers
+		.get_all(header::COOKIE)
+		.iter()
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(|cookies| cookies.split(';'))
+		.find_map(|cookie| cookie.trim().strip_prefix(name)?.strip_prefix('='))
+}
+
+pub fn session_from_headers(headers: &HeaderMap) -> Option<UserInfo> {
+	cookie(headers, SESSION_COOKIE).and_then(decode_token)
+}
+
+/// The user behind the current server function call, if the request carries a valid session.
+pub async fn current_user() -> Option<UserInfo> {
+	let headers = FullstackContext::extract::<HeaderMap, _>().await.ok()?;
+	session_from_headers(&headers)
+}
+
+/// Like [`current_user`] but fails with 401, for server functions that need an account.
+pub async fn require_user() -> Result<UserInfo, HttpError> {
+	current_user().await.ok_or_else(|| HttpError::new(dioxus::fullstack::StatusCode::UNAUTHORIZED, "login required"))
+}
+
+/// The caller's account, failing with 403 when it is banned. Bans and role changes apply immediately because the account is
+/// read from the database instead of trusting the session token.
+async fn active_account() -> Result<User, ServerFnError> {
+	let user = require_user().await?;
+	let account = with_conn(move |conn| Ok(repo::users::find(conn, user.id)?)).await?.or_unauthorized("account no longer exists")?;
+	account.banned_at.is_none().or_forbidden("this account is banned")?;
+	Ok(account)
+}
+
+/// Like [`require_user`] but also rejects banned accounts, for server functions that create content.
+pub async fn require_active_user() -> Result<UserInfo, ServerFnError> {
+	let account = active_account().await?;
+	Ok(UserInfo { id: account.id, moderator: account.is_moderator(), username: account.username })
+}
+
+pub async fn require_moderator() -> Result<UserInfo, ServerFnError> {
+	let user = require_active_user().await?;
+	user.moderator.or_forbidden("moderators only")?;
+	Ok(user)
+}
+
+/// A new API key and the hash to store for it. The key itself is only ever shown to its owner, once.
+pub fn generate_api_key() -> (String, String) {
+	let key = format!("{API_KEY_PREFIX}{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+	let hash = hash_api_key(&key);
+	(key, hash)
+}
+
+/// Keys are long and random, so a plain sha256 is enough to keep them out of the database.
+pub fn hash_api_key(key: &str) -> String {
+	hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// The id of the unrevoked key in `Authorization: Bearer <key>` and the account owning it, for the HTTP API. `None` when there is no
+/// such key.
+pub async fn api_key_account(headers: &HeaderMap) -> anyhow::Result<Option<(Uuid, User)>> {
+	let Some(key) = headers.get(header::AUTHORIZATION).and_then(|value| value.to_str().ok()).and_then(|value| value.strip_prefix("Bearer ")) else {
+		return Ok(None);
+	};
+	let key_hash = hash_api_key(key.trim());
+	with_conn(move |conn| {
+		let Some(key) = repo::api_keys::find_active(conn, &key_hash)? else {
+			return Ok(None);
+		};
+		repo::api_keys::touch(conn, key.id)?;
+		Ok(repo::users::find(conn, key.user_id)?.map(|account| (key.id, account)))
+	})
+	.await
+}
+
+/// Gives the moderator role to the comma separated usernames in `MODERATORS`. Runs on startup, so accounts registered later are
+/// promoted on the next restart.
+pub async fn promote_configured_moderators() -> anyhow::Result<()> {
+	let usernames =
+		std::env::var("MODERATORS").unwrap_or_default().split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_owned).collect::<Vec<_>>();
+	if !usernames.is_empty() {
+		with_conn(move |conn| Ok(repo::users::promote_moderators(conn, &usernames)?)).await?;
+	}
+	Ok(())
+}
+
+/// Who is looking, for deduplication: the user when logged in, otherwise the visitor cookie, which is issued on first use.
+pub async fn viewer_key() -> anyhow::Result<String> {
+	let headers = FullstackContext::extract::<HeaderMap, _>().await.unwrap_or_default();
+	if let Some(user) = session_from_headers(&headers) {
+		return Ok(format!("user:{}", user.id));
+	}
+	if let Some(visitor) = cookie(&headers, VISITOR_COOKIE).and_then(|value| value.parse::<Uuid>().ok()) {
+		return Ok(format!("visitor:{visitor}"));
+	}
+	let visitor = Uuid::new_v4();
+	let cookie = format!("{VISITOR_COOKIE}={visitor}; Max-Age={VISITOR_TTL_SECS}; {}", cookie_attributes());
+	let ctx = FullstackContext::current().context("no request context")?;
+	ctx.add_response_header(header::SET_COOKIE, cookie.parse::<header::HeaderValue>()?);
+	Ok(format!("visitor:{visitor}"))
+}
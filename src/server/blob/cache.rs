@@ -0,0 +1,138 @@
+//! Blobs kept only while they are used: images copied by the image proxy and renders cached for the HTTP API. Each use is recorded
+//! in `cached_blobs`, and a sweeper deletes the blobs nobody used for [`PROXIED_TTL`] or [`RENDER_CACHE_TTL`]. A blob a saved meme,
+//! template or tournament points at is kept for good instead.
+//!
+//! Tracking needs postgres, without it nothing is recorded or swept.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::Connection;
+use dioxus::logger::tracing::{error, info, warn};
+use tokio::runtime::Handle;
+
+use super::{BLOB_STORE, BlobKind, Sweep, content_key, validate_image};
+use crate::server::db::{pool, repo, with_conn};
+
+/// How long a proxied image is kept after it was last copied. Longer than any in-memory cache of its url.
+pub const PROXIED_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// How long a cached render is kept after it was last asked for.
+pub const RENDER_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const SWEEP_BATCH: i64 = 100;
+
+/// Like [`super::store_image`], recording the use first so a sweep running meanwhile either waits for it or is waited for.
+pub async fn store_image(kind: BlobKind, bytes: Vec<u8>) -> anyhow::Result<String> {
+	let mime = validate_image(&bytes, None)?;
+	let key = content_key(kind, &bytes, mime);
+	touch(kind, &key).await?;
+	if BLOB_STORE.head(&key).await?.is_none() {
+		BLOB_STORE.put(&key, bytes, mime.content_type()).await?;
+	}
+	Ok(key)
+}
+
+/// Records a use of the blob under `key`.
+pub async fn touch(kind: BlobKind, key: &str) -> anyhow::Result<()> {
+	if pool().is_err() {
+		return Ok(());
+	}
+	let key = key.to_owned();
+	with_conn(move |conn| Ok(repo::cached_blobs::touch(conn, &key, kind.prefix(), Utc::now())?)).await
+}
+
+/// Deletes the blobs of `kind` unused for `ttl` that nothing points at, and stops tracking the ones something does.
+pub async fn sweep(kind: BlobKind, ttl: Duration) -> anyhow::Result<Sweep> {
+	let before = Utc::now() - ttl;
+	let url_prefix = BLOB_STORE.public_url("");
+	let handle = Handle::current();
+	let mut total = Sweep::default();
+	loop {
+		let (url_prefix, handle) = (url_prefix.clone(), handle.clone());
+		let (sweep, batch) = with_conn(move |conn| {
+			conn.transaction(|conn| {
+				let stale = repo::cached_blobs::lock_stale(conn, kind.prefix(), before, SWEEP_BATCH)?;
+				let mut sweep = Sweep::default();
+				for key in &stale {
+					if !repo::cached_blobs::is_referenced(conn, &format!("{url_prefix}{key}"))? {
+						// The row stays locked until the blob is gone, so a use recorded meanwhile waits and then stores the blob again.
+						if let Err(e) = handle.block_on(BLOB_STORE.delete(key)) {
+							warn!("cannot delete cached blob {key}: {e:#}");
+							sweep.failed += 1;
+							continue;
+						}
+						sweep.removed += 1;
+					}
+					repo::cached_blobs::delete(conn, key)?;
+				}
+				anyhow::Ok((sweep, stale.len()))
+			})
+		})
+		.await?;
+		total.removed += sweep.removed;
+		total.failed += sweep.failed;
+		// Failed blobs stay the oldest rows, so another batch would only pick them up again.
+		if sweep.failed > 0 || batch < SWEEP_BATCH as usize {
+			return Ok(total);
+		}
+	}
+}
+
+/// Runs [`sweep`] for proxied images and cached renders in the background for the lifetime of the server. Does nothing without
+/// postgres.
+pub fn spawn_sweeper() {
+	if pool().is_err() {
+		return;
+	}
+	tokio::spawn(async {
+		let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+		loop {
+			interval.tick().await;
+			for (kind, ttl, what) in [(BlobKind::Proxied, PROXIED_TTL, "proxied images"), (BlobKind::RenderCache, RENDER_CACHE_TTL, "cached renders")] {
+				match sweep(kind, ttl).await {
+					Ok(Sweep { removed: 0, failed: 0 }) => {},
+					Ok(Sweep { removed, failed }) => info!("swept {removed} unused {what}, {failed} could not be deleted"),
+					Err(e) => error!("sweep of {what} failed: {e:#}"),
+				}
+			}
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::server::db::{init_test_pool, repo::templates::NewTemplate};
+
+	fn png(seed: u8) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		image::RgbImage::from_pixel(2, 2, image::Rgb([seed, 0, 0])).write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png).unwrap();
+		bytes
+	}
+
+	#[tokio::test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	async fn sweeps_unused_blobs_and_keeps_referenced_ones() {
+		init_test_pool();
+		let unused = store_image(BlobKind::Proxied, png(1)).await.unwrap();
+		let referenced = store_image(BlobKind::Proxied, png(2)).await.unwrap();
+		let url = BLOB_STORE.public_url(&referenced);
+		with_conn(move |conn| {
+			let template = NewTemplate { name: "cached", image_url: &url, width: 2, height: 2, tags: &[], dhash: None, created_by: None };
+			Ok(repo::templates::create(conn, &template)?)
+		})
+		.await
+		.unwrap();
+
+		// Nothing is stale yet.
+		sweep(BlobKind::Proxied, PROXIED_TTL).await.unwrap();
+		assert!(BLOB_STORE.head(&unused).await.unwrap().is_some());
+
+		sweep(BlobKind::Proxied, Duration::ZERO).await.unwrap();
+		assert!(BLOB_STORE.head(&unused).await.unwrap().is_none());
+		assert!(BLOB_STORE.head(&referenced).await.unwrap().is_some());
+		let keys = [unused, referenced];
+		let tracked = with_conn(move |conn| Ok(keys.iter().map(|key| repo::cached_blobs::delete(conn, key)).collect::<Result<Vec<_>, _>>()?)).await.unwrap();
+		assert_eq!(tracked, [false, false]);
+	}
+}
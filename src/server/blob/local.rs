@@ -0,0 +1,114 @@
+use std::{io::ErrorKind, path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{BlobMeta, BlobStore, validate_key};
+
+/// Stores blobs as files below a root directory, for local development and single box deployments.
+pub struct LocalBlobStore {
+	root: PathBuf,
+}
+
+impl LocalBlobStore {
+	pub fn new(root: impl Into<PathBuf>) -> Self {
+		Self { root: root.into() }
+	}
+
+	fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+		validate_key(key)?;
+		Ok(self.root.join(key))
+	}
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+	async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> anyhow::Result<()> {
+		let path = self.path(key)?;
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent).await?;
+		}
+		// Write then rename so readers never observe a half written file. Each write has its own partial file, so concurrent puts of one
+		// key cannot interleave their bytes, the last rename wins whole.
+		let mut partial = path.clone().into_os_string();
+		partial.push(format!(".{}.partial", Uuid::new_v4()));
+		if let Err(e) = tokio::fs::write(&partial, bytes).await {
+			tokio::fs::remove_file(&partial).await.ok();
+			return Err(e.into());
+		}
+		tokio::fs::rename(&partial, &path).await?;
+		Ok(())
+	}
+
+	async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+		match tokio::fs::read(self.path(key)?).await {
+			Ok(bytes) => Ok(Some(bytes)),
+			Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn head(&self, key: &str) -> anyhow::Result<Option<BlobMeta>> {
+		match tokio::fs::metadata(self.path(key)?).await {
+			Ok(metadata) => Ok(Some(BlobMeta { size: metadata.len() })),
+			Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn delete(&self, key: &str) -> anyhow::Result<()> {
+		match tokio::fs::remove_file(self.path(key)?).await {
+			Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Uploads go through the server's own `PUT /blobs/{key}`, which only accepts keys with a live pending upload.
+	async fn presign_put(&self, key: &str, _expires_in: Duration) -> anyhow::Result<String> {
+		validate_key(key)?;
+		Ok(self.public_url(key))
+	}
+
+	fn public_url(&self, key: &str) -> String {
+		format!("{}/blobs/{key}", env!("SERVER_URL").trim_end_matches('/'))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A fresh root below `target`, which cargo runs tests from and git ignores.
+	fn scratch_root() -> PathBuf {
+		PathBuf::from(format!("target/test-blobs/{}", Uuid::new_v4()))
+	}
+
+	#[tokio::test]
+	async fn concurrent_puts_of_one_key_leave_one_whole_blob() {
+		let root = scratch_root();
+		let store = std::sync::Arc::new(LocalBlobStore::new(&root));
+		let key = "templates/ab/same.png";
+		let mut puts = tokio::task::JoinSet::new();
+		for i in 0..8u8 {
+			let store = store.clone();
+			puts.spawn(async move { store.put(key, vec![i; 64 * 1024], "image/png").await });
+		}
+		for result in puts.join_all().await {
+			result.unwrap();
+		}
+		let bytes = store.get(key).await.unwrap().unwrap();
+		assert_eq!(bytes.len(), 64 * 1024);
+		assert!(bytes.iter().all(|&byte| byte == bytes[0]), "bytes of different puts were mixed");
+		let files = std::fs::read_dir(root.join("templates/ab")).unwrap().count();
+		assert_eq!(files, 1, "partial files were left behind");
+		std::fs::remove_dir_all(root).unwrap();
+	}
+
+	#[tokio::test]
+	async fn keys_outside_the_root_are_refused() {
+		let store = LocalBlobStore::new(scratch_root());
+		assert!(store.put("../escape.png", vec![0], "image/png").await.is_err());
+		assert!(store.get("/etc/passwd").await.is_err());
+		assert!(store.delete("templates/../../escape.png").await.is_err());
+	}
+}
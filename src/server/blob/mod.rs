@@ -0,0 +1,285 @@
+//! Object storage for uploaded template images and rendered memes.
+//!
+//! Blobs are content addressed: the key is derived from the sha256 of the bytes, so uploading the same image twice stores it once.
+
+pub mod cache;
+mod local;
+mod s3;
+
+use std::{sync::LazyLock, time::Duration};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use sha2::{Digest, Sha256};
+
+pub use self::{local::LocalBlobStore, s3::S3BlobStore};
+
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlobMeta {
+	pub size: u64,
+}
+
+/// What a sweep over stored blobs did. Blobs that failed to delete are left for the next sweep.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sweep {
+	pub removed: usize,
+	pub failed: usize,
+}
+
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+	async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> anyhow::Result<()>;
+	async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+	async fn head(&self, key: &str) -> anyhow::Result<Option<BlobMeta>>;
+	async fn delete(&self, key: &str) -> anyhow::Result<()>;
+	/// Url a browser can `PUT` the object to directly, valid for `expires_in`.
+	async fn presign_put(&self, key: &str, expires_in: Duration) -> anyhow::Result<String>;
+	/// Url a browser can load the blob from.
+	fn public_url(&self, key: &str) -> String;
+}
+
+/// `BLOB_STORE=s3` uses an S3 compatible bucket (minio, R2) configured by the `S3_*` variables, anything else stores blobs under
+/// `BLOB_DIR` (default `blobs`) and serves them from `/blobs`.
+pub static BLOB_STORE: LazyLock<Box<dyn BlobStore>> = LazyLock::new(|| match std::env::var("BLOB_STORE").as_deref() {
+	Ok("s3") => Box::new(S3BlobStore::from_env().unwrap_or_else(|e| panic!("invalid s3 blob store config: {e:#}"))),
+	_ => Box::new(LocalBlobStore::new(std::env::var("BLOB_DIR").unwrap_or_else(|_| "blobs".to_owned()))),
+});
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlobKind {
+	Template,
+	Render,
+	/// Unconfirmed direct uploads, moved to [`BlobKind::Template`] once confirmed.
+	Upload,
+	/// Images copied from other sites by the image proxy.
+	Proxied,
+	/// Images rendered for the HTTP API, keyed by the hash of the request instead of the bytes.
+	RenderCache,
+}
+
+impl BlobKind {
+	fn prefix(self) -> &'static str {
+		match self {
+			Self::Template => "templates",
+			Self::Render => "renders",
+			Self::Upload => "uploads",
+			Self::Proxied => "proxied",
+			Self::RenderCache => "render-cache",
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageMime {
+	Png,
+	Jpeg,
+	Gif,
+	Webp,
+}
+
+impl ImageMime {
+	/// Detects the format from magic bytes, never trusting the declared content type.
+	pub fn sniff(bytes: &[u8]) -> Option<Self> {
+		match bytes {
+			[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(Self::Png),
+			[0xFF, 0xD8, 0xFF, ..] => Some(Self::Jpeg),
+			[b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(Self::Gif),
+			[b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::Webp),
+			_ => None,
+		}
+	}
+
+	pub fn from_content_type(content_type: &str) -> Option<Self> {
+		match content_type.split(';').next()?.trim() {
+			"image/png" => Some(Self::Png),
+			"image/jpeg" | "image/jpg" => Some(Self::Jpeg),
+			"image/gif" => Some(Self::Gif),
+			"image/webp" => Some(Self::Webp),
+			_ => None,
+		}
+	}
+
+	pub fn content_type(self) -> &'static str {
+		match self {
+			Self::Png => "image/png",
+			Self::Jpeg => "image/jpeg",
+			Self::Gif => "image/gif",
+			Self::Webp => "image/webp",
+		}
+	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			Self::Png => "png",
+			Self::Jpeg => "jpg",
+			Self::Gif => "gif",
+			Self::Webp => "webp",
+		}
+	}
+}
+
+/// Checks size and format of an uploaded image, returning the sniffed type.
+pub fn validate_image(bytes: &[u8], declared: Option<&str>) -> anyhow::Result<ImageMime> {
+	anyhow::ensure!(!bytes.is_empty(), "image is empty");
+	anyhow::ensure!(bytes.len() <= MAX_IMAGE_BYTES, "image is larger than {} MiB", MAX_IMAGE_BYTES / 1024 / 1024);
+	let mime = ImageMime::sniff(bytes).context("unsupported image format, use png, jpeg, gif or webp")?;
+	if let Some(declared) = declared {
+		anyhow::ensure!(ImageMime::from_content_type(declared) == Some(mime), "declared type {declared} does not match the image contents");
+	}
+	Ok(mime)
+}
+
+/// `<kind>/<first two hex chars>/<sha256>.<ext>`, the shard directory keeps local listings small.
+pub fn content_key(kind: BlobKind, bytes: &[u8], mime: ImageMime) -> String {
+	digest_key(kind, &Sha256::digest(bytes), mime)
+}
+
+/// Like [`content_key`] for a digest of something other than the bytes stored.
+pub fn digest_key(kind: BlobKind, digest: &[u8], mime: ImageMime) -> String {
+	let hash = hex::encode(digest);
+	format!("{}/{}/{hash}.{}", kind.prefix(), &hash[..2], mime.extension())
+}
+
+/// Keys for direct uploads cannot be content addressed since the bytes are not known yet, the upload id stands in for the hash.
+pub fn upload_key(id: uuid::Uuid, mime: ImageMime) -> String {
+	format!("{}/{id}.{}", BlobKind::Upload.prefix(), mime.extension())
+}
+
+/// Decodes a `data:<mime>;base64,<payload>` url as produced by `canvas.toDataURL` or `FileReader.readAsDataURL`.
+pub fn decode_data_url(data_url: &str) -> anyhow::Result<(Vec<u8>, String)> {
+	let rest = data_url.strip_prefix("data:").context("not a data url")?;
+	let (header, payload) = rest.split_once(',').context("malformed data url")?;
+	let content_type = header.strip_suffix(";base64").context("data url must be base64 encoded")?;
+	anyhow::ensure!(payload.len() <= MAX_IMAGE_BYTES / 3 * 4 + 4, "image is larger than {} MiB", MAX_IMAGE_BYTES / 1024 / 1024);
+	Ok((STANDARD.decode(payload).context("invalid base64 in data url")?, content_type.to_owned()))
+}
+
+/// Keys are generated by [`content_key`], anything else (`..`, absolute paths, odd characters) is rejected before touching storage.
+pub fn validate_key(key: &str) -> anyhow::Result<()> {
+	anyhow::ensure!(
+		!key.is_empty()
+			&& key.len() <= 256
+			&& key.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+			&& key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '-' | '_')),
+		"invalid blob key"
+	);
+	Ok(())
+}
+
+/// The key of a blob from its [`BlobStore::public_url`], `None` for urls pointing elsewhere.
+pub fn key_from_url(url: &str) -> Option<&str> {
+	let key = url.strip_prefix(BLOB_STORE.public_url("").as_str())?;
+	validate_key(key).ok().map(|()| key)
+}
+
+/// Validates and stores an image, returning its key. Storing an image that already exists is a no-op.
+pub async fn store_image(kind: BlobKind, bytes: Vec<u8>, declared: Option<&str>) -> anyhow::Result<String> {
+	let mime = validate_image(&bytes, declared)?;
+	let key = content_key(kind, &bytes, mime);
+	if BLOB_STORE.head(&key).await?.is_none() {
+		BLOB_STORE.put(&key, bytes, mime.content_type()).await?;
+	}
+	Ok(key)
+}
+
+/// Width and height read from the image header.
+pub fn image_dimensions(bytes: &[u8]) -> anyhow::Result<(u32, u32)> {
+	Ok(image::ImageReader::new(std::io::Cursor::new(bytes)).with_guessed_format()?.into_dimensions()?)
+}
+
+/// Axum handler behind `/blobs/{*key}`, used when blobs are not served by the bucket itself.
+pub async fn serve_blob(dioxus::fullstack::extract::Path(key): dioxus::fullstack::extract::Path<String>) -> dioxus::fullstack::response::Response {
+	use dioxus::fullstack::{
+		StatusCode,
+		http::header,
+		response::{IntoResponse, Response},
+	};
+	let Some(mime) = key.rsplit_once('.').and_then(|(_, ext)| ImageMime::from_content_type(&format!("image/{ext}"))) else {
+		return StatusCode::NOT_FOUND.into_response();
+	};
+	match BLOB_STORE.get(&key).await {
+		Ok(Some(bytes)) => Response::builder()
+			.header(header::CONTENT_TYPE, mime.content_type())
+			.header(header::CACHE_CONTROL, "public, max-age=31536000, immutable")
+			.header("x-content-type-options", "nosniff")
+			.body(bytes.into())
+			.unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+		Ok(None) => StatusCode::NOT_FOUND.into_response(),
+		Err(e) => {
+			dioxus::logger::tracing::error!("cannot read blob {key}: {e:#}");
+			StatusCode::INTERNAL_SERVER_ERROR.into_response()
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn keys_cannot_leave_their_directory() {
+		for key in ["templates/ab/abcd.png", "render-cache/00/x_y.webp", "uploads/1b4e28ba-2fa1-11d2-883f-0016d3cca427.jpg"] {
+			assert!(validate_key(key).is_ok(), "{key}");
+		}
+		for key in [
+			"",
+			"..",
+			"../etc/passwd",
+			"templates/../../secret",
+			"templates/./x.png",
+			"/etc/passwd",
+			"templates//x.png",
+			"templates/",
+			"templates\\..\\x.png",
+			"templates/x.png?raw",
+			"templates/%2e%2e/x.png",
+			"templates/ü.png",
+		] {
+			assert!(validate_key(key).is_err(), "{key}");
+		}
+		assert!(validate_key(&"a".repeat(257)).is_err());
+	}
+
+	#[test]
+	fn sniffs_by_magic_bytes() {
+		assert_eq!(ImageMime::sniff(b"\x89PNG\r\n\x1a\n rest"), Some(ImageMime::Png));
+		assert_eq!(ImageMime::sniff(b"\xff\xd8\xff\xe0"), Some(ImageMime::Jpeg));
+		assert_eq!(ImageMime::sniff(b"GIF87a"), Some(ImageMime::Gif));
+		assert_eq!(ImageMime::sniff(b"GIF89a"), Some(ImageMime::Gif));
+		assert_eq!(ImageMime::sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(ImageMime::Webp));
+		assert_eq!(ImageMime::sniff(b"RIFF\0\0\0\0WAVEfmt "), None);
+		assert_eq!(ImageMime::sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), None);
+		assert_eq!(ImageMime::sniff(b"\x89PN"), None);
+		assert_eq!(ImageMime::sniff(b""), None);
+		assert!(validate_image(b"GIF89a", Some("image/png")).is_err(), "the declared type has to match");
+		assert_eq!(validate_image(b"GIF89a", Some("image/gif; charset=binary")).unwrap(), ImageMime::Gif);
+	}
+
+	#[test]
+	fn decodes_base64_data_urls_only() {
+		let (bytes, content_type) = decode_data_url("data:image/png;base64,iVBORw0KGgo=").unwrap();
+		assert_eq!(bytes, b"\x89PNG\r\n\x1a\n");
+		assert_eq!(content_type, "image/png");
+		assert!(decode_data_url("image/png;base64,iVBORw0KGgo=").is_err());
+		assert!(decode_data_url("data:image/png,iVBORw0KGgo=").is_err());
+		assert!(decode_data_url("data:image/png;base64").is_err());
+		assert!(decode_data_url("data:image/png;base64,not base64!").is_err());
+		let oversized = format!("data:image/png;base64,{}", "A".repeat(MAX_IMAGE_BYTES / 3 * 4 + 8));
+		assert!(decode_data_url(&oversized).is_err());
+	}
+
+	#[test]
+	fn content_keys_are_sharded_by_hash() {
+		let key = content_key(BlobKind::Template, b"meme", ImageMime::Png);
+		let hash = hex::encode(Sha256::digest(b"meme"));
+		assert_eq!(key, format!("templates/{}/{hash}.png", &hash[..2]));
+		assert_eq!(content_key(BlobKind::Template, b"meme", ImageMime::Png), key, "equal bytes share a key");
+		assert_ne!(content_key(BlobKind::Template, b"memes", ImageMime::Png), key);
+		assert!(content_key(BlobKind::Proxied, b"meme", ImageMime::Webp).starts_with("proxied/"));
+		assert!(validate_key(&key).is_ok());
+		assert!(validate_key(&upload_key(uuid::Uuid::new_v4(), ImageMime::Jpeg)).is_ok());
+	}
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use dioxus::fullstack::Method;
+use object_store::{
+	Attribute, Attributes, ObjectStore, PutOptions, PutPayload,
+	aws::{AmazonS3, AmazonS3Builder},
+	path::Path,
+	signer::Signer,
+};
+
+use super::{BlobMeta, BlobStore, validate_key};
+
+/// S3 compatible bucket. Works against minio locally and cloudflare R2 in production.
+pub struct S3BlobStore {
+	client: AmazonS3,
+	/// Base url objects are publicly readable from, e.g. the R2 public bucket domain.
+	public_base_url: String,
+}
+
+impl S3BlobStore {
+	/// Reads `S3_ENDPOINT`, `S3_BUCKET`, `S3_REGION`, `S3_ACCESS_KEY_ID`, `S3_SECRET_ACCESS_KEY` and `S3_PUBLIC_URL`.
+	pub fn from_env() -> anyhow::Result<Self> {
+		let var = |name: &str| std::env::var(name).with_context(|| format!("missing {name}"));
+		let endpoint = var("S3_ENDPOINT")?;
+		let bucket = var("S3_BUCKET")?;
+		let client = AmazonS3Builder::new()
+			.with_endpoint(&endpoint)
+			.with_bucket_name(&bucket)
+			.with_region(std::env::var("S3_REGION").unwrap_or_else(|_| "auto".to_owned()))
+			.with_access_key_id(var("S3_ACCESS_KEY_ID")?)
+			.with_secret_access_key(var("S3_SECRET_ACCESS_KEY")?)
+			.with_allow_http(endpoint.starts_with("http://"))
+			.with_virtual_hosted_style_request(false)
+			.build()?;
+		let public_base_url = std::env::var("S3_PUBLIC_URL").unwrap_or_else(|_| format!("{}/{bucket}", endpoint.trim_end_matches('/')));
+		Ok(Self { client, public_base_url: public_base_url.trim_end_matches('/').to_owned() })
+	}
+
+	fn path(key: &str) -> anyhow::Result<Path> {
+		validate_key(key)?;
+		Ok(Path::from(key))
+	}
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+	async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> anyhow::Result<()> {
+		let mut attributes = Attributes::new();
+		attributes.insert(Attribute::ContentType, content_type.to_owned().into());
+		self.client.put_opts(&Self::path(key)?, PutPayload::from(bytes), PutOptions { attributes, ..Default::default() }).await?;
+		Ok(())
+	}
+
+	async fn get(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+		match self.client.get(&Self::path(key)?).await {
+			Ok(result) => Ok(Some(result.bytes().await?.to_vec())),
+			Err(object_store::Error::NotFound { .. }) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn head(&self, key: &str) -> anyhow::Result<Option<BlobMeta>> {
+		match self.client.head(&Self::path(key)?).await {
+			Ok(meta) => Ok(Some(BlobMeta { size: meta.size })),
+			Err(object_store::Error::NotFound { .. }) => Ok(None),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn delete(&self, key: &str) -> anyhow::Result<()> {
+		match self.client.delete(&Self::path(key)?).await {
+			Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn presign_put(&self, key: &str, expires_in: Duration) -> anyhow::Result<String> {
+		Ok(self.client.signed_url(Method::PUT, &Self::path(key)?, expires_in).await?.to_string())
+	}
+
+	fn public_url(&self, key: &str) -> String {
+		format!("{}/{key}", self.public_base_url)
+	}
+}
@@ -0,0 +1,117 @@
+pub mod models;
+pub mod repo;
+pub mod schema;
+
+#[cfg(test)]
+use std::sync::Once;
+use std::sync::OnceLock;
+
+use anyhow::Context;
+#[cfg(test)]
+use diesel::{Connection, r2d2::TestCustomizer};
+use diesel::{
+	PgConnection,
+	r2d2::{ConnectionManager, Pool, PooledConnection},
+};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+
+pub type PgPool = Pool<ConnectionManager<PgConnection>>;
+pub type PgConn = PooledConnection<ConnectionManager<PgConnection>>;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+static POOL: OnceLock<PgPool> = OnceLock::new();
+
+/// Connects to `DATABASE_URL` when it points at postgres and brings the schema up to date. Called once on server startup.
+pub fn init() -> anyhow::Result<()> {
+	let Some(url) = std::env::var("DATABASE_URL").ok().filter(|url| url.starts_with("postgres")) else {
+		return Ok(());
+	};
+	let pool = Pool::builder().build(ConnectionManager::<PgConnection>::new(url)).context("cannot connect to postgres")?;
+	run_migrations(&mut *pool.get()?)?;
+	POOL.set(pool).ok();
+	Ok(())
+}
+
+pub fn run_migrations(conn: &mut PgConnection) -> anyhow::Result<()> {
+	conn.run_pending_migrations(MIGRATIONS).map_err(|e| anyhow::anyhow!("migrations failed: {e}"))?;
+	Ok(())
+}
+
+pub fn pool() -> anyhow::Result<&'static PgPool> {
+	POOL.get().context("postgres is not configured, set DATABASE_URL to a postgres:// url")
+}
+
+/// Runs blocking diesel work on the blocking thread pool so server functions do not stall the runtime.
+pub async fn with_conn<T, F>(f: F) -> anyhow::Result<T>
+where
+	T: Send + 'static,
+	F: FnOnce(&mut PgConnection) -> anyhow::Result<T> + Send + 'static,
+{
+	let pool = pool()?;
+	tokio::task::spawn_blocking(move || f(&mut *pool.get()?)).await?
+}
+
+/// A connection for database tests, in a transaction that is rolled back when it drops. The database tests are `#[ignore]`d, `just
+/// test-db` runs them against the postgres at `DATABASE_URL`.
+#[cfg(test)]
+pub fn test_conn() -> PgConnection {
+	static MIGRATED: Once = Once::new();
+	let mut conn = PgConnection::establish(&test_database_url()).expect("cannot connect to DATABASE_URL");
+	MIGRATED.call_once(|| run_migrations(&mut conn).expect("migrations failed"));
+	conn.begin_test_transaction().expect("cannot begin a test transaction");
+	conn
+}
+
+/// A pool of one connection in a transaction that is never committed, for code that takes the pool.
+#[cfg(test)]
+pub fn test_pool() -> &'static PgPool {
+	Box::leak(Box::new(new_test_pool()))
+}
+
+/// Points [`pool`], and so [`with_conn`], at a pool like [`test_pool`]'s shared by the whole test run, for code that goes through
+/// them.
+#[cfg(test)]
+pub fn init_test_pool() {
+	if POOL.get().is_none() {
+		// Another test may have won the race, either pool does.
+		POOL.set(new_test_pool()).ok();
+	}
+}
+
+#[cfg(test)]
+fn new_test_pool() -> PgPool {
+	// Brings the schema up to date.
+	drop(test_conn());
+	Pool::builder()
+		.max_size(1)
+		.connection_customizer(Box::new(TestCustomizer))
+		.build(ConnectionManager::new(test_database_url()))
+		.expect("cannot connect to DATABASE_URL")
+}
+
+#[cfg(test)]
+fn test_database_url() -> String {
+	std::env::var("DATABASE_URL").ok().filter(|url| url.starts_with("postgres")).expect("database tests need DATABASE_URL set to a postgres:// url")
+}
+
+#[cfg(test)]
+mod tests {
+	use diesel::{RunQueryDsl, migration::MigrationSource};
+
+	use super::*;
+
+	/// Every migration applies to an empty schema, reverts and applies again. The schema is dropped with the test transaction.
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn migrations_apply_revert_and_reapply() {
+		let mut conn = test_conn();
+		diesel::sql_query("CREATE SCHEMA migration_smoke").execute(&mut conn).unwrap();
+		diesel::sql_query("SET LOCAL search_path TO migration_smoke").execute(&mut conn).unwrap();
+		let count = MigrationSource::<diesel::pg::Pg>::migrations(&MIGRATIONS).unwrap().len();
+		assert_eq!(conn.run_pending_migrations(MIGRATIONS).unwrap().len(), count);
+		assert_eq!(conn.revert_all_migrations(MIGRATIONS).unwrap().len(), count);
+		assert!(conn.applied_migrations().unwrap().is_empty());
+		assert_eq!(conn.run_pending_migrations(MIGRATIONS).unwrap().len(), count);
+	}
+}
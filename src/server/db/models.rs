@@ -0,0 +1,355 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::schema::{
+	api_keys, audit_log, comments, game_bets, layers, ledger_accounts, ledger_entries, ledger_transactions, meme_records, meme_stats, memes, notifications,
+	pending_uploads, reports, templates, tournament_entries, tournaments, users, votes,
+};
+use crate::{
+	api::tournaments::TournamentPhase,
+	stores::text_box::{TextBox, TextBoxStyle},
+};
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = users, check_for_backend(diesel::pg::Pg))]
+pub struct User {
+	pub id: Uuid,
+	pub username: String,
+	pub password_hash: String,
+	pub created_at: DateTime<Utc>,
+	/// `user` or [`User::MODERATOR`].
+	pub role: String,
+	pub banned_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+	pub const MODERATOR: &str = "moderator";
+
+	pub fn is_moderator(&self) -> bool {
+		self.role == Self::MODERATOR
+	}
+}
+
+#[derive(Clone, Debug, Queryable, QueryableByName, Selectable, Insertable)]
+#[diesel(table_name = templates, check_for_backend(diesel::pg::Pg))]
+pub struct Template {
+	pub id: Uuid,
+	pub name: String,
+	pub image_url: String,
+	pub width: i32,
+	pub height: i32,
+	pub created_by: Option<Uuid>,
+	pub created_at: DateTime<Utc>,
+	pub tags: Vec<String>,
+	/// Perceptual hash of the image, see [`crate::server::image_hash`].
+	pub dhash: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = api_keys, check_for_backend(diesel::pg::Pg))]
+pub struct ApiKey {
+	pub id: Uuid,
+	pub user_id: Uuid,
+	pub name: String,
+	/// Hex sha256 of the key.
+	pub key_hash: String,
+	pub created_at: DateTime<Utc>,
+	pub last_used_at: Option<DateTime<Utc>>,
+	pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = pending_uploads, check_for_backend(diesel::pg::Pg))]
+pub struct PendingUpload {
+	pub id: Uuid,
+	pub user_id: Uuid,
+	pub name: String,
+	pub object_key: String,
+	pub content_type: String,
+	pub expires_at: DateTime<Utc>,
+	pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = memes, check_for_backend(diesel::pg::Pg))]
+pub struct Meme {
+	pub id: Uuid,
+	pub user_id: Option<Uuid>,
+	pub template_id: Option<Uuid>,
+	pub main_img_url: String,
+	pub width: i32,
+	pub height: i32,
+	pub created_at: DateTime<Utc>,
+	pub title: Option<String>,
+	/// Rendered image, set when the meme is published.
+	pub image_url: Option<String>,
+	pub published_at: Option<DateTime<Utc>>,
+	/// The post this meme was remixed from.
+	pub parent_id: Option<Uuid>,
+	/// Text of every layer, kept on the meme for search.
+	pub caption: String,
+	pub tags: Vec<String>,
+	pub hidden_at: Option<DateTime<Utc>>,
+	/// Perceptual hash of the rendered image, set on publish.
+	pub dhash: Option<i64>,
+	pub watermark_url: Option<String>,
+}
+
+impl Meme {
+	/// Published and not hidden by moderation, or published at all for moderators.
+	pub fn is_visible_post(&self, moderator: bool) -> bool {
+		self.published_at.is_some() && (self.hidden_at.is_none() || moderator)
+	}
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = comments, check_for_backend(diesel::pg::Pg))]
+pub struct Comment {
+	pub id: Uuid,
+	pub meme_id: Uuid,
+	pub parent_id: Option<Uuid>,
+	pub user_id: Option<Uuid>,
+	pub body: String,
+	pub created_at: DateTime<Utc>,
+	pub edited_at: Option<DateTime<Utc>>,
+	pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = notifications, check_for_backend(diesel::pg::Pg))]
+pub struct Notification {
+	pub id: Uuid,
+	pub user_id: Uuid,
+	pub actor_id: Option<Uuid>,
+	/// See `crate::api::notifications::NotificationKind`.
+	pub kind: String,
+	pub meme_id: Uuid,
+	pub comment_id: Option<Uuid>,
+	pub created_at: DateTime<Utc>,
+	pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = meme_stats, check_for_backend(diesel::pg::Pg))]
+pub struct MemeStats {
+	pub meme_id: Uuid,
+	pub upvotes: i32,
+	pub downvotes: i32,
+	pub views: i64,
+	pub hot: f64,
+	pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = layers, check_for_backend(diesel::pg::Pg))]
+pub struct Layer {
+	pub meme_id: Uuid,
+	pub position: i32,
+	pub text: String,
+	pub x: f64,
+	pub y: f64,
+	pub rotation: f64,
+	pub scale_x: f64,
+	pub scale_y: f64,
+	pub font_size: i32,
+	pub font_family: String,
+	pub font_effect: String,
+}
+
+impl Layer {
+	pub fn from_text_box(meme_id: Uuid, position: i32, text_box: &TextBox) -> Self {
+		Self {
+			meme_id,
+			position,
+			text: text_box.text.clone(),
+			x: text_box.x,
+			y: text_box.y,
+			rotation: text_box.rotation,
+			scale_x: text_box.scale_x,
+			scale_y: text_box.scale_y,
+			font_size: text_box.style.size as i32,
+			font_family: text_box.style.family.clone(),
+			font_effect: text_box.style.effect.clone(),
+		}
+	}
+}
+
+impl From<Layer> for TextBox {
+	fn from(layer: Layer) -> Self {
+		Self {
+			text: layer.text,
+			x: layer.x,
+			y: layer.y,
+			rotation: layer.rotation,
+			scale_x: layer.scale_x,
+			scale_y: layer.scale_y,
+			style: TextBoxStyle { size: layer.font_size.max(1) as u32, family: layer.font_family, effect: layer.font_effect },
+			is_selected: false,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = votes, check_for_backend(diesel::pg::Pg))]
+pub struct Vote {
+	pub user_id: Uuid,
+	pub meme_id: Uuid,
+	pub value: i16,
+	pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = reports, check_for_backend(diesel::pg::Pg))]
+pub struct Report {
+	pub id: Uuid,
+	pub meme_id: Uuid,
+	/// `None` when an automatic filter filed the report.
+	pub reporter_id: Option<Uuid>,
+	pub reason: String,
+	pub details: String,
+	pub created_at: DateTime<Utc>,
+	pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = audit_log, check_for_backend(diesel::pg::Pg))]
+pub struct AuditEntry {
+	pub id: Uuid,
+	pub actor_id: Option<Uuid>,
+	pub action: String,
+	pub meme_id: Option<Uuid>,
+	pub user_id: Option<Uuid>,
+	pub note: String,
+	pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Queryable, QueryableByName, Selectable, Insertable)]
+#[diesel(table_name = meme_records, check_for_backend(diesel::pg::Pg))]
+pub struct MemeRecord {
+	pub id: Uuid,
+	/// Where the meme was scraped or imported from, e.g. a subreddit. Percentiles only compare records of the same source.
+	pub source: String,
+	pub external_id: String,
+	pub title: String,
+	pub image_url: String,
+	pub posted_at: DateTime<Utc>,
+	pub score: i64,
+	pub comments: i64,
+	/// See [`crate::game::percentile::engagement`].
+	pub engagement: f64,
+	/// When `score` and `comments` were captured.
+	pub observed_at: DateTime<Utc>,
+	pub created_at: DateTime<Utc>,
+	/// Perceptual hash of the image, see [`crate::server::image_hash`].
+	pub dhash: Option<i64>,
+}
+
+impl MemeRecord {
+	/// How long after posting a record's engagement counts as final.
+	pub const SETTLE_AGE_HOURS: i32 = 24;
+
+	/// Whether the metrics were captured late enough to settle bets on.
+	pub fn is_settled(&self) -> bool {
+		self.observed_at >= self.posted_at + chrono::Duration::hours(i64::from(Self::SETTLE_AGE_HOURS))
+	}
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = game_bets, check_for_backend(diesel::pg::Pg))]
+pub struct GameBet {
+	pub id: Uuid,
+	pub user_id: Uuid,
+	pub record_id: Uuid,
+	pub window_low: i16,
+	pub window_high: i16,
+	pub stake: i64,
+	pub created_at: DateTime<Utc>,
+	pub settled_at: Option<DateTime<Utc>>,
+	pub percentile: Option<f64>,
+	/// 0 for a lost bet, `None` until settled.
+	pub payout: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = tournaments, check_for_backend(diesel::pg::Pg))]
+pub struct Tournament {
+	pub id: Uuid,
+	pub sponsor: String,
+	pub title: String,
+	pub brief: String,
+	/// The template every entry must be made on.
+	pub template_id: Option<Uuid>,
+	/// Brand watermark every entry must carry.
+	pub watermark_url: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	/// See `crate::api::tournaments::JudgingMode`.
+	pub judging: String,
+	/// Points paid to first place, second place and so on.
+	pub prizes: Vec<i64>,
+	pub created_by: Option<Uuid>,
+	pub created_at: DateTime<Utc>,
+	pub finalized_at: Option<DateTime<Utc>>,
+}
+
+impl Tournament {
+	pub fn phase(&self, now: DateTime<Utc>) -> TournamentPhase {
+		if self.finalized_at.is_some() {
+			TournamentPhase::Finalized
+		} else if now < self.starts_at {
+			TournamentPhase::Upcoming
+		} else if now < self.ends_at {
+			TournamentPhase::Open
+		} else {
+			TournamentPhase::Judging
+		}
+	}
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = tournament_entries, check_for_backend(diesel::pg::Pg))]
+pub struct TournamentEntry {
+	pub meme_id: Uuid,
+	pub tournament_id: Uuid,
+	pub user_id: Uuid,
+	pub created_at: DateTime<Utc>,
+	/// Final standing, set when the tournament is finalized.
+	pub place: Option<i32>,
+	/// Points won, 0 outside the prizes and `None` until finalized.
+	pub prize: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = ledger_accounts, check_for_backend(diesel::pg::Pg))]
+pub struct LedgerAccount {
+	pub id: Uuid,
+	/// See `crate::server::ledger::Account`.
+	pub kind: String,
+	pub user_id: Option<Uuid>,
+	pub scope: Option<Uuid>,
+	pub balance: i64,
+	pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = ledger_transactions, check_for_backend(diesel::pg::Pg))]
+pub struct LedgerTransaction {
+	pub id: Uuid,
+	pub idempotency_key: String,
+	/// See `crate::api::points::TransactionKind`.
+	pub kind: String,
+	pub memo: String,
+	pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Queryable, Selectable)]
+#[diesel(table_name = ledger_entries, check_for_backend(diesel::pg::Pg))]
+pub struct LedgerEntry {
+	pub id: i64,
+	pub transaction_id: Uuid,
+	pub account_id: Uuid,
+	/// Positive when points came in.
+	pub amount: i64,
+	pub balance_after: i64,
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::server::db::{models::ApiKey, schema::api_keys};
+
+pub fn create(conn: &mut PgConnection, key: &ApiKey) -> QueryResult<()> {
+	diesel::insert_into(api_keys::table).values(key).execute(conn)?;
+	Ok(())
+}
+
+/// The unrevoked key with that hash.
+pub fn find_active(conn: &mut PgConnection, key_hash: &str) -> QueryResult<Option<ApiKey>> {
+	api_keys::table.filter(api_keys::key_hash.eq(key_hash)).filter(api_keys::revoked_at.is_null()).select(ApiKey::as_select()).first(conn).optional()
+}
+
+/// The user's unrevoked keys, newest first.
+pub fn list_active(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<Vec<ApiKey>> {
+	api_keys::table
+		.filter(api_keys::user_id.eq(user_id))
+		.filter(api_keys::revoked_at.is_null())
+		.select(ApiKey::as_select())
+		.order(api_keys::created_at.desc())
+		.load(conn)
+}
+
+pub fn count_active(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<i64> {
+	api_keys::table.filter(api_keys::user_id.eq(user_id)).filter(api_keys::revoked_at.is_null()).count().get_result(conn)
+}
+
+/// Returns false when the user has no such unrevoked key.
+pub fn revoke(conn: &mut PgConnection, user_id: Uuid, id: Uuid) -> QueryResult<bool> {
+	let revoked = diesel::update(api_keys::table.find(id))
+		.filter(api_keys::user_id.eq(user_id))
+		.filter(api_keys::revoked_at.is_null())
+		.set(api_keys::revoked_at.eq(Utc::now()))
+		.execute(conn)?;
+	Ok(revoked > 0)
+}
+
+pub fn touch(conn: &mut PgConnection, id: Uuid) -> QueryResult<()> {
+	diesel::update(api_keys::table.find(id)).set(api_keys::last_used_at.eq(Utc::now())).execute(conn)?;
+	Ok(())
+}
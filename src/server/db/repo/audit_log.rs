@@ -0,0 +1,31 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::AuditEntry,
+	schema::{audit_log, users},
+};
+
+pub fn record(
+	conn: &mut PgConnection,
+	actor_id: Option<Uuid>,
+	action: &str,
+	meme_id: Option<Uuid>,
+	user_id: Option<Uuid>,
+	note: &str,
+) -> QueryResult<AuditEntry> {
+	let entry = AuditEntry { id: Uuid::new_v4(), actor_id, action: action.to_owned(), meme_id, user_id, note: note.to_owned(), created_at: Utc::now() };
+	diesel::insert_into(audit_log::table).values(&entry).execute(conn)?;
+	Ok(entry)
+}
+
+/// Newest entries first, each with the acting moderator's username.
+pub fn list_recent(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<(AuditEntry, Option<String>)>> {
+	audit_log::table
+		.left_join(users::table)
+		.select((AuditEntry::as_select(), users::username.nullable()))
+		.order(audit_log::created_at.desc())
+		.limit(limit)
+		.load(conn)
+}
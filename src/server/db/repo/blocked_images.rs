@@ -0,0 +1,17 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::server::db::schema::blocked_images;
+
+pub fn block(conn: &mut PgConnection, sha256: &str, blocked_by: Option<Uuid>) -> QueryResult<()> {
+	diesel::insert_into(blocked_images::table)
+		.values((blocked_images::sha256.eq(sha256), blocked_images::blocked_by.eq(blocked_by), blocked_images::created_at.eq(Utc::now())))
+		.on_conflict_do_nothing()
+		.execute(conn)?;
+	Ok(())
+}
+
+pub fn is_blocked(conn: &mut PgConnection, sha256: &str) -> QueryResult<bool> {
+	diesel::select(diesel::dsl::exists(blocked_images::table.find(sha256))).get_result(conn)
+}
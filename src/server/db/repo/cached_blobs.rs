@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use diesel::{dsl::exists, prelude::*, upsert::excluded};
+
+use crate::server::db::schema::{cached_blobs, memes, templates, tournaments};
+
+/// Records that the blob was just used, tracking it from now on if it was not.
+pub fn touch(conn: &mut PgConnection, key: &str, kind: &str, now: DateTime<Utc>) -> QueryResult<()> {
+	diesel::insert_into(cached_blobs::table)
+		.values((cached_blobs::key.eq(key), cached_blobs::kind.eq(kind), cached_blobs::last_used_at.eq(now)))
+		.on_conflict(cached_blobs::key)
+		.do_update()
+		.set(cached_blobs::last_used_at.eq(excluded(cached_blobs::last_used_at)))
+		.execute(conn)?;
+	Ok(())
+}
+
+/// Keys of `kind` unused since `before`, oldest first, locked until the transaction ends. Rows another sweep holds are skipped.
+pub fn lock_stale(conn: &mut PgConnection, kind: &str, before: DateTime<Utc>, limit: i64) -> QueryResult<Vec<String>> {
+	cached_blobs::table
+		.filter(cached_blobs::kind.eq(kind))
+		.filter(cached_blobs::last_used_at.lt(before))
+		.order(cached_blobs::last_used_at.asc())
+		.limit(limit)
+		.select(cached_blobs::key)
+		.for_update()
+		.skip_locked()
+		.load(conn)
+}
+
+/// Whether a saved meme, a template or a tournament points at `url`.
+pub fn is_referenced(conn: &mut PgConnection, url: &str) -> QueryResult<bool> {
+	let in_memes = diesel::select(exists(memes::table.filter(memes::main_img_url.eq(url).or(memes::watermark_url.eq(url))))).get_result(conn)?;
+	let in_templates = diesel::select(exists(templates::table.filter(templates::image_url.eq(url)))).get_result(conn)?;
+	let in_tournaments = diesel::select(exists(tournaments::table.filter(tournaments::watermark_url.eq(url)))).get_result(conn)?;
+	Ok(in_memes || in_templates || in_tournaments)
+}
+
+pub fn delete(conn: &mut PgConnection, key: &str) -> QueryResult<bool> {
+	Ok(diesel::delete(cached_blobs::table.find(key)).execute(conn)? > 0)
+}
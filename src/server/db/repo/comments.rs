@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use diesel::{dsl::count_star, prelude::*};
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::Comment,
+	schema::{comments, users},
+};
+
+pub fn create(conn: &mut PgConnection, meme_id: Uuid, parent_id: Option<Uuid>, user_id: Uuid, body: &str) -> QueryResult<Comment> {
+	let comment = Comment {
+		id: Uuid::new_v4(),
+		meme_id,
+		parent_id,
+		user_id: Some(user_id),
+		body: body.to_owned(),
+		created_at: Utc::now(),
+		edited_at: None,
+		deleted_at: None,
+	};
+	diesel::insert_into(comments::table).values(&comment).execute(conn)?;
+	Ok(comment)
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<Comment>> {
+	comments::table.find(id).select(Comment::as_select()).first(conn).optional()
+}
+
+/// Every comment on a meme, oldest first, with its author's username.
+pub fn list_for_meme(conn: &mut PgConnection, meme_id: Uuid) -> QueryResult<Vec<(Comment, Option<String>)>> {
+	comments::table
+		.left_join(users::table)
+		.filter(comments::meme_id.eq(meme_id))
+		.select((Comment::as_select(), users::username.nullable()))
+		.order((comments::created_at, comments::id))
+		.load(conn)
+}
+
+pub fn edit(conn: &mut PgConnection, id: Uuid, body: &str) -> QueryResult<Comment> {
+	diesel::update(comments::table.find(id))
+		.set((comments::body.eq(body), comments::edited_at.eq(Utc::now())))
+		.returning(Comment::as_returning())
+		.get_result(conn)
+}
+
+/// Clears the body but keeps the row, so replies still have a parent.
+pub fn soft_delete(conn: &mut PgConnection, id: Uuid) -> QueryResult<Comment> {
+	diesel::update(comments::table.find(id)).set((comments::body.eq(""), comments::deleted_at.eq(Utc::now()))).returning(Comment::as_returning()).get_result(conn)
+}
+
+/// How many comments the user wrote since `since`, for rate limiting.
+pub fn count_by_user_since(conn: &mut PgConnection, user_id: Uuid, since: DateTime<Utc>) -> QueryResult<i64> {
+	comments::table.filter(comments::user_id.eq(user_id)).filter(comments::created_at.ge(since)).select(count_star()).first(conn)
+}
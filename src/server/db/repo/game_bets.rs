@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use diesel::{dsl::IntervalDsl, prelude::*};
+use uuid::Uuid;
+
+use crate::{
+	game::percentile::PercentileWindow,
+	server::db::{
+		models::{GameBet, MemeRecord},
+		schema::{game_bets, meme_records},
+	},
+};
+
+pub fn create(conn: &mut PgConnection, user_id: Uuid, record_id: Uuid, window: PercentileWindow, stake: i64) -> QueryResult<GameBet> {
+	let bet = GameBet {
+		id: Uuid::new_v4(),
+		user_id,
+		record_id,
+		window_low: i16::from(window.low),
+		window_high: i16::from(window.high),
+		stake,
+		created_at: Utc::now(),
+		settled_at: None,
+		percentile: None,
+		payout: None,
+	};
+	diesel::insert_into(game_bets::table).values(&bet).execute(conn)?;
+	Ok(bet)
+}
+
+pub fn exists(conn: &mut PgConnection, user_id: Uuid, record_id: Uuid) -> QueryResult<bool> {
+	diesel::select(diesel::dsl::exists(game_bets::table.filter(game_bets::user_id.eq(user_id)).filter(game_bets::record_id.eq(record_id)))).get_result(conn)
+}
+
+/// The user's most recent bets with the memes they were placed on.
+pub fn list_for_user(conn: &mut PgConnection, user_id: Uuid, limit: i64) -> QueryResult<Vec<(GameBet, MemeRecord)>> {
+	game_bets::table
+		.inner_join(meme_records::table)
+		.filter(game_bets::user_id.eq(user_id))
+		.select((GameBet::as_select(), MemeRecord::as_select()))
+		.order(game_bets::created_at.desc())
+		.limit(limit)
+		.load(conn)
+}
+
+/// Open bets whose memes have settled, oldest first.
+pub fn list_due(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<(GameBet, MemeRecord)>> {
+	game_bets::table
+		.inner_join(meme_records::table)
+		.filter(game_bets::settled_at.is_null())
+		.filter(meme_records::observed_at.ge(meme_records::posted_at + MemeRecord::SETTLE_AGE_HOURS.hours()))
+		.select((GameBet::as_select(), MemeRecord::as_select()))
+		.order(game_bets::created_at)
+		.limit(limit)
+		.load(conn)
+}
+
+/// Records the result of a bet. Returns the settled bet, or `None` when it had already been settled.
+pub fn settle(conn: &mut PgConnection, id: Uuid, percentile: f64, payout: i64, now: DateTime<Utc>) -> QueryResult<Option<GameBet>> {
+	diesel::update(game_bets::table.find(id).filter(game_bets::settled_at.is_null()))
+		.set((game_bets::settled_at.eq(now), game_bets::percentile.eq(percentile), game_bets::payout.eq(payout)))
+		.returning(GameBet::as_returning())
+		.get_result(conn)
+		.optional()
+}
@@ -0,0 +1,98 @@
+use chrono::Utc;
+use diesel::{
+	dsl::sql,
+	prelude::*,
+	sql_types::{BigInt, Bool},
+};
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::{LedgerAccount, LedgerEntry, LedgerTransaction},
+	schema::{ledger_accounts, ledger_entries, ledger_transactions},
+};
+
+/// The account of that kind, owner and scope, opened with a zero balance the first time it is asked for.
+pub fn account(conn: &mut PgConnection, kind: &str, user_id: Option<Uuid>, scope: Option<Uuid>) -> QueryResult<LedgerAccount> {
+	let account = LedgerAccount { id: Uuid::new_v4(), kind: kind.to_owned(), user_id, scope, balance: 0, created_at: Utc::now() };
+	diesel::insert_into(ledger_accounts::table).values(&account).on_conflict_do_nothing().execute(conn)?;
+	ledger_accounts::table
+		.filter(ledger_accounts::kind.eq(kind))
+		.filter(ledger_accounts::user_id.is_not_distinct_from(user_id))
+		.filter(ledger_accounts::scope.is_not_distinct_from(scope))
+		.select(LedgerAccount::as_select())
+		.first(conn)
+}
+
+pub fn find_account(conn: &mut PgConnection, kind: &str, user_id: Option<Uuid>, scope: Option<Uuid>) -> QueryResult<Option<LedgerAccount>> {
+	ledger_accounts::table
+		.filter(ledger_accounts::kind.eq(kind))
+		.filter(ledger_accounts::user_id.is_not_distinct_from(user_id))
+		.filter(ledger_accounts::scope.is_not_distinct_from(scope))
+		.select(LedgerAccount::as_select())
+		.first(conn)
+		.optional()
+}
+
+/// Locks the accounts until the end of the transaction. Always in id order, so concurrent transfers cannot deadlock.
+pub fn lock_accounts(conn: &mut PgConnection, ids: &[Uuid]) -> QueryResult<Vec<LedgerAccount>> {
+	ledger_accounts::table.filter(ledger_accounts::id.eq_any(ids)).select(LedgerAccount::as_select()).order(ledger_accounts::id).for_update().load(conn)
+}
+
+pub fn find_transaction(conn: &mut PgConnection, idempotency_key: &str) -> QueryResult<Option<LedgerTransaction>> {
+	ledger_transactions::table.filter(ledger_transactions::idempotency_key.eq(idempotency_key)).select(LedgerTransaction::as_select()).first(conn).optional()
+}
+
+/// Returns false when a transaction with the same idempotency key already exists.
+pub fn insert_transaction(conn: &mut PgConnection, transaction: &LedgerTransaction) -> QueryResult<bool> {
+	Ok(diesel::insert_into(ledger_transactions::table).values(transaction).on_conflict_do_nothing().execute(conn)? == 1)
+}
+
+/// Books `amount` on the account and records the entry. Callers hold the account's lock.
+pub fn insert_entry(conn: &mut PgConnection, transaction_id: Uuid, account: &LedgerAccount, amount: i64) -> QueryResult<()> {
+	let balance = account.balance + amount;
+	diesel::insert_into(ledger_entries::table)
+		.values((
+			ledger_entries::transaction_id.eq(transaction_id),
+			ledger_entries::account_id.eq(account.id),
+			ledger_entries::amount.eq(amount),
+			ledger_entries::balance_after.eq(balance),
+		))
+		.execute(conn)?;
+	diesel::update(ledger_accounts::table.find(account.id)).set(ledger_accounts::balance.eq(balance)).execute(conn)?;
+	Ok(())
+}
+
+/// The account's entries, newest first, starting below the entry id `before`.
+pub fn history(conn: &mut PgConnection, account_id: Uuid, before: Option<i64>, limit: i64) -> QueryResult<Vec<(LedgerEntry, LedgerTransaction)>> {
+	ledger_entries::table
+		.inner_join(ledger_transactions::table)
+		.filter(ledger_entries::account_id.eq(account_id))
+		.filter(ledger_entries::id.lt(before.unwrap_or(i64::MAX)))
+		.select((LedgerEntry::as_select(), LedgerTransaction::as_select()))
+		.order(ledger_entries::id.desc())
+		.limit(limit)
+		.load(conn)
+}
+
+/// Accounts of that kind still holding points.
+pub fn funded(conn: &mut PgConnection, kind: &str) -> QueryResult<Vec<LedgerAccount>> {
+	ledger_accounts::table.filter(ledger_accounts::kind.eq(kind)).filter(ledger_accounts::balance.ne(0)).select(LedgerAccount::as_select()).load(conn)
+}
+
+/// Sum of every account's balance. Zero unless the ledger is broken.
+pub fn total_balance(conn: &mut PgConnection) -> QueryResult<i64> {
+	ledger_accounts::table.select(sql::<BigInt>("COALESCE(SUM(balance), 0)::BIGINT")).get_result(conn)
+}
+
+/// Ids of transactions whose entries do not add up to zero.
+pub fn unbalanced_transactions(conn: &mut PgConnection) -> QueryResult<Vec<Uuid>> {
+	ledger_entries::table.group_by(ledger_entries::transaction_id).having(sql::<Bool>("SUM(amount) <> 0")).select(ledger_entries::transaction_id).load(conn)
+}
+
+/// Ids of accounts whose balance differs from the sum of their entries.
+pub fn drifted_accounts(conn: &mut PgConnection) -> QueryResult<Vec<Uuid>> {
+	ledger_accounts::table
+		.filter(sql::<Bool>("balance <> COALESCE((SELECT SUM(e.amount) FROM ledger_entries e WHERE e.account_id = ledger_accounts.id), 0)"))
+		.select(ledger_accounts::id)
+		.load(conn)
+}
@@ -0,0 +1,101 @@
+use diesel::{
+	dsl::{IntervalDsl, count_star, exists, not, sql},
+	prelude::*,
+	sql_types::{BigInt, Double, Int4},
+};
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::MemeRecord,
+	schema::{game_bets, meme_records},
+};
+
+/// Inserts a record, or refreshes title, image and metrics of the one already stored for the same source and external id.
+pub fn upsert(conn: &mut PgConnection, record: &MemeRecord) -> QueryResult<MemeRecord> {
+	diesel::insert_into(meme_records::table)
+		.values(record)
+		.on_conflict((meme_records::source, meme_records::external_id))
+		.do_update()
+		.set((
+			meme_records::title.eq(&record.title),
+			meme_records::image_url.eq(&record.image_url),
+			meme_records::score.eq(record.score),
+			meme_records::comments.eq(record.comments),
+			meme_records::engagement.eq(record.engagement),
+			meme_records::observed_at.eq(record.observed_at),
+		))
+		.returning(MemeRecord::as_returning())
+		.get_result(conn)
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<MemeRecord>> {
+	meme_records::table.find(id).select(MemeRecord::as_select()).first(conn).optional()
+}
+
+pub fn find_by_external_id(conn: &mut PgConnection, source: &str, external_id: &str) -> QueryResult<Option<MemeRecord>> {
+	meme_records::table
+		.filter(meme_records::source.eq(source))
+		.filter(meme_records::external_id.eq(external_id))
+		.select(MemeRecord::as_select())
+		.first(conn)
+		.optional()
+}
+
+/// The record, from any source, whose image hash is closest to `dhash` if it is within `max_distance` bits.
+pub fn find_similar(conn: &mut PgConnection, dhash: i64, max_distance: u32) -> QueryResult<Option<MemeRecord>> {
+	diesel::sql_query(
+		"SELECT * FROM meme_records
+		WHERE dhash IS NOT NULL AND bit_count((dhash # $1)::BIT(64)) <= $2
+		ORDER BY bit_count((dhash # $1)::BIT(64)), created_at
+		LIMIT 1",
+	)
+	.bind::<BigInt, _>(dhash)
+	.bind::<Int4, _>(max_distance as i32)
+	.get_result(conn)
+	.optional()
+}
+
+/// How many settled records of `source` have engagement below and equal to `engagement`, and how many there are in total.
+pub fn reference_rank(conn: &mut PgConnection, source: &str, engagement: f64) -> QueryResult<(i64, i64, i64)> {
+	let settled = meme_records::table
+		.filter(meme_records::source.eq(source))
+		.filter(meme_records::observed_at.ge(meme_records::posted_at + MemeRecord::SETTLE_AGE_HOURS.hours()));
+	let below = settled.filter(meme_records::engagement.lt(engagement)).select(count_star()).get_result(conn)?;
+	let equal = settled.filter(meme_records::engagement.eq(engagement)).select(count_star()).get_result(conn)?;
+	let total = settled.select(count_star()).get_result(conn)?;
+	Ok((below, equal, total))
+}
+
+/// Sources with at least `min_reference` settled records, enough for percentiles to mean something.
+fn ranked_sources(conn: &mut PgConnection, min_reference: i64) -> QueryResult<Vec<String>> {
+	meme_records::table
+		.filter(meme_records::observed_at.ge(meme_records::posted_at + MemeRecord::SETTLE_AGE_HOURS.hours()))
+		.group_by(meme_records::source)
+		.having(count_star().ge(min_reference))
+		.select(meme_records::source)
+		.load(conn)
+}
+
+/// A random record `user_id` has not bet on yet, from a source with at least `min_reference` settled records to rank it against.
+pub fn random_unplayed(conn: &mut PgConnection, user_id: Uuid, min_reference: i64) -> QueryResult<Option<MemeRecord>> {
+	let sources = ranked_sources(conn, min_reference)?;
+	meme_records::table
+		.filter(meme_records::source.eq_any(sources))
+		.filter(not(exists(game_bets::table.filter(game_bets::record_id.eq(meme_records::id)).filter(game_bets::user_id.eq(user_id)))))
+		.select(MemeRecord::as_select())
+		.order(sql::<Double>("random()"))
+		.first(conn)
+		.optional()
+}
+
+/// Up to `limit` random settled records from sources with at least `min_reference` settled records.
+pub fn random_settled(conn: &mut PgConnection, min_reference: i64, limit: i64) -> QueryResult<Vec<MemeRecord>> {
+	let sources = ranked_sources(conn, min_reference)?;
+	meme_records::table
+		.filter(meme_records::source.eq_any(sources))
+		.filter(meme_records::observed_at.ge(meme_records::posted_at + MemeRecord::SETTLE_AGE_HOURS.hours()))
+		.select(MemeRecord::as_select())
+		.order(sql::<Double>("random()"))
+		.limit(limit)
+		.load(conn)
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+	server::db::{
+		models::{Layer, Meme},
+		schema::{layers, memes},
+	},
+	stores::meme_canvas::MemeDocument,
+};
+
+pub fn insert_document(conn: &mut PgConnection, user_id: Option<Uuid>, document: &MemeDocument) -> QueryResult<Meme> {
+	conn.transaction(|conn| {
+		let meme = Meme {
+			id: Uuid::new_v4(),
+			user_id,
+			template_id: None,
+			main_img_url: document.main_img_url.clone(),
+			width: document.width as i32,
+			height: document.height as i32,
+			created_at: Utc::now(),
+			title: None,
+			image_url: None,
+			published_at: None,
+			parent_id: None,
+			caption: document.caption(),
+			tags: Vec::new(),
+			hidden_at: None,
+			dhash: None,
+			watermark_url: document.watermark_url.clone(),
+		};
+		diesel::insert_into(memes::table).values(&meme).execute(conn)?;
+		let layers =
+			document.text_boxes.iter().enumerate().map(|(position, text_box)| Layer::from_text_box(meme.id, position as i32, text_box)).collect::<Vec<_>>();
+		diesel::insert_into(layers::table).values(&layers).execute(conn)?;
+		Ok(meme)
+	})
+}
+
+/// Turns a saved meme into a post, recording its tags and the post it was remixed from.
+pub fn publish(conn: &mut PgConnection, id: Uuid, title: &str, image_url: &str, tags: &[String], parent_id: Option<Uuid>) -> QueryResult<Meme> {
+	diesel::update(memes::table.find(id))
+		.set((memes::title.eq(title), memes::image_url.eq(image_url), memes::published_at.eq(Utc::now()), memes::tags.eq(tags), memes::parent_id.eq(parent_id)))
+		.returning(Meme::as_returning())
+		.get_result(conn)
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<Meme>> {
+	memes::table.find(id).select(Meme::as_select()).first(conn).optional()
+}
+
+/// Like [`find`] but locks the row until the transaction ends.
+pub fn find_for_update(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<Meme>> {
+	memes::table.find(id).select(Meme::as_select()).for_update().first(conn).optional()
+}
+
+pub fn load_document(conn: &mut PgConnection, meme: Meme) -> QueryResult<MemeDocument> {
+	let layers = layers::table.filter(layers::meme_id.eq(meme.id)).select(Layer::as_select()).order(layers::position).load(conn)?;
+	Ok(MemeDocument {
+		main_img_url: meme.main_img_url,
+		width: meme.width.max(1) as u32,
+		height: meme.height.max(1) as u32,
+		text_boxes: layers.into_iter().map(Into::into).collect(),
+		watermark_url: meme.watermark_url,
+	})
+}
+
+pub fn find_document(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<MemeDocument>> {
+	find(conn, id)?.map(|meme| load_document(conn, meme)).transpose()
+}
+
+/// Memes created before `before` (or the newest ones), newest first, each with its layers.
+pub fn list_recent(conn: &mut PgConnection, before: Option<DateTime<Utc>>, limit: i64) -> QueryResult<Vec<(Meme, Vec<Layer>)>> {
+	let mut query = memes::table.select(Meme::as_select()).order(memes::created_at.desc()).limit(limit).into_boxed();
+	if let Some(before) = before {
+		query = query.filter(memes::created_at.lt(before));
+	}
+	let memes: Vec<Meme> = query.load(conn)?;
+	let layers: Vec<Layer> =
+		layers::table.filter(layers::meme_id.eq_any(memes.iter().map(|meme| meme.id))).select(Layer::as_select()).order(layers::position).load(conn)?;
+	let mut layers_by_meme: HashMap<Uuid, Vec<Layer>> = HashMap::new();
+	for layer in layers {
+		layers_by_meme.entry(layer.meme_id).or_default().push(layer);
+	}
+	Ok(
+		memes
+			.into_iter()
+			.map(|meme| {
+				let layers = layers_by_meme.remove(&meme.id).unwrap_or_default();
+				(meme, layers)
+			})
+			.collect(),
+	)
+}
+
+/// Id and render url of published posts without an image hash, ordered by id and starting after `after`.
+pub fn list_unhashed_posts(conn: &mut PgConnection, after: Option<Uuid>, limit: i64) -> QueryResult<Vec<(Uuid, String)>> {
+	let mut query = memes::table
+		.filter(memes::image_url.is_not_null().and(memes::dhash.is_null()))
+		.select((memes::id, memes::image_url.assume_not_null()))
+		.order(memes::id.asc())
+		.limit(limit)
+		.into_boxed();
+	if let Some(after) = after {
+		query = query.filter(memes::id.gt(after));
+	}
+	query.load(conn)
+}
+
+pub fn set_dhash(conn: &mut PgConnection, id: Uuid, dhash: i64) -> QueryResult<()> {
+	diesel::update(memes::table.find(id)).set(memes::dhash.eq(dhash)).execute(conn)?;
+	Ok(())
+}
+
+/// Hides or unhides a post. Returns false when the meme does not exist.
+pub fn set_hidden(conn: &mut PgConnection, id: Uuid, hidden: bool) -> QueryResult<bool> {
+	Ok(diesel::update(memes::table.find(id)).set(memes::hidden_at.eq(hidden.then(Utc::now))).execute(conn)? > 0)
+}
+
+/// Hides every visible post by `user_id`, returning how many were hidden.
+pub fn hide_all_by_user(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<usize> {
+	diesel::update(memes::table.filter(memes::user_id.eq(user_id)).filter(memes::hidden_at.is_null())).set(memes::hidden_at.eq(Utc::now())).execute(conn)
+}
+
+pub fn delete(conn: &mut PgConnection, id: Uuid) -> QueryResult<bool> {
+	Ok(diesel::delete(memes::table.find(id)).execute(conn)? > 0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		server::db::{repo::users, test_conn},
+		stores::text_box::TextBox,
+	};
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn document_round_trip() {
+		let mut conn = test_conn();
+		let user = users::create(&mut conn, "repo_round_trip", "hash").unwrap();
+		let mut rotated = TextBox::new("bottom".to_owned(), 320.5, 400.25, 48, "Impact".to_owned(), "bold".to_owned());
+		rotated.rotation = 0.3;
+		rotated.scale_x = 1.5;
+		let document = MemeDocument {
+			main_img_url: "https://example.com/template.png".to_owned(),
+			width: 640,
+			height: 480,
+			text_boxes: vec![TextBox::new("top".to_owned(), 320.0, 40.0, 36, "Arial".to_owned(), "normal".to_owned()), rotated],
+			watermark_url: Some("https://example.com/sponsor.png".to_owned()),
+		};
+		let meme = insert_document(&mut conn, Some(user.id), &document).unwrap();
+		assert_eq!(meme.caption, document.caption());
+		assert_eq!(find_document(&mut conn, meme.id).unwrap(), Some(document));
+
+		let published = publish(&mut conn, meme.id, "A title", "https://example.com/render.png", &["cats".to_owned()], None).unwrap();
+		assert!(published.published_at.is_some());
+		assert_eq!(find(&mut conn, meme.id).unwrap().and_then(|meme| meme.title).as_deref(), Some("A title"));
+
+		assert!(delete(&mut conn, meme.id).unwrap());
+		assert!(!delete(&mut conn, meme.id).unwrap());
+		assert_eq!(find_document(&mut conn, meme.id).unwrap(), None);
+	}
+}
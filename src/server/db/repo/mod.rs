@@ -0,0 +1,21 @@
+//! Query functions over the diesel schema. Each takes a connection so callers decide about pooling and transactions.
+
+pub mod api_keys;
+pub mod audit_log;
+pub mod blocked_images;
+pub mod cached_blobs;
+pub mod comments;
+pub mod game_bets;
+pub mod ledger;
+pub mod meme_records;
+pub mod memes;
+pub mod notifications;
+pub mod pending_uploads;
+pub mod posts;
+pub mod remixes;
+pub mod reports;
+pub mod stats;
+pub mod templates;
+pub mod tournaments;
+pub mod users;
+pub mod votes;
@@ -0,0 +1,35 @@
+use chrono::Utc;
+use diesel::{dsl::count_star, prelude::*};
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::Notification,
+	schema::{memes, notifications, users},
+};
+
+pub fn create(conn: &mut PgConnection, notification: &Notification) -> QueryResult<()> {
+	diesel::insert_into(notifications::table).values(notification).execute(conn)?;
+	Ok(())
+}
+
+/// Newest first, with the actor's username and the title of the meme it happened on.
+pub fn list_for_user(conn: &mut PgConnection, user_id: Uuid, limit: i64) -> QueryResult<Vec<(Notification, Option<String>, Option<String>)>> {
+	notifications::table
+		.left_join(users::table.on(users::id.nullable().eq(notifications::actor_id)))
+		.inner_join(memes::table)
+		.filter(notifications::user_id.eq(user_id))
+		.select((Notification::as_select(), users::username.nullable(), memes::title))
+		.order(notifications::created_at.desc())
+		.limit(limit)
+		.load(conn)
+}
+
+pub fn count_unread(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<i64> {
+	notifications::table.filter(notifications::user_id.eq(user_id)).filter(notifications::read_at.is_null()).select(count_star()).first(conn)
+}
+
+pub fn mark_all_read(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<usize> {
+	diesel::update(notifications::table.filter(notifications::user_id.eq(user_id)).filter(notifications::read_at.is_null()))
+		.set(notifications::read_at.eq(Utc::now()))
+		.execute(conn)
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::server::db::{models::PendingUpload, schema::pending_uploads};
+
+pub fn create(conn: &mut PgConnection, upload: &PendingUpload) -> QueryResult<()> {
+	diesel::insert_into(pending_uploads::table).values(upload).execute(conn)?;
+	Ok(())
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<PendingUpload>> {
+	pending_uploads::table.find(id).select(PendingUpload::as_select()).first(conn).optional()
+}
+
+pub fn find_by_key(conn: &mut PgConnection, object_key: &str) -> QueryResult<Option<PendingUpload>> {
+	pending_uploads::table.filter(pending_uploads::object_key.eq(object_key)).select(PendingUpload::as_select()).first(conn).optional()
+}
+
+pub fn delete(conn: &mut PgConnection, id: Uuid) -> QueryResult<bool> {
+	Ok(diesel::delete(pending_uploads::table.find(id)).execute(conn)? > 0)
+}
+
+pub fn list_expired(conn: &mut PgConnection, now: DateTime<Utc>, limit: i64) -> QueryResult<Vec<PendingUpload>> {
+	pending_uploads::table
+		.filter(pending_uploads::expires_at.lt(now))
+		.select(PendingUpload::as_select())
+		.order(pending_uploads::expires_at.asc())
+		.limit(limit)
+		.load(conn)
+}
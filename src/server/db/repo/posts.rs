@@ -0,0 +1,113 @@
+//! Read side of published memes. Both feeds page with keyset cursors so rows do not shift between pages as new posts arrive.
+
+use chrono::{DateTime, Utc};
+use diesel::{
+	prelude::*,
+	sql_types::{Array, BigInt, Double, Int4, Nullable, Text, Timestamptz, Uuid as SqlUuid},
+};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, QueryableByName)]
+pub struct PostRow {
+	#[diesel(sql_type = SqlUuid)]
+	pub id: Uuid,
+	#[diesel(sql_type = Text)]
+	pub title: String,
+	#[diesel(sql_type = Text)]
+	pub image_url: String,
+	#[diesel(sql_type = Timestamptz)]
+	pub published_at: DateTime<Utc>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub author: Option<String>,
+	#[diesel(sql_type = BigInt)]
+	pub score: i64,
+	#[diesel(sql_type = BigInt)]
+	pub views: i64,
+	#[diesel(sql_type = Double)]
+	pub hot: f64,
+	#[diesel(sql_type = Array<Text>)]
+	pub tags: Vec<String>,
+}
+
+const POSTS: &str = "
+	SELECT m.id, m.title, m.image_url, m.published_at, u.username AS author,
+		(COALESCE(s.upvotes, 0) - COALESCE(s.downvotes, 0))::INT8 AS score, COALESCE(s.views, 0) AS views, COALESCE(s.hot, 0) AS hot,
+		m.tags
+	FROM memes m
+	LEFT JOIN users u ON u.id = m.user_id
+	LEFT JOIN meme_stats s ON s.meme_id = m.id
+	WHERE m.published_at IS NOT NULL AND m.title IS NOT NULL AND m.image_url IS NOT NULL AND m.hidden_at IS NULL";
+
+/// Newest first, strictly after the `(published_at, id)` cursor.
+pub fn list_new(conn: &mut PgConnection, after: Option<(DateTime<Utc>, Uuid)>, limit: i64) -> QueryResult<Vec<PostRow>> {
+	diesel::sql_query(format!(
+		"SELECT * FROM ({POSTS}) posts
+		WHERE $1::TIMESTAMPTZ IS NULL OR (published_at, id) < ($1, $2)
+		ORDER BY published_at DESC, id DESC
+		LIMIT $3"
+	))
+	.bind::<Nullable<Timestamptz>, _>(after.map(|(published_at, _)| published_at))
+	.bind::<Nullable<SqlUuid>, _>(after.map(|(_, id)| id))
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
+
+/// Highest score first, strictly after the `(score, id)` cursor.
+pub fn list_top(conn: &mut PgConnection, after: Option<(i64, Uuid)>, limit: i64) -> QueryResult<Vec<PostRow>> {
+	diesel::sql_query(format!(
+		"SELECT * FROM ({POSTS}) posts
+		WHERE $1::INT8 IS NULL OR (score, id) < ($1, $2)
+		ORDER BY score DESC, id DESC
+		LIMIT $3"
+	))
+	.bind::<Nullable<BigInt>, _>(after.map(|(score, _)| score))
+	.bind::<Nullable<SqlUuid>, _>(after.map(|(_, id)| id))
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
+
+/// Highest [`super::stats::hot_score`] first, strictly after the `(hot, id)` cursor.
+pub fn list_hot(conn: &mut PgConnection, after: Option<(f64, Uuid)>, limit: i64) -> QueryResult<Vec<PostRow>> {
+	diesel::sql_query(format!(
+		"SELECT * FROM ({POSTS}) posts
+		WHERE $1::FLOAT8 IS NULL OR (hot, id) < ($1, $2)
+		ORDER BY hot DESC, id DESC
+		LIMIT $3"
+	))
+	.bind::<Nullable<Double>, _>(after.map(|(hot, _)| hot))
+	.bind::<Nullable<SqlUuid>, _>(after.map(|(_, id)| id))
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<PostRow>> {
+	diesel::sql_query(format!("SELECT * FROM ({POSTS}) posts WHERE id = $1")).bind::<SqlUuid, _>(id).get_result(conn).optional()
+}
+
+/// Posts matching the full text `query` (empty matches all) and carrying every tag in `tags`, best match first.
+pub fn search(conn: &mut PgConnection, query: &str, tags: &[String], limit: i64) -> QueryResult<Vec<PostRow>> {
+	diesel::sql_query(format!(
+		"{POSTS}
+		AND ($1 = '' OR meme_search_document(m.title, m.caption, m.tags) @@ websearch_to_tsquery('english', $1)) AND m.tags @> $2
+		ORDER BY ts_rank(meme_search_document(m.title, m.caption, m.tags), websearch_to_tsquery('english', $1)) DESC, m.published_at DESC, m.id DESC
+		LIMIT $3"
+	))
+	.bind::<Text, _>(query)
+	.bind::<Array<Text>, _>(tags)
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
+
+/// Visible posts whose render hash is within `max_distance` bits of `dhash`, closest first.
+pub fn find_similar(conn: &mut PgConnection, dhash: i64, max_distance: u32, limit: i64) -> QueryResult<Vec<PostRow>> {
+	diesel::sql_query(format!(
+		"{POSTS}
+		AND m.dhash IS NOT NULL AND bit_count((m.dhash # $1)::BIT(64)) <= $2
+		ORDER BY bit_count((m.dhash # $1)::BIT(64)), m.published_at
+		LIMIT $3"
+	))
+	.bind::<BigInt, _>(dhash)
+	.bind::<Int4, _>(max_distance as i32)
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
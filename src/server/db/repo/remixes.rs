@@ -0,0 +1,68 @@
+//! Remix lineage. Memes point at their parent, so both directions are walked with recursive queries capped at a depth.
+
+use chrono::{DateTime, Utc};
+use diesel::{
+	prelude::*,
+	sql_types::{BigInt, Int4, Nullable, Text, Timestamptz, Uuid as SqlUuid},
+};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, QueryableByName)]
+pub struct RemixRow {
+	#[diesel(sql_type = SqlUuid)]
+	pub id: Uuid,
+	#[diesel(sql_type = Nullable<SqlUuid>)]
+	pub parent_id: Option<Uuid>,
+	#[diesel(sql_type = Text)]
+	pub title: String,
+	#[diesel(sql_type = Text)]
+	pub image_url: String,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub author: Option<String>,
+	#[diesel(sql_type = Timestamptz)]
+	pub published_at: DateTime<Utc>,
+	/// Steps away from the meme the lineage was requested for.
+	#[diesel(sql_type = Int4)]
+	pub depth: i32,
+}
+
+const REMIX_COLUMNS: &str = "
+	SELECT m.id, m.parent_id, m.title, m.image_url, u.username AS author, m.published_at, c.depth
+	FROM chain c
+	JOIN memes m ON m.id = c.id
+	LEFT JOIN users u ON u.id = m.user_id
+	WHERE m.published_at IS NOT NULL AND m.title IS NOT NULL AND m.image_url IS NOT NULL AND m.hidden_at IS NULL";
+
+/// Published ancestors of `id`, the original first and the direct parent last.
+pub fn ancestors(conn: &mut PgConnection, id: Uuid, max_depth: i32) -> QueryResult<Vec<RemixRow>> {
+	diesel::sql_query(format!(
+		"WITH RECURSIVE chain (id, depth) AS (
+			SELECT parent_id, 1 FROM memes WHERE id = $1 AND parent_id IS NOT NULL
+			UNION ALL
+			SELECT m.parent_id, c.depth + 1 FROM chain c JOIN memes m ON m.id = c.id WHERE m.parent_id IS NOT NULL AND c.depth < $2
+		)
+		{REMIX_COLUMNS}
+		ORDER BY c.depth DESC"
+	))
+	.bind::<SqlUuid, _>(id)
+	.bind::<Int4, _>(max_depth)
+	.load(conn)
+}
+
+/// Published remixes of `id` and their remixes, shallowest and oldest first so parents always precede their children.
+pub fn descendants(conn: &mut PgConnection, id: Uuid, max_depth: i32, limit: i64) -> QueryResult<Vec<RemixRow>> {
+	diesel::sql_query(format!(
+		"WITH RECURSIVE chain (id, depth) AS (
+			SELECT id, 1 FROM memes WHERE parent_id = $1
+			UNION ALL
+			SELECT m.id, c.depth + 1 FROM chain c JOIN memes m ON m.parent_id = c.id WHERE c.depth < $2
+		)
+		{REMIX_COLUMNS}
+		ORDER BY c.depth, m.published_at, m.id
+		LIMIT $3"
+	))
+	.bind::<SqlUuid, _>(id)
+	.bind::<Int4, _>(max_depth)
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use diesel::{
+	prelude::*,
+	sql_types::{Array, BigInt, Bool, Nullable, Text, Timestamptz, Uuid as SqlUuid},
+};
+use uuid::Uuid;
+
+use crate::server::db::{models::Report, schema::reports};
+
+/// One post in the review queue with its open reports rolled up.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct QueueRow {
+	#[diesel(sql_type = SqlUuid)]
+	pub meme_id: Uuid,
+	#[diesel(sql_type = Text)]
+	pub title: String,
+	#[diesel(sql_type = Text)]
+	pub image_url: String,
+	#[diesel(sql_type = Nullable<SqlUuid>)]
+	pub author_id: Option<Uuid>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub author: Option<String>,
+	#[diesel(sql_type = Bool)]
+	pub hidden: bool,
+	#[diesel(sql_type = BigInt)]
+	pub reports: i64,
+	#[diesel(sql_type = Array<Text>)]
+	pub reasons: Vec<String>,
+	/// Non empty report details, oldest first.
+	#[diesel(sql_type = Array<Text>)]
+	pub details: Vec<String>,
+	#[diesel(sql_type = Timestamptz)]
+	pub first_reported_at: DateTime<Utc>,
+}
+
+/// Files a report. Reporting the same post again replaces the earlier report and reopens it.
+pub fn upsert(conn: &mut PgConnection, meme_id: Uuid, reporter_id: Uuid, reason: &str, details: &str) -> QueryResult<Report> {
+	let report = Report {
+		id: Uuid::new_v4(),
+		meme_id,
+		reporter_id: Some(reporter_id),
+		reason: reason.to_owned(),
+		details: details.to_owned(),
+		created_at: Utc::now(),
+		resolved_at: None,
+	};
+	diesel::insert_into(reports::table)
+		.values(&report)
+		.on_conflict((reports::meme_id, reports::reporter_id))
+		.do_update()
+		.set((reports::reason.eq(reason), reports::details.eq(details), reports::created_at.eq(report.created_at), reports::resolved_at.eq(None::<DateTime<Utc>>)))
+		.returning(Report::as_returning())
+		.get_result(conn)
+}
+
+/// Files a report on behalf of an automatic filter.
+pub fn create_automatic(conn: &mut PgConnection, meme_id: Uuid, reason: &str, details: &str) -> QueryResult<Report> {
+	let report = Report {
+		id: Uuid::new_v4(),
+		meme_id,
+		reporter_id: None,
+		reason: reason.to_owned(),
+		details: details.to_owned(),
+		created_at: Utc::now(),
+		resolved_at: None,
+	};
+	diesel::insert_into(reports::table).values(&report).execute(conn)?;
+	Ok(report)
+}
+
+/// Posts with open reports, the most reported first.
+pub fn queue(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<QueueRow>> {
+	diesel::sql_query(
+		"SELECT m.id AS meme_id, COALESCE(m.title, '') AS title, COALESCE(m.image_url, '') AS image_url, m.user_id AS author_id, u.username AS author,
+			m.hidden_at IS NOT NULL AS hidden, COUNT(*) AS reports, array_agg(DISTINCT r.reason) AS reasons,
+			array_remove(array_agg(r.details ORDER BY r.created_at), '') AS details, MIN(r.created_at) AS first_reported_at
+		FROM reports r
+		JOIN memes m ON m.id = r.meme_id
+		LEFT JOIN users u ON u.id = m.user_id
+		WHERE r.resolved_at IS NULL
+		GROUP BY m.id, u.username
+		ORDER BY COUNT(*) DESC, MIN(r.created_at)
+		LIMIT $1",
+	)
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
+
+/// Closes every open report on the given posts, returning how many were closed.
+pub fn resolve_for_memes(conn: &mut PgConnection, meme_ids: &[Uuid]) -> QueryResult<usize> {
+	diesel::update(reports::table.filter(reports::meme_id.eq_any(meme_ids)).filter(reports::resolved_at.is_null()))
+		.set(reports::resolved_at.eq(Utc::now()))
+		.execute(conn)
+}
+
+/// Closes the open reports on every post by `user_id`.
+pub fn resolve_for_author(conn: &mut PgConnection, user_id: Uuid) -> QueryResult<usize> {
+	diesel::sql_query("UPDATE reports r SET resolved_at = now() FROM memes m WHERE m.id = r.meme_id AND m.user_id = $1 AND r.resolved_at IS NULL")
+		.bind::<SqlUuid, _>(user_id)
+		.execute(conn)
+}
@@ -0,0 +1,78 @@
+//! Aggregated counters per meme. Vote counts are recounted from `votes` on every change rather than incremented, with the meme row
+//! locked so concurrent votes recount one after the other, so they cannot drift.
+//!
+//! Views are deduplicated per [`viewer_key`](crate::server::auth::viewer_key) and day. Anonymous viewers are told apart by a cookie
+//! only, so one who drops it counts again: view counts are a popularity hint, nothing pays out on them.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{dsl::count_star, prelude::*, upsert::excluded};
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::MemeStats,
+	schema::{meme_stats, meme_views, memes, votes},
+};
+
+/// Seconds of freshness worth one order of magnitude of score.
+const HOT_DECAY_SECS: f64 = 45_000.0;
+
+/// Reddit style hot rank: the log of the score plus a term growing with publish time, so a post needs ten times the votes to keep
+/// up with one published 12.5 hours later. Only recomputed when votes change, since the time term does not change with age.
+pub fn hot_score(score: i64, published_at: DateTime<Utc>) -> f64 {
+	let order = (score.unsigned_abs().max(1) as f64).log10();
+	score.signum() as f64 * order + published_at.timestamp() as f64 / HOT_DECAY_SECS
+}
+
+pub fn find(conn: &mut PgConnection, meme_id: Uuid) -> QueryResult<Option<MemeStats>> {
+	meme_stats::table.find(meme_id).select(MemeStats::as_select()).first(conn).optional()
+}
+
+/// Recounts votes and the hot score of a meme, creating its stats row if needed. Run it in the transaction that changed the vote: it
+/// locks the meme row until then, so a concurrent vote waits and recounts with this one committed instead of writing a stale count.
+pub fn refresh_votes(conn: &mut PgConnection, meme_id: Uuid) -> QueryResult<MemeStats> {
+	let (published_at, created_at) =
+		memes::table.find(meme_id).select((memes::published_at, memes::created_at)).for_update().first::<(Option<DateTime<Utc>>, DateTime<Utc>)>(conn)?;
+	let counts: Vec<(i16, i64)> = votes::table.filter(votes::meme_id.eq(meme_id)).group_by(votes::value).select((votes::value, count_star())).load(conn)?;
+	let count = |value: i16| counts.iter().find(|(counted, _)| *counted == value).map_or(0, |(_, count)| *count);
+	let (upvotes, downvotes) = (count(1), count(-1));
+	let stats = MemeStats {
+		meme_id,
+		upvotes: upvotes as i32,
+		downvotes: downvotes as i32,
+		views: 0,
+		hot: hot_score(upvotes - downvotes, published_at.unwrap_or(created_at)),
+		updated_at: Utc::now(),
+	};
+	diesel::insert_into(meme_stats::table)
+		.values(&stats)
+		.on_conflict(meme_stats::meme_id)
+		.do_update()
+		.set((
+			meme_stats::upvotes.eq(excluded(meme_stats::upvotes)),
+			meme_stats::downvotes.eq(excluded(meme_stats::downvotes)),
+			meme_stats::hot.eq(excluded(meme_stats::hot)),
+			meme_stats::updated_at.eq(excluded(meme_stats::updated_at)),
+		))
+		.returning(MemeStats::as_returning())
+		.get_result(conn)
+}
+
+/// Counts a view unless `viewer` already viewed the meme on `day`. Returns whether it counted.
+pub fn record_view(conn: &mut PgConnection, meme_id: Uuid, viewer: &str, day: NaiveDate) -> QueryResult<bool> {
+	conn.transaction(|conn| {
+		let inserted = diesel::insert_into(meme_views::table)
+			.values((meme_views::meme_id.eq(meme_id), meme_views::viewer.eq(viewer), meme_views::viewed_on.eq(day)))
+			.on_conflict_do_nothing()
+			.execute(conn)?;
+		if inserted == 0 {
+			return Ok(false);
+		}
+		diesel::insert_into(meme_stats::table)
+			.values((meme_stats::meme_id.eq(meme_id), meme_stats::views.eq(1)))
+			.on_conflict(meme_stats::meme_id)
+			.do_update()
+			.set(meme_stats::views.eq(meme_stats::views + 1))
+			.execute(conn)?;
+		Ok(true)
+	})
+}
@@ -0,0 +1,107 @@
+use chrono::Utc;
+use diesel::{
+	prelude::*,
+	sql_types::{Array, BigInt, Int4, Text},
+};
+use uuid::Uuid;
+
+use crate::server::{
+	db::{models::Template, schema::templates},
+	image_hash::FULL_MASK,
+};
+
+pub struct NewTemplate<'a> {
+	pub name: &'a str,
+	pub image_url: &'a str,
+	pub width: i32,
+	pub height: i32,
+	pub tags: &'a [String],
+	pub dhash: Option<i64>,
+	pub created_by: Option<Uuid>,
+}
+
+#[derive(Clone, Debug, QueryableByName)]
+pub struct SimilarTemplateRow {
+	#[diesel(embed)]
+	pub template: Template,
+	#[diesel(sql_type = Int4)]
+	pub distance: i32,
+}
+
+pub fn create(conn: &mut PgConnection, new: &NewTemplate<'_>) -> QueryResult<Template> {
+	let template = Template {
+		id: Uuid::new_v4(),
+		name: new.name.to_owned(),
+		image_url: new.image_url.to_owned(),
+		width: new.width,
+		height: new.height,
+		created_by: new.created_by,
+		created_at: Utc::now(),
+		tags: new.tags.to_vec(),
+		dhash: new.dhash,
+	};
+	diesel::insert_into(templates::table).values(&template).execute(conn)?;
+	Ok(template)
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<Template>> {
+	templates::table.find(id).select(Template::as_select()).first(conn).optional()
+}
+
+pub fn list(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<Template>> {
+	templates::table.select(Template::as_select()).order(templates::created_at.desc()).limit(limit).load(conn)
+}
+
+/// Id and name of every template, newest first, for matching names the full text search would miss.
+pub fn names(conn: &mut PgConnection) -> QueryResult<Vec<(Uuid, String)>> {
+	templates::table.select((templates::id, templates::name)).order(templates::created_at.desc()).load(conn)
+}
+
+/// Templates matching the full text `query` (empty matches all) and carrying every tag in `tags`, best match first.
+pub fn search(conn: &mut PgConnection, query: &str, tags: &[String], limit: i64) -> QueryResult<Vec<Template>> {
+	diesel::sql_query(
+		"SELECT * FROM templates
+		WHERE ($1 = '' OR template_search_document(name, tags) @@ websearch_to_tsquery('english', $1)) AND tags @> $2
+		ORDER BY ts_rank(template_search_document(name, tags), websearch_to_tsquery('english', $1)) DESC, created_at DESC
+		LIMIT $3",
+	)
+	.bind::<Text, _>(query)
+	.bind::<Array<Text>, _>(tags)
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
+
+/// Id and image url of templates without an image hash, ordered by id and starting after `after`.
+pub fn list_unhashed(conn: &mut PgConnection, after: Option<Uuid>, limit: i64) -> QueryResult<Vec<(Uuid, String)>> {
+	let mut query =
+		templates::table.filter(templates::dhash.is_null()).select((templates::id, templates::image_url)).order(templates::id.asc()).limit(limit).into_boxed();
+	if let Some(after) = after {
+		query = query.filter(templates::id.gt(after));
+	}
+	query.load(conn)
+}
+
+pub fn set_dhash(conn: &mut PgConnection, id: Uuid, dhash: i64) -> QueryResult<()> {
+	diesel::update(templates::table.find(id)).set(templates::dhash.eq(dhash)).execute(conn)?;
+	Ok(())
+}
+
+/// Templates whose image hash is within `max_distance` bits of `dhash`, closest first.
+pub fn find_similar(conn: &mut PgConnection, dhash: i64, max_distance: u32, limit: i64) -> QueryResult<Vec<SimilarTemplateRow>> {
+	find_similar_masked(conn, dhash, FULL_MASK, max_distance, limit)
+}
+
+/// Like [`find_similar`], counting only the hash bits set in `mask`.
+pub fn find_similar_masked(conn: &mut PgConnection, dhash: i64, mask: i64, max_distance: u32, limit: i64) -> QueryResult<Vec<SimilarTemplateRow>> {
+	diesel::sql_query(
+		"SELECT * FROM (SELECT *, bit_count(((dhash # $1) & $2)::BIT(64))::INT4 AS distance FROM templates WHERE dhash IS NOT NULL) t
+		WHERE distance <= $3
+		ORDER BY distance, created_at
+		LIMIT $4",
+	)
+	.bind::<BigInt, _>(dhash)
+	.bind::<BigInt, _>(mask)
+	.bind::<Int4, _>(max_distance as i32)
+	.bind::<BigInt, _>(limit)
+	.load(conn)
+}
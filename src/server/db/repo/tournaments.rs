@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use diesel::{
+	dsl::count_star,
+	prelude::*,
+	sql_types::{BigInt, Double, Int2, Int4, Nullable, Text, Timestamptz, Uuid as SqlUuid},
+	upsert::excluded,
+};
+use uuid::Uuid;
+
+use crate::server::db::{
+	models::{Tournament, TournamentEntry},
+	schema::{tournament_entries, tournament_judges, tournament_scores, tournaments, users},
+};
+
+/// An entry with what it is judged on. Only published posts that are not hidden are listed.
+#[derive(Clone, Debug, QueryableByName)]
+pub struct EntryRow {
+	#[diesel(sql_type = SqlUuid)]
+	pub meme_id: Uuid,
+	#[diesel(sql_type = SqlUuid)]
+	pub user_id: Uuid,
+	#[diesel(sql_type = Text)]
+	pub title: String,
+	#[diesel(sql_type = Text)]
+	pub image_url: String,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub author: Option<String>,
+	#[diesel(sql_type = Timestamptz)]
+	pub entered_at: DateTime<Utc>,
+	/// Upvotes minus downvotes, counting only votes cast before the tournament ended.
+	#[diesel(sql_type = BigInt)]
+	pub votes: i64,
+	/// Average judge score, `None` before any judge scored the entry.
+	#[diesel(sql_type = Nullable<Double>)]
+	pub panel: Option<f64>,
+	/// The viewing judge's own score.
+	#[diesel(sql_type = Nullable<Int2>)]
+	pub my_score: Option<i16>,
+	#[diesel(sql_type = Nullable<Int4>)]
+	pub place: Option<i32>,
+	#[diesel(sql_type = Nullable<BigInt>)]
+	pub prize: Option<i64>,
+}
+
+pub fn create(conn: &mut PgConnection, tournament: &Tournament, judge_ids: &[Uuid]) -> QueryResult<()> {
+	conn.transaction(|conn| {
+		diesel::insert_into(tournaments::table).values(tournament).execute(conn)?;
+		let judges =
+			judge_ids.iter().map(|user_id| (tournament_judges::tournament_id.eq(tournament.id), tournament_judges::user_id.eq(user_id))).collect::<Vec<_>>();
+		diesel::insert_into(tournament_judges::table).values(&judges).on_conflict_do_nothing().execute(conn)?;
+		Ok(())
+	})
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<Tournament>> {
+	tournaments::table.find(id).select(Tournament::as_select()).first(conn).optional()
+}
+
+/// Locks the tournament's row until the end of the transaction, so it is finalized only once.
+pub fn lock(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<Tournament>> {
+	tournaments::table.find(id).select(Tournament::as_select()).for_update().first(conn).optional()
+}
+
+/// Tournaments ending last first, each with its number of entries.
+pub fn list(conn: &mut PgConnection, limit: i64) -> QueryResult<Vec<(Tournament, i64)>> {
+	let tournaments: Vec<Tournament> = tournaments::table.select(Tournament::as_select()).order(tournaments::ends_at.desc()).limit(limit).load(conn)?;
+	let counts: Vec<(Uuid, i64)> = tournament_entries::table
+		.filter(tournament_entries::tournament_id.eq_any(tournaments.iter().map(|tournament| tournament.id)))
+		.group_by(tournament_entries::tournament_id)
+		.select((tournament_entries::tournament_id, count_star()))
+		.load(conn)?;
+	Ok(
+		tournaments
+			.into_iter()
+			.map(|tournament| {
+				let entries = counts.iter().find(|(id, _)| *id == tournament.id).map_or(0, |(_, count)| *count);
+				(tournament, entries)
+			})
+			.collect(),
+	)
+}
+
+/// Ids and usernames of the tournament's judges.
+pub fn judges(conn: &mut PgConnection, id: Uuid) -> QueryResult<Vec<(Uuid, String)>> {
+	tournament_judges::table
+		.inner_join(users::table)
+		.filter(tournament_judges::tournament_id.eq(id))
+		.select((users::id, users::username))
+		.order(users::username)
+		.load(conn)
+}
+
+pub fn is_judge(conn: &mut PgConnection, id: Uuid, user_id: Uuid) -> QueryResult<bool> {
+	diesel::select(diesel::dsl::exists(tournament_judges::table.find((id, user_id)))).get_result(conn)
+}
+
+pub fn has_entered(conn: &mut PgConnection, id: Uuid, user_id: Uuid) -> QueryResult<bool> {
+	diesel::select(diesel::dsl::exists(
+		tournament_entries::table.filter(tournament_entries::tournament_id.eq(id)).filter(tournament_entries::user_id.eq(user_id)),
+	))
+	.get_result(conn)
+}
+
+pub fn add_entry(conn: &mut PgConnection, id: Uuid, meme_id: Uuid, user_id: Uuid) -> QueryResult<TournamentEntry> {
+	let entry = TournamentEntry { meme_id, tournament_id: id, user_id, created_at: Utc::now(), place: None, prize: None };
+	diesel::insert_into(tournament_entries::table).values(&entry).execute(conn)?;
+	Ok(entry)
+}
+
+pub fn find_entry(conn: &mut PgConnection, meme_id: Uuid) -> QueryResult<Option<TournamentEntry>> {
+	tournament_entries::table.find(meme_id).select(TournamentEntry::as_select()).first(conn).optional()
+}
+
+/// Records or replaces a judge's score for an entry.
+pub fn score(conn: &mut PgConnection, meme_id: Uuid, judge_id: Uuid, score: i16) -> QueryResult<()> {
+	diesel::insert_into(tournament_scores::table)
+		.values((tournament_scores::meme_id.eq(meme_id), tournament_scores::judge_id.eq(judge_id), tournament_scores::score.eq(score)))
+		.on_conflict((tournament_scores::meme_id, tournament_scores::judge_id))
+		.do_update()
+		.set((tournament_scores::score.eq(excluded(tournament_scores::score)), tournament_scores::created_at.eq(Utc::now())))
+		.execute(conn)?;
+	Ok(())
+}
+
+/// Entries in final order once finalized, otherwise oldest first. `judge_id` fills in [`EntryRow::my_score`].
+pub fn entries(conn: &mut PgConnection, id: Uuid, judge_id: Option<Uuid>) -> QueryResult<Vec<EntryRow>> {
+	diesel::sql_query(
+		"SELECT e.meme_id, e.user_id, m.title, m.image_url, u.username AS author, e.created_at AS entered_at,
+			(SELECT COALESCE(SUM(v.value), 0) FROM votes v WHERE v.meme_id = e.meme_id AND v.created_at <= t.ends_at)::INT8 AS votes,
+			(SELECT AVG(ts.score)::FLOAT8 FROM tournament_scores ts WHERE ts.meme_id = e.meme_id) AS panel,
+			(SELECT ts.score FROM tournament_scores ts WHERE ts.meme_id = e.meme_id AND ts.judge_id = $2) AS my_score,
+			e.place, e.prize
+		FROM tournament_entries e
+		JOIN tournaments t ON t.id = e.tournament_id
+		JOIN memes m ON m.id = e.meme_id
+		LEFT JOIN users u ON u.id = e.user_id
+		WHERE e.tournament_id = $1 AND m.published_at IS NOT NULL AND m.title IS NOT NULL AND m.image_url IS NOT NULL AND m.hidden_at IS NULL
+		ORDER BY e.place NULLS LAST, e.created_at, e.meme_id",
+	)
+	.bind::<SqlUuid, _>(id)
+	.bind::<Nullable<SqlUuid>, _>(judge_id)
+	.load(conn)
+}
+
+/// Whether the meme is an entry of a tournament that ended by `now`, so votes on it no longer count.
+pub fn voting_closed(conn: &mut PgConnection, meme_id: Uuid, now: DateTime<Utc>) -> QueryResult<bool> {
+	diesel::select(diesel::dsl::exists(
+		tournament_entries::table.inner_join(tournaments::table).filter(tournament_entries::meme_id.eq(meme_id)).filter(tournaments::ends_at.le(now)),
+	))
+	.get_result(conn)
+}
+
+pub fn set_result(conn: &mut PgConnection, meme_id: Uuid, place: i32, prize: i64) -> QueryResult<()> {
+	diesel::update(tournament_entries::table.find(meme_id)).set((tournament_entries::place.eq(place), tournament_entries::prize.eq(prize))).execute(conn)?;
+	Ok(())
+}
+
+pub fn mark_finalized(conn: &mut PgConnection, id: Uuid, now: DateTime<Utc>) -> QueryResult<()> {
+	diesel::update(tournaments::table.find(id)).set(tournaments::finalized_at.eq(now)).execute(conn)?;
+	Ok(())
+}
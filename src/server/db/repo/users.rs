@@ -0,0 +1,42 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::server::db::{models::User, schema::users};
+
+pub fn create(conn: &mut PgConnection, username: &str, password_hash: &str) -> QueryResult<User> {
+	let user = User {
+		id: Uuid::new_v4(),
+		username: username.to_owned(),
+		password_hash: password_hash.to_owned(),
+		created_at: Utc::now(),
+		role: "user".to_owned(),
+		banned_at: None,
+	};
+	diesel::insert_into(users::table).values(&user).execute(conn)?;
+	Ok(user)
+}
+
+pub fn find(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<User>> {
+	users::table.find(id).select(User::as_select()).first(conn).optional()
+}
+
+pub fn find_by_username(conn: &mut PgConnection, username: &str) -> QueryResult<Option<User>> {
+	users::table.filter(users::username.eq(username)).select(User::as_select()).first(conn).optional()
+}
+
+/// Locks the user's row until the end of the transaction, serializing balance checks and rate limits against it.
+pub fn lock(conn: &mut PgConnection, id: Uuid) -> QueryResult<Option<User>> {
+	users::table.find(id).select(User::as_select()).for_update().first(conn).optional()
+}
+
+/// Gives the moderator role to every listed username that has an account, returning how many changed.
+pub fn promote_moderators(conn: &mut PgConnection, usernames: &[String]) -> QueryResult<usize> {
+	diesel::update(users::table.filter(users::username.eq_any(usernames)).filter(users::role.ne(User::MODERATOR)))
+		.set(users::role.eq(User::MODERATOR))
+		.execute(conn)
+}
+
+pub fn ban(conn: &mut PgConnection, id: Uuid) -> QueryResult<bool> {
+	Ok(diesel::update(users::table.find(id)).set(users::banned_at.eq(Utc::now())).execute(conn)? > 0)
+}
@@ -0,0 +1,31 @@
+use chrono::Utc;
+use diesel::{dsl::sum, prelude::*, upsert::excluded};
+use uuid::Uuid;
+
+use crate::server::db::{models::Vote, schema::votes};
+
+/// Records the user's vote on a meme, replacing any earlier vote from the same user. Callers refresh [`super::stats`] afterwards.
+pub fn cast(conn: &mut PgConnection, user_id: Uuid, meme_id: Uuid, value: i16) -> QueryResult<()> {
+	let vote = Vote { user_id, meme_id, value: value.signum(), created_at: Utc::now() };
+	diesel::insert_into(votes::table)
+		.values(&vote)
+		.on_conflict((votes::user_id, votes::meme_id))
+		.do_update()
+		.set((votes::value.eq(excluded(votes::value)), votes::created_at.eq(excluded(votes::created_at))))
+		.execute(conn)?;
+	Ok(())
+}
+
+pub fn retract(conn: &mut PgConnection, user_id: Uuid, meme_id: Uuid) -> QueryResult<()> {
+	diesel::delete(votes::table.find((user_id, meme_id))).execute(conn)?;
+	Ok(())
+}
+
+pub fn score(conn: &mut PgConnection, meme_id: Uuid) -> QueryResult<i64> {
+	Ok(votes::table.filter(votes::meme_id.eq(meme_id)).select(sum(votes::value)).first::<Option<i64>>(conn)?.unwrap_or_default())
+}
+
+/// The user's current vote on each of `meme_ids`, memes they did not vote on are left out.
+pub fn find_for_user(conn: &mut PgConnection, user_id: Uuid, meme_ids: &[Uuid]) -> QueryResult<Vec<(Uuid, i16)>> {
+	votes::table.filter(votes::user_id.eq(user_id)).filter(votes::meme_id.eq_any(meme_ids)).select((votes::meme_id, votes::value)).load(conn)
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use super::{ContentFilter, Submission, Verdict};
+use crate::server::db::{repo, with_conn};
+
+/// Rejects renders whose exact bytes a moderator already deleted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImageBlocklistFilter;
+
+/// The sha256 a render is stored under, read back from its content addressed url.
+pub fn render_hash(image_url: &str) -> Option<&str> {
+	let file = image_url.rsplit('/').next()?;
+	let hash = file.split_once('.').map_or(file, |(stem, _)| stem);
+	(hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())).then_some(hash)
+}
+
+#[async_trait]
+impl ContentFilter for ImageBlocklistFilter {
+	fn name(&self) -> &'static str {
+		"image_blocklist"
+	}
+
+	async fn check(&self, submission: &Submission<'_>) -> anyhow::Result<Verdict> {
+		let hash = hex::encode(Sha256::digest(submission.image));
+		let blocked = with_conn(move |conn| Ok(repo::blocked_images::is_blocked(conn, &hash)?)).await?;
+		Ok(if blocked { Verdict::Reject("this image was removed by moderators".to_owned()) } else { Verdict::Allow })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::server::db::init_test_pool;
+
+	fn submission(image: &[u8]) -> Submission<'_> {
+		Submission { title: "", caption: "", tags: &[], image, dhash: 0 }
+	}
+
+	#[test]
+	fn reads_the_hash_back_from_render_urls() {
+		let hash = hex::encode(Sha256::digest(b"render"));
+		assert_eq!(render_hash(&format!("http://localhost:8080/blobs/renders/{}/{hash}.png", &hash[..2])), Some(hash.as_str()));
+		assert_eq!(render_hash(&hash), Some(hash.as_str()));
+		assert_eq!(render_hash("http://localhost:8080/blobs/uploads/1b4e28ba-2fa1-11d2-883f-0016d3cca427.png"), None);
+		assert_eq!(render_hash(&format!("http://localhost:8080/blobs/renders/{}.png", &hash[1..])), None);
+		assert_eq!(render_hash(""), None);
+	}
+
+	#[tokio::test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	async fn rejects_exactly_the_blocked_bytes() {
+		init_test_pool();
+		let hash = hex::encode(Sha256::digest(b"removed render"));
+		with_conn(move |conn| Ok(repo::blocked_images::block(conn, &hash, None)?)).await.unwrap();
+		assert!(matches!(ImageBlocklistFilter.check(&submission(b"removed render")).await.unwrap(), Verdict::Reject(_)));
+		assert_eq!(ImageBlocklistFilter.check(&submission(b"removed render!")).await.unwrap(), Verdict::Allow);
+	}
+}
@@ -0,0 +1,72 @@
+//! Automatic checks run on every post before it is published. Filters either let a post through, publish it but put it in the
+//! moderation queue, or refuse it outright.
+
+mod image_blocklist;
+mod repost;
+mod word_list;
+
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+pub use self::{
+	image_blocklist::{ImageBlocklistFilter, render_hash},
+	repost::RepostFilter,
+	word_list::WordListFilter,
+};
+
+/// What is about to be published.
+pub struct Submission<'a> {
+	pub title: &'a str,
+	pub caption: &'a str,
+	pub tags: &'a [String],
+	/// The rendered image.
+	pub image: &'a [u8],
+	/// Perceptual hash of `image`.
+	pub dhash: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+	Allow,
+	/// Publish, and file an automatic report so a moderator takes a look.
+	Flag(String),
+	/// Refuse to publish. The message is shown to the author.
+	Reject(String),
+}
+
+#[async_trait]
+pub trait ContentFilter: Send + Sync {
+	/// Short name recorded on the reports the filter files.
+	fn name(&self) -> &'static str;
+	async fn check(&self, submission: &Submission<'_>) -> anyhow::Result<Verdict>;
+}
+
+static CONTENT_FILTERS: OnceLock<Vec<Box<dyn ContentFilter>>> = OnceLock::new();
+
+/// Loads the filters. The image blocklist and repost check always run. Word lists are read from `BLOCKED_WORDS_FILE` and
+/// `FLAGGED_WORDS_FILE` when set, and a list that cannot be read or names no words fails here rather than on the first post. Called
+/// once on server startup.
+pub fn init() -> anyhow::Result<()> {
+	let mut filters: Vec<Box<dyn ContentFilter>> = vec![Box::new(ImageBlocklistFilter), Box::new(RepostFilter)];
+	let words = WordListFilter::from_env().context("invalid word list config")?;
+	if !words.is_empty() {
+		filters.push(Box::new(words));
+	}
+	CONTENT_FILTERS.set(filters).ok();
+	Ok(())
+}
+
+/// Runs every filter. Any rejection wins, otherwise the flags of all filters are combined into one.
+pub async fn screen(submission: &Submission<'_>) -> anyhow::Result<Verdict> {
+	let mut flags = Vec::new();
+	for filter in CONTENT_FILTERS.get().context("content filters are not loaded")? {
+		match filter.check(submission).await? {
+			Verdict::Allow => {},
+			Verdict::Flag(reason) => flags.push(format!("{}: {reason}", filter.name())),
+			Verdict::Reject(reason) => return Ok(Verdict::Reject(reason)),
+		}
+	}
+	Ok(if flags.is_empty() { Verdict::Allow } else { Verdict::Flag(flags.join("; ")) })
+}
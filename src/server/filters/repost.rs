@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+use super::{ContentFilter, Submission, Verdict};
+use crate::server::{
+	db::{repo, with_conn},
+	image_hash::REPOST_DISTANCE,
+};
+
+/// Flags posts whose render looks like one that is already up, so moderators can spot reposts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RepostFilter;
+
+#[async_trait]
+impl ContentFilter for RepostFilter {
+	fn name(&self) -> &'static str {
+		"repost"
+	}
+
+	async fn check(&self, submission: &Submission<'_>) -> anyhow::Result<Verdict> {
+		let dhash = submission.dhash;
+		let similar = with_conn(move |conn| Ok(repo::posts::find_similar(conn, dhash, REPOST_DISTANCE, 1)?)).await?;
+		Ok(match similar.first() {
+			Some(original) => Verdict::Flag(format!("looks like \"{}\" (/m/{})", original.title, original.id)),
+			None => Verdict::Allow,
+		})
+	}
+}
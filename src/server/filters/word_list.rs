@@ -0,0 +1,113 @@
+use anyhow::Context;
+use async_trait::async_trait;
+
+use super::{ContentFilter, Submission, Verdict};
+
+/// Matches whole words and phrases in the title, caption and tags, ignoring case and punctuation.
+#[derive(Clone, Debug, Default)]
+pub struct WordListFilter {
+	blocked: Vec<String>,
+	flagged: Vec<String>,
+}
+
+/// The letter a digit or symbol commonly stands in for, so `h4t3` reads as `hate`.
+fn unleet(c: char) -> char {
+	match c {
+		'0' => 'o',
+		'1' => 'i',
+		'3' => 'e',
+		'4' | '@' => 'a',
+		'5' | '$' => 's',
+		'7' => 't',
+		c => c,
+	}
+}
+
+/// Lowercase words separated by single spaces and padded with one on each side, so `contains(" phrase ")` only matches whole words.
+/// Leetspeak is read as the letters it stands for.
+fn normalize(text: &str) -> String {
+	let text = text.chars().map(unleet).collect::<String>().to_lowercase();
+	let words = text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect::<Vec<_>>();
+	format!(" {} ", words.join(" "))
+}
+
+/// One word or phrase per line. Blank lines and lines starting with `#` are skipped.
+fn parse_list(contents: &str) -> Vec<String> {
+	contents.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).map(normalize).filter(|phrase| !phrase.trim().is_empty()).collect()
+}
+
+fn read_list(var: &str) -> anyhow::Result<Vec<String>> {
+	let Ok(path) = std::env::var(var) else {
+		return Ok(Vec::new());
+	};
+	let contents = std::fs::read_to_string(&path).with_context(|| format!("cannot read {var} at {path}"))?;
+	let list = parse_list(&contents);
+	anyhow::ensure!(!list.is_empty(), "{var} at {path} lists no words");
+	Ok(list)
+}
+
+impl WordListFilter {
+	pub fn from_env() -> anyhow::Result<Self> {
+		Ok(Self { blocked: read_list("BLOCKED_WORDS_FILE")?, flagged: read_list("FLAGGED_WORDS_FILE")? })
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.blocked.is_empty() && self.flagged.is_empty()
+	}
+}
+
+#[async_trait]
+impl ContentFilter for WordListFilter {
+	fn name(&self) -> &'static str {
+		"word_list"
+	}
+
+	async fn check(&self, submission: &Submission<'_>) -> anyhow::Result<Verdict> {
+		let text = normalize(&format!("{} {} {}", submission.title, submission.caption, submission.tags.join(" ")));
+		if self.blocked.iter().any(|phrase| text.contains(phrase.as_str())) {
+			return Ok(Verdict::Reject("the title, caption or tags contain blocked words".to_owned()));
+		}
+		Ok(match self.flagged.iter().find(|phrase| text.contains(phrase.as_str())) {
+			Some(phrase) => Verdict::Flag(format!("contains \"{}\"", phrase.trim())),
+			None => Verdict::Allow,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn submission<'a>(title: &'a str, tags: &'a [String]) -> Submission<'a> {
+		Submission { title, caption: "", tags, image: &[], dhash: 0 }
+	}
+
+	#[test]
+	fn normalizes_case_punctuation_and_leetspeak() {
+		assert_eq!(normalize("Hello,   WORLD!"), " hello world ");
+		assert_eq!(normalize("h4t3 $p33ch"), " hate speech ");
+		assert_eq!(normalize("n00b_l1f3"), " noob life ");
+		assert_eq!(normalize("Ünïcode Straße"), " ünïcode straße ");
+		assert_eq!(normalize("  ...  "), "  ");
+	}
+
+	#[test]
+	fn parses_one_phrase_per_line() {
+		let list = parse_list("# blocked words\n\nSpam\n  hate   speech  \n\t\n#not a word\n!!!\nfr33 m0ney\n");
+		assert_eq!(list, [" spam ", " hate speech ", " free money "]);
+	}
+
+	#[tokio::test]
+	async fn matches_whole_words_and_phrases() {
+		let filter = WordListFilter { blocked: parse_list("free money"), flagged: parse_list("spam") };
+		let filter = &filter;
+		let check = |title| async move { filter.check(&submission(title, &[])).await.unwrap() };
+		assert!(matches!(check("FR33 M0NEY inside").await, Verdict::Reject(_)));
+		assert!(matches!(check("free\nmoney").await, Verdict::Reject(_)));
+		assert_eq!(check("free of money").await, Verdict::Allow);
+		assert_eq!(check("Sp4m!").await, Verdict::Flag("contains \"spam\"".to_owned()));
+		assert_eq!(check("spammer").await, Verdict::Allow);
+		let tags = ["spam".to_owned()];
+		assert!(matches!(filter.check(&submission("clean", &tags)).await.unwrap(), Verdict::Flag(_)));
+	}
+}
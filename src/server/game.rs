@@ -0,0 +1,165 @@
+//! Settles analytics game bets once the memes they were placed on have final engagement numbers.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::{Connection, PgConnection};
+use dioxus::logger::tracing::{error, info};
+
+use crate::{
+	api::points::TransactionKind,
+	game::{
+		percentile::{PercentileWindow, percentile_rank, settle},
+		poker::rng::SeededRng,
+	},
+	server::{
+		db::{
+			models::{GameBet, MemeRecord},
+			pool, repo, with_conn,
+		},
+		ledger::{self, Account, Posting},
+	},
+};
+
+/// Only sources with this many settled memes get played, so percentiles mean something.
+pub const MIN_REFERENCE_SIZE: i64 = 20;
+const SETTLE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const SETTLE_BATCH: i64 = 200;
+
+/// Ranks the meme against the settled memes of its source, records the bet's result and pays a won bet out of the house. Returns
+/// `None` when the meme has not settled yet or the bet was already settled.
+///
+/// Ties are broken with a draw seeded by the bet id, which the player only learns once the window is chosen, and which gives a retry
+/// the same percentile.
+pub fn settle_bet(conn: &mut PgConnection, bet: &GameBet, record: &MemeRecord) -> anyhow::Result<Option<GameBet>> {
+	if !record.is_settled() {
+		return Ok(None);
+	}
+	let (high, low) = bet.id.as_u64_pair();
+	let percentile = record_percentile(conn, record, SeededRng::new(high ^ low).unit())?;
+	let window = PercentileWindow::new(bet.window_low as u8, bet.window_high as u8)?;
+	let payout = settle(window, bet.stake, percentile);
+	conn.transaction(|conn| {
+		let Some(settled) = repo::game_bets::settle(conn, bet.id, percentile, payout, Utc::now())? else {
+			return Ok(None);
+		};
+		if payout > 0 {
+			let posting = Posting::transfer(
+				format!("payout:{}", bet.id),
+				TransactionKind::Payout,
+				format!("Won the bet on {}", record.title),
+				Account::House,
+				Account::Wallet(bet.user_id),
+				payout,
+			);
+			ledger::post(conn, &posting)?.applied()?;
+		}
+		Ok(Some(settled))
+	})
+}
+
+/// Where a settled record's engagement ranks among the settled records of its source, see [`percentile_rank`] for `tie_break`.
+pub fn record_percentile(conn: &mut PgConnection, record: &MemeRecord, tie_break: f64) -> anyhow::Result<f64> {
+	let (below, equal, total) = repo::meme_records::reference_rank(conn, &record.source, record.engagement)?;
+	// The record itself is settled, so the reference is never empty.
+	Ok(percentile_rank(below as u64, equal as u64, total as u64, tie_break).unwrap_or(50.0))
+}
+
+/// Settles every open bet whose meme has settled, returning how many were.
+pub async fn settle_due() -> anyhow::Result<usize> {
+	let mut settled = 0;
+	loop {
+		let (due, count) = with_conn(|conn| {
+			let due = repo::game_bets::list_due(conn, SETTLE_BATCH)?;
+			let mut count = 0;
+			for (bet, record) in &due {
+				count += usize::from(settle_bet(conn, bet, record)?.is_some());
+			}
+			Ok((due.len(), count))
+		})
+		.await?;
+		settled += count;
+		if due < SETTLE_BATCH as usize {
+			return Ok(settled);
+		}
+	}
+}
+
+/// Runs [`settle_due`] in the background for the lifetime of the server. Does nothing without postgres.
+pub fn spawn_settler() {
+	if pool().is_err() {
+		return;
+	}
+	tokio::spawn(async {
+		let mut interval = tokio::time::interval(SETTLE_INTERVAL);
+		loop {
+			interval.tick().await;
+			match settle_due().await {
+				Ok(0) => {},
+				Ok(settled) => info!("settled {settled} game bets"),
+				Err(e) => error!("game bet settlement failed: {e:#}"),
+			}
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::Duration;
+	use uuid::Uuid;
+
+	use super::*;
+	use crate::{game::percentile::engagement, server::db::test_conn};
+
+	fn record(source: &str, score: i64) -> MemeRecord {
+		let now = Utc::now();
+		MemeRecord {
+			id: Uuid::new_v4(),
+			source: source.to_owned(),
+			external_id: format!("settle-{score}"),
+			title: format!("Meme {score}"),
+			image_url: "https://example.com/meme.png".to_owned(),
+			posted_at: now - Duration::hours(48),
+			score,
+			comments: 0,
+			engagement: engagement(score, 0),
+			observed_at: now,
+			created_at: now,
+			dhash: None,
+		}
+	}
+
+	/// Stakes go to the house through the same kind of posting as `place_bet`, won bets come back through `settle_bet`, and the
+	/// books audit after both.
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn settled_bets_keep_the_books_balanced() {
+		let mut conn = test_conn();
+		let source = format!("settle-test-{}", Uuid::new_v4());
+		let records = (0..20).map(|score| repo::meme_records::upsert(&mut conn, &record(&source, score)).unwrap()).collect::<Vec<_>>();
+		let user = repo::users::create(&mut conn, "settle_bets", "hash").unwrap();
+		ledger::open_wallet(&mut conn, user.id).unwrap();
+		let wallet = Account::Wallet(user.id);
+		let house = ledger::balance(&mut conn, Account::House).unwrap();
+
+		let top = PercentileWindow::new(90, 100).unwrap();
+		let won = repo::game_bets::create(&mut conn, user.id, records[19].id, top, 100).unwrap();
+		let lost = repo::game_bets::create(&mut conn, user.id, records[0].id, top, 100).unwrap();
+		for bet in [&won, &lost] {
+			let stake = Posting::transfer(format!("bet:{}:{}", user.id, bet.record_id), TransactionKind::Bet, "Bet", wallet, Account::House, bet.stake);
+			assert_eq!(stake.legs.iter().map(|(_, amount)| amount).sum::<i64>(), 0);
+			ledger::post(&mut conn, &stake).unwrap().applied().unwrap();
+		}
+
+		let settled = settle_bet(&mut conn, &won, &records[19]).unwrap().unwrap();
+		assert!(settled.percentile.is_some_and(|percentile| (95.0..100.0).contains(&percentile)));
+		assert_eq!(settled.payout, Some(top.payout(100)));
+		assert_eq!(settle_bet(&mut conn, &lost, &records[0]).unwrap().unwrap().payout, Some(0));
+		// Settling again pays nothing more.
+		assert!(settle_bet(&mut conn, &won, &records[19]).unwrap().is_none());
+
+		assert_eq!(ledger::balance(&mut conn, wallet).unwrap(), ledger::WELCOME_POINTS - 200 + top.payout(100));
+		assert_eq!(ledger::balance(&mut conn, Account::House).unwrap(), house + 200 - top.payout(100));
+		ledger::audit(&mut conn).unwrap();
+	}
+}
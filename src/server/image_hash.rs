@@ -0,0 +1,242 @@
+//! Perceptual hashes, for spotting the same picture after resizing, recompression or small edits.
+//!
+//! This is dHash: the image is shrunk to 9x8 grayscale pixels and each bit records whether a pixel is brighter than its right
+//! neighbour. Images that look alike differ in few bits, so similarity is the Hamming distance between two hashes.
+//!
+//! Rows stored before hashes were have none, and similarity searches skip them until [`backfill`] hashes them.
+
+use std::{fmt, io::Cursor};
+
+use anyhow::Context;
+use diesel::{PgConnection, QueryResult};
+use dioxus::logger::tracing::warn;
+use image::{ImageReader, Limits, imageops::FilterType};
+use uuid::Uuid;
+
+use super::{
+	db::{repo, with_conn},
+	image_proxy,
+};
+
+const HASH_WIDTH: u32 = 8;
+const HASH_HEIGHT: u32 = 8;
+/// Refuse to decode anything larger, so a small compressed file cannot blow up into gigabytes of pixels.
+const MAX_DECODE_DIMENSION: u32 = 8192;
+const BACKFILL_BATCH: i64 = 100;
+
+/// Templates this close to an upload are offered instead of creating a new one.
+pub const DUPLICATE_TEMPLATE_DISTANCE: u32 = 6;
+/// Posts this close to a new post get it flagged as a possible repost.
+pub const REPOST_DISTANCE: u32 = 4;
+/// Every bit of the hash.
+pub const FULL_MASK: i64 = -1;
+/// The six middle rows of the hash. Captions usually sit in the top and bottom rows, so comparing only these recognizes a template
+/// under its text.
+pub const CAPTION_FREE_MASK: i64 = 0x00ff_ffff_ffff_ff00;
+/// How far a finished meme may be from a template on [`CAPTION_FREE_MASK`] and still count as made from it.
+pub const TEMPLATE_MATCH_DISTANCE: u32 = 8;
+
+/// The 64 bit hash, as the `BIGINT` it is stored as.
+pub fn dhash(bytes: &[u8]) -> anyhow::Result<i64> {
+	let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+	let mut limits = Limits::default();
+	limits.max_image_width = Some(MAX_DECODE_DIMENSION);
+	limits.max_image_height = Some(MAX_DECODE_DIMENSION);
+	reader.limits(limits);
+	let image = reader.decode().context("cannot decode image")?;
+	let pixels = image.resize_exact(HASH_WIDTH + 1, HASH_HEIGHT, FilterType::Triangle).to_luma8();
+	let mut hash = 0_u64;
+	for y in 0..HASH_HEIGHT {
+		for x in 0..HASH_WIDTH {
+			hash = (hash << 1) | u64::from(pixels.get_pixel(x, y)[0] > pixels.get_pixel(x + 1, y)[0]);
+		}
+	}
+	Ok(hash as i64)
+}
+
+/// [`dhash`] on the blocking thread pool, since decoding a full size image takes a while.
+pub async fn dhash_blocking(bytes: Vec<u8>) -> anyhow::Result<i64> {
+	tokio::task::spawn_blocking(move || dhash(&bytes)).await?
+}
+
+/// What [`backfill`] hashed. Images that cannot be loaded or decoded are logged, counted as failed and left without a hash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Backfill {
+	pub templates: usize,
+	pub posts: usize,
+	pub failed: usize,
+}
+
+impl fmt::Display for Backfill {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let Self { templates, posts, failed } = self;
+		write!(f, "{templates} templates hashed, {posts} posts hashed, {failed} failed")
+	}
+}
+
+type ListUnhashed = fn(&mut PgConnection, Option<Uuid>, i64) -> QueryResult<Vec<(Uuid, String)>>;
+type SetHash = fn(&mut PgConnection, Uuid, i64) -> QueryResult<()>;
+
+/// Hashes the templates and published posts that have no hash yet. Running it again only retries the ones that failed.
+pub async fn backfill() -> anyhow::Result<Backfill> {
+	let (templates, failed_templates) = backfill_rows("template", repo::templates::list_unhashed, repo::templates::set_dhash).await?;
+	let (posts, failed_posts) = backfill_rows("post", repo::memes::list_unhashed_posts, repo::memes::set_dhash).await?;
+	Ok(Backfill { templates, posts, failed: failed_templates + failed_posts })
+}
+
+/// Hashes the images `list` returns batch by batch, returning how many were hashed and how many failed.
+async fn backfill_rows(what: &'static str, list: ListUnhashed, set: SetHash) -> anyhow::Result<(usize, usize)> {
+	let (mut hashed, mut failed) = (0, 0);
+	let mut after = None;
+	loop {
+		let batch = with_conn(move |conn| Ok(list(conn, after, BACKFILL_BATCH)?)).await?;
+		for (id, url) in &batch {
+			let bytes = match image_proxy::load(url).await {
+				Ok(bytes) => bytes,
+				Err(e) => {
+					warn!("cannot load the image of {what} {id}: {e:#}");
+					failed += 1;
+					continue;
+				},
+			};
+			match dhash_blocking(bytes).await {
+				Ok(dhash) => {
+					let id = *id;
+					with_conn(move |conn| Ok(set(conn, id, dhash)?)).await?;
+					hashed += 1;
+				},
+				Err(e) => {
+					warn!("cannot hash the image of {what} {id}: {e:#}");
+					failed += 1;
+				},
+			}
+		}
+		// Failed rows stay unhashed, so the next batch starts after them rather than from the top.
+		after = batch.last().map(|(id, _)| *id);
+		if batch.len() < BACKFILL_BATCH as usize {
+			return Ok((hashed, failed));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use image::{DynamicImage, ImageFormat, RgbImage};
+
+	use super::*;
+	use crate::{
+		api::templates::TemplateInfo,
+		server::{
+			blob::{BLOB_STORE, BlobKind, store_image},
+			db::{init_test_pool, repo::templates::NewTemplate, test_conn},
+			render::render,
+		},
+		stores::meme_canvas::MemeDocument,
+	};
+
+	fn distance(a: i64, b: i64) -> u32 {
+		(a ^ b).count_ones()
+	}
+
+	/// A picture with soft shapes in it, like a photo rather than noise.
+	fn picture(width: u32, height: u32, phase: f32) -> DynamicImage {
+		DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+			let (u, v) = (x as f32 / width as f32, y as f32 / height as f32);
+			let light = ((u * 7.0 + phase).sin() * (v * 5.0 - phase).cos() * 0.5 + 0.5) * 255.0;
+			image::Rgb([light as u8, (v * 255.0) as u8, ((1.0 - u) * light) as u8])
+		}))
+	}
+
+	fn encode(image: &DynamicImage, format: ImageFormat) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		image.write_to(&mut Cursor::new(&mut bytes), format).unwrap();
+		bytes
+	}
+
+	#[test]
+	fn identical_images_hash_the_same() {
+		let png = encode(&picture(640, 480, 0.0), ImageFormat::Png);
+		assert_eq!(distance(dhash(&png).unwrap(), dhash(&png.clone()).unwrap()), 0);
+	}
+
+	#[test]
+	fn resized_and_recompressed_copies_stay_close() {
+		let original = picture(640, 480, 0.0);
+		let hash = dhash(&encode(&original, ImageFormat::Png)).unwrap();
+		let copy = encode(&DynamicImage::ImageRgb8(original.resize_exact(320, 240, FilterType::Lanczos3).to_rgb8()), ImageFormat::Jpeg);
+		let copy_hash = dhash(&copy).unwrap();
+		assert!(distance(hash, copy_hash) <= REPOST_DISTANCE, "{} bits apart", distance(hash, copy_hash));
+	}
+
+	#[test]
+	fn unrelated_images_are_far_apart() {
+		let hash = dhash(&encode(&picture(640, 480, 0.0), ImageFormat::Png)).unwrap();
+		let other = dhash(&encode(&picture(640, 480, 2.5).rotate90(), ImageFormat::Png)).unwrap();
+		assert!(distance(hash, other) > 3 * DUPLICATE_TEMPLATE_DISTANCE, "{} bits apart", distance(hash, other));
+	}
+
+	#[test]
+	fn garbage_is_not_hashed() {
+		assert!(dhash(b"not an image").is_err());
+	}
+
+	/// The picture as a template, and as a meme made from it with a caption at the top and the bottom, drawn like the generator does.
+	fn template_and_meme(phase: f32) -> (i64, i64) {
+		let background = picture(640, 480, phase);
+		let template = TemplateInfo { id: Uuid::new_v4(), name: "source".to_owned(), image_url: String::new(), width: 640, height: 480, tags: Vec::new() };
+		let document = MemeDocument::template_layout(&template, vec!["WHEN THE CODE COMPILES".to_owned(), "ON THE FIRST TRY".to_owned()], 640);
+		let meme = DynamicImage::ImageRgba8(render(&document, &background, None));
+		(dhash(&encode(&background, ImageFormat::Png)).unwrap(), dhash(&encode(&meme, ImageFormat::Jpeg)).unwrap())
+	}
+
+	#[test]
+	fn captions_are_masked_out() {
+		let (template, meme) = template_and_meme(0.0);
+		let masked = distance(template & CAPTION_FREE_MASK, meme & CAPTION_FREE_MASK);
+		assert!(masked <= TEMPLATE_MATCH_DISTANCE, "{masked} bits apart under the mask");
+		assert!(distance(template, meme) > masked, "the captions should change the top and bottom rows");
+	}
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn captioned_memes_find_their_template() {
+		let mut conn = test_conn();
+		let (source, meme) = template_and_meme(0.0);
+		let create = |conn: &mut PgConnection, name, dhash| {
+			let template =
+				NewTemplate { name, image_url: "https://example.com/template.png", width: 640, height: 480, tags: &[], dhash: Some(dhash), created_by: None };
+			repo::templates::create(conn, &template).unwrap().id
+		};
+		let source = create(&mut conn, "source", source);
+		for phase in [1.5, 3.0, 4.5] {
+			let (decoy, _) = template_and_meme(phase);
+			create(&mut conn, "decoy", decoy);
+		}
+		let matches = repo::templates::find_similar_masked(&mut conn, meme, CAPTION_FREE_MASK, TEMPLATE_MATCH_DISTANCE, 6).unwrap();
+		assert_eq!(matches.first().map(|row| row.template.id), Some(source));
+		assert!(matches.iter().all(|row| row.template.name == "source"), "decoys matched too");
+	}
+
+	#[tokio::test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	async fn backfill_hashes_templates_stored_without_one() {
+		init_test_pool();
+		let png = encode(&picture(64, 48, 1.0), ImageFormat::Png);
+		let url = BLOB_STORE.public_url(&store_image(BlobKind::Template, png.clone(), None).await.unwrap());
+		let (stored, broken) = with_conn(move |conn| {
+			let unhashed = |image_url| NewTemplate { name: "unhashed", image_url, width: 64, height: 48, tags: &[], dhash: None, created_by: None };
+			let stored = repo::templates::create(conn, &unhashed(&url))?;
+			let broken = repo::templates::create(conn, &unhashed(&BLOB_STORE.public_url("templates/00/missing.png")))?;
+			Ok((stored.id, broken.id))
+		})
+		.await
+		.unwrap();
+
+		let report = backfill().await.unwrap();
+		assert!(report.templates >= 1 && report.failed >= 1, "{report}");
+		let (stored, broken) =
+			with_conn(move |conn| Ok((repo::templates::find(conn, stored)?.unwrap().dhash, repo::templates::find(conn, broken)?.unwrap().dhash))).await.unwrap();
+		assert_eq!(stored, Some(dhash(&png).unwrap()));
+		assert_eq!(broken, None);
+	}
+}
@@ -0,0 +1,312 @@
+//! Copies images from other sites into blob storage so the meme canvas can draw them without being tainted. Hosts that do not send
+//! CORS headers can only be drawn from our own storage, whose urls are same origin (or a bucket set up for cross origin reads).
+//!
+//! The server fetches whatever url it is given, so every hop is checked: only http(s) on the default ports, hosts passing
+//! `IMAGE_PROXY_ALLOWED_HOSTS` and `IMAGE_PROXY_DENIED_HOSTS`, and only public addresses. The connection is pinned to the addresses
+//! that were checked, so a second DNS answer cannot point it back inside the network.
+//!
+//! Copies are kept while they are used, see [`super::blob::cache`], and each user may only ask for so many per minute.
+
+use std::{
+	collections::HashMap,
+	net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+	sync::LazyLock,
+	time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use parking_lot::Mutex;
+use reqwest::{StatusCode, Url, header, redirect};
+use uuid::Uuid;
+
+use super::{
+	blob::{BLOB_STORE, BlobKind, MAX_IMAGE_BYTES, cache, key_from_url},
+	rate_limit::RateLimiter,
+};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_REDIRECTS: usize = 3;
+const MAX_URL_LEN: usize = 2048;
+/// How long a copied url is answered from memory. Far below [`cache::PROXIED_TTL`], so its blob cannot have been swept meanwhile.
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_CACHED_URLS: usize = 10_000;
+/// Urls one user may have copied per minute, cached ones included.
+const REQUESTS_PER_MINUTE: u32 = 20;
+
+static CACHE: LazyLock<Mutex<UrlCache>> = LazyLock::new(Default::default);
+static RATE_LIMIT: LazyLock<RateLimiter<Uuid>> = LazyLock::new(|| RateLimiter::new(REQUESTS_PER_MINUTE, Duration::from_secs(60)));
+
+/// Urls already copied, mapped to their blob key and when they were copied.
+#[derive(Default)]
+struct UrlCache(HashMap<String, (String, Instant)>);
+
+impl UrlCache {
+	fn get(&self, url: &str, now: Instant) -> Option<&str> {
+		self.0.get(url).filter(|(_, copied)| now.duration_since(*copied) < CACHE_TTL).map(|(key, _)| key.as_str())
+	}
+
+	/// When full, drops the expired urls, or the oldest one if none has expired.
+	fn insert(&mut self, url: String, key: String, now: Instant) {
+		if self.0.len() >= MAX_CACHED_URLS && !self.0.contains_key(&url) {
+			self.0.retain(|_, (_, copied)| now.duration_since(*copied) < CACHE_TTL);
+			if self.0.len() >= MAX_CACHED_URLS
+				&& let Some(oldest) = self.0.iter().min_by_key(|(_, (_, copied))| *copied).map(|(url, _)| url.clone())
+			{
+				self.0.remove(&oldest);
+			}
+		}
+		self.0.insert(url, (key, now));
+	}
+}
+
+/// Counts a request by the user, false once they used up this minute's.
+pub fn within_rate_limit(user_id: Uuid) -> bool {
+	RATE_LIMIT.allow(user_id, Instant::now())
+}
+
+/// Comma separated host names, each also matching its subdomains.
+fn read_hosts(var: &str) -> Vec<String> {
+	std::env::var(var).unwrap_or_default().split(',').map(|host| host.trim().trim_start_matches('.').to_lowercase()).filter(|host| !host.is_empty()).collect()
+}
+
+static ALLOWED_HOSTS: LazyLock<Vec<String>> = LazyLock::new(|| read_hosts("IMAGE_PROXY_ALLOWED_HOSTS"));
+static DENIED_HOSTS: LazyLock<Vec<String>> = LazyLock::new(|| read_hosts("IMAGE_PROXY_DENIED_HOSTS"));
+
+fn matches_host(host: &str, patterns: &[String]) -> bool {
+	patterns.iter().any(|pattern| host == pattern || host.strip_suffix(pattern.as_str()).is_some_and(|rest| rest.ends_with('.')))
+}
+
+/// Denied hosts are always refused. When allowed hosts are configured, nothing else is fetched.
+fn host_permitted(host: &str) -> bool {
+	!matches_host(host, &DENIED_HOSTS) && (ALLOWED_HOSTS.is_empty() || matches_host(host, &ALLOWED_HOSTS))
+}
+
+/// False for loopback, private, link local, shared, documentation, benchmarking, multicast and reserved ranges, v4 and v6 alike.
+fn is_public(ip: IpAddr) -> bool {
+	match ip {
+		IpAddr::V4(ip) => is_public_v4(ip),
+		IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+			Some(ip) => is_public_v4(ip),
+			None => is_public_v6(ip),
+		},
+	}
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+	let [a, b, c, _] = ip.octets();
+	!(ip.is_unspecified()
+		|| ip.is_loopback()
+		|| ip.is_private()
+		|| ip.is_link_local()
+		|| ip.is_broadcast()
+		|| ip.is_documentation()
+		|| ip.is_multicast()
+		|| a == 0
+		// 100.64.0.0/10, carrier grade NAT
+		|| (a == 100 && (b & 0xC0) == 64)
+		// 192.0.0.0/24, protocol assignments
+		|| (a == 192 && b == 0 && c == 0)
+		// 198.18.0.0/15, benchmarking
+		|| (a == 198 && (b & 0xFE) == 18)
+		// 240.0.0.0/4, reserved
+		|| a >= 240)
+}
+
+/// The v4 address in two segments of a v6 one.
+fn embedded_v4(high: u16, low: u16) -> Ipv4Addr {
+	Ipv4Addr::from((u32::from(high) << 16) | u32::from(low))
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+	let segments = ip.segments();
+	// 64:ff9b::/96 translates to the v4 address in its last 32 bits.
+	if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+		return is_public_v4(embedded_v4(segments[6], segments[7]));
+	}
+	// 2002::/16, 6to4, tunnels to the v4 address in the 32 bits after the prefix.
+	if segments[0] == 0x2002 && !is_public_v4(embedded_v4(segments[1], segments[2])) {
+		return false;
+	}
+	// 2001::/32, Teredo, carries the server's v4 address after the prefix and the client's inverted in the last 32 bits.
+	if segments[..2] == [0x2001, 0] && !(is_public_v4(embedded_v4(segments[2], segments[3])) && is_public_v4(embedded_v4(!segments[6], !segments[7]))) {
+		return false;
+	}
+	!(ip.is_unspecified()
+		|| ip.is_loopback()
+		|| ip.is_multicast()
+		|| ip.is_unique_local()
+		|| ip.is_unicast_link_local()
+		// ::/96, the deprecated v4 compatible addresses
+		|| segments[..6] == [0; 6]
+		// 2001:db8::/32, documentation
+		|| segments[..2] == [0x2001, 0xdb8])
+}
+
+/// Checks the url and resolves its host, returning the addresses it may be fetched from.
+async fn resolve(url: &Url) -> anyhow::Result<Vec<SocketAddr>> {
+	anyhow::ensure!(matches!(url.scheme(), "http" | "https"), "only http and https images can be proxied");
+	anyhow::ensure!(url.username().is_empty() && url.password().is_none(), "image urls cannot carry credentials");
+	anyhow::ensure!(url.port().is_none(), "image urls cannot name a port");
+	let host = url.host_str().context("image url has no host")?;
+	let port = url.port_or_known_default().context("image url has no port")?;
+	anyhow::ensure!(host_permitted(host), "images from {host} are not allowed");
+	let addrs = tokio::net::lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port))
+		.await
+		.with_context(|| format!("cannot resolve {host}"))?
+		.collect::<Vec<_>>();
+	anyhow::ensure!(!addrs.is_empty(), "cannot resolve {host}");
+	// One private answer refuses the host, rather than trying the public ones and leaving which one is used to chance.
+	anyhow::ensure!(addrs.iter().all(|addr| is_public(addr.ip())), "images from {host} are not allowed");
+	Ok(addrs)
+}
+
+/// Downloads the image, following redirects by hand so each hop goes through [`resolve`]. The bytes are not checked to be an image.
+pub async fn fetch(url: &str) -> anyhow::Result<Vec<u8>> {
+	let mut url = Url::parse(url).context("invalid image url")?;
+	for _ in 0..=MAX_REDIRECTS {
+		let addrs = resolve(&url).await?;
+		let host = url.host_str().context("image url has no host")?;
+		let client = reqwest::Client::builder()
+			.timeout(FETCH_TIMEOUT)
+			.redirect(redirect::Policy::none())
+			.no_proxy()
+			.resolve_to_addrs(host, &addrs)
+			.user_agent(concat!("memetopia-image-proxy/", env!("CARGO_PKG_VERSION")))
+			.build()?;
+		let mut response = client.get(url.clone()).send().await.with_context(|| format!("cannot fetch {url}"))?;
+		if response.status().is_redirection() {
+			let location = response.headers().get(header::LOCATION).and_then(|location| location.to_str().ok()).context("redirect without a location")?;
+			url = url.join(location).context("invalid redirect location")?;
+			continue;
+		}
+		anyhow::ensure!(response.status() == StatusCode::OK, "{url} answered {}", response.status());
+		anyhow::ensure!(response.content_length().is_none_or(|len| len <= MAX_IMAGE_BYTES as u64), "image is too large");
+		let mut bytes = Vec::new();
+		while let Some(chunk) = response.chunk().await? {
+			anyhow::ensure!(bytes.len() + chunk.len() <= MAX_IMAGE_BYTES, "image is too large");
+			bytes.extend_from_slice(&chunk);
+		}
+		return Ok(bytes);
+	}
+	anyhow::bail!("too many redirects")
+}
+
+/// The bytes behind an image url: read from blob storage for our own urls, downloaded with [`fetch`] for any other.
+pub async fn load(url: &str) -> anyhow::Result<Vec<u8>> {
+	match key_from_url(url) {
+		Some(key) => BLOB_STORE.get(key).await?.context("image not found"),
+		None => fetch(url).await,
+	}
+}
+
+/// Copies the image at `url` into blob storage and returns the url to load it from. Urls copied recently are served from the cache.
+pub async fn proxy(url: &str) -> anyhow::Result<String> {
+	anyhow::ensure!(url.len() <= MAX_URL_LEN, "image url is too long");
+	if let Some(key) = CACHE.lock().get(url, Instant::now()) {
+		return Ok(BLOB_STORE.public_url(key));
+	}
+	let bytes = fetch(url).await?;
+	// The declared content type is not trusted, the bytes have to sniff as a supported image.
+	let key = cache::store_image(BlobKind::Proxied, bytes).await?;
+	let public_url = BLOB_STORE.public_url(&key);
+	CACHE.lock().insert(url.to_owned(), key, Instant::now());
+	Ok(public_url)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn only_public_addresses_are_fetched() {
+		let cases: &[(&str, bool)] = &[
+			("93.184.216.34", true),
+			("127.0.0.1", false),
+			("10.1.2.3", false),
+			("172.16.0.1", false),
+			("172.32.0.1", true),
+			("192.168.1.1", false),
+			("169.254.169.254", false),
+			("100.64.0.1", false),
+			("100.127.255.254", false),
+			("100.128.0.1", true),
+			("0.0.0.0", false),
+			("0.1.2.3", false),
+			("255.255.255.255", false),
+			("224.0.0.1", false),
+			("198.18.0.1", false),
+			("240.0.0.1", false),
+			("2606:2800:220:1:248:1893:25c8:1946", true),
+			("::", false),
+			("::1", false),
+			("::ffff:127.0.0.1", false),
+			("::ffff:10.0.0.1", false),
+			("::ffff:93.184.216.34", true),
+			("::127.0.0.1", false),
+			("64:ff9b::7f00:1", false),
+			("64:ff9b::a9fe:a9fe", false),
+			("64:ff9b::5db8:d822", true),
+			("fc00::1", false),
+			("fd12:3456::1", false),
+			("fe80::1", false),
+			("ff02::1", false),
+			("2001:db8::1", false),
+			// 6to4 around 127.0.0.1, 192.168.0.1 and 93.184.216.34
+			("2002:7f00:1::1", false),
+			("2002:c0a8:1::1", false),
+			("2002:5db8:d822::1", true),
+			// Teredo with a private server, then a private client (10.0.0.1 inverted), then both public
+			("2001:0:a00:1::a247:27dd", false),
+			("2001:0:5db8:d822::f5ff:fffe", false),
+			("2001:0:5db8:d822::a247:27dd", true),
+		];
+		for &(ip, public) in cases {
+			assert_eq!(is_public(ip.parse().unwrap()), public, "{ip}");
+		}
+	}
+
+	#[test]
+	fn hosts_match_themselves_and_their_subdomains_only() {
+		let patterns = vec!["imgflip.com".to_owned(), "i.redd.it".to_owned()];
+		let cases = [
+			("imgflip.com", true),
+			("i.imgflip.com", true),
+			("a.b.imgflip.com", true),
+			("i.redd.it", true),
+			("evil-imgflip.com", false),
+			("evilimgflip.com", false),
+			("imgflip.com.evil.com", false),
+			("imgflip.co", false),
+			("redd.it", false),
+			("xi.redd.it", false),
+			("", false),
+		];
+		for (host, matches) in cases {
+			assert_eq!(matches_host(host, &patterns), matches, "{host}");
+		}
+		assert!(!matches_host("imgflip.com", &[]));
+	}
+
+	#[test]
+	fn url_cache_expires_and_stays_bounded() {
+		let start = Instant::now();
+		let mut cache = UrlCache::default();
+		cache.insert("a".to_owned(), "proxied/a".to_owned(), start);
+		assert_eq!(cache.get("a", start + CACHE_TTL / 2), Some("proxied/a"));
+		assert_eq!(cache.get("a", start + CACHE_TTL), None);
+
+		let mut cache = UrlCache::default();
+		for i in 0..MAX_CACHED_URLS {
+			cache.insert(i.to_string(), i.to_string(), start + Duration::from_millis(i as u64));
+		}
+		let later = start + Duration::from_secs(60);
+		cache.insert("new".to_owned(), "new".to_owned(), later);
+		assert_eq!(cache.0.len(), MAX_CACHED_URLS);
+		assert_eq!(cache.get("0", later), None, "the oldest url makes room");
+		assert!(cache.get("1", later).is_some());
+		assert!(cache.get("new", later).is_some());
+
+		cache.insert("newer".to_owned(), "newer".to_owned(), start + CACHE_TTL + Duration::from_secs(30));
+		assert!(cache.0.len() < MAX_CACHED_URLS / 2, "expired urls are dropped together");
+	}
+}
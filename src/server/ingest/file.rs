@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use super::{MemeSource, RawMeme};
+
+/// A JSON or CSV export on disk, told apart by extension.
+pub struct FileSource {
+	path: PathBuf,
+}
+
+impl FileSource {
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+#[async_trait]
+impl MemeSource for FileSource {
+	fn name(&self) -> String {
+		self.path.display().to_string()
+	}
+
+	async fn fetch(&self) -> anyhow::Result<Vec<RawMeme>> {
+		let bytes = tokio::fs::read(&self.path).await.with_context(|| format!("cannot read {}", self.path.display()))?;
+		match self.path.extension().and_then(|extension| extension.to_str()) {
+			Some("json") => parse_json(&bytes),
+			Some("csv") => parse_csv(&bytes),
+			_ => anyhow::bail!("{} is neither .json nor .csv", self.path.display()),
+		}
+	}
+}
+
+pub(super) fn parse_json(bytes: &[u8]) -> anyhow::Result<Vec<RawMeme>> {
+	serde_json::from_slice(bytes).context("invalid meme json")
+}
+
+/// Empty cells read as missing, so `observed_at` can be left blank.
+pub(super) fn parse_csv(bytes: &[u8]) -> anyhow::Result<Vec<RawMeme>> {
+	csv::Reader::from_reader(bytes).deserialize().collect::<Result<_, _>>().context("invalid meme csv")
+}
@@ -0,0 +1,100 @@
+use std::{
+	net::SocketAddr,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use anyhow::Context;
+use dioxus::logger::tracing::warn;
+use tokio::{
+	io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+	net::{TcpListener, TcpStream},
+	task::JoinHandle,
+};
+
+/// Serves the files of a directory over plain HTTP on a free localhost port, so [`super::HttpSource`] and image downloads can be
+/// exercised without network access. Only `GET` of files inside the directory is answered. Stops when dropped.
+pub struct FixtureServer {
+	addr: SocketAddr,
+	task: JoinHandle<()>,
+}
+
+impl FixtureServer {
+	pub async fn start(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+		let dir = dir.into();
+		let dir = Arc::new(dir.canonicalize().with_context(|| format!("cannot open {}", dir.display()))?);
+		let listener = TcpListener::bind("127.0.0.1:0").await?;
+		let addr = listener.local_addr()?;
+		let task = tokio::spawn(async move {
+			loop {
+				let Ok((stream, _)) = listener.accept().await else {
+					continue;
+				};
+				let dir = dir.clone();
+				tokio::spawn(async move {
+					if let Err(e) = respond(stream, &dir).await {
+						warn!("fixture request failed: {e:#}");
+					}
+				});
+			}
+		});
+		Ok(Self { addr, task })
+	}
+
+	/// The url `path`, relative to the served directory, is reachable at.
+	pub fn url(&self, path: &str) -> String {
+		format!("http://{}/{}", self.addr, path.trim_start_matches('/'))
+	}
+}
+
+impl Drop for FixtureServer {
+	fn drop(&mut self) {
+		self.task.abort();
+	}
+}
+
+async fn respond(mut stream: TcpStream, dir: &Path) -> anyhow::Result<()> {
+	let mut reader = BufReader::new(&mut stream);
+	let mut request_line = String::new();
+	reader.read_line(&mut request_line).await?;
+	loop {
+		let mut header = String::new();
+		if reader.read_line(&mut header).await? == 0 || header.trim_end().is_empty() {
+			break;
+		}
+	}
+	let mut parts = request_line.split_whitespace();
+	let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+	let file = match (method, resolve(dir, target)) {
+		("GET", Some(path)) => tokio::fs::read(&path).await.ok().map(|body| (content_type(&path), body)),
+		_ => None,
+	};
+	let (status, content_type, body) = match file {
+		Some((content_type, body)) => ("200 OK", content_type, body),
+		None if method != "GET" => ("405 Method Not Allowed", "text/plain", Vec::new()),
+		None => ("404 Not Found", "text/plain", Vec::new()),
+	};
+	let head = format!("HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+	stream.write_all(head.as_bytes()).await?;
+	stream.write_all(&body).await?;
+	Ok(stream.shutdown().await?)
+}
+
+/// Maps a request target to a file under `dir`, refusing anything that could step outside it.
+fn resolve(dir: &Path, target: &str) -> Option<PathBuf> {
+	let path = target.split(['?', '#']).next()?.trim_start_matches('/');
+	let safe = !path.is_empty() && path.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != ".." && !segment.contains('\\'));
+	safe.then(|| dir.join(path))
+}
+
+fn content_type(path: &Path) -> &'static str {
+	match path.extension().and_then(|extension| extension.to_str()) {
+		Some("json") => "application/json",
+		Some("csv") => "text/csv",
+		Some("png") => "image/png",
+		Some("jpg" | "jpeg") => "image/jpeg",
+		Some("gif") => "image/gif",
+		Some("webp") => "image/webp",
+		_ => "application/octet-stream",
+	}
+}
@@ -0,0 +1,42 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use reqwest::Url;
+
+use super::{
+	MemeSource, RawMeme,
+	file::{parse_csv, parse_json},
+};
+
+/// A JSON export served over HTTP, or CSV when the url path ends in `.csv`. Relative image urls are resolved against the export's url,
+/// which lets a `FixtureServer` serve a feed and its images without knowing its own port.
+pub struct HttpSource {
+	url: Url,
+	client: reqwest::Client,
+}
+
+impl HttpSource {
+	pub fn new(client: reqwest::Client, url: &str) -> anyhow::Result<Self> {
+		let url = Url::parse(url).with_context(|| format!("invalid url {url}"))?;
+		anyhow::ensure!(matches!(url.scheme(), "http" | "https"), "{url} is not http(s)");
+		Ok(Self { url, client })
+	}
+}
+
+#[async_trait]
+impl MemeSource for HttpSource {
+	fn name(&self) -> String {
+		self.url.to_string()
+	}
+
+	async fn fetch(&self) -> anyhow::Result<Vec<RawMeme>> {
+		let bytes = self.client.get(self.url.clone()).send().await?.error_for_status()?.bytes().await?;
+		let rows = if self.url.path().ends_with(".csv") { parse_csv(&bytes)? } else { parse_json(&bytes)? };
+		rows
+			.into_iter()
+			.map(|row| {
+				let image_url = self.url.join(&row.image_url).with_context(|| format!("invalid image url {}", row.image_url))?;
+				Ok(RawMeme { image_url: image_url.into(), ..row })
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,292 @@
+//! Gathers memes with their engagement metrics from other sites for the analytics game. A [`MemeSource`] yields raw rows and
+//! [`import`] normalizes them into `meme_records`, skipping images that are already in the catalog.
+
+mod file;
+#[cfg(any(test, feature = "fixtures"))]
+mod fixture;
+mod http;
+
+use std::{fmt, time::Duration};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dioxus::logger::tracing::warn;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[cfg(any(test, feature = "fixtures"))]
+pub use self::fixture::FixtureServer;
+pub use self::{file::FileSource, http::HttpSource};
+use crate::{
+	game::percentile::engagement,
+	server::{
+		blob::MAX_IMAGE_BYTES,
+		db::{models::MemeRecord, repo, with_conn},
+		image_hash::{REPOST_DISTANCE, dhash_blocking},
+	},
+};
+
+const MAX_SOURCE_LEN: usize = 64;
+const MAX_EXTERNAL_ID_LEN: usize = 128;
+const MAX_TITLE_LEN: usize = 300;
+const MAX_URL_LEN: usize = 2048;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// One meme as a source reports it. JSON sources give an array of these, CSV sources a header row with the same field names.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RawMeme {
+	/// Where the meme was posted, e.g. `reddit/r/memes`.
+	pub source: String,
+	/// The meme's id on the source, unique within it.
+	pub external_id: String,
+	pub title: String,
+	pub image_url: String,
+	pub posted_at: DateTime<Utc>,
+	pub score: i64,
+	pub comments: i64,
+	/// When `score` and `comments` were captured, the time of the import when missing.
+	#[serde(default)]
+	pub observed_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait MemeSource: Send + Sync {
+	/// Names the source in logs and import reports.
+	fn name(&self) -> String;
+
+	async fn fetch(&self) -> anyhow::Result<Vec<RawMeme>>;
+}
+
+/// What an [`import`] did with the rows it got.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+	pub inserted: usize,
+	/// Known rows whose metrics were refreshed.
+	pub updated: usize,
+	/// Known rows whose metrics were not newer than the stored ones.
+	pub unchanged: usize,
+	/// New rows whose image is already in the catalog.
+	pub duplicates: usize,
+	pub invalid: usize,
+	/// Rows that could not be stored, mostly because their image could not be downloaded.
+	pub failed: usize,
+}
+
+impl fmt::Display for ImportReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let Self { inserted, updated, unchanged, duplicates, invalid, failed } = self;
+		write!(f, "{inserted} inserted, {updated} updated, {unchanged} unchanged, {duplicates} duplicates, {invalid} invalid, {failed} failed")
+	}
+}
+
+enum Stored {
+	Inserted,
+	Updated,
+	Unchanged,
+	Duplicate,
+}
+
+/// The client sources and image downloads share.
+pub fn http_client() -> anyhow::Result<reqwest::Client> {
+	Ok(reqwest::Client::builder().timeout(FETCH_TIMEOUT).user_agent(concat!("memetopia-ingest/", env!("CARGO_PKG_VERSION"))).build()?)
+}
+
+/// Cleans up a raw row into a record ready to store, rejecting rows the game cannot use.
+pub fn normalize(raw: &RawMeme, now: DateTime<Utc>) -> anyhow::Result<MemeRecord> {
+	let source = raw.source.trim().to_lowercase();
+	anyhow::ensure!(
+		!source.is_empty() && source.len() <= MAX_SOURCE_LEN && source.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')),
+		"invalid source {:?}",
+		raw.source
+	);
+	let external_id = raw.external_id.trim();
+	anyhow::ensure!(!external_id.is_empty() && external_id.len() <= MAX_EXTERNAL_ID_LEN, "invalid external id {:?}", raw.external_id);
+	let title = raw.title.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(MAX_TITLE_LEN).collect::<String>();
+	anyhow::ensure!(!title.is_empty(), "{external_id} has no title");
+	let image_url = raw.image_url.trim();
+	anyhow::ensure!(image_url.starts_with("https://") || image_url.starts_with("http://"), "{external_id} image url must be http(s)");
+	anyhow::ensure!(image_url.len() <= MAX_URL_LEN, "{external_id} image url too long");
+	anyhow::ensure!(raw.comments >= 0, "{external_id} has a negative comment count");
+	let observed_at = raw.observed_at.unwrap_or(now).min(now);
+	anyhow::ensure!(raw.posted_at <= observed_at, "{external_id} was posted after it was observed");
+	Ok(MemeRecord {
+		id: Uuid::new_v4(),
+		source,
+		external_id: external_id.to_owned(),
+		title,
+		image_url: image_url.to_owned(),
+		posted_at: raw.posted_at,
+		score: raw.score,
+		comments: raw.comments,
+		engagement: engagement(raw.score, raw.comments),
+		observed_at,
+		created_at: now,
+		dhash: None,
+	})
+}
+
+/// Fetches everything from `source` and stores it. Rows imported before get their metrics refreshed, new rows have their image
+/// downloaded and hashed and are skipped when a record with the same picture exists.
+pub async fn import(source: &dyn MemeSource, client: &reqwest::Client) -> anyhow::Result<ImportReport> {
+	let name = source.name();
+	let rows = source.fetch().await.with_context(|| format!("cannot fetch {name}"))?;
+	let now = Utc::now();
+	let mut report = ImportReport::default();
+	for raw in rows {
+		let record = match normalize(&raw, now) {
+			Ok(record) => record,
+			Err(e) => {
+				warn!("{name}: skipping row: {e:#}");
+				report.invalid += 1;
+				continue;
+			},
+		};
+		let external_id = record.external_id.clone();
+		match store(record, client).await {
+			Ok(Stored::Inserted) => report.inserted += 1,
+			Ok(Stored::Updated) => report.updated += 1,
+			Ok(Stored::Unchanged) => report.unchanged += 1,
+			Ok(Stored::Duplicate) => report.duplicates += 1,
+			Err(e) => {
+				warn!("{name}: cannot store {external_id}: {e:#}");
+				report.failed += 1;
+			},
+		}
+	}
+	Ok(report)
+}
+
+async fn store(record: MemeRecord, client: &reqwest::Client) -> anyhow::Result<Stored> {
+	let (source, external_id) = (record.source.clone(), record.external_id.clone());
+	let existing = with_conn(move |conn| Ok(repo::meme_records::find_by_external_id(conn, &source, &external_id)?)).await?;
+	if let Some(existing) = existing {
+		if existing.observed_at >= record.observed_at {
+			return Ok(Stored::Unchanged);
+		}
+		with_conn(move |conn| Ok(repo::meme_records::upsert(conn, &record)?)).await?;
+		return Ok(Stored::Updated);
+	}
+	let bytes = download_image(client, &record.image_url).await?;
+	let dhash = dhash_blocking(bytes).await?;
+	with_conn(move |conn| {
+		if repo::meme_records::find_similar(conn, dhash, REPOST_DISTANCE)?.is_some() {
+			return Ok(Stored::Duplicate);
+		}
+		repo::meme_records::upsert(conn, &MemeRecord { dhash: Some(dhash), ..record })?;
+		Ok(Stored::Inserted)
+	})
+	.await
+}
+
+/// Downloads an image, giving up as soon as it grows past [`MAX_IMAGE_BYTES`].
+async fn download_image(client: &reqwest::Client, url: &str) -> anyhow::Result<Vec<u8>> {
+	let mut response = client.get(url).send().await?.error_for_status()?;
+	anyhow::ensure!(response.content_length().is_none_or(|len| len <= MAX_IMAGE_BYTES as u64), "image is too large");
+	let mut bytes = Vec::new();
+	while let Some(chunk) = response.chunk().await? {
+		anyhow::ensure!(bytes.len() + chunk.len() <= MAX_IMAGE_BYTES, "image is too large");
+		bytes.extend_from_slice(&chunk);
+	}
+	Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::TimeZone;
+	use diesel::{QueryDsl, RunQueryDsl, TextExpressionMethods};
+
+	use super::*;
+	use crate::server::db::{self, schema::meme_records};
+
+	const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/ingest");
+
+	fn raw() -> RawMeme {
+		RawMeme {
+			source: " Reddit/r/Memes ".to_owned(),
+			external_id: " abc ".to_owned(),
+			title: "  Such \n wow  ".to_owned(),
+			image_url: "https://example.com/meme.png".to_owned(),
+			posted_at: Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap(),
+			score: 10,
+			comments: 2,
+			observed_at: None,
+		}
+	}
+
+	#[test]
+	fn normalize_cleans_up_and_rejects_unusable_rows() {
+		let now = Utc.with_ymd_and_hms(2026, 10, 3, 12, 0, 0).unwrap();
+		let record = normalize(&raw(), now).unwrap();
+		assert_eq!((record.source.as_str(), record.external_id.as_str(), record.title.as_str()), ("reddit/r/memes", "abc", "Such wow"));
+		assert_eq!((record.observed_at, record.engagement), (now, engagement(10, 2)));
+		// Metrics cannot be observed in the future.
+		assert_eq!(normalize(&RawMeme { observed_at: Some(now + chrono::Duration::days(1)), ..raw() }, now).unwrap().observed_at, now);
+
+		let rejected = [
+			RawMeme { source: "reddit memes".to_owned(), ..raw() },
+			RawMeme { external_id: " ".to_owned(), ..raw() },
+			RawMeme { title: " \t ".to_owned(), ..raw() },
+			RawMeme { image_url: "images/meme.png".to_owned(), ..raw() },
+			RawMeme { image_url: "ftp://example.com/meme.png".to_owned(), ..raw() },
+			RawMeme { comments: -1, ..raw() },
+			RawMeme { posted_at: now + chrono::Duration::hours(1), ..raw() },
+		];
+		for raw in rejected {
+			assert!(normalize(&raw, now).is_err(), "{raw:?}");
+		}
+	}
+
+	#[tokio::test]
+	async fn file_sources_parse_json_and_csv() {
+		let feed = FileSource::new(format!("{FIXTURES}/feed.json")).fetch().await.unwrap();
+		assert_eq!(feed.iter().map(|raw| raw.external_id.as_str()).collect::<Vec<_>>(), ["f1", "f2", "f3"]);
+		assert_eq!(feed[2].observed_at, None);
+
+		let new = FileSource::new(format!("{FIXTURES}/new.csv")).fetch().await.unwrap();
+		assert_eq!(new.len(), 4);
+		assert_eq!((new[0].score, new[0].comments, new[0].observed_at.is_some()), (150, 20, true));
+		// A blank cell is a missing value.
+		assert_eq!(new[1].observed_at, None);
+		// Exports name their images relative to themselves, which only works over HTTP.
+		assert!(new.iter().all(|raw| normalize(raw, Utc::now()).is_err()));
+
+		assert!(FileSource::new(format!("{FIXTURES}/images/doge.png")).fetch().await.is_err());
+		assert!(FileSource::new(format!("{FIXTURES}/missing.json")).fetch().await.is_err());
+	}
+
+	#[tokio::test]
+	async fn http_sources_resolve_images_against_the_export() {
+		let server = FixtureServer::start(FIXTURES).await.unwrap();
+		let client = http_client().unwrap();
+		let feed = HttpSource::new(client.clone(), &server.url("feed.json")).unwrap().fetch().await.unwrap();
+		assert_eq!(feed[0].image_url, server.url("images/doge.png"));
+		let new = HttpSource::new(client.clone(), &server.url("new.csv")).unwrap().fetch().await.unwrap();
+		assert_eq!(new.iter().filter(|raw| normalize(raw, Utc::now()).is_ok()).count(), 3);
+
+		assert!(HttpSource::new(client.clone(), &server.url("missing.json")).unwrap().fetch().await.is_err());
+		assert!(HttpSource::new(client.clone(), "file:///etc/passwd").is_err());
+		assert!(download_image(&client, &server.url("../Cargo.toml")).await.is_err());
+	}
+
+	/// The whole pipeline over the fixtures: the repost of the doge is caught by its hash, the missing image fails, and importing
+	/// again changes nothing.
+	#[tokio::test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	async fn import_skips_reposts_and_known_rows() {
+		db::init_test_pool();
+		with_conn(|conn| Ok(diesel::delete(meme_records::table.filter(meme_records::source.like("fixture/%"))).execute(conn)?)).await.unwrap();
+		let server = FixtureServer::start(FIXTURES).await.unwrap();
+		let client = http_client().unwrap();
+		let feed = HttpSource::new(client.clone(), &server.url("feed.json")).unwrap();
+		let new = HttpSource::new(client.clone(), &server.url("new.csv")).unwrap();
+
+		assert_eq!(import(&feed, &client).await.unwrap(), ImportReport { inserted: 2, duplicates: 1, ..ImportReport::default() });
+		assert_eq!(import(&new, &client).await.unwrap(), ImportReport { inserted: 2, invalid: 1, failed: 1, ..ImportReport::default() });
+		assert_eq!(import(&feed, &client).await.unwrap(), ImportReport { unchanged: 2, duplicates: 1, ..ImportReport::default() });
+
+		let stored = with_conn(|conn| Ok(repo::meme_records::find_by_external_id(conn, "fixture/hot", "f2")?)).await.unwrap().unwrap();
+		assert_eq!((stored.title.as_str(), stored.image_url), ("Tests passing on the first try", server.url("images/drake.png")));
+		assert!(stored.dhash.is_some());
+	}
+}
@@ -0,0 +1,303 @@
+//! Double-entry bookkeeping for points. Points are not withdrawable: they come out of the mint, move between accounts through
+//! balanced transactions and only ever buy bets, poker buy-ins and the like. Every transaction carries an idempotency key naming the
+//! operation it records, so retrying an operation that may or may not have gone through is always safe.
+
+use chrono::Utc;
+use diesel::{Connection, PgConnection};
+use dioxus::logger::tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+	api::points::TransactionKind,
+	server::db::{
+		models::{LedgerAccount, LedgerTransaction},
+		pool, repo, with_conn,
+	},
+};
+
+/// Points every player starts with.
+pub const WELCOME_POINTS: i64 = 1000;
+const SEAT_KIND: &str = "seat";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Account {
+	/// Where new points come from. Its balance is minus every point issued.
+	Mint,
+	/// Banks the analytics game, taking stakes and paying out won bets.
+	House,
+	/// Takes its share of poker pots. Points never leave it.
+	Rake,
+	/// A player's points.
+	Wallet(Uuid),
+	/// A player's chips at a poker table, between hands.
+	Seat { table: Uuid, user: Uuid },
+	/// A tournament's prizes until they are paid out.
+	Pool(Uuid),
+}
+
+impl Account {
+	/// The kind as stored in `ledger_accounts.kind`.
+	pub fn kind(self) -> &'static str {
+		match self {
+			Self::Mint => "mint",
+			Self::House => "house",
+			Self::Rake => "rake",
+			Self::Wallet(_) => "wallet",
+			Self::Seat { .. } => SEAT_KIND,
+			Self::Pool(_) => "pool",
+		}
+	}
+
+	fn user_id(self) -> Option<Uuid> {
+		match self {
+			Self::Wallet(user) | Self::Seat { user, .. } => Some(user),
+			Self::Mint | Self::House | Self::Rake | Self::Pool(_) => None,
+		}
+	}
+
+	fn scope(self) -> Option<Uuid> {
+		match self {
+			Self::Seat { table, .. } => Some(table),
+			Self::Pool(tournament) => Some(tournament),
+			Self::Mint | Self::House | Self::Rake | Self::Wallet(_) => None,
+		}
+	}
+
+	/// Only the mint and the house may go below zero.
+	pub fn may_overdraw(self) -> bool {
+		matches!(self, Self::Mint | Self::House)
+	}
+}
+
+/// A balanced set of movements between accounts, recorded once under its idempotency key.
+#[derive(Clone, Debug)]
+pub struct Posting {
+	pub key: String,
+	pub kind: TransactionKind,
+	/// What the transaction reads as in account histories.
+	pub memo: String,
+	/// Amounts booked on each account, positive when points come in. They add up to zero.
+	pub legs: Vec<(Account, i64)>,
+}
+
+impl Posting {
+	/// Moves `amount` points from one account to another.
+	pub fn transfer(key: impl Into<String>, kind: TransactionKind, memo: impl Into<String>, from: Account, to: Account, amount: i64) -> Self {
+		Self { key: key.into(), kind, memo: memo.into(), legs: vec![(from, -amount), (to, amount)] }
+	}
+}
+
+/// What [`post`] did.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Posted {
+	Applied,
+	/// A transaction with the same key was posted before. Nothing changed.
+	Duplicate,
+	/// The account cannot cover its leg. Nothing changed.
+	Insufficient(Account),
+}
+
+impl Posted {
+	/// For postings that can only fail through a bug, like paying out of an account known to hold the points.
+	pub fn applied(self) -> anyhow::Result<()> {
+		match self {
+			Self::Applied | Self::Duplicate => Ok(()),
+			Self::Insufficient(account) => anyhow::bail!("{account:?} cannot cover the posting"),
+		}
+	}
+}
+
+/// Records the posting unless its key was posted before. Legs of zero are dropped and a posting without legs records nothing.
+pub fn post(conn: &mut PgConnection, posting: &Posting) -> anyhow::Result<Posted> {
+	let legs = posting.legs.iter().copied().filter(|(_, amount)| *amount != 0).collect::<Vec<_>>();
+	anyhow::ensure!(legs.iter().map(|(_, amount)| amount).sum::<i64>() == 0, "posting {} does not balance", posting.key);
+	anyhow::ensure!(
+		legs.iter().enumerate().all(|(i, (account, _))| legs[..i].iter().all(|(other, _)| other != account)),
+		"posting {} books an account twice",
+		posting.key
+	);
+	if legs.is_empty() {
+		return Ok(Posted::Applied);
+	}
+	conn.transaction(|conn| {
+		if repo::ledger::find_transaction(conn, &posting.key)?.is_some() {
+			return Ok(Posted::Duplicate);
+		}
+		let mut ids = Vec::with_capacity(legs.len());
+		for (account, _) in &legs {
+			ids.push(repo::ledger::account(conn, account.kind(), account.user_id(), account.scope())?.id);
+		}
+		let locked = repo::ledger::lock_accounts(conn, &ids)?;
+		let mut booked = Vec::with_capacity(legs.len());
+		for ((account, amount), id) in legs.iter().zip(&ids) {
+			let row = locked.iter().find(|row| row.id == *id).ok_or_else(|| anyhow::anyhow!("ledger account {id} vanished"))?;
+			if !account.may_overdraw() && row.balance + amount < 0 {
+				return Ok(Posted::Insufficient(*account));
+			}
+			booked.push((row, *amount));
+		}
+		let transaction = LedgerTransaction {
+			id: Uuid::new_v4(),
+			idempotency_key: posting.key.clone(),
+			kind: posting.kind.to_string(),
+			memo: posting.memo.clone(),
+			created_at: Utc::now(),
+		};
+		// A concurrent post of the same key got in while we waited on the locks.
+		if !repo::ledger::insert_transaction(conn, &transaction)? {
+			return Ok(Posted::Duplicate);
+		}
+		for (row, amount) in booked {
+			repo::ledger::insert_entry(conn, transaction.id, row, amount)?;
+		}
+		Ok(Posted::Applied)
+	})
+}
+
+/// The account, opened empty if nothing was ever booked on it.
+pub fn account(conn: &mut PgConnection, account: Account) -> anyhow::Result<LedgerAccount> {
+	Ok(repo::ledger::account(conn, account.kind(), account.user_id(), account.scope())?)
+}
+
+pub fn balance(conn: &mut PgConnection, account: Account) -> anyhow::Result<i64> {
+	Ok(repo::ledger::find_account(conn, account.kind(), account.user_id(), account.scope())?.map_or(0, |account| account.balance))
+}
+
+/// Grants the player their [`WELCOME_POINTS`], once.
+pub fn open_wallet(conn: &mut PgConnection, user_id: Uuid) -> anyhow::Result<()> {
+	post(
+		conn,
+		&Posting::transfer(format!("welcome:{user_id}"), TransactionKind::Welcome, "Welcome points", Account::Mint, Account::Wallet(user_id), WELCOME_POINTS),
+	)?
+	.applied()
+}
+
+/// Checks that every transaction balances and that the balances are the sums of their entries, and so add up to zero.
+pub fn audit(conn: &mut PgConnection) -> anyhow::Result<()> {
+	let unbalanced = repo::ledger::unbalanced_transactions(conn)?;
+	anyhow::ensure!(unbalanced.is_empty(), "ledger transactions do not balance: {unbalanced:?}");
+	let drifted = repo::ledger::drifted_accounts(conn)?;
+	anyhow::ensure!(drifted.is_empty(), "ledger account balances differ from their entries: {drifted:?}");
+	let total = repo::ledger::total_balance(conn)?;
+	anyhow::ensure!(total == 0, "ledger balances add up to {total}");
+	Ok(())
+}
+
+/// Runs [`audit`] on startup. A failed audit is logged rather than stopping the server, points only buy games and the books
+/// can be repaired while it runs. Does nothing without postgres.
+pub async fn audit_books() {
+	if pool().is_err() {
+		return;
+	}
+	if let Err(e) = with_conn(audit).await {
+		error!("ledger audit failed: {e:#}");
+	}
+}
+
+/// Pays chips left on poker seats back to their players. Tables live in memory, so seats still funded on startup belong to tables
+/// that went down with the last server. Does nothing without postgres.
+pub async fn refund_seats() -> anyhow::Result<()> {
+	if pool().is_err() {
+		return Ok(());
+	}
+	let refunded = with_conn(|conn| {
+		let seats = repo::ledger::funded(conn, SEAT_KIND)?;
+		for seat in &seats {
+			let (Some(user), Some(table)) = (seat.user_id, seat.scope) else { continue };
+			// Every opened table gets a new id, so a seat is never bought into again once its table is gone.
+			let posting = Posting::transfer(
+				format!("refund:{}", seat.id),
+				TransactionKind::Refund,
+				"Chips left at a closed table",
+				Account::Seat { table, user },
+				Account::Wallet(user),
+				seat.balance,
+			);
+			post(conn, &posting)?.applied()?;
+		}
+		Ok(seats.len())
+	})
+	.await?;
+	if refunded > 0 {
+		info!("refunded {refunded} abandoned poker seats");
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::server::db::test_conn;
+
+	fn player(conn: &mut PgConnection, username: &str) -> Uuid {
+		let user = repo::users::create(conn, username, "hash").unwrap();
+		open_wallet(conn, user.id).unwrap();
+		user.id
+	}
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn a_repeated_key_posts_once() {
+		let mut conn = test_conn();
+		let (alice, bob) = (player(&mut conn, "ledger_repeat_a"), player(&mut conn, "ledger_repeat_b"));
+		let posting = Posting::transfer("test:repeat", TransactionKind::Bet, "Repeated", Account::Wallet(alice), Account::Wallet(bob), 300);
+		assert_eq!(post(&mut conn, &posting).unwrap(), Posted::Applied);
+		assert_eq!(post(&mut conn, &posting).unwrap(), Posted::Duplicate);
+		// Only the key counts: a different posting under a used key is a retry of the first.
+		let retry = Posting::transfer("test:repeat", TransactionKind::Bet, "Repeated", Account::Wallet(bob), Account::Wallet(alice), 50);
+		assert_eq!(post(&mut conn, &retry).unwrap(), Posted::Duplicate);
+		assert_eq!(balance(&mut conn, Account::Wallet(alice)).unwrap(), WELCOME_POINTS - 300);
+		assert_eq!(balance(&mut conn, Account::Wallet(bob)).unwrap(), WELCOME_POINTS + 300);
+		// The welcome grant is keyed on the player too.
+		open_wallet(&mut conn, alice).unwrap();
+		assert_eq!(balance(&mut conn, Account::Wallet(alice)).unwrap(), WELCOME_POINTS - 300);
+		audit(&mut conn).unwrap();
+	}
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn an_uncovered_leg_leaves_every_balance_untouched() {
+		let mut conn = test_conn();
+		let (alice, bob) = (player(&mut conn, "ledger_short_a"), player(&mut conn, "ledger_short_b"));
+		post(&mut conn, &Posting::transfer("test:short:setup", TransactionKind::Bet, "Setup", Account::Wallet(bob), Account::House, 600))
+			.unwrap()
+			.applied()
+			.unwrap();
+		let house = balance(&mut conn, Account::House).unwrap();
+		let posting = Posting {
+			key: "test:short".to_owned(),
+			kind: TransactionKind::Bet,
+			memo: "Both pay".to_owned(),
+			legs: vec![(Account::Wallet(alice), -500), (Account::Wallet(bob), -500), (Account::House, 1000)],
+		};
+		assert_eq!(post(&mut conn, &posting).unwrap(), Posted::Insufficient(Account::Wallet(bob)));
+		assert!(post(&mut conn, &posting).unwrap().applied().is_err());
+		assert_eq!(balance(&mut conn, Account::Wallet(alice)).unwrap(), WELCOME_POINTS);
+		assert_eq!(balance(&mut conn, Account::Wallet(bob)).unwrap(), WELCOME_POINTS - 600);
+		assert_eq!(balance(&mut conn, Account::House).unwrap(), house);
+		assert!(repo::ledger::find_transaction(&mut conn, "test:short").unwrap().is_none());
+		// The mint and the house may go below zero.
+		post(&mut conn, &Posting::transfer("test:short:house", TransactionKind::Payout, "Payout", Account::House, Account::Wallet(alice), house.max(0) + 1))
+			.unwrap()
+			.applied()
+			.unwrap();
+		audit(&mut conn).unwrap();
+	}
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn unbalanced_postings_are_refused() {
+		let mut conn = test_conn();
+		let alice = player(&mut conn, "ledger_unbalanced");
+		let wallet = Account::Wallet(alice);
+		let unbalanced =
+			Posting { key: "test:unbalanced".to_owned(), kind: TransactionKind::Bet, memo: String::new(), legs: vec![(wallet, -10), (Account::House, 5)] };
+		assert!(post(&mut conn, &unbalanced).is_err());
+		let twice = Posting { key: "test:twice".to_owned(), kind: TransactionKind::Bet, memo: String::new(), legs: vec![(wallet, -10), (wallet, 10)] };
+		assert!(post(&mut conn, &twice).is_err());
+		assert_eq!(post(&mut conn, &Posting::transfer("test:empty", TransactionKind::Bet, "", wallet, Account::House, 0)).unwrap(), Posted::Applied);
+		assert!(repo::ledger::find_transaction(&mut conn, "test:empty").unwrap().is_none());
+		assert_eq!(balance(&mut conn, wallet).unwrap(), WELCOME_POINTS);
+		audit(&mut conn).unwrap();
+	}
+}
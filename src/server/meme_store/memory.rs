@@ -0,0 +1,46 @@
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+use super::{Deleter, Deletion, MemeStore, deletion, summarize};
+use crate::{api::memes::MemeSummary, server::now_millis, stores::meme_canvas::MemeDocument};
+
+struct StoredMeme {
+	id: Uuid,
+	created_at: i64,
+	owner: Option<Uuid>,
+	document: MemeDocument,
+}
+
+#[derive(Default)]
+pub struct InMemoryMemeStore {
+	memes: RwLock<Vec<StoredMeme>>,
+}
+
+impl MemeStore for InMemoryMemeStore {
+	fn save(&self, document: &MemeDocument, owner: Option<Uuid>) -> anyhow::Result<Uuid> {
+		let id = Uuid::new_v4();
+		self.memes.write().push(StoredMeme { id, created_at: now_millis(), owner, document: document.clone() });
+		Ok(id)
+	}
+
+	fn get(&self, id: Uuid) -> anyhow::Result<Option<MemeDocument>> {
+		Ok(self.memes.read().iter().find(|meme| meme.id == id).map(|meme| meme.document.clone()))
+	}
+
+	fn list_recent(&self, limit: usize) -> anyhow::Result<Vec<MemeSummary>> {
+		Ok(self.memes.read().iter().rev().take(limit).map(|meme| summarize(meme.id, meme.created_at, &meme.document)).collect())
+	}
+
+	fn delete(&self, id: Uuid, deleter: Deleter) -> anyhow::Result<Deletion> {
+		let mut memes = self.memes.write();
+		let Some(index) = memes.iter().position(|meme| meme.id == id) else {
+			return Ok(Deletion::NotFound);
+		};
+		// Nothing is published without postgres.
+		let outcome = deletion(memes[index].owner, false, deleter);
+		if outcome == Deletion::Deleted {
+			memes.remove(index);
+		}
+		Ok(outcome)
+	}
+}
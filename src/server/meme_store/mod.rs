@@ -0,0 +1,134 @@
+mod memory;
+mod postgres;
+mod sqlite;
+
+use std::sync::LazyLock;
+
+use uuid::Uuid;
+
+pub use self::{memory::InMemoryMemeStore, postgres::PgMemeStore, sqlite::SqliteMemeStore};
+use crate::{api::memes::MemeSummary, server::db, stores::meme_canvas::MemeDocument};
+
+/// Persistence for meme documents, independent of the database behind it.
+pub trait MemeStore: Send + Sync {
+	/// `owner` is the account saving the meme, `None` when saved without one.
+	fn save(&self, document: &MemeDocument, owner: Option<Uuid>) -> anyhow::Result<Uuid>;
+	fn get(&self, id: Uuid) -> anyhow::Result<Option<MemeDocument>>;
+	/// Most recently saved first.
+	fn list_recent(&self, limit: usize) -> anyhow::Result<Vec<MemeSummary>>;
+	/// Removes the meme when [`deletion`] allows `deleter` to, checking and deleting atomically.
+	fn delete(&self, id: Uuid, deleter: Deleter) -> anyhow::Result<Deletion>;
+}
+
+/// Who asks to delete a meme.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Deleter {
+	pub user_id: Uuid,
+	pub moderator: bool,
+}
+
+/// What [`MemeStore::delete`] did.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Deletion {
+	Deleted,
+	NotFound,
+	/// Saved by another account, or without one, and the deleter is no moderator.
+	Forbidden,
+	/// Published posts are removed through moderation, so the removal is audited.
+	Published,
+}
+
+/// The rule every backend applies: authors delete their own unpublished memes, moderators any unpublished meme. Memes saved without
+/// an account have no author, so only moderators can delete them.
+pub fn deletion(owner: Option<Uuid>, published: bool, deleter: Deleter) -> Deletion {
+	if !deleter.moderator && owner != Some(deleter.user_id) {
+		Deletion::Forbidden
+	} else if published {
+		Deletion::Published
+	} else {
+		Deletion::Deleted
+	}
+}
+
+/// Picks the backend from `DATABASE_URL`: postgres once [`db::init`] has connected, `sqlite://<path>` (or `sqlite::memory:`) uses sqlite,
+/// anything else keeps memes in memory.
+pub static MEME_STORE: LazyLock<Box<dyn MemeStore>> = LazyLock::new(|| {
+	if let Ok(pool) = db::pool() {
+		return Box::new(PgMemeStore::new(pool));
+	}
+	match std::env::var("DATABASE_URL") {
+		Ok(url) if url.starts_with("sqlite:") => {
+			let path = url.trim_start_matches("sqlite:").trim_start_matches("//");
+			Box::new(SqliteMemeStore::open(path).unwrap_or_else(|e| panic!("cannot open sqlite meme store at {path}: {e:#}")))
+		},
+		_ => Box::new(InMemoryMemeStore::default()),
+	}
+});
+
+/// Runs `f` against [`MEME_STORE`] on the blocking thread pool, since every backend but the in-memory one blocks on its database.
+pub async fn with_store<T, F>(f: F) -> anyhow::Result<T>
+where
+	T: Send + 'static,
+	F: FnOnce(&dyn MemeStore) -> anyhow::Result<T> + Send + 'static,
+{
+	tokio::task::spawn_blocking(move || f(MEME_STORE.as_ref())).await?
+}
+
+pub(crate) fn summarize(id: Uuid, created_at: i64, document: &MemeDocument) -> MemeSummary {
+	MemeSummary { id, created_at, main_img_url: document.main_img_url.clone(), caption: document.caption() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn document() -> MemeDocument {
+		MemeDocument { main_img_url: "https://example.com/template.png".to_owned(), width: 500, height: 400, text_boxes: Vec::new(), watermark_url: None }
+	}
+
+	/// Authors delete their own memes, moderators anyone's, and nobody but moderators the ones saved without an account.
+	fn deletes_by_owner(store: &dyn MemeStore, author: Uuid) {
+		let (author, other) = (Deleter { user_id: author, moderator: false }, Deleter { user_id: Uuid::new_v4(), moderator: false });
+		let moderator = Deleter { user_id: Uuid::new_v4(), moderator: true };
+
+		let owned = store.save(&document(), Some(author.user_id)).unwrap();
+		assert_eq!(store.delete(owned, other).unwrap(), Deletion::Forbidden);
+		assert_eq!(store.delete(owned, author).unwrap(), Deletion::Deleted);
+		assert_eq!(store.get(owned).unwrap(), None);
+		assert_eq!(store.delete(owned, author).unwrap(), Deletion::NotFound);
+
+		let ownerless = store.save(&document(), None).unwrap();
+		assert_eq!(store.delete(ownerless, author).unwrap(), Deletion::Forbidden);
+		assert_eq!(store.get(ownerless).unwrap(), Some(document()));
+		assert_eq!(store.delete(ownerless, moderator).unwrap(), Deletion::Deleted);
+
+		let owned = store.save(&document(), Some(author.user_id)).unwrap();
+		assert_eq!(store.delete(owned, moderator).unwrap(), Deletion::Deleted);
+	}
+
+	#[test]
+	fn published_memes_are_left_to_moderation() {
+		let author = Deleter { user_id: Uuid::new_v4(), moderator: false };
+		assert_eq!(deletion(Some(author.user_id), true, author), Deletion::Published);
+		assert_eq!(deletion(Some(author.user_id), true, Deleter { moderator: true, ..author }), Deletion::Published);
+		assert_eq!(deletion(Some(Uuid::new_v4()), true, author), Deletion::Forbidden);
+	}
+
+	#[test]
+	fn in_memory_deletes_by_owner() {
+		deletes_by_owner(&InMemoryMemeStore::default(), Uuid::new_v4());
+	}
+
+	#[test]
+	fn sqlite_deletes_by_owner() {
+		deletes_by_owner(&SqliteMemeStore::open(":memory:").unwrap(), Uuid::new_v4());
+	}
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn postgres_deletes_by_owner() {
+		let pool = db::test_pool();
+		let author = db::repo::users::create(&mut pool.get().unwrap(), "meme_store_author", "hash").unwrap();
+		deletes_by_owner(&PgMemeStore::new(pool), author.id);
+	}
+}
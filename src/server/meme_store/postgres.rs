@@ -0,0 +1,61 @@
+use diesel::Connection;
+use uuid::Uuid;
+
+use super::{Deleter, Deletion, MemeStore, deletion, summarize};
+use crate::{
+	api::memes::MemeSummary,
+	server::db::{PgPool, repo},
+	stores::meme_canvas::MemeDocument,
+};
+
+pub struct PgMemeStore {
+	pool: &'static PgPool,
+}
+
+impl PgMemeStore {
+	pub fn new(pool: &'static PgPool) -> Self {
+		Self { pool }
+	}
+}
+
+impl MemeStore for PgMemeStore {
+	fn save(&self, document: &MemeDocument, owner: Option<Uuid>) -> anyhow::Result<Uuid> {
+		Ok(repo::memes::insert_document(&mut *self.pool.get()?, owner, document)?.id)
+	}
+
+	fn get(&self, id: Uuid) -> anyhow::Result<Option<MemeDocument>> {
+		Ok(repo::memes::find_document(&mut *self.pool.get()?, id)?)
+	}
+
+	fn list_recent(&self, limit: usize) -> anyhow::Result<Vec<MemeSummary>> {
+		let memes = repo::memes::list_recent(&mut *self.pool.get()?, None, limit as i64)?;
+		Ok(
+			memes
+				.into_iter()
+				.map(|(meme, layers)| {
+					let document = MemeDocument {
+						main_img_url: meme.main_img_url,
+						width: meme.width as u32,
+						height: meme.height as u32,
+						text_boxes: layers.into_iter().map(Into::into).collect(),
+						watermark_url: meme.watermark_url,
+					};
+					summarize(meme.id, meme.created_at.timestamp_millis(), &document)
+				})
+				.collect(),
+		)
+	}
+
+	fn delete(&self, id: Uuid, deleter: Deleter) -> anyhow::Result<Deletion> {
+		self.pool.get()?.transaction(|conn| {
+			let Some(meme) = repo::memes::find_for_update(conn, id)? else {
+				return Ok(Deletion::NotFound);
+			};
+			let outcome = deletion(meme.user_id, meme.published_at.is_some(), deleter);
+			if outcome == Deletion::Deleted {
+				repo::memes::delete(conn, id)?;
+			}
+			Ok(outcome)
+		})
+	}
+}
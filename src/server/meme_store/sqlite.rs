@@ -0,0 +1,76 @@
+use parking_lot::Mutex;
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+use super::{Deleter, Deletion, MemeStore, deletion, summarize};
+use crate::{api::memes::MemeSummary, server::now_millis, stores::meme_canvas::MemeDocument};
+
+/// Single-file store for local development. Documents are kept as json, there is nothing to query inside them yet.
+pub struct SqliteMemeStore {
+	conn: Mutex<Connection>,
+}
+
+impl SqliteMemeStore {
+	pub fn open(path: &str) -> anyhow::Result<Self> {
+		let conn = if path == ":memory:" { Connection::open_in_memory()? } else { Connection::open(path)? };
+		conn.execute_batch(
+			"CREATE TABLE IF NOT EXISTS memes (
+				id TEXT PRIMARY KEY NOT NULL,
+				created_at INTEGER NOT NULL,
+				document TEXT NOT NULL,
+				user_id TEXT
+			);
+			CREATE INDEX IF NOT EXISTS memes_created_at ON memes (created_at DESC);",
+		)?;
+		// Files created before memes had owners.
+		let has_owner: bool = conn.query_row("SELECT COUNT(*) > 0 FROM pragma_table_info('memes') WHERE name = 'user_id'", [], |row| row.get(0))?;
+		if !has_owner {
+			conn.execute("ALTER TABLE memes ADD COLUMN user_id TEXT", [])?;
+		}
+		Ok(Self { conn: Mutex::new(conn) })
+	}
+}
+
+impl MemeStore for SqliteMemeStore {
+	fn save(&self, document: &MemeDocument, owner: Option<Uuid>) -> anyhow::Result<Uuid> {
+		let id = Uuid::new_v4();
+		self.conn.lock().execute(
+			"INSERT INTO memes (id, created_at, document, user_id) VALUES (?1, ?2, ?3, ?4)",
+			params![id.to_string(), now_millis(), serde_json::to_string(document)?, owner.map(|owner| owner.to_string())],
+		)?;
+		Ok(id)
+	}
+
+	fn get(&self, id: Uuid) -> anyhow::Result<Option<MemeDocument>> {
+		let document: Option<String> =
+			self.conn.lock().query_row("SELECT document FROM memes WHERE id = ?1", params![id.to_string()], |row| row.get(0)).optional()?;
+		Ok(document.map(|document| serde_json::from_str(&document)).transpose()?)
+	}
+
+	fn list_recent(&self, limit: usize) -> anyhow::Result<Vec<MemeSummary>> {
+		let conn = self.conn.lock();
+		let mut statement = conn.prepare("SELECT id, created_at, document FROM memes ORDER BY created_at DESC LIMIT ?1")?;
+		let rows = statement.query_map(params![limit as i64], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))?;
+		rows
+			.map(|row| {
+				let (id, created_at, document) = row?;
+				Ok(summarize(id.parse()?, created_at, &serde_json::from_str(&document)?))
+			})
+			.collect()
+	}
+
+	fn delete(&self, id: Uuid, deleter: Deleter) -> anyhow::Result<Deletion> {
+		// The lock keeps the check and the delete together.
+		let conn = self.conn.lock();
+		let owner: Option<Option<String>> = conn.query_row("SELECT user_id FROM memes WHERE id = ?1", params![id.to_string()], |row| row.get(0)).optional()?;
+		let Some(owner) = owner else {
+			return Ok(Deletion::NotFound);
+		};
+		// Nothing is published without postgres.
+		let outcome = deletion(owner.map(|owner| owner.parse()).transpose()?, false, deleter);
+		if outcome == Deletion::Deleted {
+			conn.execute("DELETE FROM memes WHERE id = ?1", params![id.to_string()])?;
+		}
+		Ok(outcome)
+	}
+}
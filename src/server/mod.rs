@@ -0,0 +1,21 @@
+pub mod auth;
+pub mod blob;
+pub mod db;
+pub mod filters;
+pub mod game;
+pub mod image_hash;
+pub mod image_proxy;
+pub mod ingest;
+pub mod ledger;
+pub mod meme_store;
+pub mod rate_limit;
+pub mod render;
+pub mod slash_commands;
+pub mod tables;
+pub mod tournaments;
+pub mod uploads;
+
+/// Milliseconds since the unix epoch.
+pub fn now_millis() -> i64 {
+	std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as i64).unwrap_or_default()
+}
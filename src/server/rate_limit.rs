@@ -0,0 +1,73 @@
+//! In-process request quotas for endpoints that are costly but write nothing to the database to count, like the image proxy and the render API.
+//!
+//! Counts live in memory, so they reset on restart and each server instance keeps its own.
+
+use std::{
+	collections::HashMap,
+	hash::Hash,
+	time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+/// Windows beyond this many are pruned of the ones that ended, so callers that stop coming back do not pile up.
+const MAX_TRACKED: usize = 10_000;
+
+/// Allows `limit` requests per caller in each fixed `window`, starting with the caller's first request.
+pub struct RateLimiter<K> {
+	limit: u32,
+	window: Duration,
+	windows: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+	pub fn new(limit: u32, window: Duration) -> Self {
+		Self { limit, window, windows: Mutex::default() }
+	}
+
+	/// Counts a request by `caller` at `now`, false when its window is used up. Refused requests are not counted.
+	pub fn allow(&self, caller: K, now: Instant) -> bool {
+		let mut windows = self.windows.lock();
+		if windows.len() >= MAX_TRACKED {
+			windows.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+		}
+		let (start, count) = windows.entry(caller).or_insert((now, 0));
+		if now.duration_since(*start) >= self.window {
+			*start = now;
+			*count = 0;
+		}
+		if *count >= self.limit {
+			return false;
+		}
+		*count += 1;
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn refuses_past_the_limit_until_the_window_ends() {
+		let limiter = RateLimiter::new(2, Duration::from_secs(60));
+		let start = Instant::now();
+		assert!(limiter.allow("a", start));
+		assert!(limiter.allow("a", start + Duration::from_secs(1)));
+		assert!(!limiter.allow("a", start + Duration::from_secs(2)));
+		assert!(limiter.allow("b", start + Duration::from_secs(2)), "callers have their own windows");
+		assert!(!limiter.allow("a", start + Duration::from_secs(59)));
+		assert!(limiter.allow("a", start + Duration::from_secs(60)));
+	}
+
+	#[test]
+	fn prunes_ended_windows_when_full() {
+		let limiter = RateLimiter::new(1, Duration::from_secs(60));
+		let start = Instant::now();
+		for caller in 0..MAX_TRACKED {
+			assert!(limiter.allow(caller, start));
+		}
+		assert!(limiter.allow(MAX_TRACKED, start + Duration::from_secs(60)));
+		assert_eq!(limiter.windows.lock().len(), 1);
+	}
+}
@@ -0,0 +1,485 @@
+//! Server side rendering for the HTTP API, so bots and integrations can make memes without a browser. Draws what the generator's
+//! canvas draws: the background stretched over the canvas, white text with a black outline, then the watermark. The font is bundled,
+//! so text looks the same whatever family the document names.
+
+use std::{
+	io::Cursor,
+	sync::LazyLock,
+	time::{Duration, Instant},
+};
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use dioxus::{
+	fullstack::{
+		HeaderMap, StatusCode,
+		body::Bytes,
+		http::header,
+		response::{IntoResponse, Response},
+	},
+	logger::tracing::{error, warn},
+};
+use image::{DynamicImage, ImageFormat, ImageReader, Limits, RgbaImage, imageops::FilterType};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+	server::{
+		auth::api_key_account,
+		blob::{BLOB_STORE, BlobKind, ImageMime, cache, digest_key, validate_image},
+		db::{models::Template, repo, with_conn},
+		image_proxy,
+		rate_limit::RateLimiter,
+	},
+	stores::{
+		meme_canvas::{MAX_TEXT_BOXES, MemeDocument, WATERMARK_MARGIN, WATERMARK_WIDTH},
+		text_box::TextBox,
+	},
+};
+
+/// Largest width or height of a render.
+pub const MAX_RENDER_DIMENSION: u32 = 2048;
+/// Most characters of text over all text boxes of a render.
+pub const MAX_RENDER_TEXT_LEN: usize = 1000;
+/// Largest request body accepted by [`render_endpoint`].
+pub const MAX_RENDER_REQUEST_BYTES: usize = 64 * 1024;
+/// Largest width or height of a background or watermark, checked before decoding it.
+const MAX_SOURCE_DIMENSION: u32 = 8192;
+const OUTLINE_WIDTH: f32 = 3.0;
+const JPEG_QUALITY: u8 = 90;
+/// Share of the width captions may take before their font is shrunk.
+const CAPTION_WIDTH: f32 = 0.95;
+/// Most pixels the upright text of one text box may cover before it is drawn onto the canvas, and of all text boxes together.
+/// Font size and text length are capped separately, but their product is what the layers allocate.
+const MAX_LAYER_PIXELS: usize = 2048 * 2048;
+const MAX_TOTAL_LAYER_PIXELS: usize = 4 * MAX_LAYER_PIXELS;
+/// Requests one API key may make per minute, cached renders included.
+const RENDERS_PER_MINUTE: u32 = 60;
+
+static RENDER_QUOTA: LazyLock<RateLimiter<Uuid>> = LazyLock::new(|| RateLimiter::new(RENDERS_PER_MINUTE, Duration::from_secs(60)));
+
+static FONT: LazyLock<FontRef<'static>> =
+	LazyLock::new(|| FontRef::try_from_slice(include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf")).expect("the bundled font is valid"));
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderFormat {
+	#[default]
+	Png,
+	Jpeg,
+}
+
+impl RenderFormat {
+	fn mime(self) -> ImageMime {
+		match self {
+			Self::Png => ImageMime::Png,
+			Self::Jpeg => ImageMime::Jpeg,
+		}
+	}
+}
+
+/// Body of `POST /api/render`: either a whole `document` as the generator saves it, or a `template_id` with `captions`, the first
+/// drawn at the top, the last at the bottom and any others spread between.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenderRequest {
+	#[serde(default)]
+	pub document: Option<MemeDocument>,
+	#[serde(default)]
+	pub template_id: Option<Uuid>,
+	#[serde(default)]
+	pub captions: Vec<String>,
+	#[serde(default)]
+	pub format: RenderFormat,
+}
+
+/// Status and message of a refused render.
+pub type Rejection = (StatusCode, String);
+
+fn bad_request(message: impl Into<String>) -> Rejection {
+	(StatusCode::BAD_REQUEST, message.into())
+}
+
+fn internal(context: &str, e: &anyhow::Error) -> Rejection {
+	error!("{context}: {e:#}");
+	(StatusCode::INTERNAL_SERVER_ERROR, "internal error".to_owned())
+}
+
+/// A finished render and where it is cached.
+pub struct Rendered {
+	pub bytes: Vec<u8>,
+	pub mime: ImageMime,
+	/// Blob key of the render, also set when caching it failed.
+	pub key: String,
+	/// Quoted hash of the resolved document, for the `ETag`.
+	pub etag: String,
+	/// Whether the render came from the cache.
+	pub hit: bool,
+	/// Whether the render is in blob storage under `key`.
+	pub stored: bool,
+}
+
+/// `POST /api/render`, authenticated with `Authorization: Bearer <api key>`. Answers with the image bytes. Renders are cached by the
+/// hash of the resolved document, which is also the `ETag`. Each key gets [`RENDERS_PER_MINUTE`] requests a minute.
+pub async fn render_endpoint(headers: HeaderMap, body: Bytes) -> Response {
+	match render_request(&headers, &body).await {
+		Ok(response) => response,
+		Err(rejection) => rejection.into_response(),
+	}
+}
+
+async fn render_request(headers: &HeaderMap, body: &[u8]) -> Result<Response, Rejection> {
+	let account = api_key_account(headers).await.map_err(|e| internal("cannot check api key", &e))?;
+	let (key_id, account) = account.ok_or((StatusCode::UNAUTHORIZED, "missing or unknown api key".to_owned()))?;
+	if account.banned_at.is_some() {
+		return Err((StatusCode::FORBIDDEN, "this account is banned".to_owned()));
+	}
+	if !RENDER_QUOTA.allow(key_id, Instant::now()) {
+		return Err((StatusCode::TOO_MANY_REQUESTS, format!("at most {RENDERS_PER_MINUTE} renders a minute per api key")));
+	}
+	let request = serde_json::from_slice::<RenderRequest>(body).map_err(|e| bad_request(format!("invalid request: {e}")))?;
+	let rendered = render_cached(request).await?;
+	Ok(image_response(rendered))
+}
+
+/// Resolves the request and renders it, or returns the cached render of the same document.
+pub async fn render_cached(request: RenderRequest) -> Result<Rendered, Rejection> {
+	let format = request.format;
+	let document = resolve_document(request).await?;
+
+	let mime = format.mime();
+	let digest = Sha256::digest(serde_json::to_vec(&(&document, format)).map_err(|e| internal("cannot hash document", &e.into()))?);
+	let etag = format!("\"{}\"", hex::encode(digest));
+	let key = digest_key(BlobKind::RenderCache, &digest, mime);
+	// Recorded before the lookup, so a sweep cannot drop the render between finding it and storing it again.
+	cache::touch(BlobKind::RenderCache, &key).await.map_err(|e| internal("cannot record cached render", &e))?;
+	if let Some(bytes) = BLOB_STORE.get(&key).await.map_err(|e| internal("cannot read cached render", &e))? {
+		return Ok(Rendered { bytes, mime, key, etag, hit: true, stored: true });
+	}
+
+	let background = load_image(&document.main_img_url).await.map_err(|e| bad_request(format!("cannot load the image: {e:#}")))?;
+	let watermark = match &document.watermark_url {
+		Some(url) => Some(load_image(url).await.map_err(|e| bad_request(format!("cannot load the watermark: {e:#}")))?),
+		None => None,
+	};
+	let bytes = tokio::task::spawn_blocking(move || encode(&render(&document, &background, watermark.as_ref()), format))
+		.await
+		.map_err(|e| internal("render task failed", &e.into()))?
+		.map_err(|e| internal("cannot encode render", &e))?;
+	// A render that cannot be cached is still a render.
+	let stored = match BLOB_STORE.put(&key, bytes.clone(), mime.content_type()).await {
+		Ok(()) => true,
+		Err(e) => {
+			warn!("cannot cache render {key}: {e:#}");
+			false
+		},
+	};
+	Ok(Rendered { bytes, mime, key, etag, hit: false, stored })
+}
+
+/// Renders a document the server already holds, like a tournament entry, under the same limits as the API.
+pub async fn render_document(document: MemeDocument, format: RenderFormat) -> Result<Rendered, Rejection> {
+	render_cached(RenderRequest { document: Some(document), template_id: None, captions: Vec::new(), format }).await
+}
+
+fn image_response(rendered: Rendered) -> Response {
+	Response::builder()
+		.header(header::CONTENT_TYPE, rendered.mime.content_type())
+		.header(header::CACHE_CONTROL, "private, max-age=86400")
+		.header(header::ETAG, rendered.etag)
+		.header("x-render-cache", if rendered.hit { "hit" } else { "miss" })
+		.body(rendered.bytes.into())
+		.unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// The document to draw, checked against the render limits.
+async fn resolve_document(request: RenderRequest) -> Result<MemeDocument, Rejection> {
+	let document = match (request.document, request.template_id) {
+		(Some(document), None) => {
+			if !request.captions.is_empty() {
+				return Err(bad_request("captions go with a template_id, put text in the document's text boxes instead"));
+			}
+			document
+		},
+		(None, Some(template_id)) => {
+			let template = with_conn(move |conn| Ok(repo::templates::find(conn, template_id)?))
+				.await
+				.map_err(|e| internal("cannot load template", &e))?
+				.ok_or((StatusCode::NOT_FOUND, "template not found".to_owned()))?;
+			if request.captions.len() > MAX_TEXT_BOXES {
+				return Err(bad_request(format!("at most {MAX_TEXT_BOXES} captions")));
+			}
+			template_document(template, request.captions)
+		},
+		_ => return Err(bad_request("send either a document or a template_id")),
+	};
+	document.validate().map_err(|e| bad_request(format!("{e:#}")))?;
+	if document.width > MAX_RENDER_DIMENSION || document.height > MAX_RENDER_DIMENSION {
+		return Err(bad_request(format!("renders are at most {MAX_RENDER_DIMENSION} pixels wide and high")));
+	}
+	if document.text_boxes.iter().map(|text_box| text_box.text.chars().count()).sum::<usize>() > MAX_RENDER_TEXT_LEN {
+		return Err(bad_request(format!("renders carry at most {MAX_RENDER_TEXT_LEN} characters of text")));
+	}
+	let layers = document
+		.text_boxes
+		.iter()
+		.filter(|text_box| !text_box.text.trim().is_empty())
+		.map(|text_box| TextLayer::pixels(&text_box.text, text_box.style.size as f32));
+	let (largest, total) = layers.fold((0, 0), |(largest, total), pixels| (largest.max(pixels), total + pixels));
+	if largest > MAX_LAYER_PIXELS || total > MAX_TOTAL_LAYER_PIXELS {
+		return Err(bad_request("the text is too large to render, use smaller fonts or less text"));
+	}
+	Ok(document)
+}
+
+/// The template laid out like the generator opens it, at up to the render limit, with captions shrunk to fit the width.
+fn template_document(template: Template, captions: Vec<String>) -> MemeDocument {
+	let mut document = MemeDocument::template_layout(&template.into(), captions, MAX_RENDER_DIMENSION);
+	let max_width = CAPTION_WIDTH * document.width as f32;
+	for text_box in &mut document.text_boxes {
+		let size = text_box.style.size as f32;
+		let fitting = (size * max_width / text_width(&text_box.text, size).max(1.0)) as u32;
+		text_box.style.size = text_box.style.size.min(fitting).max(1);
+	}
+	document
+}
+
+/// Loads and decodes an image from our blob storage, or through the image proxy's checks for any other url.
+async fn load_image(url: &str) -> anyhow::Result<DynamicImage> {
+	let bytes = image_proxy::load(url).await?;
+	validate_image(&bytes, None)?;
+	tokio::task::spawn_blocking(move || {
+		let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+		let mut limits = Limits::default();
+		limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+		limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+		reader.limits(limits);
+		Ok(reader.decode()?)
+	})
+	.await?
+}
+
+fn encode(image: &RgbaImage, format: RenderFormat) -> anyhow::Result<Vec<u8>> {
+	let mut bytes = Vec::new();
+	match format {
+		RenderFormat::Png => image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?,
+		// Jpeg has no alpha channel.
+		RenderFormat::Jpeg => {
+			let rgb = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+			image::codecs::jpeg::JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY).encode_image(&rgb)?;
+		},
+	}
+	Ok(bytes)
+}
+
+/// Draws the document over `background`, which is stretched to the document's size like the canvas does.
+pub fn render(document: &MemeDocument, background: &DynamicImage, watermark: Option<&DynamicImage>) -> RgbaImage {
+	let mut canvas = background.resize_exact(document.width, document.height, FilterType::Triangle).to_rgba8();
+	for text_box in &document.text_boxes {
+		draw_text_box(&mut canvas, text_box);
+	}
+	if let Some(watermark) = watermark {
+		let canvas_width = f64::from(canvas.width());
+		let width = canvas_width * WATERMARK_WIDTH;
+		let height = width * f64::from(watermark.height()) / f64::from(watermark.width().max(1));
+		let margin = canvas_width * WATERMARK_MARGIN;
+		let scaled = watermark.resize_exact(width.round().max(1.0) as u32, height.round().max(1.0) as u32, FilterType::Triangle).to_rgba8();
+		let x = canvas_width - width - margin;
+		let y = f64::from(canvas.height()) - height - margin;
+		image::imageops::overlay(&mut canvas, &scaled, x.round() as i64, y.round() as i64);
+	}
+	canvas
+}
+
+/// A canvas font size of `size` pixels is the em size, while `ab_glyph` scales by the height from descent to ascent.
+fn px_scale(size: f32) -> PxScale {
+	PxScale::from(size * FONT.height_unscaled() / FONT.units_per_em().unwrap_or(1000.0))
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+	let font = FONT.as_scaled(px_scale(size));
+	let mut previous = None;
+	let mut width = 0.0;
+	for c in text.chars() {
+		let id = font.glyph_id(c);
+		if let Some(previous) = previous {
+			width += font.kern(previous, id);
+		}
+		width += font.h_advance(id);
+		previous = Some(id);
+	}
+	width
+}
+
+/// The text rasterized upright, as white over black outline with premultiplied alpha: `(gray, alpha)` per pixel.
+struct TextLayer {
+	width: usize,
+	height: usize,
+	pixels: Vec<(f32, f32)>,
+}
+
+impl TextLayer {
+	/// Room around the text for the outline.
+	fn pad() -> usize {
+		(OUTLINE_WIDTH / 2.0).ceil() as usize + 1
+	}
+
+	fn size(text: &str, size: f32) -> (usize, usize) {
+		let font = FONT.as_scaled(px_scale(size));
+		let pad = Self::pad();
+		(text_width(text, size).ceil() as usize + 2 * pad, (font.ascent() - font.descent()).ceil() as usize + 2 * pad)
+	}
+
+	/// How many pixels [`TextLayer::new`] allocates for the text, without allocating them.
+	fn pixels(text: &str, size: f32) -> usize {
+		let (width, height) = Self::size(text, size);
+		width.saturating_mul(height)
+	}
+
+	/// `None` when the layer would take more than [`MAX_LAYER_PIXELS`].
+	fn new(text: &str, size: f32) -> Option<Self> {
+		let font = FONT.as_scaled(px_scale(size));
+		let pad = Self::pad();
+		let (width, height) = Self::size(text, size);
+		if width.saturating_mul(height) > MAX_LAYER_PIXELS {
+			return None;
+		}
+		let mut fill = vec![0.0_f32; width * height];
+		let mut caret = pad as f32;
+		let mut previous = None;
+		for c in text.chars() {
+			let id = font.glyph_id(c);
+			if let Some(previous) = previous {
+				caret += font.kern(previous, id);
+			}
+			let glyph = id.with_scale_and_position(font.scale(), point(caret, pad as f32 + font.ascent()));
+			caret += font.h_advance(id);
+			previous = Some(id);
+			let Some(outlined) = FONT.outline_glyph(glyph) else { continue };
+			let bounds = outlined.px_bounds();
+			outlined.draw(|x, y, coverage| {
+				let (x, y) = (bounds.min.x as i64 + i64::from(x), bounds.min.y as i64 + i64::from(y));
+				if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+					let pixel = &mut fill[y as usize * width + x as usize];
+					*pixel = (*pixel + coverage).min(1.0);
+				}
+			});
+		}
+		// The canvas strokes the outline centered on the glyph edges, so it reaches half its width past them.
+		let reach = OUTLINE_WIDTH / 2.0;
+		let radius = reach.ceil() as i64;
+		let offsets = (-radius..=radius)
+			.flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+			.filter(|&(dx, dy)| ((dx * dx + dy * dy) as f32).sqrt() <= reach)
+			.collect::<Vec<_>>();
+		let pixels = (0..width * height)
+			.map(|i| {
+				let (x, y) = ((i % width) as i64, (i / width) as i64);
+				let outline = offsets
+					.iter()
+					.filter_map(|&(dx, dy)| {
+						let (x, y) = (x + dx, y + dy);
+						((0..width as i64).contains(&x) && (0..height as i64).contains(&y)).then(|| fill[y as usize * width + x as usize])
+					})
+					.fold(0.0_f32, f32::max);
+				// White fill over the black outline.
+				let fill = fill[i];
+				(fill, fill + outline * (1.0 - fill))
+			})
+			.collect();
+		Some(Self { width, height, pixels })
+	}
+
+	/// Bilinear sample at layer coordinates, transparent outside.
+	fn sample(&self, x: f32, y: f32) -> (f32, f32) {
+		let (x, y) = (x - 0.5, y - 0.5);
+		let (x0, y0) = (x.floor(), y.floor());
+		let (fx, fy) = (x - x0, y - y0);
+		let pixel = |x: f32, y: f32| {
+			if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+				return (0.0, 0.0);
+			}
+			self.pixels[y as usize * self.width + x as usize]
+		};
+		let mix = |a: (f32, f32), b: (f32, f32), t: f32| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+		mix(mix(pixel(x0, y0), pixel(x0 + 1.0, y0), fx), mix(pixel(x0, y0 + 1.0), pixel(x0 + 1.0, y0 + 1.0), fx), fy)
+	}
+}
+
+/// Draws the text box centered on its position, rotated and scaled around it like the canvas transform in `TextBox::draw_to_canvas`.
+fn draw_text_box(canvas: &mut RgbaImage, text_box: &TextBox) {
+	if text_box.text.trim().is_empty() || text_box.scale_x == 0.0 || text_box.scale_y == 0.0 {
+		return;
+	}
+	let Some(layer) = TextLayer::new(&text_box.text, text_box.style.size as f32) else { return };
+	let (half_width, half_height) = (layer.width as f64 / 2.0, layer.height as f64 / 2.0);
+	let (sin, cos) = text_box.rotation.sin_cos();
+	let to_canvas = |x: f64, y: f64| {
+		let (x, y) = (x * text_box.scale_x, y * text_box.scale_y);
+		(text_box.x + x * cos - y * sin, text_box.y + x * sin + y * cos)
+	};
+	let corners = [(-half_width, -half_height), (half_width, -half_height), (half_width, half_height), (-half_width, half_height)].map(|(x, y)| to_canvas(x, y));
+	let clamp = |value: f64, max: u32| value.clamp(0.0, f64::from(max)) as u32;
+	let left = clamp(corners.iter().map(|corner| corner.0).fold(f64::INFINITY, f64::min).floor(), canvas.width());
+	let right = clamp(corners.iter().map(|corner| corner.0).fold(f64::NEG_INFINITY, f64::max).ceil(), canvas.width());
+	let top = clamp(corners.iter().map(|corner| corner.1).fold(f64::INFINITY, f64::min).floor(), canvas.height());
+	let bottom = clamp(corners.iter().map(|corner| corner.1).fold(f64::NEG_INFINITY, f64::max).ceil(), canvas.height());
+	for y in top..bottom {
+		for x in left..right {
+			// Back from the pixel center to the upright, unscaled layer.
+			let (dx, dy) = (f64::from(x) + 0.5 - text_box.x, f64::from(y) + 0.5 - text_box.y);
+			let (local_x, local_y) = ((dx * cos + dy * sin) / text_box.scale_x, (dy * cos - dx * sin) / text_box.scale_y);
+			let (gray, alpha) = layer.sample((local_x + half_width) as f32, (local_y + half_height) as f32);
+			if alpha <= 0.0 {
+				continue;
+			}
+			let pixel = canvas.get_pixel_mut(x, y);
+			for channel in &mut pixel.0[..3] {
+				*channel = (gray * 255.0 + f32::from(*channel) * (1.0 - alpha)).round().clamp(0.0, 255.0) as u8;
+			}
+			pixel.0[3] = ((alpha + f32::from(pixel.0[3]) / 255.0 * (1.0 - alpha)) * 255.0).round().clamp(0.0, 255.0) as u8;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::stores::meme_canvas::MAX_FONT_SIZE;
+
+	fn document(text_boxes: Vec<TextBox>) -> MemeDocument {
+		MemeDocument { main_img_url: "https://example.com/template.png".to_owned(), width: 500, height: 500, text_boxes, watermark_url: None }
+	}
+
+	fn caption(text: &str, size: u32) -> TextBox {
+		TextBox::new(text.to_owned(), 250.0, 250.0, size, "Impact".to_owned(), "bold".to_owned())
+	}
+
+	fn request(document: MemeDocument) -> RenderRequest {
+		RenderRequest { document: Some(document), template_id: None, captions: Vec::new(), format: RenderFormat::Png }
+	}
+
+	#[tokio::test]
+	async fn text_layers_are_bounded() {
+		let long = "W".repeat(MAX_RENDER_TEXT_LEN);
+		assert!(TextLayer::pixels(&long, MAX_FONT_SIZE as f32) > MAX_LAYER_PIXELS);
+		assert!(TextLayer::new(&long, MAX_FONT_SIZE as f32).is_none());
+		let rejected = resolve_document(request(document(vec![caption(&long, MAX_FONT_SIZE)]))).await.unwrap_err();
+		assert_eq!(rejected.0, StatusCode::BAD_REQUEST);
+
+		// Boxes that each fit can still add up to too much.
+		let wide = "W".repeat(30);
+		assert!(TextLayer::pixels(&wide, 300.0) <= MAX_LAYER_PIXELS);
+		assert!(resolve_document(request(document(vec![caption(&wide, 300); 8]))).await.is_err());
+		assert!(resolve_document(request(document(vec![caption("Top text", 60), caption("Bottom text", 60)]))).await.is_ok());
+	}
+
+	#[test]
+	fn oversized_text_is_skipped_when_drawing() {
+		let background = DynamicImage::ImageRgba8(RgbaImage::new(50, 50));
+		let canvas = render(&document(vec![caption(&"W".repeat(MAX_RENDER_TEXT_LEN), MAX_FONT_SIZE)]), &background, None);
+		assert!(canvas.pixels().all(|pixel| pixel.0 == [0, 0, 0, 0]));
+		let canvas = render(&document(vec![caption("W", 200)]), &background, None);
+		assert!(canvas.pixels().any(|pixel| pixel.0[3] > 0));
+	}
+}
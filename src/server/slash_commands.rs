@@ -0,0 +1,517 @@
+//! Slash commands for Slack and Discord: `/meme template | top | bottom` renders the template best matching the name through
+//! [`render_cached`] and posts it to the channel. Captions after the first two are spread between the top and the bottom.
+//!
+//! Slack signs requests with an HMAC of the body keyed by `SLACK_SIGNING_SECRET`, Discord with an Ed25519 key whose public half is
+//! `DISCORD_PUBLIC_KEY`. Either endpoint answers 404 while its secret is unset. Discord commands take the text as one string option.
+//!
+//! Both platforms give up on a reply after three seconds, so renders taking longer are acknowledged first and posted when done.
+
+use std::{future::Future, sync::LazyLock, time::Duration};
+
+use anyhow::Context;
+use dioxus::{
+	fullstack::{
+		HeaderMap, StatusCode,
+		body::Bytes,
+		http::header,
+		response::{IntoResponse, Response},
+	},
+	logger::tracing::{error, warn},
+};
+use ed25519_dalek::{Signature, VerifyingKey};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::server::{
+	blob::BLOB_STORE,
+	db::{repo, with_conn},
+	now_millis,
+	render::{RenderFormat, RenderRequest, render_cached},
+};
+
+/// Largest request body accepted by the slash command endpoints.
+pub const MAX_SLASH_REQUEST_BYTES: usize = 64 * 1024;
+/// Oldest signed timestamp accepted, in seconds either way, so a captured request cannot be replayed later.
+const MAX_CLOCK_SKEW: i64 = 5 * 60;
+/// How long a render may take before the reply is deferred, leaving headroom under the platforms' three seconds.
+const REPLY_WITHIN: Duration = Duration::from_millis(2500);
+const FOLLOW_UP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Lowest [`score`] of a template name still taken as a match.
+const MIN_SCORE: f64 = 0.6;
+const SUGGESTIONS: usize = 3;
+const USAGE: &str = "Usage: /meme template | top text | bottom text";
+const DISCORD_API: &str = "https://discord.com/api/v10";
+/// Discord's flag for messages only the caller sees.
+const EPHEMERAL: u32 = 1 << 6;
+
+static SLACK_SIGNING_SECRET: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("SLACK_SIGNING_SECRET").ok().filter(|secret| !secret.is_empty()));
+static DISCORD_PUBLIC_KEY: LazyLock<Option<VerifyingKey>> = LazyLock::new(|| {
+	let key = std::env::var("DISCORD_PUBLIC_KEY").ok().filter(|key| !key.is_empty())?;
+	match parse_public_key(&key) {
+		Ok(key) => Some(key),
+		Err(e) => {
+			error!("ignoring DISCORD_PUBLIC_KEY: {e:#}");
+			None
+		},
+	}
+});
+
+/// A parsed `/meme` command.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SlashCommand {
+	pub template: String,
+	pub captions: Vec<String>,
+}
+
+/// Splits `template | caption | caption` on the bars. Captions may be empty to leave a line blank.
+pub fn parse_command(text: &str) -> anyhow::Result<SlashCommand> {
+	let mut parts = text.split('|').map(str::trim);
+	let template = parts.next().unwrap_or_default();
+	anyhow::ensure!(!template.is_empty() && !template.eq_ignore_ascii_case("help"), "{USAGE}");
+	Ok(SlashCommand { template: template.to_owned(), captions: parts.map(str::to_owned).collect() })
+}
+
+/// Lowercase words, punctuation dropped.
+fn normalize(name: &str) -> String {
+	name.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+	let b = b.chars().collect::<Vec<_>>();
+	let mut row = (0..=b.len()).collect::<Vec<_>>();
+	for (i, a) in a.chars().enumerate() {
+		let mut diagonal = row[0];
+		row[0] = i + 1;
+		for (j, &b) in b.iter().enumerate() {
+			let substitution = diagonal + usize::from(a != b);
+			diagonal = row[j + 1];
+			row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+		}
+	}
+	row[b.len()]
+}
+
+/// 1 for equal strings down to 0 for nothing in common.
+fn similarity(a: &str, b: &str) -> f64 {
+	let len = a.chars().count().max(b.chars().count());
+	if len == 0 {
+		return 1.0;
+	}
+	1.0 - levenshtein(a, b) as f64 / len as f64
+}
+
+/// How well the normalized `query` names the normalized `name`, from 0 to 1. Besides the whole names, each query word is matched
+/// against the closest word of the name, counting prefixes as exact, so `drake` finds "Drake Hotline Bling".
+fn score(query: &str, name: &str) -> f64 {
+	if query == name {
+		return 1.0;
+	}
+	let name_words = name.split(' ').collect::<Vec<_>>();
+	let query_words = query.split(' ').collect::<Vec<_>>();
+	let words = query_words
+		.iter()
+		.map(|query_word| {
+			name_words.iter().map(|name_word| if name_word.starts_with(query_word) { 1.0 } else { similarity(query_word, name_word) }).fold(0.0, f64::max)
+		})
+		.sum::<f64>()
+		/ query_words.len() as f64;
+	// Short of an exact match, word matches never beat it.
+	similarity(query, name).max(0.9 * words)
+}
+
+/// The templates ordered by how well `query` names them, best first, ties going to the shorter name.
+pub fn rank<'a>(query: &str, templates: &'a [(Uuid, String)]) -> Vec<(f64, &'a (Uuid, String))> {
+	let query = normalize(query);
+	let mut ranked = templates.iter().map(|template| (score(&query, &normalize(&template.1)), template)).collect::<Vec<_>>();
+	ranked.sort_by(|(a_score, a), (b_score, b)| b_score.total_cmp(a_score).then_with(|| a.1.len().cmp(&b.1.len())));
+	ranked
+}
+
+/// What a command answers with.
+enum Reply {
+	Meme {
+		url: String,
+		title: String,
+		alt: String,
+	},
+	/// Shown to the caller only, when the platform allows.
+	Error(String),
+}
+
+async fn run(text: &str) -> Reply {
+	let command = match parse_command(text) {
+		Ok(command) => command,
+		Err(e) => return Reply::Error(format!("{e:#}")),
+	};
+	let templates = match with_conn(|conn| Ok(repo::templates::names(conn)?)).await {
+		Ok(templates) => templates,
+		Err(e) => {
+			error!("cannot load template names: {e:#}");
+			return Reply::Error("internal error".to_owned());
+		},
+	};
+	let ranked = rank(&command.template, &templates);
+	let Some(&(_, template)) = ranked.first().filter(|(score, _)| *score >= MIN_SCORE) else {
+		let suggestions = ranked.iter().take(SUGGESTIONS).map(|(_, (_, name))| name.as_str()).collect::<Vec<_>>();
+		return Reply::Error(if suggestions.is_empty() {
+			format!("No template matches \"{}\".", command.template)
+		} else {
+			format!("No template matches \"{}\". Closest: {}.", command.template, suggestions.join(", "))
+		});
+	};
+	let (id, name) = template;
+	let alt = command.captions.iter().map(|caption| caption.as_str()).filter(|caption| !caption.is_empty()).collect::<Vec<_>>().join(" / ");
+	let alt = if alt.is_empty() { name.clone() } else { alt };
+	let request = RenderRequest { document: None, template_id: Some(*id), captions: command.captions, format: RenderFormat::Jpeg };
+	match render_cached(request).await {
+		Ok(rendered) if rendered.stored => Reply::Meme { url: BLOB_STORE.public_url(&rendered.key), title: name.clone(), alt },
+		Ok(_) => Reply::Error("Could not store the meme, try again.".to_owned()),
+		Err((_, message)) => Reply::Error(message),
+	}
+}
+
+/// Runs the command, or hands its reply to `follow_up` when it is not ready in time and returns `None` to acknowledge it meanwhile.
+async fn reply_in_time<F, Fut>(text: String, follow_up: F) -> Option<Reply>
+where
+	F: FnOnce(Reply) -> Fut + Send + 'static,
+	Fut: Future<Output = anyhow::Result<()>> + Send,
+{
+	let mut task = tokio::spawn(async move { run(&text).await });
+	let finished = |result: Result<Reply, tokio::task::JoinError>| {
+		result.unwrap_or_else(|e| {
+			error!("slash command task failed: {e}");
+			Reply::Error("internal error".to_owned())
+		})
+	};
+	if let Ok(result) = tokio::time::timeout(REPLY_WITHIN, &mut task).await {
+		return Some(finished(result));
+	}
+	tokio::spawn(async move {
+		if let Err(e) = follow_up(finished(task.await)).await {
+			warn!("cannot post slash command reply: {e:#}");
+		}
+	});
+	None
+}
+
+/// Refuses timestamps too far from `now`, both in unix seconds.
+fn check_timestamp(timestamp: &str, now: i64) -> anyhow::Result<()> {
+	let timestamp = timestamp.parse::<i64>().context("missing or malformed timestamp")?;
+	anyhow::ensure!((now - timestamp).abs() <= MAX_CLOCK_SKEW, "timestamp too far from now");
+	Ok(())
+}
+
+/// Checks Slack's `x-slack-signature`, `v0=` and the hex HMAC-SHA256 of `v0:{timestamp}:{body}` keyed with the signing secret.
+pub fn verify_slack(secret: &[u8], timestamp: &str, signature: &str, body: &[u8], now: i64) -> anyhow::Result<()> {
+	check_timestamp(timestamp, now)?;
+	let signature = signature.strip_prefix("v0=").and_then(|signature| hex::decode(signature).ok()).context("missing or malformed signature")?;
+	let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac takes keys of any length");
+	mac.update(format!("v0:{timestamp}:").as_bytes());
+	mac.update(body);
+	anyhow::ensure!(mac.verify_slice(&signature).is_ok(), "signature does not match");
+	Ok(())
+}
+
+/// The hex public key Discord shows for the application.
+pub fn parse_public_key(key: &str) -> anyhow::Result<VerifyingKey> {
+	let bytes = hex::decode(key.trim()).context("public key is not hex")?;
+	let bytes = <[u8; 32]>::try_from(bytes.as_slice()).context("public key is not 32 bytes")?;
+	Ok(VerifyingKey::from_bytes(&bytes)?)
+}
+
+/// Checks Discord's `x-signature-ed25519`, the hex Ed25519 signature of the timestamp followed by the body.
+pub fn verify_discord(public_key: &VerifyingKey, timestamp: &str, signature: &str, body: &[u8], now: i64) -> anyhow::Result<()> {
+	check_timestamp(timestamp, now)?;
+	let signature = hex::decode(signature).ok().and_then(|bytes| Signature::from_slice(&bytes).ok()).context("missing or malformed signature")?;
+	let message = [timestamp.as_bytes(), body].concat();
+	anyhow::ensure!(public_key.verify_strict(&message, &signature).is_ok(), "signature does not match");
+	Ok(())
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+	headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default()
+}
+
+fn json_response(value: &impl Serialize) -> Response {
+	match serde_json::to_vec(value) {
+		Ok(bytes) => ([(header::CONTENT_TYPE, "application/json")], bytes).into_response(),
+		Err(e) => {
+			error!("cannot encode slash command reply: {e}");
+			StatusCode::INTERNAL_SERVER_ERROR.into_response()
+		},
+	}
+}
+
+async fn send_json(method: reqwest::Method, url: Url, value: &impl Serialize) -> anyhow::Result<()> {
+	let client = reqwest::Client::builder().timeout(FOLLOW_UP_TIMEOUT).build()?;
+	let response = client.request(method, url).header(header::CONTENT_TYPE.as_str(), "application/json").body(serde_json::to_vec(value)?).send().await?;
+	anyhow::ensure!(response.status().is_success(), "answered {}", response.status());
+	Ok(())
+}
+
+/// The form fields of a Slack slash command that are used.
+#[derive(Deserialize)]
+struct SlackCommand {
+	#[serde(default)]
+	text: String,
+	#[serde(default)]
+	response_url: String,
+	/// Set on the checks Slack makes of the endpoint's certificate, which need no reply.
+	#[serde(default)]
+	ssl_check: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SlackMessage {
+	response_type: &'static str,
+	text: String,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	blocks: Vec<SlackImageBlock>,
+}
+
+#[derive(Serialize)]
+struct SlackImageBlock {
+	r#type: &'static str,
+	image_url: String,
+	alt_text: String,
+}
+
+impl SlackMessage {
+	fn ephemeral(text: impl Into<String>) -> Self {
+		Self { response_type: "ephemeral", text: text.into(), blocks: Vec::new() }
+	}
+}
+
+impl From<Reply> for SlackMessage {
+	fn from(reply: Reply) -> Self {
+		match reply {
+			Reply::Meme { url, title, alt } => {
+				Self { response_type: "in_channel", text: title, blocks: vec![SlackImageBlock { r#type: "image", image_url: url, alt_text: alt }] }
+			},
+			Reply::Error(message) => Self::ephemeral(message),
+		}
+	}
+}
+
+/// Only Slack's own hooks are posted to, whatever url the request names.
+fn slack_response_url(url: &str) -> anyhow::Result<Url> {
+	let url = Url::parse(url).context("invalid response_url")?;
+	anyhow::ensure!(url.scheme() == "https" && url.host_str() == Some("hooks.slack.com"), "response_url is not a Slack hook");
+	Ok(url)
+}
+
+/// `POST /api/slash/slack`, the request url of the Slack command.
+pub async fn slack_endpoint(headers: HeaderMap, body: Bytes) -> Response {
+	let Some(secret) = SLACK_SIGNING_SECRET.as_deref() else {
+		return StatusCode::NOT_FOUND.into_response();
+	};
+	let timestamp = header_str(&headers, "x-slack-request-timestamp");
+	if let Err(e) = verify_slack(secret.as_bytes(), timestamp, header_str(&headers, "x-slack-signature"), &body, now_millis() / 1000) {
+		return (StatusCode::UNAUTHORIZED, format!("{e:#}")).into_response();
+	}
+	let command = match serde_urlencoded::from_bytes::<SlackCommand>(&body) {
+		Ok(command) => command,
+		Err(e) => return (StatusCode::BAD_REQUEST, format!("invalid command: {e}")).into_response(),
+	};
+	if command.ssl_check.is_some() {
+		return StatusCode::OK.into_response();
+	}
+	let response_url = slack_response_url(&command.response_url);
+	let reply = reply_in_time(command.text, move |reply| async move { send_json(reqwest::Method::POST, response_url?, &SlackMessage::from(reply)).await }).await;
+	json_response(&reply.map_or_else(|| SlackMessage::ephemeral("Rendering your meme..."), SlackMessage::from))
+}
+
+/// The parts of a Discord interaction that are used.
+#[derive(Deserialize)]
+struct Interaction {
+	r#type: u8,
+	#[serde(default)]
+	application_id: String,
+	#[serde(default)]
+	token: String,
+	#[serde(default)]
+	data: Option<InteractionData>,
+}
+
+#[derive(Deserialize)]
+struct InteractionData {
+	#[serde(default)]
+	options: Vec<InteractionOption>,
+}
+
+#[derive(Deserialize)]
+struct InteractionOption {
+	#[serde(default)]
+	value: serde_json::Value,
+}
+
+impl Interaction {
+	const PING: u8 = 1;
+	const APPLICATION_COMMAND: u8 = 2;
+
+	/// The command's first string option.
+	fn text(&self) -> String {
+		self.data.iter().flat_map(|data| &data.options).find_map(|option| option.value.as_str()).unwrap_or_default().to_owned()
+	}
+}
+
+#[derive(Serialize)]
+struct DiscordResponse {
+	r#type: u8,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	data: Option<DiscordMessage>,
+}
+
+impl DiscordResponse {
+	const PONG: u8 = 1;
+	const CHANNEL_MESSAGE: u8 = 4;
+	const DEFERRED_CHANNEL_MESSAGE: u8 = 5;
+}
+
+#[derive(Serialize)]
+struct DiscordMessage {
+	content: String,
+	embeds: Vec<DiscordEmbed>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	flags: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct DiscordEmbed {
+	title: String,
+	description: String,
+	image: DiscordImage,
+}
+
+#[derive(Serialize)]
+struct DiscordImage {
+	url: String,
+}
+
+impl From<Reply> for DiscordMessage {
+	fn from(reply: Reply) -> Self {
+		match reply {
+			Reply::Meme { url, title, alt } => {
+				Self { content: String::new(), embeds: vec![DiscordEmbed { title, description: alt, image: DiscordImage { url } }], flags: None }
+			},
+			Reply::Error(message) => Self { content: message, embeds: Vec::new(), flags: Some(EPHEMERAL) },
+		}
+	}
+}
+
+/// Edits the deferred reply, which Discord has already shown publicly, so errors cannot be made ephemeral anymore.
+async fn edit_discord_reply(application_id: &str, token: &str, reply: Reply) -> anyhow::Result<()> {
+	let mut url = Url::parse(DISCORD_API)?;
+	url.path_segments_mut().map_err(|()| anyhow::anyhow!("discord api url cannot have a path"))?.extend([
+		"webhooks",
+		application_id,
+		token,
+		"messages",
+		"@original",
+	]);
+	send_json(reqwest::Method::PATCH, url, &DiscordMessage { flags: None, ..DiscordMessage::from(reply) }).await
+}
+
+/// `POST /api/slash/discord`, the interactions endpoint url of the Discord application.
+pub async fn discord_endpoint(headers: HeaderMap, body: Bytes) -> Response {
+	let Some(public_key) = DISCORD_PUBLIC_KEY.as_ref() else {
+		return StatusCode::NOT_FOUND.into_response();
+	};
+	let timestamp = header_str(&headers, "x-signature-timestamp");
+	if let Err(e) = verify_discord(public_key, timestamp, header_str(&headers, "x-signature-ed25519"), &body, now_millis() / 1000) {
+		return (StatusCode::UNAUTHORIZED, format!("{e:#}")).into_response();
+	}
+	let interaction = match serde_json::from_slice::<Interaction>(&body) {
+		Ok(interaction) => interaction,
+		Err(e) => return (StatusCode::BAD_REQUEST, format!("invalid interaction: {e}")).into_response(),
+	};
+	match interaction.r#type {
+		Interaction::PING => json_response(&DiscordResponse { r#type: DiscordResponse::PONG, data: None }),
+		Interaction::APPLICATION_COMMAND => {
+			let Interaction { application_id, token, .. } = &interaction;
+			let (application_id, token) = (application_id.clone(), token.clone());
+			let reply = reply_in_time(interaction.text(), move |reply| async move { edit_discord_reply(&application_id, &token, reply).await }).await;
+			json_response(&match reply {
+				Some(reply) => DiscordResponse { r#type: DiscordResponse::CHANNEL_MESSAGE, data: Some(reply.into()) },
+				None => DiscordResponse { r#type: DiscordResponse::DEFERRED_CHANNEL_MESSAGE, data: None },
+			})
+		},
+		_ => (StatusCode::BAD_REQUEST, "unsupported interaction").into_response(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ed25519_dalek::{Signer, SigningKey};
+
+	use super::*;
+
+	const NOW: i64 = 1_700_000_000;
+	const SECRET: &[u8] = b"slack-secret";
+
+	fn slack_signature(timestamp: &str, body: &[u8]) -> String {
+		let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+		mac.update(format!("v0:{timestamp}:").as_bytes());
+		mac.update(body);
+		format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+	}
+
+	#[test]
+	fn slack_signatures() {
+		let timestamp = NOW.to_string();
+		let body = b"command=%2Fmeme&text=drake";
+		let signature = slack_signature(&timestamp, body);
+		verify_slack(SECRET, &timestamp, &signature, body, NOW).unwrap();
+		assert!(verify_slack(b"other-secret", &timestamp, &signature, body, NOW).is_err());
+		assert!(verify_slack(SECRET, &timestamp, &signature, b"command=%2Fmeme&text=drakf", NOW).is_err());
+		assert!(verify_slack(SECRET, &timestamp, &signature, body, NOW + MAX_CLOCK_SKEW + 1).is_err());
+		assert!(verify_slack(SECRET, "", &signature, body, NOW).is_err());
+		assert!(verify_slack(SECRET, &timestamp, &signature["v0=".len()..], body, NOW).is_err());
+	}
+
+	#[test]
+	fn discord_signatures() {
+		let key = SigningKey::from_bytes(&[7; 32]);
+		let public_key = parse_public_key(&hex::encode(key.verifying_key().as_bytes())).unwrap();
+		let timestamp = NOW.to_string();
+		let body = br#"{"type":1}"#;
+		let signature = hex::encode(key.sign(&[timestamp.as_bytes(), body].concat()).to_bytes());
+		verify_discord(&public_key, &timestamp, &signature, body, NOW).unwrap();
+		assert!(verify_discord(&public_key, &timestamp, &signature, br#"{"type":2}"#, NOW).is_err());
+		assert!(verify_discord(&public_key, &(NOW + 1).to_string(), &signature, body, NOW).is_err());
+		assert!(verify_discord(&public_key, &timestamp, &signature, body, NOW - MAX_CLOCK_SKEW - 1).is_err());
+		assert!(verify_discord(&SigningKey::from_bytes(&[8; 32]).verifying_key(), &timestamp, &signature, body, NOW).is_err());
+		assert!(verify_discord(&public_key, &timestamp, "abcd", body, NOW).is_err());
+		assert!(parse_public_key("abcd").is_err());
+	}
+
+	#[test]
+	fn commands() {
+		let command = parse_command(" drake |  top | | bottom ").unwrap();
+		assert_eq!(command, SlashCommand { template: "drake".to_owned(), captions: vec!["top".to_owned(), String::new(), "bottom".to_owned()] });
+		assert!(parse_command("").is_err());
+		assert!(parse_command(" | top").is_err());
+		assert!(parse_command("help").is_err());
+	}
+
+	#[test]
+	fn fuzzy_matching() {
+		let templates =
+			["Drake Hotline Bling", "Distracted Boyfriend", "Two Buttons", "Expanding Brain", "Change My Mind"].map(|name| (Uuid::new_v4(), name.to_owned()));
+		let best = |query: &str| {
+			let ranked = rank(query, &templates);
+			(ranked[0].0, ranked[0].1.1.as_str())
+		};
+		let (score, name) = best("drake");
+		assert_eq!(name, "Drake Hotline Bling");
+		assert!(score >= MIN_SCORE);
+		assert_eq!(best("distracted boyfreind").1, "Distracted Boyfriend");
+		assert_eq!(best("DISTRACTED-BF").1, "Distracted Boyfriend");
+		assert_eq!(best("two butons").1, "Two Buttons");
+		assert_eq!(best("brain").1, "Expanding Brain");
+		assert!(best("spongebob").0 < MIN_SCORE);
+	}
+}
@@ -0,0 +1,585 @@
+use std::{collections::VecDeque, time::Duration};
+
+use dioxus::logger::tracing::error;
+use tokio::{
+	sync::{mpsc, watch},
+	time::Instant,
+};
+use uuid::Uuid;
+
+use crate::{
+	api::{
+		auth::UserInfo,
+		points::TransactionKind,
+		tables::{BUY_IN_BIG_BLINDS, CardView, ClientMessage, HandView, Payout, RAKE_PERCENT, SeatView, ServerMessage, TableKind, TableSummary, TableView},
+	},
+	game::poker::{
+		Holdem, Variant,
+		evaluator::HandValue,
+		meme::{MemeBet, MemeHandValue, MemePoker},
+		table::{Action, Blinds, Hand, Street},
+	},
+	server::{
+		db::with_conn,
+		ledger::{self, Account, Posted, Posting},
+		now_millis,
+	},
+};
+
+pub const TABLE_SEATS: usize = 6;
+/// How long the seat to act has before it is checked or folded for and sat out.
+const ACTION_TIMEOUT: Duration = Duration::from_secs(30);
+/// Pause between hands, so everyone sees how the last one ended.
+const NEXT_HAND_DELAY: Duration = Duration::from_secs(5);
+/// A table with nobody seated closes after this long.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// A [`Variant`] that can be played at a live table.
+pub trait LiveVariant: Variant<Card: Send + Sync, Value: Send + Sync> + Clone + Send + Sync + 'static {
+	fn card_view(card: &Self::Card) -> CardView;
+
+	/// How a hand's value reads in the payouts, like "Full House".
+	fn describe(value: &Self::Value) -> String;
+}
+
+impl LiveVariant for Holdem {
+	fn card_view(card: &Self::Card) -> CardView {
+		CardView::Playing(*card)
+	}
+
+	fn describe(value: &HandValue) -> String {
+		value.category.to_string()
+	}
+}
+
+impl LiveVariant for MemePoker {
+	fn card_view(card: &MemeBet) -> CardView {
+		CardView::Meme(card.clone())
+	}
+
+	fn describe(value: &MemeHandValue) -> String {
+		format!("{} points", value.total)
+	}
+}
+
+pub enum Command {
+	Connect { connection: u64, user: UserInfo, outbox: mpsc::UnboundedSender<ServerMessage> },
+	Disconnect { connection: u64 },
+	Message { connection: u64, message: ClientMessage },
+}
+
+struct Connection {
+	id: u64,
+	user: UserInfo,
+	outbox: mpsc::UnboundedSender<ServerMessage>,
+}
+
+struct Player {
+	user_id: Uuid,
+	username: String,
+	/// Names this sitting's buy-in and cash-out in the ledger.
+	session: Uuid,
+	stack: u64,
+	sitting_out: bool,
+	/// Asked to leave during a hand. The seat folds when its turn comes and is freed once the hand is over.
+	leaving: bool,
+}
+
+struct LiveHand<V: Variant> {
+	number: u64,
+	hand: Hand<V>,
+	/// Table seat of every seat in `hand`.
+	seats: Vec<usize>,
+	/// Who sat there, for the payouts of players who left or busted.
+	usernames: Vec<String>,
+	users: Vec<Uuid>,
+	/// Stacks when the hand was dealt.
+	stacks: Vec<u64>,
+	/// Taken from each seat's winnings once the hand is over.
+	rake: Vec<u64>,
+}
+
+impl<V: Variant> LiveHand<V> {
+	fn index_of(&self, seat: usize) -> Option<usize> {
+		self.seats.iter().position(|&other| other == seat)
+	}
+
+	fn in_progress(&self) -> bool {
+		self.hand.street() != Street::Complete
+	}
+}
+
+pub struct Table<V: LiveVariant> {
+	id: Uuid,
+	kind: TableKind,
+	blinds: Blinds,
+	variant: V,
+	seats: Vec<Option<Player>>,
+	connections: Vec<Connection>,
+	/// The hand being played, or the last one until the next is dealt.
+	hand: Option<LiveHand<V>>,
+	/// Table seat of the last button.
+	button: usize,
+	hands_played: u64,
+	version: u64,
+	/// When the seat to act times out, with the same moment in milliseconds since the unix epoch for clients.
+	deadline: Option<(Instant, i64)>,
+	next_hand_at: Option<Instant>,
+	idle_since: Option<Instant>,
+	summary: watch::Sender<TableSummary>,
+	/// Chip movements waiting to be booked in the ledger, oldest first.
+	postings: VecDeque<Posting>,
+}
+
+impl<V: LiveVariant> Table<V> {
+	pub fn new(id: Uuid, kind: TableKind, blinds: Blinds, variant: V) -> (Self, watch::Receiver<TableSummary>) {
+		let (summary, receiver) = watch::channel(TableSummary { id, kind, blinds, players: 0, seats: TABLE_SEATS, hands_played: 0 });
+		let table = Self {
+			id,
+			kind,
+			blinds,
+			variant,
+			seats: (0..TABLE_SEATS).map(|_| None).collect(),
+			connections: Vec::new(),
+			hand: None,
+			button: TABLE_SEATS - 1,
+			hands_played: 0,
+			version: 0,
+			deadline: None,
+			next_hand_at: None,
+			idle_since: Some(Instant::now()),
+			summary,
+			postings: VecDeque::new(),
+		};
+		(table, receiver)
+	}
+
+	/// Handles commands and timers until the table has been empty for [`IDLE_TIMEOUT`] or every handle to it is gone.
+	pub async fn run(mut self, mut commands: mpsc::UnboundedReceiver<Command>) {
+		loop {
+			let wake = [self.deadline.map(|(at, _)| at), self.next_hand_at, self.idle_since.map(|since| since + IDLE_TIMEOUT)].into_iter().flatten().min();
+			tokio::select! {
+				command = commands.recv() => match command {
+					Some(command) => self.handle(command).await,
+					None => break,
+				},
+				() = sleep_until(wake) => {
+					if self.idle_since.is_some_and(|since| since.elapsed() >= IDLE_TIMEOUT) {
+						break;
+					}
+					self.tick();
+				},
+			}
+			self.flush().await;
+		}
+		self.flush().await;
+	}
+
+	async fn handle(&mut self, command: Command) {
+		match command {
+			Command::Connect { connection, user, outbox } => {
+				self.connections.push(Connection { id: connection, user, outbox });
+			},
+			Command::Disconnect { connection } => self.connections.retain(|other| other.id != connection),
+			Command::Message { connection, message } => {
+				let Some(user) = self.connections.iter().find(|other| other.id == connection).map(|other| other.user.clone()) else {
+					return;
+				};
+				let result = match message {
+					ClientMessage::Join => self.join(&user).await,
+					ClientMessage::Leave => self.leave(user.id),
+					ClientMessage::Act(action) => self.act(user.id, action),
+					ClientMessage::Resync => {
+						self.send_view(connection);
+						return;
+					},
+				};
+				if let Err(e) = result {
+					self.send(connection, ServerMessage::Error(format!("{e:#}")));
+					return;
+				}
+			},
+		}
+		self.publish();
+	}
+
+	fn tick(&mut self) {
+		let now = Instant::now();
+		if self.deadline.is_some_and(|(at, _)| at <= now) {
+			self.deadline = None;
+			if let Some(live) = &mut self.hand
+				&& let Some(index) = live.hand.to_act()
+			{
+				if let Some(player) = &mut self.seats[live.seats[index]] {
+					player.sitting_out = true;
+				}
+				let action = if live.hand.legal_actions().check { Action::Check } else { Action::Fold };
+				live.hand.apply(index, action).expect("checking or folding is always legal");
+			}
+			self.advance();
+		}
+		if self.next_hand_at.is_some_and(|at| at <= now) {
+			self.next_hand_at = None;
+			self.start_hand();
+		}
+		self.publish();
+	}
+
+	fn seat_of(&self, user_id: Uuid) -> Option<usize> {
+		self.seats.iter().position(|player| player.as_ref().is_some_and(|player| player.user_id == user_id))
+	}
+
+	async fn join(&mut self, user: &UserInfo) -> anyhow::Result<()> {
+		if let Some(seat) = self.seat_of(user.id) {
+			let player = self.seats[seat].as_mut().expect("seat_of returns occupied seats");
+			player.sitting_out = false;
+			player.leaving = false;
+		} else {
+			let seat = self.seats.iter().position(Option::is_none).ok_or_else(|| anyhow::anyhow!("the table is full"))?;
+			let session = Uuid::new_v4();
+			let stack = self.blinds.big * BUY_IN_BIG_BLINDS;
+			self.buy_in(user.id, session, stack).await?;
+			self.seats[seat] = Some(Player { user_id: user.id, username: user.username.clone(), session, stack, sitting_out: false, leaving: false });
+		}
+		self.schedule_hand();
+		Ok(())
+	}
+
+	/// Moves the chips from the player's wallet onto their seat. Earlier cash-outs are booked first, so they can pay for it.
+	async fn buy_in(&mut self, user_id: Uuid, session: Uuid, chips: u64) -> anyhow::Result<()> {
+		self.flush().await;
+		let posting = self.buy_in_posting(user_id, session, chips);
+		let posted = with_conn(move |conn| {
+			ledger::open_wallet(conn, user_id)?;
+			ledger::post(conn, &posting)
+		})
+		.await?;
+		match posted {
+			Posted::Applied | Posted::Duplicate => Ok(()),
+			Posted::Insufficient(_) => anyhow::bail!("sitting down takes {chips} points"),
+		}
+	}
+
+	fn buy_in_posting(&self, user_id: Uuid, session: Uuid, chips: u64) -> Posting {
+		Posting::transfer(
+			format!("buy-in:{session}"),
+			TransactionKind::BuyIn,
+			format!("Sat down at a {} table", self.kind),
+			Account::Wallet(user_id),
+			Account::Seat { table: self.id, user: user_id },
+			chips as i64,
+		)
+	}
+
+	/// Frees the seat and pays the player's chips back into their wallet.
+	fn vacate(&mut self, seat: usize) {
+		let Some(player) = self.seats[seat].take() else { return };
+		if player.stack > 0 {
+			self.postings.push_back(Posting::transfer(
+				format!("cash-out:{}", player.session),
+				TransactionKind::CashOut,
+				format!("Left a {} table", self.kind),
+				Account::Seat { table: self.id, user: player.user_id },
+				Account::Wallet(player.user_id),
+				player.stack as i64,
+			));
+		}
+	}
+
+	/// Books what the ledger is owed, in order. Whatever fails stays queued for the next try; seats the table never got to cash
+	/// out are refunded when the server starts again.
+	async fn flush(&mut self) {
+		while let Some(posting) = self.postings.front().cloned() {
+			let key = posting.key.clone();
+			match with_conn(move |conn| ledger::post(conn, &posting)).await {
+				Ok(Posted::Applied | Posted::Duplicate) => {},
+				Ok(Posted::Insufficient(account)) => error!("table {} dropped posting {key}: {account:?} cannot cover it", self.id),
+				Err(e) => {
+					error!("table {} could not post {key}: {e:#}", self.id);
+					return;
+				},
+			}
+			self.postings.pop_front();
+		}
+	}
+
+	fn leave(&mut self, user_id: Uuid) -> anyhow::Result<()> {
+		let seat = self.seat_of(user_id).ok_or_else(|| anyhow::anyhow!("you are not seated"))?;
+		// Seats dealt into a hand stay taken until it ends, so nobody else can sit in one mid-hand.
+		let Some(live) = self.hand.as_ref().filter(|live| live.in_progress()) else {
+			self.vacate(seat);
+			return Ok(());
+		};
+		if let Some(index) = live.index_of(seat) {
+			let to_act = live.hand.to_act() == Some(index);
+			self.seats[seat].as_mut().expect("seat_of returns occupied seats").leaving = true;
+			if to_act {
+				self.advance();
+			}
+		} else {
+			self.vacate(seat);
+		}
+		Ok(())
+	}
+
+	fn act(&mut self, user_id: Uuid, action: Action) -> anyhow::Result<()> {
+		let seat = self.seat_of(user_id).ok_or_else(|| anyhow::anyhow!("you are not seated"))?;
+		let live = self.hand.as_mut().filter(|live| live.in_progress()).ok_or_else(|| anyhow::anyhow!("no hand is being played"))?;
+		let index = live.index_of(seat).ok_or_else(|| anyhow::anyhow!("you are not in this hand"))?;
+		live.hand.apply(index, action)?;
+		self.advance();
+		Ok(())
+	}
+
+	/// Moves past seats that can't act for themselves, then starts the clock on the next one or wraps the hand up.
+	fn advance(&mut self) {
+		self.deadline = None;
+		let Some(live) = &mut self.hand else { return };
+		while let Some(index) = live.hand.to_act() {
+			match &self.seats[live.seats[index]] {
+				Some(player) if !player.leaving && !player.sitting_out => {
+					let at = Instant::now() + ACTION_TIMEOUT;
+					self.deadline = Some((at, now_millis() + ACTION_TIMEOUT.as_millis() as i64));
+					return;
+				},
+				Some(player) if !player.leaving && live.hand.legal_actions().check => {
+					live.hand.apply(index, Action::Check).expect("checking was legal");
+				},
+				_ => live.hand.apply(index, Action::Fold).expect("folding is always legal"),
+			}
+		}
+		self.finish_hand();
+	}
+
+	/// Pays the stacks back to the players less the rake, books the hand, frees the seats of leavers and the busted, and schedules
+	/// the next hand.
+	fn finish_hand(&mut self) {
+		let Some(live) = &mut self.hand else { return };
+		// No flop, no rake.
+		let raked = !live.hand.board().is_empty();
+		live.rake = live
+			.hand
+			.seats()
+			.iter()
+			.zip(&live.stacks)
+			.map(|(state, &stack)| if raked { state.stack.saturating_sub(stack) * RAKE_PERCENT / 100 } else { 0 })
+			.collect();
+		let mut legs = Vec::with_capacity(live.seats.len() + 1);
+		for (index, &seat) in live.seats.iter().enumerate() {
+			let stack = live.hand.seats()[index].stack - live.rake[index];
+			if let Some(player) = &mut self.seats[seat] {
+				player.stack = stack;
+			}
+			legs.push((Account::Seat { table: self.id, user: live.users[index] }, stack as i64 - live.stacks[index] as i64));
+		}
+		legs.push((Account::Rake, live.rake.iter().sum::<u64>() as i64));
+		self.postings.push_back(Posting {
+			key: format!("hand:{}:{}", self.id, live.number),
+			kind: TransactionKind::Hand,
+			memo: format!("Hand #{} at a {} table", live.number, self.kind),
+			legs,
+		});
+		self.hands_played += 1;
+		for seat in 0..TABLE_SEATS {
+			if self.seats[seat].as_ref().is_some_and(|player| player.leaving || player.stack == 0) {
+				self.vacate(seat);
+			}
+		}
+		self.schedule_hand();
+	}
+
+	fn ready_seats(&self) -> Vec<usize> {
+		(0..TABLE_SEATS).filter(|&seat| self.seats[seat].as_ref().is_some_and(|player| !player.sitting_out && player.stack > 0)).collect()
+	}
+
+	fn schedule_hand(&mut self) {
+		let idle = self.hand.as_ref().is_none_or(|live| !live.in_progress());
+		if idle && self.next_hand_at.is_none() && self.ready_seats().len() >= 2 {
+			self.next_hand_at = Some(Instant::now() + NEXT_HAND_DELAY);
+		}
+	}
+
+	fn start_hand(&mut self) {
+		let seats = self.ready_seats();
+		if seats.len() < 2 {
+			return;
+		}
+		let button = seats.iter().position(|&seat| seat > self.button).unwrap_or(0);
+		self.button = seats[button];
+		let players = seats.iter().filter_map(|&seat| self.seats[seat].as_ref()).collect::<Vec<_>>();
+		let stacks = players.iter().map(|player| player.stack).collect::<Vec<_>>();
+		let usernames = players.iter().map(|player| player.username.clone()).collect();
+		let users = players.iter().map(|player| player.user_id).collect();
+		let seed = Uuid::new_v4().as_u64_pair().0;
+		match Hand::new(self.variant.clone(), self.blinds, &stacks, button, seed) {
+			Ok(hand) => {
+				let rake = vec![0; seats.len()];
+				self.hand = Some(LiveHand { number: self.hands_played + 1, hand, seats, usernames, users, stacks, rake });
+				self.advance();
+			},
+			Err(e) => error!("table {} could not deal: {e:#}", self.id),
+		}
+	}
+
+	fn send(&self, connection: u64, message: ServerMessage) {
+		if let Some(connection) = self.connections.iter().find(|other| other.id == connection) {
+			let _ = connection.outbox.send(message);
+		}
+	}
+
+	fn send_view(&self, connection: u64) {
+		if let Some(connection) = self.connections.iter().find(|other| other.id == connection) {
+			let _ = connection.outbox.send(ServerMessage::Table(self.view(connection.user.id)));
+		}
+	}
+
+	/// Sends every connection its view of the new state.
+	fn publish(&mut self) {
+		self.version += 1;
+		let players = self.seats.iter().flatten().count();
+		self.idle_since = match (players, self.idle_since) {
+			(0, None) => Some(Instant::now()),
+			(0, since) => since,
+			_ => None,
+		};
+		self.summary.send_modify(|summary| {
+			summary.players = players;
+			summary.hands_played = self.hands_played;
+		});
+		for connection in &self.connections {
+			let _ = connection.outbox.send(ServerMessage::Table(self.view(connection.user.id)));
+		}
+	}
+
+	fn view(&self, viewer: Uuid) -> TableView {
+		let you = self.seat_of(viewer);
+		let live = self.hand.as_ref();
+		let shown = |index: usize| live.and_then(|live| live.hand.result()).is_some_and(|result| result.shown.iter().any(|(seat, _)| *seat == index));
+		let seats = self
+			.seats
+			.iter()
+			.enumerate()
+			.map(|(seat, player)| {
+				let player = player.as_ref()?;
+				let in_hand = live.and_then(|live| live.index_of(seat).map(|index| (index, &live.hand.seats()[index])));
+				Some(SeatView {
+					username: player.username.clone(),
+					stack: in_hand.map_or(player.stack, |(index, state)| state.stack - live.map_or(0, |live| live.rake[index])),
+					bet: in_hand.map_or(0, |(_, state)| state.street_bet),
+					in_hand: in_hand.is_some(),
+					folded: in_hand.is_some_and(|(_, state)| state.folded),
+					all_in: in_hand.is_some_and(|(_, state)| state.all_in),
+					sitting_out: player.sitting_out,
+					connected: self.connections.iter().any(|connection| connection.user.id == player.user_id),
+					hole: in_hand.filter(|(index, _)| you == Some(seat) || shown(*index)).map(|(_, state)| state.hole.iter().map(V::card_view).collect()),
+				})
+			})
+			.collect();
+		let hand = live.map(|live| HandView {
+			number: live.number,
+			button: live.seats[live.hand.button()],
+			street: live.hand.street(),
+			board: live.hand.board().iter().map(V::card_view).collect(),
+			pot: live.hand.pot(),
+			current_bet: live.hand.current_bet(),
+			to_act: live.hand.to_act().map(|index| live.seats[index]),
+			deadline: self.deadline.map(|(_, millis)| millis),
+		});
+		let legal =
+			live.filter(|live| live.hand.to_act().is_some_and(|index| Some(live.seats[index]) == you)).map(|live| live.hand.legal_actions()).unwrap_or_default();
+		TableView { id: self.id, kind: self.kind, blinds: self.blinds, version: self.version, seats, hand, payouts: self.payouts(), you, legal }
+	}
+
+	fn payouts(&self) -> Vec<Payout> {
+		let Some((live, result)) = self.hand.as_ref().and_then(|live| Some((live, live.hand.result()?))) else {
+			return Vec::new();
+		};
+		let mut payouts: Vec<Payout> = Vec::new();
+		for (index, amount) in result.pots.iter().flat_map(|pot| &pot.shares) {
+			let seat = live.seats[*index];
+			match payouts.iter_mut().find(|payout| payout.seat == seat) {
+				Some(payout) => payout.amount += amount,
+				None => payouts.push(Payout {
+					seat,
+					username: live.usernames[*index].clone(),
+					amount: *amount,
+					hand: result.shown.iter().find(|(shown, _)| shown == index).map(|(_, value)| V::describe(value)),
+				}),
+			}
+		}
+		for payout in &mut payouts {
+			if let Some(index) = live.index_of(payout.seat) {
+				payout.amount = payout.amount.saturating_sub(live.rake[index]);
+			}
+		}
+		payouts
+	}
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+	match deadline {
+		Some(deadline) => tokio::time::sleep_until(deadline).await,
+		None => std::future::pending().await,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::server::db::{repo, test_conn};
+
+	/// Seats three players, plays hands of all-ins, min-raises and calls until someone has every chip, then stands the rest up.
+	/// Every posting the table queued along the way balances and books, and the books still audit.
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn table_postings_balance() {
+		let mut conn = test_conn();
+		let blinds = Blinds::new(1, 2).unwrap();
+		let (mut table, _summary) = Table::new(Uuid::new_v4(), TableKind::Holdem, blinds, Holdem);
+		let users = (0..3).map(|i| repo::users::create(&mut conn, &format!("table_postings_{i}"), "hash").unwrap().id).collect::<Vec<_>>();
+		for (seat, &user_id) in users.iter().enumerate() {
+			ledger::open_wallet(&mut conn, user_id).unwrap();
+			let (session, stack) = (Uuid::new_v4(), blinds.big * BUY_IN_BIG_BLINDS);
+			assert_eq!(ledger::post(&mut conn, &table.buy_in_posting(user_id, session, stack)).unwrap(), Posted::Applied);
+			table.seats[seat] = Some(Player { user_id, username: format!("player {seat}"), session, stack, sitting_out: false, leaving: false });
+		}
+		let rake = ledger::balance(&mut conn, Account::Rake).unwrap();
+
+		for number in 0..200 {
+			if table.ready_seats().len() < 2 {
+				break;
+			}
+			table.start_hand();
+			while let Some(live) = table.hand.as_ref().filter(|live| live.in_progress()) {
+				let user_id = live.users[live.hand.to_act().expect("a hand in progress waits on a seat")];
+				let legal = live.hand.legal_actions();
+				let action = match (number % 3, legal.raise) {
+					(0, Some(_)) => Action::AllIn,
+					(1, Some((least, _))) => Action::RaiseTo(least),
+					_ if legal.check => Action::Check,
+					_ => Action::Call,
+				};
+				table.act(user_id, action).unwrap();
+			}
+		}
+		let seated = users.iter().copied().filter(|&user_id| table.seat_of(user_id).is_some()).collect::<Vec<_>>();
+		for user_id in seated {
+			table.leave(user_id).unwrap();
+		}
+		assert!(table.hands_played > 0);
+		assert!(table.postings.iter().any(|posting| posting.kind == TransactionKind::Hand));
+		assert!(table.postings.iter().any(|posting| posting.kind == TransactionKind::CashOut));
+
+		for posting in table.postings.drain(..) {
+			assert_eq!(posting.legs.iter().map(|(_, amount)| amount).sum::<i64>(), 0, "{posting:?}");
+			assert_eq!(ledger::post(&mut conn, &posting).unwrap(), Posted::Applied, "{posting:?}");
+		}
+		let mut wallets = 0;
+		for &user_id in &users {
+			assert_eq!(ledger::balance(&mut conn, Account::Seat { table: table.id, user: user_id }).unwrap(), 0);
+			wallets += ledger::balance(&mut conn, Account::Wallet(user_id)).unwrap();
+		}
+		assert_eq!(wallets + ledger::balance(&mut conn, Account::Rake).unwrap() - rake, 3 * ledger::WELCOME_POINTS);
+		ledger::audit(&mut conn).unwrap();
+	}
+}
@@ -0,0 +1,166 @@
+//! Live poker tables. Each table is an actor task that owns its state and is only reached through its command channel; websocket
+//! connections forward what players send and relay the views the actor publishes.
+
+mod actor;
+
+use std::{
+	collections::HashMap,
+	sync::{
+		LazyLock,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+use diesel::PgConnection;
+use dioxus::{fullstack::TypedWebsocket, logger::tracing::info};
+use parking_lot::Mutex;
+use tokio::sync::{mpsc, watch};
+use uuid::Uuid;
+
+pub use actor::LiveVariant;
+use actor::{Command, Table};
+
+use crate::{
+	api::{
+		auth::UserInfo,
+		tables::{ClientMessage, ServerMessage, TableKind, TableSummary},
+	},
+	game::{
+		percentile::{PercentileWindow, WINDOW_STEP},
+		poker::{meme::MemeBet, rng::SeededRng, table::Blinds},
+	},
+	server::{
+		db::repo,
+		game::{MIN_REFERENCE_SIZE, record_percentile},
+	},
+};
+
+pub const MEME_DECK_SIZE: i64 = 52;
+/// Every table is a task holding its state in memory, so how many run at once is bounded.
+pub const MAX_TABLES: usize = 100;
+/// Tables one player may have open at once. They close on their own once nobody has sat at them for a while.
+pub const MAX_TABLES_PER_USER: usize = 3;
+/// Widths of the windows meme poker cards bet on.
+const MEME_WINDOW_WIDTHS: [u8; 4] = [10, 20, 30, 50];
+
+static TABLES: LazyLock<Mutex<HashMap<Uuid, TableHandle>>> = LazyLock::new(Default::default);
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone)]
+pub struct TableHandle {
+	commands: mpsc::UnboundedSender<Command>,
+	summary: watch::Receiver<TableSummary>,
+	opened_by: Uuid,
+}
+
+/// An open table of that kind and blinds with nobody seated, to send players to before opening another.
+pub fn find_empty(kind: TableKind, blinds: Blinds) -> Option<Uuid> {
+	TABLES
+		.lock()
+		.values()
+		.map(|table| table.summary.borrow().clone())
+		.find(|summary| summary.kind == kind && summary.blinds == blinds && summary.players == 0)
+		.map(|summary| summary.id)
+}
+
+/// Starts a table actor for `opened_by` and returns the table's id, unless they already have [`MAX_TABLES_PER_USER`] open or
+/// [`MAX_TABLES`] are. The actor removes the table again once it closes.
+pub fn open<V: LiveVariant>(kind: TableKind, blinds: Blinds, opened_by: Uuid, variant: V) -> anyhow::Result<Uuid> {
+	let id = Uuid::new_v4();
+	let (commands, inbox) = mpsc::unbounded_channel();
+	let (table, summary) = Table::new(id, kind, blinds, variant);
+	{
+		let mut tables = TABLES.lock();
+		anyhow::ensure!(tables.len() < MAX_TABLES, "every table is taken, join one of the open tables");
+		let opened = tables.values().filter(|table| table.opened_by == opened_by).count();
+		anyhow::ensure!(opened < MAX_TABLES_PER_USER, "you already have {MAX_TABLES_PER_USER} tables open");
+		tables.insert(id, TableHandle { commands, summary, opened_by });
+	}
+	info!("opened {kind} table {id}");
+	tokio::spawn(async move {
+		table.run(inbox).await;
+		TABLES.lock().remove(&id);
+		info!("closed table {id}");
+	});
+	Ok(id)
+}
+
+pub fn find(id: Uuid) -> Option<TableHandle> {
+	TABLES.lock().get(&id).cloned()
+}
+
+/// Open tables, busiest first.
+pub fn list() -> Vec<TableSummary> {
+	let mut tables = TABLES.lock().values().map(|table| table.summary.borrow().clone()).collect::<Vec<_>>();
+	tables.sort_by(|a, b| b.players.cmp(&a.players).then(b.hands_played.cmp(&a.hands_played)));
+	tables
+}
+
+/// Relays between one websocket and the table until either side goes away.
+pub async fn serve(table: TableHandle, user: UserInfo, mut socket: TypedWebsocket<ClientMessage, ServerMessage>) {
+	let connection = NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed);
+	let (outbox, mut updates) = mpsc::unbounded_channel();
+	if table.commands.send(Command::Connect { connection, user, outbox }).is_err() {
+		return;
+	}
+	loop {
+		tokio::select! {
+			message = socket.recv() => match message {
+				Ok(message) => {
+					if table.commands.send(Command::Message { connection, message }).is_err() {
+						break;
+					}
+				},
+				Err(_) => break,
+			},
+			update = updates.recv() => match update {
+				Some(update) => {
+					if socket.send(update).await.is_err() {
+						break;
+					}
+				},
+				None => break,
+			},
+		}
+	}
+	let _ = table.commands.send(Command::Disconnect { connection });
+}
+
+/// Cards for a meme poker table: random settled memes, each bet on a random window and worth what that bet returned.
+pub fn meme_deck(conn: &mut PgConnection) -> anyhow::Result<Vec<MemeBet>> {
+	let records = repo::meme_records::random_settled(conn, MIN_REFERENCE_SIZE, MEME_DECK_SIZE)?;
+	let mut rng = SeededRng::new(Uuid::new_v4().as_u64_pair().0);
+	records
+		.into_iter()
+		.map(|record| {
+			let percentile = record_percentile(conn, &record, rng.unit())?;
+			let width = MEME_WINDOW_WIDTHS[rng.below(MEME_WINDOW_WIDTHS.len() as u64) as usize];
+			let low = WINDOW_STEP * rng.below(u64::from((100 - width) / WINDOW_STEP) + 1) as u8;
+			Ok(MemeBet { record_id: record.id, title: record.title, image_url: record.image_url, window: PercentileWindow::new(low, low + width)?, percentile })
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::game::poker::Holdem;
+
+	#[tokio::test]
+	async fn empty_tables_are_reused_and_openers_capped() {
+		let blinds = Blinds::new(3, 7).unwrap();
+		let opener = Uuid::new_v4();
+		assert_eq!(find_empty(TableKind::Holdem, blinds), None);
+		let first = open(TableKind::Holdem, blinds, opener, Holdem).unwrap();
+		assert_eq!(find_empty(TableKind::Holdem, blinds), Some(first));
+		assert_eq!(find_empty(TableKind::Meme, blinds), None);
+		assert_eq!(find_empty(TableKind::Holdem, Blinds::new(3, 6).unwrap()), None);
+
+		for _ in 1..MAX_TABLES_PER_USER {
+			open(TableKind::Holdem, blinds, opener, Holdem).unwrap();
+		}
+		assert!(open(TableKind::Holdem, blinds, opener, Holdem).is_err());
+		let other = open(TableKind::Holdem, blinds, Uuid::new_v4(), Holdem).unwrap();
+		assert!(find(other).is_some());
+	}
+}
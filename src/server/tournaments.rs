@@ -0,0 +1,227 @@
+//! Judging of sponsored tournaments: ordering entries by the tournament's judging mode, writing the final results and paying out
+//! the prize pool.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use diesel::PgConnection;
+
+use crate::{
+	api::{points::TransactionKind, tournaments::JudgingMode},
+	server::{
+		db::{
+			models::Tournament,
+			repo::{self, tournaments::EntryRow},
+		},
+		ledger::{self, Account, Posting},
+	},
+};
+
+/// Indices of `entries`, best first. Panel mode ranks by the average judge score with votes breaking ties, unscored entries
+/// last. Combined mode adds up each entry's position in both orders. Remaining ties go to the earlier entry.
+pub fn standings(mode: JudgingMode, entries: &[EntryRow]) -> Vec<usize> {
+	let by_votes = ranked(entries, |a, b| b.votes.cmp(&a.votes));
+	let by_panel = ranked(entries, |a, b| panel_score(b).total_cmp(&panel_score(a)).then(b.votes.cmp(&a.votes)));
+	match mode {
+		JudgingMode::Votes => by_votes,
+		JudgingMode::Panel => by_panel,
+		JudgingMode::Combined => {
+			let mut positions = vec![0; entries.len()];
+			for (position, index) in by_votes.into_iter().enumerate().chain(by_panel.into_iter().enumerate()) {
+				positions[index] += position;
+			}
+			let mut order = (0..entries.len()).collect::<Vec<_>>();
+			order.sort_by(|&a, &b| positions[a].cmp(&positions[b]).then_with(|| earlier(&entries[a], &entries[b])));
+			order
+		},
+	}
+}
+
+fn ranked(entries: &[EntryRow], better: impl Fn(&EntryRow, &EntryRow) -> Ordering) -> Vec<usize> {
+	let mut order = (0..entries.len()).collect::<Vec<_>>();
+	order.sort_by(|&a, &b| better(&entries[a], &entries[b]).then_with(|| earlier(&entries[a], &entries[b])));
+	order
+}
+
+fn panel_score(entry: &EntryRow) -> f64 {
+	entry.panel.unwrap_or(f64::NEG_INFINITY)
+}
+
+fn earlier(a: &EntryRow, b: &EntryRow) -> Ordering {
+	a.entered_at.cmp(&b.entered_at).then(a.meme_id.cmp(&b.meme_id))
+}
+
+/// The prize of each of `placed` places: the prizes in order, then nothing for places beyond them. Prizes without a place to take
+/// them stay in the pool and go back to the mint.
+pub fn prize_split(prizes: &[i64], placed: usize) -> Vec<i64> {
+	(0..placed).map(|place| prizes.get(place).copied().unwrap_or(0)).collect()
+}
+
+/// Issues the tournament's prizes into its pool, where they wait for [`finalize`].
+pub fn fund(conn: &mut PgConnection, tournament: &Tournament) -> anyhow::Result<()> {
+	let posting = Posting::transfer(
+		format!("tournament:{}:fund", tournament.id),
+		TransactionKind::Sponsor,
+		format!("Prize pool of {}", tournament.title),
+		Account::Mint,
+		Account::Pool(tournament.id),
+		tournament.prizes.iter().sum(),
+	);
+	ledger::post(conn, &posting)?.applied()
+}
+
+/// Places every listed entry and pays out the prizes, returning what nobody won to the mint. Callers hold the tournament's row
+/// lock and check it has ended. Returns how many entries were placed.
+pub fn finalize(conn: &mut PgConnection, tournament: &Tournament, mode: JudgingMode, now: DateTime<Utc>) -> anyhow::Result<usize> {
+	let entries = repo::tournaments::entries(conn, tournament.id, None)?;
+	let order = standings(mode, &entries);
+	let pool = Account::Pool(tournament.id);
+	for ((place, &index), prize) in order.iter().enumerate().zip(prize_split(&tournament.prizes, order.len())) {
+		let entry = &entries[index];
+		repo::tournaments::set_result(conn, entry.meme_id, place as i32 + 1, prize)?;
+		let memo = format!("Place {} in {}", place + 1, tournament.title);
+		ledger::post(conn, &Posting::transfer(format!("prize:{}", entry.meme_id), TransactionKind::Prize, memo, pool, Account::Wallet(entry.user_id), prize))?
+			.applied()?;
+	}
+	let unclaimed = ledger::balance(conn, pool)?;
+	let memo = format!("Unclaimed prizes of {}", tournament.title);
+	ledger::post(conn, &Posting::transfer(format!("tournament:{}:close", tournament.id), TransactionKind::Sponsor, memo, pool, Account::Mint, unclaimed))?
+		.applied()?;
+	repo::tournaments::mark_finalized(conn, tournament.id, now)?;
+	Ok(order.len())
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::Duration;
+	use diesel::RunQueryDsl;
+	use uuid::Uuid;
+
+	use super::*;
+	use crate::{
+		server::db::{models::Vote, schema::votes, test_conn},
+		stores::meme_canvas::MemeDocument,
+	};
+
+	fn entry(minute: i64, votes: i64, panel: Option<f64>) -> EntryRow {
+		EntryRow {
+			meme_id: Uuid::new_v4(),
+			user_id: Uuid::new_v4(),
+			title: format!("Entry {minute}"),
+			image_url: "https://example.com/entry.png".to_owned(),
+			author: None,
+			entered_at: DateTime::UNIX_EPOCH + Duration::minutes(minute),
+			votes,
+			panel,
+			my_score: None,
+			place: None,
+			prize: None,
+		}
+	}
+
+	#[test]
+	fn votes_rank_by_score_with_ties_to_the_earlier_entry() {
+		let entries = [entry(0, 3, None), entry(2, 5, None), entry(1, 5, None), entry(3, -1, None)];
+		assert_eq!(standings(JudgingMode::Votes, &entries), [2, 1, 0, 3]);
+		assert!(standings(JudgingMode::Votes, &[]).is_empty());
+	}
+
+	#[test]
+	fn panel_ranks_by_average_with_votes_breaking_ties_and_unscored_last() {
+		let entries = [entry(0, 1, Some(4.0)), entry(1, 9, None), entry(2, 2, Some(4.0)), entry(3, 0, Some(2.5)), entry(4, 0, Some(2.5))];
+		assert_eq!(standings(JudgingMode::Panel, &entries), [2, 0, 3, 4, 1]);
+	}
+
+	#[test]
+	fn combined_adds_positions_with_ties_to_the_earlier_entry() {
+		// First, second and third by votes are third, second and first by the panel, so every entry sums to the same position.
+		let entries = [entry(1, 10, Some(1.0)), entry(2, 0, Some(5.0)), entry(0, 5, Some(3.0))];
+		assert_eq!(standings(JudgingMode::Combined, &entries), [2, 0, 1]);
+		let entries = [entry(0, 0, Some(1.0)), entry(1, 10, Some(3.0)), entry(2, 5, Some(5.0))];
+		assert_eq!(standings(JudgingMode::Combined, &entries), [1, 2, 0]);
+	}
+
+	#[test]
+	fn prizes_are_split_by_place() {
+		assert!(prize_split(&[100, 50, 25], 0).is_empty());
+		assert_eq!(prize_split(&[100, 50, 25], 2), [100, 50]);
+		assert_eq!(prize_split(&[100, 50], 4), [100, 50, 0, 0]);
+	}
+
+	fn tournament(conn: &mut PgConnection, ends_at: DateTime<Utc>) -> Tournament {
+		let tournament = Tournament {
+			id: Uuid::new_v4(),
+			sponsor: "Sponsor".to_owned(),
+			title: "Finals".to_owned(),
+			brief: "Make it funny".to_owned(),
+			template_id: None,
+			watermark_url: "https://example.com/brand.png".to_owned(),
+			starts_at: ends_at - Duration::days(7),
+			ends_at,
+			judging: JudgingMode::Votes.to_string(),
+			prizes: vec![100, 50, 25],
+			created_by: None,
+			created_at: ends_at - Duration::days(7),
+			finalized_at: None,
+		};
+		repo::tournaments::create(conn, &tournament, &[]).unwrap();
+		fund(conn, &tournament).unwrap();
+		tournament
+	}
+
+	fn vote(conn: &mut PgConnection, meme_id: Uuid, value: i16, created_at: DateTime<Utc>) {
+		let voter = repo::users::create(conn, &format!("voter_{}", Uuid::new_v4().simple()), "hash").unwrap();
+		diesel::insert_into(votes::table).values(Vote { user_id: voter.id, meme_id, value, created_at }).execute(conn).unwrap();
+	}
+
+	/// Two entries in a tournament with three prizes: the first two prizes are paid, the third goes back to the mint, and votes cast
+	/// after the end do not move the standings.
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn finalize_pays_the_places_and_returns_the_rest() {
+		let mut conn = test_conn();
+		let ends_at = Utc::now() - Duration::hours(1);
+		let tournament = tournament(&mut conn, ends_at);
+		let mint = ledger::balance(&mut conn, Account::Mint).unwrap();
+		let mut entrants = Vec::new();
+		for name in ["finalist_one", "finalist_two"] {
+			let user = repo::users::create(&mut conn, name, "hash").unwrap();
+			ledger::open_wallet(&mut conn, user.id).unwrap();
+			let document =
+				MemeDocument { main_img_url: "https://example.com/template.png".to_owned(), width: 10, height: 10, text_boxes: Vec::new(), watermark_url: None };
+			let meme = repo::memes::insert_document(&mut conn, Some(user.id), &document).unwrap();
+			repo::memes::publish(&mut conn, meme.id, name, "https://example.com/entry.png", &[], None).unwrap();
+			repo::tournaments::add_entry(&mut conn, tournament.id, meme.id, user.id).unwrap();
+			entrants.push((user.id, meme.id));
+		}
+		vote(&mut conn, entrants[0].1, 1, ends_at - Duration::minutes(5));
+		vote(&mut conn, entrants[1].1, 1, ends_at + Duration::minutes(5));
+		vote(&mut conn, entrants[1].1, 1, ends_at + Duration::minutes(6));
+		assert!(repo::tournaments::voting_closed(&mut conn, entrants[1].1, Utc::now()).unwrap());
+		assert!(!repo::tournaments::voting_closed(&mut conn, entrants[1].1, ends_at - Duration::minutes(1)).unwrap());
+
+		assert_eq!(finalize(&mut conn, &tournament, JudgingMode::Votes, Utc::now()).unwrap(), 2);
+		let placed = repo::tournaments::entries(&mut conn, tournament.id, None).unwrap();
+		let results = placed.iter().map(|entry| (entry.meme_id, entry.votes, entry.place, entry.prize)).collect::<Vec<_>>();
+		assert_eq!(results, [(entrants[0].1, 1, Some(1), Some(100)), (entrants[1].1, 0, Some(2), Some(50))]);
+		for ((user_id, _), prize) in entrants.iter().zip([100, 50]) {
+			assert_eq!(ledger::balance(&mut conn, Account::Wallet(*user_id)).unwrap(), ledger::WELCOME_POINTS + prize);
+		}
+		assert_eq!(ledger::balance(&mut conn, Account::Pool(tournament.id)).unwrap(), 0);
+		assert_eq!(ledger::balance(&mut conn, Account::Mint).unwrap(), mint + 25 - 2 * ledger::WELCOME_POINTS);
+		ledger::audit(&mut conn).unwrap();
+	}
+
+	#[test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	fn finalizing_without_entries_returns_the_whole_pool() {
+		let mut conn = test_conn();
+		let tournament = tournament(&mut conn, Utc::now() - Duration::hours(1));
+		let mint = ledger::balance(&mut conn, Account::Mint).unwrap();
+		assert_eq!(finalize(&mut conn, &tournament, JudgingMode::Combined, Utc::now()).unwrap(), 0);
+		assert_eq!(ledger::balance(&mut conn, Account::Pool(tournament.id)).unwrap(), 0);
+		assert_eq!(ledger::balance(&mut conn, Account::Mint).unwrap(), mint + 175);
+		assert!(repo::tournaments::find(&mut conn, tournament.id).unwrap().unwrap().finalized_at.is_some());
+		ledger::audit(&mut conn).unwrap();
+	}
+}
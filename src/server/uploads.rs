@@ -0,0 +1,161 @@
+//! Direct browser uploads: the server hands out a presigned `PUT` url plus a pending upload row, the browser sends the bytes straight to
+//! storage, and a confirmation turns the object into a template. Uploads nobody confirms are swept once they expire.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use dioxus::{
+	fullstack::{
+		HeaderMap, StatusCode,
+		body::Bytes,
+		extract::Path,
+		http::header,
+		response::{IntoResponse, Response},
+	},
+	logger::tracing::{error, info, warn},
+};
+use uuid::Uuid;
+
+use crate::server::{
+	blob::{BLOB_STORE, ImageMime, MAX_IMAGE_BYTES, Sweep, upload_key},
+	db::{models::PendingUpload, pool, repo, with_conn},
+};
+
+/// How long a presigned url, and so the pending upload, stays valid.
+pub const UPLOAD_TTL: Duration = Duration::from_secs(15 * 60);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const SWEEP_BATCH: i64 = 100;
+
+/// Records a pending upload for `user_id` and presigns the url its bytes go to.
+pub async fn create(user_id: Uuid, name: String, mime: ImageMime) -> anyhow::Result<(PendingUpload, String)> {
+	let id = Uuid::new_v4();
+	let now = Utc::now();
+	let upload = PendingUpload {
+		id,
+		user_id,
+		name,
+		object_key: upload_key(id, mime),
+		content_type: mime.content_type().to_owned(),
+		expires_at: now + UPLOAD_TTL,
+		created_at: now,
+	};
+	let url = BLOB_STORE.presign_put(&upload.object_key, UPLOAD_TTL).await?;
+	let record = upload.clone();
+	with_conn(move |conn| Ok(repo::pending_uploads::create(conn, &record)?)).await?;
+	Ok((upload, url))
+}
+
+/// Drops the object and the row, used when a confirmation fails validation and by the sweeper.
+pub async fn discard(upload: &PendingUpload) -> anyhow::Result<()> {
+	BLOB_STORE.delete(&upload.object_key).await?;
+	let id = upload.id;
+	with_conn(move |conn| Ok(repo::pending_uploads::delete(conn, id)?)).await?;
+	Ok(())
+}
+
+/// `PUT /blobs/{*key}` for the local blob store, standing in for the presigned url a bucket would accept.
+pub async fn receive_upload(Path(key): Path<String>, headers: HeaderMap, body: Bytes) -> Response {
+	let lookup = key.clone();
+	let upload = match with_conn(move |conn| Ok(repo::pending_uploads::find_by_key(conn, &lookup)?)).await {
+		Ok(Some(upload)) if upload.expires_at > Utc::now() => upload,
+		Ok(_) => return StatusCode::FORBIDDEN.into_response(),
+		Err(e) => {
+			error!("cannot look up upload {key}: {e:#}");
+			return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+		},
+	};
+	if headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()) != Some(upload.content_type.as_str()) {
+		return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+	}
+	if body.len() > MAX_IMAGE_BYTES {
+		return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+	}
+	match BLOB_STORE.put(&key, body.to_vec(), &upload.content_type).await {
+		Ok(()) => StatusCode::OK.into_response(),
+		Err(e) => {
+			error!("cannot store upload {key}: {e:#}");
+			StatusCode::INTERNAL_SERVER_ERROR.into_response()
+		},
+	}
+}
+
+/// Deletes expired pending uploads and whatever was uploaded for them. One that cannot be discarded is logged and left for the next
+/// sweep, the others are still removed.
+pub async fn sweep_expired() -> anyhow::Result<Sweep> {
+	let mut sweep = Sweep::default();
+	loop {
+		let expired = with_conn(|conn| Ok(repo::pending_uploads::list_expired(conn, Utc::now(), SWEEP_BATCH)?)).await?;
+		let mut failed = 0;
+		for upload in &expired {
+			match discard(upload).await {
+				Ok(()) => sweep.removed += 1,
+				Err(e) => {
+					warn!("cannot discard upload {}: {e:#}", upload.id);
+					failed += 1;
+				},
+			}
+		}
+		sweep.failed += failed;
+		// Failed uploads stay the oldest expired rows, so another batch would only pick them up again.
+		if failed > 0 || expired.len() < SWEEP_BATCH as usize {
+			return Ok(sweep);
+		}
+	}
+}
+
+/// Runs [`sweep_expired`] in the background for the lifetime of the server. Does nothing without postgres.
+pub fn spawn_sweeper() {
+	if pool().is_err() {
+		return;
+	}
+	tokio::spawn(async {
+		let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+		loop {
+			interval.tick().await;
+			match sweep_expired().await {
+				Ok(Sweep { removed: 0, failed: 0 }) => {},
+				Ok(Sweep { removed, failed }) => info!("swept {removed} abandoned uploads, {failed} could not be discarded"),
+				Err(e) => error!("upload sweep failed: {e:#}"),
+			}
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::server::db::init_test_pool;
+
+	#[tokio::test]
+	#[ignore = "needs postgres, set DATABASE_URL and run with --ignored"]
+	async fn sweep_goes_on_past_uploads_it_cannot_discard() {
+		init_test_pool();
+		let expired = Utc::now() - UPLOAD_TTL;
+		let (broken, abandoned) = with_conn(move |conn| {
+			let user = repo::users::create(conn, "sweep_uploader", "hash")?;
+			let upload = |object_key: String| PendingUpload {
+				id: Uuid::new_v4(),
+				user_id: user.id,
+				name: "abandoned".to_owned(),
+				object_key,
+				content_type: "image/png".to_owned(),
+				expires_at: expired,
+				created_at: expired,
+			};
+			// The blob store refuses the key, so discarding it fails.
+			let broken = upload("../outside.png".to_owned());
+			let id = Uuid::new_v4();
+			let abandoned = PendingUpload { id, ..upload(upload_key(id, ImageMime::Png)) };
+			repo::pending_uploads::create(conn, &broken)?;
+			repo::pending_uploads::create(conn, &abandoned)?;
+			Ok((broken.id, abandoned.id))
+		})
+		.await
+		.unwrap();
+
+		assert_eq!(sweep_expired().await.unwrap(), Sweep { removed: 1, failed: 1 });
+		let left =
+			with_conn(move |conn| Ok((repo::pending_uploads::find(conn, broken)?.is_some(), repo::pending_uploads::find(conn, abandoned)?.is_some()))).await.unwrap();
+		assert_eq!(left, (true, false));
+	}
+}
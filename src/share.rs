@@ -0,0 +1,131 @@
+use anyhow::Context;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+use crate::stores::meme_canvas::MemeDocument;
+
+/// Longest `m` query parameter we put in a link. Longer documents are stored on the server and shared by id instead.
+pub const MAX_SHARE_PARAM_LEN: usize = 2000;
+/// Upper bound on the inflated json, so a tiny crafted link cannot expand into a huge allocation.
+const MAX_DECODED_LEN: usize = 64 * 1024;
+/// Leading character of every encoded document, bumped whenever the encoding changes.
+const FORMAT_VERSION: char = '1';
+
+/// Encodes a document as `version + base64url(deflate(json))`.
+pub fn encode_document(document: &MemeDocument) -> anyhow::Result<String> {
+	let json = serde_json::to_vec(document)?;
+	let compressed = miniz_oxide::deflate::compress_to_vec(&json, 9);
+	Ok(format!("{FORMAT_VERSION}{}", URL_SAFE_NO_PAD.encode(compressed)))
+}
+
+pub fn decode_document(encoded: &str) -> anyhow::Result<MemeDocument> {
+	anyhow::ensure!(encoded.len() <= MAX_SHARE_PARAM_LEN, "shared meme is too large");
+	let payload = encoded.strip_prefix(FORMAT_VERSION).context("unsupported share link version")?;
+	let compressed = URL_SAFE_NO_PAD.decode(payload).context("malformed share link")?;
+	let json = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_DECODED_LEN).map_err(|e| anyhow::anyhow!("corrupt share link: {e}"))?;
+	let document: MemeDocument = serde_json::from_slice(&json).context("invalid meme in share link")?;
+	document.validate()?;
+	Ok(document)
+}
+
+/// Returns the encoded document when it is small enough to live in a link.
+pub fn encode_for_link(document: &MemeDocument) -> anyhow::Result<Option<String>> {
+	let encoded = encode_document(document)?;
+	Ok((encoded.len() <= MAX_SHARE_PARAM_LEN).then_some(encoded))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::stores::{
+		meme_canvas::{MAX_DIMENSION, MAX_TEXT_BOXES, MAX_TEXT_LEN},
+		text_box::TextBox,
+	};
+
+	fn document() -> MemeDocument {
+		MemeDocument {
+			main_img_url: "https://example.com/template.png".to_owned(),
+			width: 600,
+			height: 400,
+			text_boxes: vec![
+				TextBox::new("when the build is green".to_owned(), 300.0, 50.0, 48, "Impact".to_owned(), "bold".to_owned()),
+				TextBox::new("ünïcödé 🦀".to_owned(), 300.0, 350.0, 32, "Arial".to_owned(), "italic".to_owned()),
+			],
+			watermark_url: Some("https://example.com/brand.png".to_owned()),
+		}
+	}
+
+	/// Text that deflate cannot shrink, from a fixed xorshift sequence.
+	fn noise(len: usize, seed: u32) -> String {
+		let mut state = seed;
+		(0..len)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 17;
+				state ^= state << 5;
+				char::from(b'!' + (state % 94) as u8)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn round_trips() {
+		let document = document();
+		let encoded = encode_for_link(&document).unwrap().expect("a small document fits in a link");
+		assert!(encoded.starts_with(FORMAT_VERSION));
+		assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{encoded} is not url safe");
+		assert_eq!(decode_document(&encoded).unwrap(), document);
+		assert_eq!(decode_document(&encode_document(&MemeDocument::default()).unwrap()).unwrap(), MemeDocument::default());
+	}
+
+	#[test]
+	fn rejects_truncated_links() {
+		let encoded = encode_document(&document()).unwrap();
+		for len in [0, 1, 2, encoded.len() / 2, encoded.len() - 3, encoded.len() - 1] {
+			assert!(decode_document(&encoded[..len]).is_err(), "accepted {len} of {} characters", encoded.len());
+		}
+	}
+
+	#[test]
+	fn rejects_tampered_links() {
+		let encoded = encode_document(&document()).unwrap();
+		let payload = &encoded[FORMAT_VERSION.len_utf8()..];
+		assert!(decode_document(&format!("2{payload}")).is_err(), "unknown version");
+		assert!(decode_document(payload).is_err(), "missing version");
+		assert!(decode_document(&format!("{FORMAT_VERSION}{}", payload.replacen(|c: char| c.is_ascii_alphanumeric(), "+", 1))).is_err(), "not base64url");
+		let mut compressed = URL_SAFE_NO_PAD.decode(payload).unwrap();
+		compressed[0] ^= 0xff;
+		assert!(decode_document(&format!("{FORMAT_VERSION}{}", URL_SAFE_NO_PAD.encode(compressed))).is_err(), "corrupt deflate stream");
+		let not_a_meme = miniz_oxide::deflate::compress_to_vec(br#"{"hello":"world"}"#, 9);
+		assert!(decode_document(&format!("{FORMAT_VERSION}{}", URL_SAFE_NO_PAD.encode(not_a_meme))).is_err(), "json that is not a meme");
+	}
+
+	#[test]
+	fn rejects_documents_the_generator_cannot_make() {
+		let mut huge = document();
+		huge.width = MAX_DIMENSION + 1;
+		assert!(decode_document(&encode_document(&huge).unwrap()).is_err());
+		let mut script = document();
+		script.main_img_url = "javascript:alert(1)".to_owned();
+		assert!(decode_document(&encode_document(&script).unwrap()).is_err());
+	}
+
+	#[test]
+	fn keeps_links_under_the_size_limit() {
+		let mut large = document();
+		large.text_boxes =
+			(0..MAX_TEXT_BOXES as u32).map(|seed| TextBox::new(noise(MAX_TEXT_LEN, seed + 1), 10.0, 10.0, 24, "Impact".to_owned(), "bold".to_owned())).collect();
+		large.validate().unwrap();
+		assert!(encode_document(&large).unwrap().len() > MAX_SHARE_PARAM_LEN);
+		assert_eq!(encode_for_link(&large).unwrap(), None, "too large for a link, shared by id instead");
+		assert!(decode_document(&encode_document(&large).unwrap()).is_err(), "links past the limit are refused before decoding");
+	}
+
+	#[test]
+	fn caps_the_inflated_size() {
+		// Compresses to a few hundred bytes but inflates past the limit.
+		let bomb = format!(r#"{{"main_img_url":"https://example.com/a.png","width":1,"height":1,"text_boxes":[],"padding":"{}"}}"#, " ".repeat(MAX_DECODED_LEN));
+		let encoded = format!("{FORMAT_VERSION}{}", URL_SAFE_NO_PAD.encode(miniz_oxide::deflate::compress_to_vec(bomb.as_bytes(), 9)));
+		assert!(encoded.len() <= MAX_SHARE_PARAM_LEN);
+		assert!(decode_document(&encoded).is_err());
+	}
+}
@@ -0,0 +1,94 @@
+use crate::stores::meme_canvas::MemeDocument;
+use dioxus::prelude::*;
+use dioxus_sdk::storage::{LocalStorage, use_synced_storage};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const DRAFTS_KEY: &str = "memetopia-drafts";
+const CURRENT_DRAFT_KEY: &str = "memetopia-current-draft";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Draft {
+	pub id: Uuid,
+	pub name: String,
+	/// Milliseconds since the unix epoch, as reported by `Date.now()`.
+	pub updated_at: f64,
+	/// Small jpeg data url of the rendered canvas.
+	pub thumbnail: Option<String>,
+	pub document: MemeDocument,
+}
+
+/// Drafts persisted in local storage, plus the id of the draft the generator is currently editing.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Drafts {
+	pub drafts: Signal<Vec<Draft>>,
+	pub current: Signal<Option<Uuid>>,
+}
+
+impl Drafts {
+	pub fn current_document(&self) -> Option<MemeDocument> {
+		let current = (self.current)()?;
+		self.drafts.read().iter().find(|draft| draft.id == current).map(|draft| draft.document.clone())
+	}
+
+	/// Writes the document into the current draft, creating one if nothing is being edited yet.
+	pub fn save_current(&mut self, document: MemeDocument, thumbnail: Option<String>) {
+		let now = js_sys::Date::now();
+		let current = (self.current)();
+		let mut drafts = self.drafts.write();
+		if let Some(draft) = current.and_then(|id| drafts.iter_mut().find(|draft| draft.id == id)) {
+			if draft.document == document {
+				return;
+			}
+			draft.document = document;
+			draft.updated_at = now;
+			if thumbnail.is_some() {
+				draft.thumbnail = thumbnail;
+			}
+		} else {
+			let id = Uuid::new_v4();
+			drafts.push(Draft { id, name: format!("Draft {}", drafts.len() + 1), updated_at: now, thumbnail, document });
+			self.current.set(Some(id));
+		}
+	}
+
+	pub fn create(&mut self, document: MemeDocument) -> Uuid {
+		let id = Uuid::new_v4();
+		let mut drafts = self.drafts.write();
+		drafts.push(Draft { id, name: format!("Draft {}", drafts.len() + 1), updated_at: js_sys::Date::now(), thumbnail: None, document });
+		self.current.set(Some(id));
+		id
+	}
+
+	pub fn open(&mut self, id: Uuid) -> Option<MemeDocument> {
+		let document = self.drafts.read().iter().find(|draft| draft.id == id).map(|draft| draft.document.clone())?;
+		self.current.set(Some(id));
+		Some(document)
+	}
+
+	pub fn rename(&mut self, id: Uuid, name: String) {
+		if let Some(draft) = self.drafts.write().iter_mut().find(|draft| draft.id == id) {
+			draft.name = name;
+		}
+	}
+
+	pub fn delete(&mut self, id: Uuid) {
+		self.drafts.write().retain(|draft| draft.id != id);
+		if (self.current)() == Some(id) {
+			self.current.set(None);
+		}
+	}
+
+	/// Drafts sorted with the most recently edited first.
+	pub fn sorted(&self) -> Vec<Draft> {
+		let mut drafts = self.drafts.read().clone();
+		drafts.sort_by(|a, b| b.updated_at.total_cmp(&a.updated_at));
+		drafts
+	}
+}
+
+pub fn use_drafts() -> Drafts {
+	let drafts = use_synced_storage::<LocalStorage, Vec<Draft>>(DRAFTS_KEY.to_owned(), Vec::new);
+	let current = use_synced_storage::<LocalStorage, Option<Uuid>>(CURRENT_DRAFT_KEY.to_owned(), || None);
+	Drafts { drafts, current }
+}
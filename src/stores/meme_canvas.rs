@@ -1,12 +1,14 @@
+use crate::api::templates::TemplateInfo;
 use crate::stores::interaction_mode::InteractionMode;
 use crate::stores::interaction_mode::InteractionModeStoreImplExt;
 use crate::stores::text_box::HandleType;
 use crate::stores::text_box::TextBounds;
 use crate::stores::text_box::TextBox;
-use crate::utils::get_meme_canvas;
 use crate::utils::get_meme_canvas_ctx;
 use dioxus::html::geometry::euclid::Point2D;
 use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use web_sys::CanvasRenderingContext2d;
 use web_sys::HtmlImageElement;
 use web_sys::wasm_bindgen::JsCast;
 use web_sys::wasm_bindgen::prelude::*;
@@ -17,18 +19,137 @@ pub struct MemeCanvas {
 	pub width: u32,
 	pub height: u32,
 	pub text_boxes: Vec<TextBox>,
+	pub watermark_url: Option<String>,
 	pub selected_index: Option<usize>,
 	pub interaction_mode: InteractionMode,
 }
 
+pub const MAX_DIMENSION: u32 = 4096;
+pub const MAX_TEXT_BOXES: usize = 32;
+pub const MAX_TEXT_LEN: usize = 500;
+pub const MAX_FONT_SIZE: u32 = 512;
+
+/// Longest side of a template opened in the generator, whose canvas is drawn at the document's size.
+pub const EDITOR_MAX_DIMENSION: u32 = 500;
+
+const DEFAULT_WIDTH: u32 = 500;
+const DEFAULT_HEIGHT: u32 = 500;
+const DEFAULT_IMG_URL: &str = "https://i.imgflip.com/4/30b1gx.jpg";
+/// Width of the watermark relative to the canvas, and its distance from the bottom right corner.
+pub const WATERMARK_WIDTH: f64 = 0.2;
+pub const WATERMARK_MARGIN: f64 = 0.02;
+
+/// The persistable part of a [`MemeCanvas`]: everything needed to redraw the meme, without editor state like selection or drag mode.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MemeDocument {
+	pub main_img_url: String,
+	pub width: u32,
+	pub height: u32,
+	pub text_boxes: Vec<TextBox>,
+	/// Brand image drawn over the bottom right corner, required on tournament entries.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub watermark_url: Option<String>,
+}
+
+impl Default for MemeDocument {
+	fn default() -> Self {
+		Self {
+			main_img_url: DEFAULT_IMG_URL.to_owned(),
+			width: DEFAULT_WIDTH,
+			height: DEFAULT_HEIGHT,
+			text_boxes: vec![
+				TextBox::new("top text".to_owned(), 0.75 * DEFAULT_WIDTH as f64, 0.25 * DEFAULT_HEIGHT as f64, 48, "Arial".to_owned(), "bold".to_owned()),
+				TextBox::new("bottom text".to_owned(), 0.75 * DEFAULT_WIDTH as f64, 0.75 * DEFAULT_HEIGHT as f64, 48, "Arial".to_owned(), "bold".to_owned()),
+			],
+			watermark_url: None,
+		}
+	}
+}
+
+impl MemeDocument {
+	/// A fresh meme on the template, sized for the generator, with blank top and bottom text boxes for the user to fill in.
+	pub fn from_template(template: &TemplateInfo) -> Self {
+		Self::template_layout(template, vec![String::new(); 2], EDITOR_MAX_DIMENSION)
+	}
+
+	/// The template at its own aspect ratio, scaled down until neither side exceeds `max_dimension`, with one centered caption per
+	/// entry of `captions`: the first at the top, the last at the bottom and any others spread evenly between.
+	pub fn template_layout(template: &TemplateInfo, captions: Vec<String>, max_dimension: u32) -> Self {
+		let (template_width, template_height) = (f64::from(template.width.max(1)), f64::from(template.height.max(1)));
+		let scale = (f64::from(max_dimension) / template_width.max(template_height)).min(1.0);
+		let (width, height) = ((template_width * scale).round().max(1.0) as u32, (template_height * scale).round().max(1.0) as u32);
+		let size = (width / 10).clamp(12, MAX_FONT_SIZE);
+		let count = captions.len();
+		let text_boxes = captions
+			.into_iter()
+			.enumerate()
+			.map(|(i, text)| {
+				let position = if count == 1 { 0.0 } else { i as f64 / (count - 1) as f64 };
+				let y = f64::from(size) * 0.75 + position * (f64::from(height) - f64::from(size) * 1.5);
+				TextBox::new(text, f64::from(width) / 2.0, y, size, "Arial".to_owned(), "bold".to_owned())
+			})
+			.collect();
+		Self { main_img_url: template.image_url.clone(), width, height, text_boxes, watermark_url: None }
+	}
+
+	/// Rejects documents that could not have come out of the generator, so untrusted input (links, uploads) can be loaded safely.
+	pub fn validate(&self) -> anyhow::Result<()> {
+		anyhow::ensure!((1..=MAX_DIMENSION).contains(&self.width) && (1..=MAX_DIMENSION).contains(&self.height), "canvas size out of range");
+		anyhow::ensure!(self.main_img_url.starts_with("https://") || self.main_img_url.starts_with("http://"), "image url must be http(s)");
+		anyhow::ensure!(self.main_img_url.len() <= 2048, "image url too long");
+		if let Some(watermark_url) = &self.watermark_url {
+			anyhow::ensure!(watermark_url.starts_with("https://") || watermark_url.starts_with("http://"), "watermark url must be http(s)");
+			anyhow::ensure!(watermark_url.len() <= 2048, "watermark url too long");
+		}
+		anyhow::ensure!(self.text_boxes.len() <= MAX_TEXT_BOXES, "too many text boxes");
+		for text_box in &self.text_boxes {
+			anyhow::ensure!(text_box.text.chars().count() <= MAX_TEXT_LEN, "text too long");
+			anyhow::ensure!((1..=MAX_FONT_SIZE).contains(&text_box.style.size), "font size out of range");
+			anyhow::ensure!(text_box.style.family.len() <= 64 && text_box.style.effect.len() <= 64, "font style too long");
+			anyhow::ensure!(
+				[text_box.x, text_box.y, text_box.rotation, text_box.scale_x, text_box.scale_y].iter().all(|value| value.is_finite()),
+				"text box geometry must be finite"
+			);
+		}
+		Ok(())
+	}
+
+	/// Text of every text box in drawing order, which is what search indexes.
+	pub fn caption(&self) -> String {
+		self.text_boxes.iter().map(|text_box| text_box.text.as_str()).collect::<Vec<_>>().join(" / ")
+	}
+}
+
 impl MemeCanvas {
 	pub fn new(width: u32, height: u32, main_img_url: String, text_boxes: Vec<TextBox>) -> Self {
-		Self { main_img_url, width, height, text_boxes, selected_index: None, interaction_mode: InteractionMode::None }
+		Self { main_img_url, width, height, text_boxes, watermark_url: None, selected_index: None, interaction_mode: InteractionMode::None }
+	}
+}
+
+impl From<MemeDocument> for MemeCanvas {
+	fn from(MemeDocument { main_img_url, width, height, text_boxes, watermark_url }: MemeDocument) -> Self {
+		Self { watermark_url, ..Self::new(width, height, main_img_url, text_boxes) }
 	}
 }
 
 #[store(pub)]
 impl<Lens> Store<MemeCanvas, Lens> {
+	fn document(&self) -> MemeDocument {
+		let MemeCanvasStoreTransposed { main_img_url, width, height, text_boxes, watermark_url, .. } = self.transpose();
+		MemeDocument { main_img_url: main_img_url(), width: width(), height: height(), text_boxes: text_boxes(), watermark_url: watermark_url() }
+	}
+
+	fn load_document(&mut self, document: MemeDocument) {
+		let MemeDocument { main_img_url, width, height, text_boxes, watermark_url } = document;
+		self.interaction_mode().set(InteractionMode::None);
+		self.selected_index().set(None);
+		self.width().set(width);
+		self.height().set(height);
+		self.text_boxes().set(text_boxes);
+		self.watermark_url().set(watermark_url);
+		self.main_img_url().set(main_img_url);
+	}
+
 	fn select_handle(&mut self, x: f64, y: f64) -> Option<HandleType> {
 		if let Some(selected_idx) = self.selected_index()()
 			&& let Some(text_box) = self.text_boxes()().get(selected_idx)
@@ -162,25 +283,28 @@ impl<Lens> Store<MemeCanvas, Lens> {
 		}
 	}
 	fn render_canvas(&self) {
-		let MemeCanvasStoreTransposed { main_img_url, .. } = self.transpose();
+		let MemeCanvasStoreTransposed { main_img_url, width, height, watermark_url, .. } = self.transpose();
 		let text_boxes = self.text_boxes();
 		let text_boxes = text_boxes();
 		let main_img_url = main_img_url();
-		let meme_canvas = get_meme_canvas();
+		let watermark_url = watermark_url();
+		// The canvas element is sized from the document, so reading the size here also redraws after it changes.
+		let (canvas_width, canvas_height) = (f64::from(width()), f64::from(height()));
 		let ctx = get_meme_canvas_ctx();
 		let img_elem = HtmlImageElement::new().expect("cannot create img elem");
 		img_elem.set_cross_origin(Some("anonymous"));
 		let onload = Closure::wrap(Box::new({
 			to_owned![img_elem];
 			move || {
-				let canvas_width = meme_canvas.width() as f64;
-				let canvas_height = meme_canvas.height() as f64;
 				ctx.clear_rect(0.0, 0.0, canvas_width, canvas_height);
 				match ctx.draw_image_with_html_image_element_and_dw_and_dh(&img_elem, 0.0, 0.0, canvas_width, canvas_height) {
 					Ok(()) => {
 						for text_box in text_boxes.clone() {
 							text_box.draw_to_canvas(&ctx);
 						}
+						if let Some(watermark_url) = &watermark_url {
+							draw_watermark(ctx.clone(), canvas_width, canvas_height, watermark_url);
+						}
 					},
 					Err(e) => {
 						error!("{e:#?}");
@@ -194,8 +318,30 @@ impl<Lens> Store<MemeCanvas, Lens> {
 	}
 }
 
-pub fn use_meme_canvas(width: u32, height: u32, main_img_url: String, text_boxes: Vec<TextBox>) -> Store<MemeCanvas> {
-	let meme_canvas_store = use_store(|| MemeCanvas::new(width, height, main_img_url, text_boxes));
+/// Draws the watermark once it has loaded, scaled to [`WATERMARK_WIDTH`] of the canvas and keeping its aspect ratio.
+fn draw_watermark(ctx: CanvasRenderingContext2d, canvas_width: f64, canvas_height: f64, url: &str) {
+	let img_elem = HtmlImageElement::new().expect("cannot create img elem");
+	img_elem.set_cross_origin(Some("anonymous"));
+	let onload = Closure::wrap(Box::new({
+		to_owned![img_elem];
+		move || {
+			let width = canvas_width * WATERMARK_WIDTH;
+			let height = width * f64::from(img_elem.natural_height()) / f64::from(img_elem.natural_width().max(1));
+			let margin = canvas_width * WATERMARK_MARGIN;
+			if let Err(e) =
+				ctx.draw_image_with_html_image_element_and_dw_and_dh(&img_elem, canvas_width - width - margin, canvas_height - height - margin, width, height)
+			{
+				error!("{e:#?}");
+			}
+		}
+	}) as Box<dyn Fn()>);
+	img_elem.set_onload(Some(onload.as_ref().unchecked_ref()));
+	onload.forget();
+	img_elem.set_src(url);
+}
+
+pub fn use_meme_canvas(init: impl FnOnce() -> MemeDocument) -> Store<MemeCanvas> {
+	let meme_canvas_store = use_store(|| MemeCanvas::from(init()));
 	use_effect(move || meme_canvas_store.render_canvas());
 	meme_canvas_store
 }
@@ -1,3 +1,6 @@
+pub mod drafts;
 pub mod interaction_mode;
 pub mod meme_canvas;
+pub mod poker_table;
+pub mod session;
 pub mod text_box;
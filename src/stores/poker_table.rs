@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use dioxus::{
+	fullstack::{UseWebsocket, WebSocketOptions, WebsocketState, use_websocket},
+	prelude::*,
+};
+use uuid::Uuid;
+
+use crate::api::tables::{ClientMessage, ServerMessage, TableView, table_socket};
+
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+/// A live table as the server last described it. Dropped connections are retried with backoff, and the view that arrives on
+/// reconnect replaces whatever was missed.
+#[derive(Clone, Copy)]
+pub struct PokerTable {
+	pub view: Signal<Option<TableView>>,
+	/// The last message the table refused, until the next one is sent.
+	pub error: Signal<Option<String>>,
+	socket: UseWebsocket<ClientMessage, ServerMessage>,
+}
+
+/// Handles to the same table compare equal.
+impl PartialEq for PokerTable {
+	fn eq(&self, other: &Self) -> bool {
+		self.view == other.view && self.error == other.error
+	}
+}
+
+impl PokerTable {
+	pub fn status(&self) -> WebsocketState {
+		self.socket.status().cloned()
+	}
+
+	pub fn send(&self, message: ClientMessage) {
+		let socket = self.socket;
+		let mut error = self.error;
+		spawn(async move {
+			error.set(None);
+			if let Err(e) = socket.send(message).await {
+				error.set(Some(e.to_string()));
+			}
+		});
+	}
+}
+
+pub fn use_poker_table(id: Uuid) -> PokerTable {
+	let mut view = use_signal(|| None::<TableView>);
+	let mut error = use_signal(|| None);
+	let mut socket = use_websocket(move || table_socket(id, WebSocketOptions::new()));
+
+	use_future(move || async move {
+		let mut retry = FIRST_RETRY;
+		loop {
+			match socket.recv().await {
+				Ok(ServerMessage::Table(next)) => {
+					retry = FIRST_RETRY;
+					if view.peek().as_ref().is_none_or(|current| current.version < next.version) {
+						view.set(Some(next));
+					}
+				},
+				Ok(ServerMessage::Error(message)) => error.set(Some(message)),
+				Err(_) => {
+					gloo::timers::future::sleep(retry).await;
+					retry = (retry * 2).min(MAX_RETRY);
+					socket.set(table_socket(id, WebSocketOptions::new()).await);
+				},
+			}
+		}
+	});
+
+	PokerTable { view, error, socket }
+}
@@ -0,0 +1,32 @@
+use crate::api::auth::{UserInfo, get_current_user};
+use dioxus::prelude::*;
+
+/// Who is logged in, shared through context from [`crate::layout::Layout`].
+#[derive(Clone, Copy, PartialEq)]
+pub struct Session {
+	pub user: Signal<Option<UserInfo>>,
+	/// False until the first `get_current_user` call returns, so guards do not redirect while it is in flight.
+	pub loaded: Signal<bool>,
+}
+
+impl Session {
+	pub fn is_logged_in(&self) -> bool {
+		self.user.read().is_some()
+	}
+}
+
+pub fn use_session_provider() -> Session {
+	let mut user = use_signal(|| None);
+	let mut loaded = use_signal(|| false);
+	use_future(move || async move {
+		if let Ok(current) = get_current_user().await {
+			user.set(current);
+		}
+		loaded.set(true);
+	});
+	use_context_provider(|| Session { user, loaded })
+}
+
+pub fn use_session() -> Session {
+	use_context()
+}
@@ -1,17 +1,18 @@
 use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use std::iter::FromIterator;
 use web_sys::CanvasRenderingContext2d;
 use web_sys::wasm_bindgen::prelude::*;
 
-#[derive(Clone, PartialEq, Eq, Debug, Store)]
+#[derive(Clone, PartialEq, Eq, Debug, Store, Serialize, Deserialize)]
 pub struct TextBoxStyle {
 	pub size: u32,
 	pub family: String,
 	pub effect: String,
 }
 
-#[derive(Clone, PartialEq, Debug, Store)]
+#[derive(Clone, PartialEq, Debug, Store, Serialize, Deserialize)]
 pub struct TextBox {
 	pub text: String,
 	pub x: f64,
@@ -20,6 +21,7 @@ pub struct TextBox {
 	pub scale_x: f64,
 	pub scale_y: f64,
 	pub style: TextBoxStyle,
+	#[serde(skip)]
 	pub is_selected: bool,
 }
 
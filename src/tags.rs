@@ -0,0 +1,42 @@
+//! Tags are short lowercase words of letters, digits and dashes. Search queries mark them with a leading `#`.
+
+pub const MAX_TAGS: usize = 8;
+pub const MAX_TAG_LEN: usize = 32;
+
+/// Lowercases, strips a leading `#`, drops empties and duplicates, and rejects anything outside `[a-z0-9-]`.
+pub fn normalize_tags<S: AsRef<str>>(tags: impl IntoIterator<Item = S>) -> anyhow::Result<Vec<String>> {
+	let mut normalized = Vec::new();
+	for tag in tags {
+		let tag = tag.as_ref().trim().trim_start_matches('#').to_lowercase();
+		if tag.is_empty() {
+			continue;
+		}
+		anyhow::ensure!(
+			tag.len() <= MAX_TAG_LEN && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+			"invalid tag `{tag}`, use up to {MAX_TAG_LEN} letters, digits and dashes"
+		);
+		if !normalized.contains(&tag) {
+			normalized.push(tag);
+		}
+	}
+	anyhow::ensure!(normalized.len() <= MAX_TAGS, "at most {MAX_TAGS} tags");
+	Ok(normalized)
+}
+
+/// Parses a tag input field, where tags are separated by commas or whitespace.
+pub fn parse_tags(input: &str) -> anyhow::Result<Vec<String>> {
+	normalize_tags(input.split(|c: char| c == ',' || c.is_whitespace()))
+}
+
+/// Splits a search box query into its free text and its `#tag` filters.
+pub fn parse_query(query: &str) -> (String, Vec<String>) {
+	let mut words = Vec::new();
+	let mut tags = Vec::new();
+	for word in query.split_whitespace() {
+		match word.strip_prefix('#') {
+			Some(tag) if !tag.is_empty() => tags.push(tag.to_lowercase()),
+			_ => words.push(word),
+		}
+	}
+	(words.join(" "), tags)
+}
@@ -24,3 +24,20 @@ pub fn download_canvas_as_image() {
 	anchor.click();
 	body.remove_child(&anchor).ok();
 }
+
+/// Renders a scaled down jpeg of the meme canvas. Returns `None` when the canvas is tainted by a cross origin image.
+pub fn meme_canvas_thumbnail(max_side: f64) -> Option<String> {
+	let canvas = get_meme_canvas();
+	let scale = (max_side / canvas.width().max(canvas.height()) as f64).min(1.0);
+	let thumb = document().create_element("canvas").ok()?.dyn_into::<HtmlCanvasElement>().ok()?;
+	thumb.set_width((canvas.width() as f64 * scale).round() as u32);
+	thumb.set_height((canvas.height() as f64 * scale).round() as u32);
+	let ctx = thumb.get_context("2d").ok()??.dyn_into::<CanvasRenderingContext2d>().ok()?;
+	ctx.draw_image_with_html_canvas_element_and_dw_and_dh(&canvas, 0.0, 0.0, thumb.width() as f64, thumb.height() as f64).ok()?;
+	thumb.to_data_url_with_type_and_encoder_options("image/jpeg", &web_sys::wasm_bindgen::JsValue::from_f64(0.7)).ok()
+}
+
+/// Full size png of the meme canvas, as a data url. Fails when the background comes from a host that does not allow cross origin reads.
+pub fn meme_canvas_png() -> anyhow::Result<String> {
+	get_meme_canvas().to_data_url().map_err(|_| anyhow::anyhow!("the image host does not allow exporting this image, upload it as a template instead"))
+}